@@ -0,0 +1,40 @@
+//! Minimal BoxMux plugin. Build with `cargo build -p boxmux-plugin --example hello_plugin`
+//! and point a plugin manifest's `entry_point` at the resulting `libhello_plugin.so`.
+
+use boxmux_plugin::{
+    export_plugin, Event, EventOutcome, EventRequest, Plugin, PluginError, RenderRequest,
+};
+
+#[derive(Default)]
+struct HelloPlugin {
+    keys_seen: usize,
+}
+
+impl Plugin for HelloPlugin {
+    fn render(&mut self, request: &RenderRequest) -> Result<String, PluginError> {
+        let name = request
+            .properties
+            .get("name")
+            .and_then(|value| value.as_str())
+            .unwrap_or("world");
+        Ok(format!(
+            "Hello, {}!\n{}x{} box, {} keys seen",
+            name,
+            request.bounds.width(),
+            request.bounds.height(),
+            self.keys_seen
+        ))
+    }
+
+    fn handle_event(&mut self, request: &EventRequest) -> Result<EventOutcome, PluginError> {
+        match request.event {
+            Event::KeyPress(_) => {
+                self.keys_seen += 1;
+                Ok(EventOutcome::rerender())
+            }
+            _ => Ok(EventOutcome::default()),
+        }
+    }
+}
+
+export_plugin!(HelloPlugin::default);
@@ -0,0 +1,528 @@
+//! C ABI for BoxMux dynamic plugins.
+//!
+//! A plugin is a `cdylib` that exports five `extern "C"` functions. Every payload that
+//! crosses the boundary is a NUL-terminated UTF-8 JSON string, so plugins do not have to be
+//! built with the same compiler or crate versions as boxmux:
+//!
+//! | Symbol | Signature | Purpose |
+//! |--------|-----------|---------|
+//! | `boxmux_plugin_abi_version` | `() -> u32` | Handshake; must return [`ABI_VERSION`] |
+//! | `boxmux_plugin_create` | `() -> *mut c_void` | Create the plugin instance (opaque handle) |
+//! | `boxmux_plugin_destroy` | `(*mut c_void)` | Destroy the instance |
+//! | `boxmux_plugin_call` | `(*mut c_void, *const c_char, *const c_char) -> *mut c_char` | Run a [`Method`] with a JSON request, return a JSON [`Response`] |
+//! | `boxmux_plugin_free_string` | `(*mut c_char)` | Free a string returned by `boxmux_plugin_call` |
+//!
+//! The host never calls one instance concurrently. Strings passed to the plugin are only
+//! borrowed for the duration of the call; strings returned by the plugin are owned by the
+//! plugin's allocator and must go back through `boxmux_plugin_free_string`.
+//!
+//! Implement [`Plugin`] and export it with [`export_plugin!`]:
+//!
+//! ```no_run
+//! use boxmux_plugin::{export_plugin, Plugin, PluginError, RenderRequest};
+//!
+//! #[derive(Default)]
+//! struct Clock;
+//!
+//! impl Plugin for Clock {
+//!     fn render(&mut self, request: &RenderRequest) -> Result<String, PluginError> {
+//!         Ok(format!("{} at {}x{}", request.component_type, request.bounds.width(), request.bounds.height()))
+//!     }
+//! }
+//!
+//! export_plugin!(Clock::default);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Version of the plugin ABI. Bumped on any incompatible change to the exported symbols
+/// or the JSON payloads; boxmux refuses to load a plugin reporting a different version.
+pub const ABI_VERSION: u32 = 1;
+
+pub const ABI_VERSION_SYMBOL: &str = "boxmux_plugin_abi_version";
+pub const CREATE_SYMBOL: &str = "boxmux_plugin_create";
+pub const DESTROY_SYMBOL: &str = "boxmux_plugin_destroy";
+pub const CALL_SYMBOL: &str = "boxmux_plugin_call";
+pub const FREE_STRING_SYMBOL: &str = "boxmux_plugin_free_string";
+
+pub type AbiVersionFn = unsafe extern "C" fn() -> u32;
+pub type CreateFn = unsafe extern "C" fn() -> *mut std::ffi::c_void;
+pub type DestroyFn = unsafe extern "C" fn(*mut std::ffi::c_void);
+pub type CallFn = unsafe extern "C" fn(
+    *mut std::ffi::c_void,
+    *const std::ffi::c_char,
+    *const std::ffi::c_char,
+) -> *mut std::ffi::c_char;
+pub type FreeStringFn = unsafe extern "C" fn(*mut std::ffi::c_char);
+
+/// Operation requested through `boxmux_plugin_call`, passed as its `method` argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// [`RenderRequest`] in, `String` out
+    Render,
+    /// [`RenderRequest`] in, [`ComponentState`] out
+    Update,
+    /// [`EventRequest`] in, [`EventOutcome`] out
+    Event,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Render => "render",
+            Method::Update => "update",
+            Method::Event => "event",
+        }
+    }
+
+    pub fn parse(method: &str) -> Option<Self> {
+        match method {
+            "render" => Some(Method::Render),
+            "update" => Some(Method::Update),
+            "event" => Some(Method::Event),
+            _ => None,
+        }
+    }
+}
+
+/// Inclusive screen rectangle of the box the component renders into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bounds {
+    pub x1: usize,
+    pub y1: usize,
+    pub x2: usize,
+    pub y2: usize,
+}
+
+impl Bounds {
+    pub fn width(&self) -> usize {
+        self.x2.saturating_sub(self.x1) + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.y2.saturating_sub(self.y1) + 1
+    }
+}
+
+/// Permission granted to the plugin by its manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Permission {
+    FileSystem { paths: Vec<String> },
+    Network { hosts: Vec<String> },
+    Process { commands: Vec<String> },
+    Environment { variables: Vec<String> },
+}
+
+/// Payload of [`Method::Render`] and [`Method::Update`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderRequest {
+    pub component_type: String,
+    /// The box's `plugin_config`
+    pub properties: HashMap<String, serde_json::Value>,
+    pub data_source: Option<String>,
+    pub refresh_interval: Option<u64>,
+    pub bounds: Bounds,
+    pub plugin_data: HashMap<String, serde_json::Value>,
+    pub permissions: Vec<Permission>,
+}
+
+/// Event delivered to a component
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Event {
+    KeyPress(String),
+    MouseEvent {
+        x: u16,
+        y: u16,
+        action: String,
+    },
+    Timer {
+        interval: u64,
+    },
+    DataUpdate {
+        source: String,
+        data: serde_json::Value,
+    },
+    MuxBoxResize {
+        new_bounds: Bounds,
+    },
+}
+
+/// Payload of [`Method::Event`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventRequest {
+    pub component_type: String,
+    pub bounds: Bounds,
+    pub plugin_data: HashMap<String, serde_json::Value>,
+    pub event: Event,
+}
+
+/// Result of [`Method::Event`]: what boxmux should do after the handler ran
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventOutcome {
+    /// Render the component again right away instead of waiting for its next refresh
+    pub rerender: bool,
+    /// Replaces the box's `plugin_data`, which is passed to every later render and event
+    pub plugin_data: Option<HashMap<String, serde_json::Value>>,
+    pub actions: Vec<Action>,
+}
+
+impl EventOutcome {
+    /// Outcome that only asks for a re-render
+    pub fn rerender() -> Self {
+        Self {
+            rerender: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Host action requested by an event handler
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    /// Make the layout with this id the active one
+    SwitchLayout(String),
+    /// Run the choice with this id, as if its hot key was pressed
+    ExecuteChoice(String),
+    /// Re-render the plugin component of another box
+    RefreshBox(String),
+}
+
+/// Result of [`Method::Update`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentState {
+    pub content: String,
+    pub metadata: HashMap<String, serde_json::Value>,
+    pub needs_refresh: bool,
+}
+
+/// Error reported by a plugin; mirrors boxmux's own plugin error kinds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PluginError {
+    InitializationFailed(String),
+    RenderFailed(String),
+    PermissionDenied(String),
+    InvalidConfiguration(String),
+    RuntimeError(String),
+}
+
+impl std::fmt::Display for PluginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginError::InitializationFailed(msg)
+            | PluginError::RenderFailed(msg)
+            | PluginError::PermissionDenied(msg)
+            | PluginError::InvalidConfiguration(msg)
+            | PluginError::RuntimeError(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+/// JSON returned by `boxmux_plugin_call`: `{"Ok": <result>}` or `{"Err": <PluginError>}`
+pub type Response = Result<serde_json::Value, PluginError>;
+
+/// A plugin instance. One instance serves every component type in the plugin's manifest.
+pub trait Plugin: Send + 'static {
+    fn render(&mut self, request: &RenderRequest) -> Result<String, PluginError>;
+
+    fn update(&mut self, request: &RenderRequest) -> Result<ComponentState, PluginError> {
+        Err(PluginError::RuntimeError(format!(
+            "Component '{}' does not support updates",
+            request.component_type
+        )))
+    }
+
+    fn handle_event(&mut self, _request: &EventRequest) -> Result<EventOutcome, PluginError> {
+        Ok(EventOutcome::default())
+    }
+}
+
+/// Export a [`Plugin`] through the C ABI. Takes an expression that builds the plugin,
+/// e.g. `export_plugin!(MyPlugin::default)`. Use it once per `cdylib`.
+#[macro_export]
+macro_rules! export_plugin {
+    ($constructor:expr) => {
+        #[no_mangle]
+        pub extern "C" fn boxmux_plugin_abi_version() -> u32 {
+            $crate::ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub extern "C" fn boxmux_plugin_create() -> *mut ::std::ffi::c_void {
+            $crate::__private::create(|| ::std::boxed::Box::new(($constructor)()))
+        }
+
+        /// # Safety
+        /// `handle` must come from `boxmux_plugin_create` and not be used afterwards.
+        #[no_mangle]
+        pub unsafe extern "C" fn boxmux_plugin_destroy(handle: *mut ::std::ffi::c_void) {
+            $crate::__private::destroy(handle)
+        }
+
+        /// # Safety
+        /// `handle` must come from `boxmux_plugin_create`; `method` and `request` must be
+        /// NUL-terminated strings valid for the duration of the call.
+        #[no_mangle]
+        pub unsafe extern "C" fn boxmux_plugin_call(
+            handle: *mut ::std::ffi::c_void,
+            method: *const ::std::ffi::c_char,
+            request: *const ::std::ffi::c_char,
+        ) -> *mut ::std::ffi::c_char {
+            $crate::__private::call(handle, method, request)
+        }
+
+        /// # Safety
+        /// `string` must come from `boxmux_plugin_call` and not be used afterwards.
+        #[no_mangle]
+        pub unsafe extern "C" fn boxmux_plugin_free_string(string: *mut ::std::ffi::c_char) {
+            $crate::__private::free_string(string)
+        }
+    };
+}
+
+/// Implementation of the exported functions; used by [`export_plugin!`], not a stable API
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+    use std::ffi::{c_char, c_void, CStr, CString};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    type Instance = Box<dyn Plugin>;
+
+    pub fn create(constructor: impl FnOnce() -> Instance) -> *mut c_void {
+        match catch_unwind(AssertUnwindSafe(constructor)) {
+            Ok(instance) => Box::into_raw(Box::new(instance)) as *mut c_void,
+            Err(_) => std::ptr::null_mut(),
+        }
+    }
+
+    /// # Safety
+    /// See `boxmux_plugin_destroy`.
+    pub unsafe fn destroy(handle: *mut c_void) {
+        if !handle.is_null() {
+            drop(Box::from_raw(handle as *mut Instance));
+        }
+    }
+
+    /// # Safety
+    /// See `boxmux_plugin_call`.
+    pub unsafe fn call(
+        handle: *mut c_void,
+        method: *const c_char,
+        request: *const c_char,
+    ) -> *mut c_char {
+        let response = if handle.is_null() || method.is_null() || request.is_null() {
+            Err(PluginError::RuntimeError(
+                "Null argument passed to boxmux_plugin_call".to_string(),
+            ))
+        } else {
+            let instance = &mut *(handle as *mut Instance);
+            let method = CStr::from_ptr(method).to_string_lossy();
+            let request = CStr::from_ptr(request).to_string_lossy();
+            catch_unwind(AssertUnwindSafe(|| {
+                dispatch(instance.as_mut(), &method, &request)
+            }))
+            .unwrap_or_else(|_| {
+                Err(PluginError::RuntimeError(format!(
+                    "Plugin panicked during '{}'",
+                    method
+                )))
+            })
+        };
+        into_c_string(&response)
+    }
+
+    /// # Safety
+    /// See `boxmux_plugin_free_string`.
+    pub unsafe fn free_string(string: *mut c_char) {
+        if !string.is_null() {
+            drop(CString::from_raw(string));
+        }
+    }
+
+    pub fn dispatch(plugin: &mut dyn Plugin, method: &str, request: &str) -> Response {
+        fn parse<T: serde::de::DeserializeOwned>(request: &str) -> Result<T, PluginError> {
+            serde_json::from_str(request)
+                .map_err(|e| PluginError::InvalidConfiguration(format!("Malformed request: {}", e)))
+        }
+        fn to_value<T: Serialize>(value: T) -> Response {
+            serde_json::to_value(value).map_err(|e| PluginError::RuntimeError(e.to_string()))
+        }
+
+        match Method::parse(method) {
+            Some(Method::Render) => to_value(plugin.render(&parse(request)?)?),
+            Some(Method::Update) => to_value(plugin.update(&parse(request)?)?),
+            Some(Method::Event) => to_value(plugin.handle_event(&parse(request)?)?),
+            None => Err(PluginError::InvalidConfiguration(format!(
+                "Unknown method '{}'",
+                method
+            ))),
+        }
+    }
+
+    fn into_c_string(response: &Response) -> *mut c_char {
+        let json = serde_json::to_string(response).unwrap_or_else(|e| {
+            format!(
+                r#"{{"Err":{{"RuntimeError":"Unserializable response: {}"}}}}"#,
+                e
+            )
+        });
+        // JSON escapes control characters, so an interior NUL can only come from a bug
+        CString::new(json)
+            .unwrap_or_else(|_| {
+                CString::new(r#"{"Err":{"RuntimeError":"NUL in response"}}"#).unwrap()
+            })
+            .into_raw()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::__private::{call, create, destroy, free_string};
+    use super::*;
+    use std::ffi::{CStr, CString};
+
+    struct Echo {
+        renders: usize,
+    }
+
+    impl Plugin for Echo {
+        fn render(&mut self, request: &RenderRequest) -> Result<String, PluginError> {
+            self.renders += 1;
+            match request.properties.get("fail") {
+                Some(_) => Err(PluginError::RenderFailed("asked to fail".to_string())),
+                None => Ok(format!("{} #{}", request.component_type, self.renders)),
+            }
+        }
+    }
+
+    fn render_request(properties: serde_json::Value) -> String {
+        serde_json::json!({
+            "component_type": "echo",
+            "properties": properties,
+            "data_source": null,
+            "refresh_interval": null,
+            "bounds": {"x1": 0, "y1": 0, "x2": 9, "y2": 4},
+            "plugin_data": {},
+            "permissions": [],
+        })
+        .to_string()
+    }
+
+    fn call_json(handle: *mut std::ffi::c_void, method: &str, request: &str) -> Response {
+        let method = CString::new(method).unwrap();
+        let request = CString::new(request).unwrap();
+        unsafe {
+            let raw = call(handle, method.as_ptr(), request.as_ptr());
+            let response = serde_json::from_str(CStr::from_ptr(raw).to_str().unwrap()).unwrap();
+            free_string(raw);
+            response
+        }
+    }
+
+    #[test]
+    fn test_call_round_trip_keeps_instance_state() {
+        let handle = create(|| Box::new(Echo { renders: 0 }));
+        let request = render_request(serde_json::json!({}));
+
+        assert_eq!(
+            call_json(handle, "render", &request),
+            Ok(serde_json::json!("echo #1"))
+        );
+        assert_eq!(
+            call_json(handle, "render", &request),
+            Ok(serde_json::json!("echo #2"))
+        );
+        unsafe { destroy(handle) };
+    }
+
+    #[test]
+    fn test_call_reports_errors_as_json() {
+        let handle = create(|| Box::new(Echo { renders: 0 }));
+
+        let failed = call_json(
+            handle,
+            "render",
+            &render_request(serde_json::json!({"fail": 1})),
+        );
+        assert_eq!(
+            failed,
+            Err(PluginError::RenderFailed("asked to fail".to_string()))
+        );
+
+        let unknown = call_json(handle, "resize", "{}");
+        assert!(matches!(unknown, Err(PluginError::InvalidConfiguration(_))));
+
+        let malformed = call_json(handle, "render", "not json");
+        assert!(matches!(
+            malformed,
+            Err(PluginError::InvalidConfiguration(_))
+        ));
+
+        let unsupported = call_json(handle, "update", &render_request(serde_json::json!({})));
+        assert!(matches!(unsupported, Err(PluginError::RuntimeError(_))));
+        unsafe { destroy(handle) };
+    }
+
+    #[test]
+    fn test_event_outcome_round_trip() {
+        struct Clicker;
+        impl Plugin for Clicker {
+            fn render(&mut self, _request: &RenderRequest) -> Result<String, PluginError> {
+                Ok(String::new())
+            }
+
+            fn handle_event(
+                &mut self,
+                request: &EventRequest,
+            ) -> Result<EventOutcome, PluginError> {
+                match &request.event {
+                    Event::MouseEvent { .. } => Ok(EventOutcome {
+                        actions: vec![Action::SwitchLayout("details".to_string())],
+                        ..EventOutcome::rerender()
+                    }),
+                    _ => Ok(EventOutcome::default()),
+                }
+            }
+        }
+
+        let handle = create(|| Box::new(Clicker));
+        let request = serde_json::json!({
+            "component_type": "clicker",
+            "bounds": {"x1": 0, "y1": 0, "x2": 9, "y2": 4},
+            "plugin_data": {},
+            "event": {"MouseEvent": {"x": 1, "y": 2, "action": "click"}},
+        })
+        .to_string();
+        let outcome: EventOutcome =
+            serde_json::from_value(call_json(handle, "event", &request).unwrap()).unwrap();
+        assert!(outcome.rerender);
+        assert_eq!(
+            outcome.actions,
+            vec![Action::SwitchLayout("details".to_string())]
+        );
+
+        // Missing fields fall back to their defaults
+        let empty: EventOutcome = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty, EventOutcome::default());
+        unsafe { destroy(handle) };
+    }
+
+    #[test]
+    fn test_call_catches_panics() {
+        struct Panics;
+        impl Plugin for Panics {
+            fn render(&mut self, _request: &RenderRequest) -> Result<String, PluginError> {
+                panic!("boom")
+            }
+        }
+
+        let handle = create(|| Box::new(Panics));
+        let response = call_json(handle, "render", &render_request(serde_json::json!({})));
+        assert!(matches!(response, Err(PluginError::RuntimeError(_))));
+        unsafe { destroy(handle) };
+    }
+}
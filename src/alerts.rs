@@ -0,0 +1,211 @@
+//! Alert rules on box script output.
+//!
+//! A box's `alerts` are checked in order against each finished run of a script writing
+//! to the box. The first rule whose exit code, `pattern` and `value` conditions all hold
+//! becomes the box's [`ActiveAlert`]: its border and title take the alert color (or the
+//! box's error colors), a status indicator leads its first tab, and the border may
+//! flash. The draw loop calls [`apply_alerts`] and, when the matching rule changes, rings
+//! the bell if asked and sends an `AlertChanged` message that socket subscribers see as
+//! an `alert_changed` event.
+
+use crate::components::StatusType;
+use crate::model::app::App;
+use crate::model::common::{ActiveAlert, AlertLevel, AlertRule, ExitCodeMatch, StreamUpdate};
+use crate::model::muxbox::MuxBox;
+use crate::socket_protocol::stream_update_outcome;
+use regex::Regex;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, SystemTime};
+
+/// How long a flashing border flashes
+pub const FLASH_DURATION: Duration = Duration::from_secs(3);
+/// How long a flashing border stays lit, then dark
+const FLASH_PHASE_MS: u128 = 500;
+
+/// A box's alert after a finished run, when it differs from the previous one
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertChange {
+    pub box_id: String,
+    /// `None` once no rule matches
+    pub level: Option<AlertLevel>,
+    pub message: Option<String>,
+    pub bell: bool,
+}
+
+/// Index of the first rule matching a run, with the number its `value` extracted
+pub fn matching_rule(
+    rules: &[AlertRule],
+    exit_code: Option<i32>,
+    success: bool,
+    output: &str,
+) -> Option<(usize, Option<f64>)> {
+    rules.iter().enumerate().find_map(|(index, rule)| {
+        rule_matches(rule, exit_code, success, output).map(|value| (index, value))
+    })
+}
+
+fn rule_matches(
+    rule: &AlertRule,
+    exit_code: Option<i32>,
+    success: bool,
+    output: &str,
+) -> Option<Option<f64>> {
+    if let Some(expected) = &rule.exit_code {
+        if !exit_code_matches(expected, exit_code, success) {
+            return None;
+        }
+    }
+    if let Some(pattern) = &rule.pattern {
+        if !Regex::new(pattern).is_ok_and(|re| re.is_match(output)) {
+            return None;
+        }
+    }
+    let Some(value_pattern) = &rule.value else {
+        return Some(None);
+    };
+    let value = extract_value(value_pattern, output)?;
+    if rule.above.is_some_and(|above| value <= above)
+        || rule.below.is_some_and(|below| value >= below)
+    {
+        return None;
+    }
+    Some(Some(value))
+}
+
+fn exit_code_matches(expected: &ExitCodeMatch, exit_code: Option<i32>, success: bool) -> bool {
+    match expected {
+        ExitCodeMatch::Code(code) => exit_code == Some(*code),
+        ExitCodeMatch::Codes(codes) => exit_code.is_some_and(|code| codes.contains(&code)),
+        // Runs killed before exiting, e.g. on timeout, count as nonzero
+        ExitCodeMatch::Named(name) => name == "nonzero" && exit_code.map_or(!success, |c| c != 0),
+    }
+}
+
+/// The first capture group, or the whole match, of the last match of `pattern` in
+/// `output`, read as a number
+pub fn extract_value(pattern: &str, output: &str) -> Option<f64> {
+    let captures = Regex::new(pattern).ok()?.captures_iter(output).last()?;
+    let text = captures.get(1).or_else(|| captures.get(0))?.as_str();
+    text.trim().parse().ok()
+}
+
+/// Problems with a rule that would keep it from ever matching as written
+pub fn validate_rule(rule: &AlertRule) -> Vec<(&'static str, String, String)> {
+    let mut problems = Vec::new();
+    if let Some(ExitCodeMatch::Named(name)) = &rule.exit_code {
+        if name != "nonzero" {
+            problems.push((
+                "exit_code",
+                name.clone(),
+                "expected a code, a list of codes or \"nonzero\"".to_string(),
+            ));
+        }
+    }
+    for (field, pattern) in [("pattern", &rule.pattern), ("value", &rule.value)] {
+        if let Some(pattern) = pattern {
+            if let Err(e) = Regex::new(pattern) {
+                problems.push((field, pattern.clone(), format!("invalid regex: {}", e)));
+            }
+        }
+    }
+    if rule.value.is_none() && (rule.above.is_some() || rule.below.is_some()) {
+        problems.push((
+            "value",
+            "missing".to_string(),
+            "above and below compare the number extracted by value".to_string(),
+        ));
+    }
+    problems
+}
+
+/// Check the box's `alerts` against a finished update writing to it. Returns the change
+/// when a different rule (or none) now matches; updates still streaming, boxes without
+/// alerts and runs matching the same rule as before return `None`.
+pub fn apply_alerts(
+    muxbox: &mut MuxBox,
+    update: &StreamUpdate,
+    now: SystemTime,
+) -> Option<AlertChange> {
+    let rules = muxbox.alerts.as_ref()?;
+    let (exit_code, success) = stream_update_outcome(update)?;
+    let alert = matching_rule(rules, exit_code, success, &update.content_update).map(
+        |(rule_index, value)| {
+            let rule = &rules[rule_index];
+            ActiveAlert {
+                rule_index,
+                level: rule.level,
+                message: rule.message.as_ref().map(|message| match value {
+                    Some(value) => message.replace("${value}", &value.to_string()),
+                    None => message.clone(),
+                }),
+                indicator: rule.indicator.clone(),
+                color: rule.color.clone().or_else(|| level_color(rule.level)),
+                flash: rule.flash.then_some((now, now + FLASH_DURATION)),
+            }
+        },
+    );
+
+    let previous_rule = muxbox.alert.as_ref().map(|alert| alert.rule_index);
+    if alert.as_ref().map(|alert| alert.rule_index) == previous_rule {
+        // Same rule: refresh the message but don't re-announce or flash again
+        if let (Some(current), Some(alert)) = (muxbox.alert.as_mut(), alert) {
+            current.message = alert.message;
+        }
+        return None;
+    }
+
+    let change = AlertChange {
+        box_id: muxbox.id.clone(),
+        level: alert.as_ref().map(|alert| alert.level),
+        message: alert.as_ref().and_then(|alert| alert.message.clone()),
+        bell: alert
+            .as_ref()
+            .is_some_and(|alert| rules[alert.rule_index].bell),
+    };
+    muxbox.alert = alert;
+    Some(change)
+}
+
+/// Border and title color of a level; errors use the box's error colors
+fn level_color(level: AlertLevel) -> Option<String> {
+    match level {
+        AlertLevel::Ok => Some("green".to_string()),
+        AlertLevel::Warning => Some("yellow".to_string()),
+        AlertLevel::Error => None,
+    }
+}
+
+/// Status indicator of an alert: its own glyph, or the level's
+pub fn alert_status_type(alert: &ActiveAlert) -> StatusType {
+    match (&alert.indicator, alert.level) {
+        (Some(indicator), _) => StatusType::Custom(indicator.clone()),
+        (None, AlertLevel::Ok) => StatusType::ScriptCompleted,
+        (None, AlertLevel::Warning) => StatusType::Warning,
+        (None, AlertLevel::Error) => StatusType::ScriptFailed,
+    }
+}
+
+/// Whether the alert's border shows its color at `now`; false during the dark half of
+/// each flash
+pub fn border_lit(alert: &ActiveAlert, now: SystemTime) -> bool {
+    let Some((start, end)) = alert.flash else {
+        return true;
+    };
+    if now >= end {
+        return true;
+    }
+    let elapsed = now.duration_since(start).unwrap_or_default().as_millis();
+    (elapsed / FLASH_PHASE_MS).is_multiple_of(2)
+}
+
+/// Feed the flash phase of every flashing border into the draw loop's render signature,
+/// so flashing boxes redraw although nothing else changes
+pub fn hash_flash_phases<H: Hasher>(app: &App, now: SystemTime, state: &mut H) {
+    for layout in &app.layouts {
+        for muxbox in layout.get_all_muxboxes() {
+            if let Some(alert) = muxbox.alert.as_ref().filter(|alert| alert.flash.is_some()) {
+                border_lit(alert, now).hash(state);
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use crate::model::common::Cell;
+use crate::color_utils::terminal_color;
+use crate::model::common::{Cell, TextStyle};
 use crossterm::style::Color;
 
 /// ANSI color processor for rendering text with embedded ANSI escape sequences
@@ -7,10 +8,7 @@ use crossterm::style::Color;
 pub struct AnsiColorProcessor {
     current_fg: Color,
     current_bg: Color,
-    current_bold: bool,
-    current_italic: bool,
-    current_underline: bool,
-    current_reverse: bool,
+    current_style: TextStyle,
 }
 
 impl Default for AnsiColorProcessor {
@@ -18,10 +16,7 @@ impl Default for AnsiColorProcessor {
         Self {
             current_fg: Color::Reset,
             current_bg: Color::Reset,
-            current_bold: false,
-            current_italic: false,
-            current_underline: false,
-            current_reverse: false,
+            current_style: TextStyle::default(),
         }
     }
 }
@@ -35,10 +30,7 @@ impl AnsiColorProcessor {
     pub fn reset(&mut self) {
         self.current_fg = Color::Reset;
         self.current_bg = Color::Reset;
-        self.current_bold = false;
-        self.current_italic = false;
-        self.current_underline = false;
-        self.current_reverse = false;
+        self.current_style = TextStyle::default();
     }
 
     /// Process text containing ANSI escape sequences and return vector of Cells
@@ -72,6 +64,7 @@ impl AnsiColorProcessor {
                     fg_color,
                     bg_color,
                     ch,
+                    style: self.current_style,
                 });
             }
         }
@@ -91,26 +84,66 @@ impl AnsiColorProcessor {
                 return;
             }
 
-            for param in params.split(';') {
-                if let Ok(code) = param.parse::<u8>() {
-                    self.process_sgr_code(code);
+            let mut codes = params.split(';').map(|param| param.parse::<u16>().ok());
+            while let Some(code) = codes.next() {
+                match code {
+                    // Extended colors: 38;5;n / 48;5;n (256-color) and 38;2;r;g;b / 48;2;r;g;b
+                    Some(code @ (38 | 48)) => {
+                        if let Some(color) = Self::extended_color(&mut codes) {
+                            if code == 38 {
+                                self.current_fg = color;
+                            } else {
+                                self.current_bg = color;
+                            }
+                        }
+                    }
+                    Some(code) => {
+                        if let Ok(code) = u8::try_from(code) {
+                            self.process_sgr_code(code);
+                        }
+                    }
+                    None => {}
                 }
             }
         }
     }
 
+    /// Color following a 38/48 parameter, consuming its arguments
+    fn extended_color(codes: &mut impl Iterator<Item = Option<u16>>) -> Option<Color> {
+        let mut channel = || codes.next().flatten().and_then(|c| u8::try_from(c).ok());
+        match channel()? {
+            5 => channel().map(Color::AnsiValue),
+            2 => {
+                let (r, g, b) = (channel(), channel(), channel());
+                Some(Color::Rgb {
+                    r: r?,
+                    g: g?,
+                    b: b?,
+                })
+            }
+            _ => None,
+        }
+    }
+
     /// Process SGR (Select Graphic Rendition) codes
     fn process_sgr_code(&mut self, code: u8) {
         match code {
-            0 => self.reset(),                    // Reset all attributes
-            1 => self.current_bold = true,        // Bold
-            3 => self.current_italic = true,      // Italic
-            4 => self.current_underline = true,   // Underline
-            7 => self.current_reverse = true,     // Reverse
-            22 => self.current_bold = false,      // Normal intensity
-            23 => self.current_italic = false,    // Not italic
-            24 => self.current_underline = false, // Not underlined
-            27 => self.current_reverse = false,   // Not reversed
+            0 => self.reset(),                            // Reset all attributes
+            1 => self.current_style.bold = true,          // Bold
+            2 => self.current_style.dim = true,           // Dim
+            3 => self.current_style.italic = true,        // Italic
+            4 => self.current_style.underline = true,     // Underline
+            7 => self.current_style.reverse = true,       // Reverse
+            9 => self.current_style.strikethrough = true, // Strikethrough
+            22 => {
+                // Normal intensity
+                self.current_style.bold = false;
+                self.current_style.dim = false;
+            }
+            23 => self.current_style.italic = false, // Not italic
+            24 => self.current_style.underline = false, // Not underlined
+            27 => self.current_style.reverse = false, // Not reversed
+            29 => self.current_style.strikethrough = false, // Not struck through
 
             // Standard foreground colors (30-37) - use AnsiValue to get exact codes
             30 => self.current_fg = Color::AnsiValue(0), // Black
@@ -161,13 +194,13 @@ impl AnsiColorProcessor {
     /// Convert Color enum to string format used by BoxMux (using SetForegroundColor format)
     fn color_to_string(&self, color: &Color) -> String {
         use crossterm::style::SetForegroundColor;
-        format!("{}", SetForegroundColor(*color))
+        format!("{}", SetForegroundColor(terminal_color(*color)))
     }
 
     /// Convert Color enum to background color string format used by BoxMux
     fn bg_color_to_string(&self, color: &Color) -> String {
         use crossterm::style::SetBackgroundColor;
-        format!("{}", SetBackgroundColor(*color))
+        format!("{}", SetBackgroundColor(terminal_color(*color)))
     }
 }
 
@@ -238,6 +271,27 @@ mod tests {
         assert!(cells[0].bg_color.contains("48;5;1")); // Red background
     }
 
+    #[test]
+    fn test_extended_colors() {
+        let mut processor = AnsiColorProcessor::new();
+        let text = "\x1b[38;5;208;48;2;0;95;135mX\x1b[1;38;2;300;0;0mY";
+        let cells = processor.process_text(text);
+
+        assert_eq!(cells.len(), 2);
+        assert!(cells[0].fg_color.contains("38;5;208"));
+        let rgb = terminal_color(Color::Rgb {
+            r: 0,
+            g: 95,
+            b: 135,
+        });
+        assert_eq!(
+            cells[0].bg_color,
+            format!("{}", crossterm::style::SetBackgroundColor(rgb))
+        );
+        // An out-of-range channel leaves the color unchanged
+        assert_eq!(cells[1].fg_color, cells[0].fg_color);
+    }
+
     #[test]
     fn test_no_ansi_sequences() {
         let mut processor = AnsiColorProcessor::new();
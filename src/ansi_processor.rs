@@ -199,13 +199,32 @@ pub struct TerminalAttributes {
     pub dim: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TerminalColor {
     Named(u8),                   // 0-15 standard colors
     Palette(u8),                 // 0-255 palette colors
     Rgb { r: u8, g: u8, b: u8 }, // 24-bit RGB
 }
 
+impl TerminalColor {
+    /// SGR parameters selecting this color as foreground or background
+    pub fn sgr_code(&self, background: bool) -> String {
+        let (base, bright, extended) = if background {
+            (40, 100, 48)
+        } else {
+            (30, 90, 38)
+        };
+        match *self {
+            TerminalColor::Named(index) if index < 8 => format!("{}", base + index),
+            TerminalColor::Named(index) if index < 16 => format!("{}", bright + index - 8),
+            TerminalColor::Named(index) | TerminalColor::Palette(index) => {
+                format!("{};5;{}", extended, index)
+            }
+            TerminalColor::Rgb { r, g, b } => format!("{};2;{};{};{}", extended, r, g, b),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum DeviceReportType {
     Status,         // DSR 5
@@ -269,9 +288,10 @@ pub enum CharacterSetType {
 
 #[derive(Debug, Clone, Default)]
 pub struct TextFormatting {
-    pub fg: Option<u8>,
-    pub bg: Option<u8>,
+    pub fg: Option<TerminalColor>,
+    pub bg: Option<TerminalColor>,
     pub bold: bool,
+    pub dim: bool,
     pub underline: bool,
     pub italic: bool,
     pub reverse: bool,
@@ -282,8 +302,8 @@ pub struct TextFormatting {
 /// F0306: Extended TerminalCell with comprehensive SGR attribute support
 pub struct TerminalCell {
     pub character: char,
-    pub fg_color: Option<u8>,
-    pub bg_color: Option<u8>,
+    pub fg_color: Option<TerminalColor>,
+    pub bg_color: Option<TerminalColor>,
 
     // Basic text attributes
     pub bold: bool,
@@ -636,9 +656,10 @@ impl TerminalScreenBuffer {
     /// This preserves formatting for BoxMux's main renderer
     fn terminal_row_to_string(&self, row: &[TerminalCell]) -> String {
         let mut result = String::new();
-        let mut current_fg: Option<u8> = None;
-        let mut current_bg: Option<u8> = None;
+        let mut current_fg: Option<TerminalColor> = None;
+        let mut current_bg: Option<TerminalColor> = None;
         let mut current_bold = false;
+        let mut current_dim = false;
         let mut current_underline = false;
         let mut current_italic = false;
         let mut current_reverse = false;
@@ -660,6 +681,10 @@ impl TerminalScreenBuffer {
                 current_bold = cell.bold;
                 format_changed = true;
             }
+            if cell.dim != current_dim {
+                current_dim = cell.dim;
+                format_changed = true;
+            }
             if cell.underline != current_underline {
                 current_underline = cell.underline;
                 format_changed = true;
@@ -683,6 +708,7 @@ impl TerminalScreenBuffer {
                     fg: current_fg,
                     bg: current_bg,
                     bold: current_bold,
+                    dim: current_dim,
                     underline: current_underline,
                     italic: current_italic,
                     reverse: current_reverse,
@@ -699,6 +725,7 @@ impl TerminalScreenBuffer {
         if current_fg.is_some()
             || current_bg.is_some()
             || current_bold
+            || current_dim
             || current_underline
             || current_italic
             || current_reverse
@@ -721,6 +748,9 @@ impl TerminalScreenBuffer {
         if formatting.bold {
             codes.push("1".to_string());
         }
+        if formatting.dim {
+            codes.push("2".to_string());
+        }
         if formatting.italic {
             codes.push("3".to_string());
         }
@@ -736,24 +766,12 @@ impl TerminalScreenBuffer {
 
         // Foreground color
         if let Some(fg_color) = formatting.fg {
-            if fg_color < 8 {
-                codes.push(format!("{}", 30 + fg_color));
-            } else if fg_color < 16 {
-                codes.push(format!("{}", 90 + (fg_color - 8)));
-            } else {
-                codes.push(format!("38;5;{}", fg_color));
-            }
+            codes.push(fg_color.sgr_code(false));
         }
 
         // Background color
         if let Some(bg_color) = formatting.bg {
-            if bg_color < 8 {
-                codes.push(format!("{}", 40 + bg_color));
-            } else if bg_color < 16 {
-                codes.push(format!("{}", 100 + (bg_color - 8)));
-            } else {
-                codes.push(format!("48;5;{}", bg_color));
-            }
+            codes.push(bg_color.sgr_code(true));
         }
 
         if codes.is_empty() {
@@ -2159,8 +2177,8 @@ impl AnsiProcessor {
 
                 // If no explicit colors were set, use default reverse (white on black becomes black on white)
                 if cell.fg_color.is_none() && cell.bg_color.is_none() {
-                    cell.fg_color = Some(0); // Black foreground
-                    cell.bg_color = Some(15); // White background
+                    cell.fg_color = Some(TerminalColor::Named(0)); // Black foreground
+                    cell.bg_color = Some(TerminalColor::Named(15)); // White background
                 }
             }
 
@@ -2276,7 +2294,10 @@ impl AnsiProcessor {
             29 => self.terminal_state.current_attributes.strikethrough = false,
 
             // Standard 8-color foreground (30-37)
-            30..=37 => self.terminal_state.current_attributes.fg_color = Some((param - 30) as u8),
+            30..=37 => {
+                self.terminal_state.current_attributes.fg_color =
+                    Some(TerminalColor::Named((param - 30) as u8))
+            }
             38 => {
                 // Extended foreground color (handled in apply_sgr_sequence for 38;5;n and 38;2;r;g;b)
                 self.terminal_state.sgr_state.expecting_extended_fg = true;
@@ -2284,7 +2305,10 @@ impl AnsiProcessor {
             39 => self.terminal_state.current_attributes.fg_color = None, // Default foreground
 
             // Standard 8-color background (40-47)
-            40..=47 => self.terminal_state.current_attributes.bg_color = Some((param - 40) as u8),
+            40..=47 => {
+                self.terminal_state.current_attributes.bg_color =
+                    Some(TerminalColor::Named((param - 40) as u8))
+            }
             48 => {
                 // Extended background color (handled in apply_sgr_sequence for 48;5;n and 48;2;r;g;b)
                 self.terminal_state.sgr_state.expecting_extended_bg = true;
@@ -2332,10 +2356,12 @@ impl AnsiProcessor {
 
             // Bright/intense colors (90-97 foreground, 100-107 background)
             90..=97 => {
-                self.terminal_state.current_attributes.fg_color = Some((param - 90 + 8) as u8)
+                self.terminal_state.current_attributes.fg_color =
+                    Some(TerminalColor::Named((param - 90 + 8) as u8))
             }
             100..=107 => {
-                self.terminal_state.current_attributes.bg_color = Some((param - 100 + 8) as u8)
+                self.terminal_state.current_attributes.bg_color =
+                    Some(TerminalColor::Named((param - 100 + 8) as u8))
             }
 
             _ => {} // Ignore unknown parameters
@@ -2361,7 +2387,7 @@ impl AnsiProcessor {
                                 // 256-color palette: 38;5;n
                                 if let Some(&palette_index) = param_iter.next() {
                                     self.terminal_state.current_attributes.fg_color =
-                                        Some(palette_index as u8);
+                                        Some(TerminalColor::Palette(palette_index as u8));
                                 }
                             }
                             2 => {
@@ -2369,11 +2395,12 @@ impl AnsiProcessor {
                                 if let (Some(&r), Some(&g), Some(&b)) =
                                     (param_iter.next(), param_iter.next(), param_iter.next())
                                 {
-                                    // Convert RGB to nearest palette color for now
-                                    let palette_index =
-                                        Self::rgb_to_palette(r as u8, g as u8, b as u8);
                                     self.terminal_state.current_attributes.fg_color =
-                                        Some(palette_index);
+                                        Some(TerminalColor::Rgb {
+                                            r: r as u8,
+                                            g: g as u8,
+                                            b: b as u8,
+                                        });
                                 }
                             }
                             _ => {} // Unknown extended color mode
@@ -2388,7 +2415,7 @@ impl AnsiProcessor {
                                 // 256-color palette: 48;5;n
                                 if let Some(&palette_index) = param_iter.next() {
                                     self.terminal_state.current_attributes.bg_color =
-                                        Some(palette_index as u8);
+                                        Some(TerminalColor::Palette(palette_index as u8));
                                 }
                             }
                             2 => {
@@ -2396,11 +2423,12 @@ impl AnsiProcessor {
                                 if let (Some(&r), Some(&g), Some(&b)) =
                                     (param_iter.next(), param_iter.next(), param_iter.next())
                                 {
-                                    // Convert RGB to nearest palette color for now
-                                    let palette_index =
-                                        Self::rgb_to_palette(r as u8, g as u8, b as u8);
                                     self.terminal_state.current_attributes.bg_color =
-                                        Some(palette_index);
+                                        Some(TerminalColor::Rgb {
+                                            r: r as u8,
+                                            g: g as u8,
+                                            b: b as u8,
+                                        });
                                 }
                             }
                             _ => {} // Unknown extended color mode
@@ -2415,20 +2443,6 @@ impl AnsiProcessor {
         }
     }
 
-    /// F0306: Convert 24-bit RGB to nearest 256-color palette index
-    fn rgb_to_palette(r: u8, g: u8, b: u8) -> u8 {
-        // Standard colors 0-15 (handled by existing logic)
-        // 216 color cube: 16-231
-        // Grayscale: 232-255
-
-        // Simple approximation: map RGB to 6x6x6 color cube
-        let r6 = ((r as f32 / 255.0) * 5.0).round() as u8;
-        let g6 = ((g as f32 / 255.0) * 5.0).round() as u8;
-        let b6 = ((b as f32 / 255.0) * 5.0).round() as u8;
-
-        16 + (36 * r6) + (6 * g6) + b6
-    }
-
     // Helper to extract first parameter from Params
     fn get_param(params: &Params, index: usize) -> u16 {
         if index < params.len() {
@@ -3621,13 +3635,13 @@ mod tests {
         // Add some content with formatting
         buffer.content[0][0] = TerminalCell {
             character: 'H',
-            fg_color: Some(1), // Red foreground
+            fg_color: Some(TerminalColor::Named(1)), // Red foreground
             bold: true,
             ..Default::default()
         };
         buffer.content[0][1] = TerminalCell {
             character: 'i',
-            fg_color: Some(1),
+            fg_color: Some(TerminalColor::Named(1)),
             bold: true,
             ..Default::default()
         };
@@ -4207,58 +4221,58 @@ mod tests {
         processor.apply_sgr_param(30); // Black
         assert_eq!(
             processor.terminal_state.current_attributes.fg_color,
-            Some(0)
+            Some(TerminalColor::Named(0))
         );
 
         processor.apply_sgr_param(31); // Red
         assert_eq!(
             processor.terminal_state.current_attributes.fg_color,
-            Some(1)
+            Some(TerminalColor::Named(1))
         );
 
         processor.apply_sgr_param(37); // White
         assert_eq!(
             processor.terminal_state.current_attributes.fg_color,
-            Some(7)
+            Some(TerminalColor::Named(7))
         );
 
         // Test bright colors (foreground)
         processor.apply_sgr_param(90); // Bright black
         assert_eq!(
             processor.terminal_state.current_attributes.fg_color,
-            Some(8)
+            Some(TerminalColor::Named(8))
         );
 
         processor.apply_sgr_param(97); // Bright white
         assert_eq!(
             processor.terminal_state.current_attributes.fg_color,
-            Some(15)
+            Some(TerminalColor::Named(15))
         );
 
         // Test standard 8 colors (background)
         processor.apply_sgr_param(40); // Black background
         assert_eq!(
             processor.terminal_state.current_attributes.bg_color,
-            Some(0)
+            Some(TerminalColor::Named(0))
         );
 
         processor.apply_sgr_param(47); // White background
         assert_eq!(
             processor.terminal_state.current_attributes.bg_color,
-            Some(7)
+            Some(TerminalColor::Named(7))
         );
 
         // Test bright colors (background)
         processor.apply_sgr_param(100); // Bright black background
         assert_eq!(
             processor.terminal_state.current_attributes.bg_color,
-            Some(8)
+            Some(TerminalColor::Named(8))
         );
 
         processor.apply_sgr_param(107); // Bright white background
         assert_eq!(
             processor.terminal_state.current_attributes.bg_color,
-            Some(15)
+            Some(TerminalColor::Named(15))
         );
 
         // Test color reset
@@ -4376,33 +4390,62 @@ mod tests {
     fn test_rgb_to_palette_conversion() {
         // Test primary colors
         assert_eq!(
-            AnsiProcessor::rgb_to_palette(255, 0, 0),
+            crate::color_utils::rgb_to_ansi256(255, 0, 0),
             16 + 36 * 5 + 6 * 0 + 0
         ); // Red
         assert_eq!(
-            AnsiProcessor::rgb_to_palette(0, 255, 0),
+            crate::color_utils::rgb_to_ansi256(0, 255, 0),
             16 + 36 * 0 + 6 * 5 + 0
         ); // Green
         assert_eq!(
-            AnsiProcessor::rgb_to_palette(0, 0, 255),
+            crate::color_utils::rgb_to_ansi256(0, 0, 255),
             16 + 36 * 0 + 6 * 0 + 5
         ); // Blue
 
         // Test white and black
         assert_eq!(
-            AnsiProcessor::rgb_to_palette(255, 255, 255),
+            crate::color_utils::rgb_to_ansi256(255, 255, 255),
             16 + 36 * 5 + 6 * 5 + 5
         ); // White
         assert_eq!(
-            AnsiProcessor::rgb_to_palette(0, 0, 0),
+            crate::color_utils::rgb_to_ansi256(0, 0, 0),
             16 + 36 * 0 + 6 * 0 + 0
         ); // Black
 
-        // Test mid-range color
+        // Test mid-range colors: channels snap to the 0/95/135/175/215/255
+        // cube levels and grays to the 232-255 ramp
+        assert_eq!(
+            crate::color_utils::rgb_to_ansi256(128, 64, 0),
+            16 + 36 * 2 + 6 * 1 + 0
+        ); // Brown
+        assert_eq!(crate::color_utils::rgb_to_ansi256(128, 128, 128), 244); // Gray
+    }
+
+    #[test]
+    fn test_extended_colors_keep_full_precision() {
+        let mut processor = AnsiProcessor::new();
+        processor.process_string("\x1b[38;2;255;128;0;48;5;236m");
         assert_eq!(
-            AnsiProcessor::rgb_to_palette(128, 128, 128),
-            16 + 36 * 3 + 6 * 3 + 3
-        ); // Gray
+            processor.terminal_state.current_attributes.fg_color,
+            Some(TerminalColor::Rgb {
+                r: 255,
+                g: 128,
+                b: 0
+            })
+        );
+        assert_eq!(
+            processor.terminal_state.current_attributes.bg_color,
+            Some(TerminalColor::Palette(236))
+        );
+
+        let mut buffer = TerminalScreenBuffer::new(10, 1, 0);
+        buffer.content[0][0] = TerminalCell {
+            character: 'X',
+            fg_color: processor.terminal_state.current_attributes.fg_color,
+            bg_color: processor.terminal_state.current_attributes.bg_color,
+            ..Default::default()
+        };
+        assert!(buffer.to_content_lines(false)[0].starts_with("\x1b[0;38;2;255;128;0;48;5;236mX"));
     }
 
     #[test]
@@ -4420,7 +4463,7 @@ mod tests {
         processor.apply_sgr_param(31); // Red foreground
         assert_eq!(
             processor.terminal_state.current_attributes.fg_color,
-            Some(1)
+            Some(TerminalColor::Named(1))
         );
     }
 
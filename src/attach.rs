@@ -0,0 +1,479 @@
+//! Detached sessions.
+//!
+//! `boxmux --detached app.yaml` starts boxmux again in the background as a session
+//! server. That process starts a new session, moves its stdio onto a fresh
+//! pseudo-terminal and runs the usual TUI on it, so the PTY manager, the execution
+//! sources and every PTY's scrollback and terminal state live on after the terminal
+//! that started them is closed.
+//!
+//! `boxmux attach` connects a real terminal to the session over the control socket.
+//! After an `Attach` request the connection carries [`AttachFrame`] lines: terminal
+//! output from the session one way, keystrokes and resizes the other. Pressing
+//! [`DETACH_KEY`] or running `boxmux detach` lets go of the terminal and leaves the
+//! session running.
+
+use crate::model::common::SocketFunction;
+use crate::socket_loop::default_socket_path;
+use crate::socket_protocol::{SocketError, SocketErrorCode, SocketRequest, SocketResponse};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Environment variable handing the initial terminal size (`COLSxROWS`) to the session
+/// server started by `--detached`
+pub const SESSION_ENV_VAR: &str = "BOXMUX_SESSION";
+
+/// Key that detaches `boxmux attach` from its session: Ctrl-\
+pub const DETACH_KEY: u8 = 0x1c;
+
+/// How long `--detached` waits for the session's control socket to answer
+const SESSION_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Write timeout for output sent to the attached client, so a stuck client cannot
+/// stall the session's terminal
+const CLIENT_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// One line on an attached connection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachFrame {
+    /// Terminal output of the session
+    Output(String),
+    /// Keystrokes from the client's terminal
+    Input(String),
+    /// The client's terminal was resized
+    Resize { cols: u16, rows: u16 },
+    /// The session let go of the client; the connection closes after this line
+    Detached(String),
+}
+
+impl AttachFrame {
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Take the valid UTF-8 at the start of `pending` as text, leaving a character cut off
+/// at the end for the next read. Invalid bytes become U+FFFD.
+pub fn take_utf8(pending: &mut Vec<u8>) -> String {
+    let mut text = String::new();
+    loop {
+        match std::str::from_utf8(pending) {
+            Ok(valid) => {
+                text.push_str(valid);
+                pending.clear();
+                return text;
+            }
+            Err(error) => {
+                let valid_up_to = error.valid_up_to();
+                text.push_str(std::str::from_utf8(&pending[..valid_up_to]).unwrap_or_default());
+                match error.error_len() {
+                    None => {
+                        pending.drain(..valid_up_to);
+                        return text;
+                    }
+                    Some(invalid) => {
+                        text.push(char::REPLACEMENT_CHARACTER);
+                        pending.drain(..valid_up_to + invalid);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Open a pseudo-terminal of the given size, returning its master and slave sides.
+/// Neither is inherited by child processes.
+pub fn open_pty(cols: u16, rows: u16) -> io::Result<(File, File)> {
+    let mut master = -1;
+    let mut slave = -1;
+    let size = winsize(cols, rows);
+    let result = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &size,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let (master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+    for fd in [master.as_raw_fd(), slave.as_raw_fd()] {
+        unsafe {
+            libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+        }
+    }
+    Ok((master, slave))
+}
+
+fn winsize(cols: u16, rows: u16) -> libc::winsize {
+    libc::winsize {
+        ws_row: rows,
+        ws_col: cols,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+fn write_line(stream: &mut impl Write, line: &str) -> io::Result<()> {
+    stream.write_all(line.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.flush()
+}
+
+/// The session server's side of its terminal: relays the pseudo-terminal master to the
+/// attached client, if any
+pub struct AttachSession {
+    master: Mutex<File>,
+    /// Connection ID and stream of the attached client
+    client: Mutex<Option<(u64, UnixStream)>>,
+}
+
+impl AttachSession {
+    /// Start relaying `master`. Output is read and dropped while no client is attached,
+    /// so the TUI never blocks on a full terminal.
+    pub fn start(master: File) -> io::Result<Arc<Self>> {
+        let reader = master.try_clone()?;
+        let session = Arc::new(AttachSession {
+            master: Mutex::new(master),
+            client: Mutex::new(None),
+        });
+        let relay = session.clone();
+        thread::Builder::new()
+            .name("boxmux-attach-relay".to_string())
+            .spawn(move || relay.relay_output(reader))?;
+        Ok(session)
+    }
+
+    fn relay_output(&self, mut reader: File) {
+        let mut buffer = [0u8; 8192];
+        let mut pending = Vec::new();
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => {
+                    pending.extend_from_slice(&buffer[..read]);
+                    let text = take_utf8(&mut pending);
+                    if !text.is_empty() {
+                        self.send_to_client(&AttachFrame::Output(text));
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                // EIO once the terminal's last user is gone
+                Err(_) => break,
+            }
+        }
+        self.detach("session ended");
+    }
+
+    fn send_to_client(&self, frame: &AttachFrame) {
+        let mut client = self.client.lock().unwrap();
+        if let Some((id, stream)) = client.as_mut() {
+            if let Err(e) = write_line(stream, &frame.to_line()) {
+                log::warn!("Dropping attached client {}: {}", id, e);
+                let _ = stream.shutdown(Shutdown::Both);
+                *client = None;
+            }
+        }
+    }
+
+    /// Attach connection `id` as the session's client and size the terminal to it.
+    /// A client attached before is detached.
+    pub fn attach(&self, id: u64, stream: UnixStream, cols: u16, rows: u16) -> io::Result<()> {
+        stream.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT))?;
+        self.resize(cols, rows)?;
+        let previous = self.client.lock().unwrap().replace((id, stream));
+        if let Some((previous_id, mut previous)) = previous {
+            log::info!("Client {} replaces attached client {}", id, previous_id);
+            let frame = AttachFrame::Detached("attached from another terminal".to_string());
+            let _ = write_line(&mut previous, &frame.to_line());
+            let _ = previous.shutdown(Shutdown::Both);
+        }
+        Ok(())
+    }
+
+    /// Handle a frame sent by the attached client
+    pub fn handle_client_frame(&self, frame: AttachFrame) -> io::Result<()> {
+        match frame {
+            AttachFrame::Input(text) => {
+                let mut master = self.master.lock().unwrap();
+                master.write_all(text.as_bytes())?;
+                master.flush()
+            }
+            AttachFrame::Resize { cols, rows } => self.resize(cols, rows),
+            AttachFrame::Output(_) | AttachFrame::Detached(_) => Ok(()),
+        }
+    }
+
+    /// Resize the session's terminal; the TUI sees SIGWINCH and redraws
+    fn resize(&self, cols: u16, rows: u16) -> io::Result<()> {
+        let size = winsize(cols, rows);
+        let master = self.master.lock().unwrap();
+        if unsafe { libc::ioctl(master.as_raw_fd(), libc::TIOCSWINSZ, &size) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Let go of the attached client, telling it why. Returns whether one was attached.
+    pub fn detach(&self, reason: &str) -> bool {
+        let Some((id, mut stream)) = self.client.lock().unwrap().take() else {
+            return false;
+        };
+        log::info!("Detaching client {}: {}", id, reason);
+        let _ = write_line(
+            &mut stream,
+            &AttachFrame::Detached(reason.to_string()).to_line(),
+        );
+        let _ = stream.shutdown(Shutdown::Both);
+        true
+    }
+
+    /// Forget client `id` after its connection closed
+    pub fn client_closed(&self, id: u64) {
+        let mut client = self.client.lock().unwrap();
+        if client
+            .as_ref()
+            .is_some_and(|(client_id, _)| *client_id == id)
+        {
+            *client = None;
+        }
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.client.lock().unwrap().is_some()
+    }
+}
+
+static SESSION: once_cell::sync::Lazy<Mutex<Option<Arc<AttachSession>>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(None));
+
+/// This process's session, when it was started with `--detached`
+pub fn session() -> Option<Arc<AttachSession>> {
+    SESSION.lock().unwrap().clone()
+}
+
+pub fn set_session(session: Option<Arc<AttachSession>>) {
+    *SESSION.lock().unwrap() = session;
+}
+
+/// This process's session, or the error returned to `Attach` and `Detach` requests
+/// sent to an instance running in the foreground
+pub fn require_session() -> Result<Arc<AttachSession>, SocketError> {
+    session().ok_or_else(|| {
+        SocketError::new(
+            SocketErrorCode::InvalidRequest,
+            "This boxmux instance is not a detached session (start it with --detached)",
+        )
+    })
+}
+
+/// The terminal size handed to a session server by `--detached`, `None` in any other
+/// run. Removes the variable so scripts don't inherit it.
+pub fn take_session_size() -> Option<(u16, u16)> {
+    let value = std::env::var(SESSION_ENV_VAR).ok()?;
+    std::env::remove_var(SESSION_ENV_VAR);
+    Some(parse_size(&value).unwrap_or((80, 24)))
+}
+
+fn parse_size(value: &str) -> Option<(u16, u16)> {
+    let (cols, rows) = value.split_once('x')?;
+    Some((cols.parse().ok()?, rows.parse().ok()?))
+}
+
+/// Become the session server: start a new session, make a fresh pseudo-terminal the
+/// controlling terminal and stdio of this process, and relay its master side to
+/// attaching clients. Must run before any other thread touches the terminal.
+pub fn start_session_terminal(cols: u16, rows: u16) -> io::Result<()> {
+    if unsafe { libc::setsid() } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let (master, slave) = open_pty(cols, rows)?;
+    let slave_fd = slave.as_raw_fd();
+    unsafe {
+        if libc::ioctl(slave_fd, libc::TIOCSCTTY, 0) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        for fd in [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+            if libc::dup2(slave_fd, fd) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+    }
+    set_session(Some(AttachSession::start(master)?));
+    Ok(())
+}
+
+/// Run this boxmux invocation again as a detached session server and wait until its
+/// control socket answers. Returns the socket path.
+pub fn spawn_detached_session(
+    socket_path: Option<&str>,
+    yaml_path: &str,
+) -> Result<PathBuf, Box<dyn Error>> {
+    if let Some(path) = socket_path {
+        if UnixStream::connect(path).is_ok() {
+            return Err(format!("Socket {} is in use by another boxmux instance", path).into());
+        }
+    }
+
+    let (cols, rows) = crossterm::terminal::size().unwrap_or((80, 24));
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(std::env::args_os().skip(1))
+        .env(SESSION_ENV_VAR, format!("{}x{}", cols, rows))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let path = match socket_path {
+        Some(path) => PathBuf::from(path),
+        None => default_socket_path(Some(yaml_path), child.id()),
+    };
+    let deadline = Instant::now() + SESSION_STARTUP_TIMEOUT;
+    loop {
+        if UnixStream::connect(&path).is_ok() {
+            return Ok(path);
+        }
+        if let Some(status) = child.try_wait()? {
+            return Err(format!("Detached session exited during startup ({})", status).into());
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Detached session did not open {} within {}s",
+                path.display(),
+                SESSION_STARTUP_TIMEOUT.as_secs()
+            )
+            .into());
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn send_frame(writer: &Mutex<UnixStream>, frame: &AttachFrame) -> io::Result<()> {
+    write_line(&mut *writer.lock().unwrap(), &frame.to_line())
+}
+
+/// Attach this terminal to the session behind `socket_path` until the detach key is
+/// pressed, the session lets go of it or the session ends. Returns the note to print
+/// once the terminal is restored.
+pub fn run_attach_client(socket_path: &str) -> Result<String, Box<dyn Error>> {
+    use crossterm::{cursor, event, execute, terminal};
+
+    let (cols, rows) = terminal::size().unwrap_or((80, 24));
+    let mut stream = UnixStream::connect(socket_path)?;
+    let request = SocketRequest::new(SocketFunction::Attach { cols, rows });
+    write_line(&mut stream, &serde_json::to_string(&request)?)?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut first = String::new();
+    reader.read_line(&mut first)?;
+    let response = serde_json::from_str::<SocketResponse>(first.trim())
+        .map_err(|e| format!("Invalid response from boxmux ({}): {}", e, first.trim()))?;
+    if !response.is_ok() {
+        return Err(response.to_json().into());
+    }
+
+    // The session set up its own terminal long ago; mirror the modes it relies on
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(
+        stdout,
+        terminal::EnterAlternateScreen,
+        event::EnableMouseCapture
+    )?;
+
+    let writer = Arc::new(Mutex::new(stream));
+    let detaching = Arc::new(AtomicBool::new(false));
+    forward_input(writer.clone(), detaching.clone());
+    forward_resizes(writer);
+
+    let mut note = "[boxmux session ended]".to_string();
+    for line in reader.lines() {
+        let Ok(line) = line else { break };
+        match serde_json::from_str::<AttachFrame>(&line) {
+            Ok(AttachFrame::Output(text)) => {
+                stdout.write_all(text.as_bytes())?;
+                stdout.flush()?;
+            }
+            Ok(AttachFrame::Detached(reason)) => {
+                note = format!("[detached: {}]", reason);
+                break;
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Ignoring invalid attach frame: {}", e),
+        }
+    }
+    if detaching.load(Ordering::SeqCst) {
+        note = format!("[detached from {}]", socket_path);
+    }
+
+    let _ = execute!(
+        stdout,
+        event::DisableMouseCapture,
+        terminal::LeaveAlternateScreen,
+        cursor::Show
+    );
+    let _ = terminal::disable_raw_mode();
+    Ok(note)
+}
+
+/// Send keystrokes to the session until the detach key, which closes the connection
+fn forward_input(writer: Arc<Mutex<UnixStream>>, detaching: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut buffer = [0u8; 1024];
+        let mut pending = Vec::new();
+        loop {
+            let read = match stdin.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => read,
+            };
+            let input = &buffer[..read];
+            let detach_at = input.iter().position(|byte| *byte == DETACH_KEY);
+            pending.extend_from_slice(&input[..detach_at.unwrap_or(read)]);
+            let text = take_utf8(&mut pending);
+            if !text.is_empty() && send_frame(&writer, &AttachFrame::Input(text)).is_err() {
+                break;
+            }
+            if detach_at.is_some() {
+                detaching.store(true, Ordering::SeqCst);
+                let _ = writer.lock().unwrap().shutdown(Shutdown::Both);
+                break;
+            }
+        }
+    });
+}
+
+/// Pass terminal resizes on to the session
+fn forward_resizes(writer: Arc<Mutex<UnixStream>>) {
+    use signal_hook::{consts::signal::SIGWINCH, iterator::Signals};
+
+    let mut signals = match Signals::new([SIGWINCH]) {
+        Ok(signals) => signals,
+        Err(e) => {
+            log::warn!("Terminal resizes won't reach the session: {}", e);
+            return;
+        }
+    };
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            let (cols, rows) = crossterm::terminal::size().unwrap_or((80, 24));
+            if send_frame(&writer, &AttachFrame::Resize { cols, rows }).is_err() {
+                break;
+            }
+        }
+    });
+}
@@ -1,3 +1,4 @@
+use crate::theme::{theme_color, ThemeRole};
 use crossterm::style::{Color, SetBackgroundColor, SetForegroundColor};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,28 +46,36 @@ pub fn detect_terminal_theme() -> TerminalTheme {
         .unwrap_or(TerminalTheme::Dark)
 }
 
+/// The active theme's color for `role`, or `fallback` when there's no theme or it
+/// leaves the role out
+fn themed(role: ThemeRole, fallback: &str) -> String {
+    theme_color(role).unwrap_or_else(|| fallback.to_string())
+}
+
 // A selected box uses the SAME text/background as an unselected one — selection is
 // shown by the border (default_border_color) instead. The old code inverted the
 // selected box (bright_white on bright_black), but bright_black (ANSI 8) is remapped
 // to a light tint (cyan) by some palettes, making the selected box unreadable.
-pub fn default_fg_color(selected: bool) -> &'static str {
+pub fn default_fg_color(selected: bool) -> String {
     let _ = selected;
-    match detect_terminal_theme() {
+    let fallback = match detect_terminal_theme() {
         TerminalTheme::Light => "black",
         TerminalTheme::Dark => "white",
-    }
+    };
+    themed(ThemeRole::Fg, fallback)
 }
 
-pub fn default_bg_color(selected: bool) -> &'static str {
+pub fn default_bg_color(selected: bool) -> String {
     let _ = selected;
-    match detect_terminal_theme() {
+    let fallback = match detect_terminal_theme() {
         TerminalTheme::Light => "white",
         TerminalTheme::Dark => "black",
-    }
+    };
+    themed(ThemeRole::Bg, fallback)
 }
 
-pub fn default_border_color(selected: bool) -> &'static str {
-    match (detect_terminal_theme(), selected) {
+pub fn default_border_color(selected: bool) -> String {
+    let fallback = match (detect_terminal_theme(), selected) {
         // Unselected light border is a fixed cube gray (not bright_black=ANSI 8,
         // which palettes remap to cyan). The FOCUSED box gets a distinct blue border
         // (focus_blue) so it's clearly indicated even for menu boxes whose choices
@@ -75,26 +84,32 @@ pub fn default_border_color(selected: bool) -> &'static str {
         (TerminalTheme::Light, true) => "focus_blue",
         (TerminalTheme::Dark, false) => "white",
         (TerminalTheme::Dark, true) => "focus_blue",
+    };
+    if selected {
+        themed(ThemeRole::FocusedBorder, fallback)
+    } else {
+        themed(ThemeRole::Border, fallback)
     }
 }
 
-pub fn default_title_fg_color(selected: bool) -> &'static str {
+pub fn default_title_fg_color(selected: bool) -> String {
     let _ = selected;
-    match detect_terminal_theme() {
+    let fallback = match detect_terminal_theme() {
         TerminalTheme::Light => "black",
         TerminalTheme::Dark => "white",
-    }
+    };
+    themed(ThemeRole::TitleFg, fallback)
 }
 
-pub fn default_title_bg_color(selected: bool) -> &'static str {
-    let _ = selected;
-    match (detect_terminal_theme(), selected) {
+pub fn default_title_bg_color(selected: bool) -> String {
+    let fallback = match (detect_terminal_theme(), selected) {
         (TerminalTheme::Light, _) => "bright_white",
         // Dark: inactive title/tab bars blend with the dark panel; the active tab
         // (selected-title color) is the only highlighted one.
         (TerminalTheme::Dark, false) => "black",
         (TerminalTheme::Dark, true) => "black",
-    }
+    };
+    themed(ThemeRole::TitleBg, fallback)
 }
 
 /// Colors for the ACTIVE tab / selected title bar. The active tab must stand out
@@ -102,49 +117,94 @@ pub fn default_title_bg_color(selected: bool) -> &'static str {
 /// inverted title_fg/title_bg, which made the active tab's background = title_fg =
 /// white in dark mode). Light keeps its liked dark bar; dark uses the same blue
 /// accent as the selection so it's distinct yet still dark.
-pub fn default_selected_title_bg_color() -> &'static str {
+pub fn default_selected_title_bg_color() -> String {
     // Fixed dark gray (cube index 238) in both themes — same reasoning as the
     // selected-menu color: avoid base ANSI colors that palettes remap to a light
     // tint, so the active tab is always a dark bar with readable white text.
-    "dim_gray"
+    themed(ThemeRole::TabBg, "dim_gray")
 }
 
-pub fn default_selected_title_fg_color() -> &'static str {
-    "bright_white"
+pub fn default_selected_title_fg_color() -> String {
+    themed(ThemeRole::TabFg, "bright_white")
 }
 
 /// Active-tab background for the FOCUSED (selected) box — a distinct blue so the
 /// focused box stands out from the dim-gray active tabs of the other boxes.
-pub fn default_focused_title_bg_color() -> &'static str {
-    "focus_blue"
+pub fn default_focused_title_bg_color() -> String {
+    themed(ThemeRole::FocusedTabBg, "focus_blue")
 }
 
-pub fn default_menu_fg_color() -> &'static str {
-    default_fg_color(false)
+pub fn default_menu_fg_color() -> String {
+    themed(ThemeRole::MenuFg, &default_fg_color(false))
 }
 
-pub fn default_menu_bg_color() -> &'static str {
-    default_bg_color(false)
+pub fn default_menu_bg_color() -> String {
+    themed(ThemeRole::MenuBg, &default_bg_color(false))
 }
 
-pub fn default_selected_menu_fg_color() -> &'static str {
-    "bright_white"
+pub fn default_selected_menu_fg_color() -> String {
+    themed(ThemeRole::SelectedMenuFg, "bright_white")
 }
 
-pub fn default_selected_menu_bg_color() -> &'static str {
+pub fn default_selected_menu_bg_color() -> String {
     // Fixed dark gray (cube index 238) + white text reads reliably in both themes.
     // "blue" resolves to bright-blue (ANSI 12), which terminal palettes can render
     // as a light cyan — giving unreadable white-on-cyan selected items.
-    "dim_gray"
+    themed(ThemeRole::SelectedMenuBg, "dim_gray")
 }
 
-pub fn default_hover_fg_color() -> &'static str {
+pub fn default_hover_fg_color() -> String {
     // Dark text on the yellow hover background reads well in both themes.
-    "black"
+    themed(ThemeRole::HoverFg, "black")
+}
+
+pub fn default_hover_bg_color() -> String {
+    themed(ThemeRole::HoverBg, "bright_yellow")
+}
+
+/// Colors of a box in its error state (failed script or error-level alert)
+pub fn default_error_fg_color(selected: bool) -> String {
+    themed(
+        ThemeRole::ErrorFg,
+        if selected { "bright_white" } else { "white" },
+    )
+}
+
+pub fn default_error_bg_color(selected: bool) -> String {
+    themed(
+        ThemeRole::ErrorBg,
+        if selected { "bright_red" } else { "red" },
+    )
 }
 
-pub fn default_hover_bg_color() -> &'static str {
-    "bright_yellow"
+pub fn default_error_border_color(selected: bool) -> String {
+    themed(
+        ThemeRole::ErrorBorder,
+        if selected { "bright_white" } else { "white" },
+    )
+}
+
+pub fn default_error_title_fg_color(selected: bool) -> String {
+    themed(
+        ThemeRole::ErrorTitleFg,
+        if selected { "bright_red" } else { "red" },
+    )
+}
+
+pub fn default_error_title_bg_color(selected: bool) -> String {
+    themed(
+        ThemeRole::ErrorTitleBg,
+        if selected { "bright_white" } else { "white" },
+    )
+}
+
+pub fn default_scrollbar_track_color() -> String {
+    themed(ThemeRole::ScrollbarTrack, "bright_black")
+}
+
+/// The scrollbar knob follows the box's border unless the theme colors it
+pub fn scrollbar_thumb_color(border_color: &Option<String>) -> Option<String> {
+    theme_color(ThemeRole::ScrollbarThumb).or_else(|| border_color.clone())
 }
 
 /// Get foreground color code, returning empty string for transparent (None) colors
@@ -169,60 +229,147 @@ pub fn should_draw_color(color: &Option<String>) -> bool {
 }
 
 pub fn get_fg_color(color: &str) -> String {
-    match color {
-        "red" => format!("{}", SetForegroundColor(Color::Red)),
-        "green" => format!("{}", SetForegroundColor(Color::Green)),
-        "yellow" => format!("{}", SetForegroundColor(Color::Yellow)),
-        "blue" => format!("{}", SetForegroundColor(Color::Blue)),
-        "magenta" => format!("{}", SetForegroundColor(Color::Magenta)),
-        "cyan" => format!("{}", SetForegroundColor(Color::Cyan)),
-        "white" => format!("{}", SetForegroundColor(Color::White)),
-        "black" => format!("{}", SetForegroundColor(Color::Black)),
-        "dark_gray" | "gray" | "grey" => format!("{}", SetForegroundColor(Color::AnsiValue(8))),
+    format!("{}", SetForegroundColor(resolve_color(color)))
+}
+
+pub fn get_bg_color(color: &str) -> String {
+    format!("{}", SetBackgroundColor(resolve_color(color)))
+}
+
+/// Color to draw for a YAML color value; unknown values reset to the terminal default
+fn resolve_color(color: &str) -> Color {
+    parse_color(color)
+        .map(terminal_color)
+        .unwrap_or(Color::Reset)
+}
+
+/// Parse a color value: a name, `#rrggbb`, `rgb(r,g,b)` or `ansi(n)`
+pub fn parse_color(color: &str) -> Option<Color> {
+    let color = color.trim();
+    if let Some(hex) = color.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        });
+    }
+    if let Some(args) = function_args(color, "rgb") {
+        let channels: Vec<u8> = args
+            .split(',')
+            .map(|channel| channel.trim().parse::<u8>().ok())
+            .collect::<Option<_>>()?;
+        return match channels[..] {
+            [r, g, b] => Some(Color::Rgb { r, g, b }),
+            _ => None,
+        };
+    }
+    if let Some(args) = function_args(color, "ansi") {
+        return args.trim().parse::<u8>().ok().map(Color::AnsiValue);
+    }
+
+    let color = match color {
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        "black" => Color::Black,
+        "dark_gray" | "gray" | "grey" => Color::AnsiValue(8),
         // Fixed 256-color-cube grays (indices 16-255 are NOT remapped by terminal
         // palettes, unlike the 16 base ANSI colors), so they render predictably.
-        "dim_gray" => format!("{}", SetForegroundColor(Color::AnsiValue(238))),
+        "dim_gray" => Color::AnsiValue(238),
         // Fixed cube blue for the focused box's active tab (palette-independent).
-        "focus_blue" => format!("{}", SetForegroundColor(Color::AnsiValue(25))),
-        "reset" => format!("{}", SetForegroundColor(Color::Reset)),
-        "bright_black" => format!("{}", SetForegroundColor(Color::AnsiValue(8))),
-        "bright_red" => format!("{}", SetForegroundColor(Color::AnsiValue(9))),
-        "bright_green" => format!("{}", SetForegroundColor(Color::AnsiValue(10))),
-        "bright_yellow" => format!("{}", SetForegroundColor(Color::AnsiValue(11))),
-        "bright_blue" => format!("{}", SetForegroundColor(Color::AnsiValue(12))),
-        "bright_magenta" => format!("{}", SetForegroundColor(Color::AnsiValue(13))),
-        "bright_cyan" => format!("{}", SetForegroundColor(Color::AnsiValue(14))),
-        "bright_white" => format!("{}", SetForegroundColor(Color::AnsiValue(15))),
-        _ => format!("{}", SetForegroundColor(Color::Reset)),
+        "focus_blue" => Color::AnsiValue(25),
+        "reset" => Color::Reset,
+        "bright_black" => Color::AnsiValue(8),
+        "bright_red" => Color::AnsiValue(9),
+        "bright_green" => Color::AnsiValue(10),
+        "bright_yellow" => Color::AnsiValue(11),
+        "bright_blue" => Color::AnsiValue(12),
+        "bright_magenta" => Color::AnsiValue(13),
+        "bright_cyan" => Color::AnsiValue(14),
+        "bright_white" => Color::AnsiValue(15),
+        _ => return None,
+    };
+    Some(color)
+}
+
+/// Arguments of `name(...)`, ignoring case and surrounding whitespace
+fn function_args<'a>(color: &'a str, name: &str) -> Option<&'a str> {
+    let prefix = color.get(..name.len())?;
+    if !prefix.eq_ignore_ascii_case(name) {
+        return None;
     }
+    color[name.len()..]
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')
 }
 
-pub fn get_bg_color(color: &str) -> String {
+/// Whether the terminal renders 24-bit color, per `COLORTERM`
+pub fn supports_truecolor() -> bool {
+    static TRUECOLOR: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *TRUECOLOR.get_or_init(|| {
+        std::env::var("COLORTERM")
+            .map(|value| matches!(value.as_str(), "truecolor" | "24bit"))
+            .unwrap_or(false)
+    })
+}
+
+/// `color` as this terminal can show it: 24-bit colors become their nearest
+/// 256-color palette entry unless the terminal supports truecolor
+pub fn terminal_color(color: Color) -> Color {
+    fit_color(color, supports_truecolor())
+}
+
+/// `color` for a terminal with or without truecolor support
+pub fn fit_color(color: Color, truecolor: bool) -> Color {
     match color {
-        "red" => format!("{}", SetBackgroundColor(Color::Red)),
-        "green" => format!("{}", SetBackgroundColor(Color::Green)),
-        "yellow" => format!("{}", SetBackgroundColor(Color::Yellow)),
-        "blue" => format!("{}", SetBackgroundColor(Color::Blue)),
-        "magenta" => format!("{}", SetBackgroundColor(Color::Magenta)),
-        "cyan" => format!("{}", SetBackgroundColor(Color::Cyan)),
-        "white" => format!("{}", SetBackgroundColor(Color::White)),
-        "black" => format!("{}", SetBackgroundColor(Color::Black)),
-        "dark_gray" | "gray" | "grey" => format!("{}", SetBackgroundColor(Color::AnsiValue(8))),
-        // Fixed 256-color-cube grays (indices 16-255 are NOT remapped by terminal
-        // palettes, unlike the 16 base ANSI colors), so they render predictably.
-        "dim_gray" => format!("{}", SetBackgroundColor(Color::AnsiValue(238))),
-        // Fixed cube blue for the focused box's active tab (palette-independent).
-        "focus_blue" => format!("{}", SetBackgroundColor(Color::AnsiValue(25))),
-        "reset" => format!("{}", SetBackgroundColor(Color::Reset)),
-        "bright_black" => format!("{}", SetBackgroundColor(Color::AnsiValue(8))),
-        "bright_red" => format!("{}", SetBackgroundColor(Color::AnsiValue(9))),
-        "bright_green" => format!("{}", SetBackgroundColor(Color::AnsiValue(10))),
-        "bright_yellow" => format!("{}", SetBackgroundColor(Color::AnsiValue(11))),
-        "bright_blue" => format!("{}", SetBackgroundColor(Color::AnsiValue(12))),
-        "bright_magenta" => format!("{}", SetBackgroundColor(Color::AnsiValue(13))),
-        "bright_cyan" => format!("{}", SetBackgroundColor(Color::AnsiValue(14))),
-        "bright_white" => format!("{}", SetBackgroundColor(Color::AnsiValue(15))),
-        _ => format!("{}", SetBackgroundColor(Color::Reset)),
+        Color::Rgb { r, g, b } if !truecolor => Color::AnsiValue(rgb_to_ansi256(r, g, b)),
+        color => color,
+    }
+}
+
+/// Channel values of the xterm 6x6x6 color cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Nearest entry of the 6x6x6 color cube (palette indices 16-231) or the
+/// grayscale ramp (232-255)
+pub fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        [(r, r2), (g, g2), (b, b2)]
+            .iter()
+            .map(|&(a, b)| (i32::from(a) - i32::from(b)).pow(2))
+            .sum::<i32>()
+    };
+
+    let level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (i32::from(channel) - i32::from(CUBE_LEVELS[i])).abs())
+            .unwrap_or(0) as u8
+    };
+    let (lr, lg, lb) = (level(r), level(g), level(b));
+    let cube = (
+        CUBE_LEVELS[lr as usize],
+        CUBE_LEVELS[lg as usize],
+        CUBE_LEVELS[lb as usize],
+    );
+
+    // The ramp runs from 8 to 238 in steps of 10
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let step = ((average.saturating_sub(8) + 5) / 10).min(23) as u8;
+    let gray = 8 + 10 * step;
+
+    if distance((gray, gray, gray)) < distance(cube) {
+        232 + step
+    } else {
+        16 + 36 * lr + 6 * lg + lb
     }
 }
 
@@ -244,6 +391,9 @@ mod theme_override_tests {
     // would let cargo's parallel runner race them against each other.
     #[test]
     fn test_theme_override_and_faithful_dark_inverse() {
+        let _lock = crate::theme::THEME_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
         let _guard = ResetGuard;
         let dark = ["black", "bright_black"];
         let light = ["white", "bright_white"];
@@ -261,22 +411,22 @@ mod theme_override_tests {
         // title BACKGROUND must be dark, every text FOREGROUND light, in both states.
         for selected in [false, true] {
             assert!(
-                dark.contains(&default_bg_color(selected)),
+                dark.contains(&default_bg_color(selected).as_str()),
                 "dark panel bg must be dark (selected={selected}), got {}",
                 default_bg_color(selected)
             );
             assert!(
-                light.contains(&default_fg_color(selected)),
+                light.contains(&default_fg_color(selected).as_str()),
                 "dark text must be light (selected={selected})"
             );
             assert!(
-                dark.contains(&default_title_bg_color(selected)),
+                dark.contains(&default_title_bg_color(selected).as_str()),
                 "dark title bar bg must be dark (selected={selected}), got {}",
                 default_title_bg_color(selected)
             );
         }
         // Normal menu rows share the panel bg (dark).
-        assert!(dark.contains(&default_menu_bg_color()));
+        assert!(dark.contains(&default_menu_bg_color().as_str()));
 
         // Accents match the light theme exactly (same design language).
         set_theme_override(Some(TerminalTheme::Light));
@@ -289,3 +439,61 @@ mod theme_override_tests {
         assert_eq!(default_hover_fg_color(), light_hover_fg);
     }
 }
+
+#[cfg(test)]
+mod color_value_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color_values() {
+        let orange = Some(Color::Rgb {
+            r: 255,
+            g: 136,
+            b: 0,
+        });
+        assert_eq!(parse_color("#ff8800"), orange);
+        assert_eq!(parse_color("#FF8800"), orange);
+        assert_eq!(parse_color("rgb(255, 136, 0)"), orange);
+        assert_eq!(parse_color(" RGB(255,136,0) "), orange);
+        assert_eq!(parse_color("ansi(208)"), Some(Color::AnsiValue(208)));
+        assert_eq!(parse_color("red"), Some(Color::Red));
+        assert_eq!(parse_color("bright_white"), Some(Color::AnsiValue(15)));
+
+        for invalid in [
+            "#f80",
+            "#ff880g",
+            "#ff88€",
+            "rgb(256, 0, 0)",
+            "rgb(1, 2)",
+            "ansi(-1)",
+            "ansi 5",
+            "purple",
+        ] {
+            assert_eq!(parse_color(invalid), None, "{}", invalid);
+        }
+        assert_eq!(get_fg_color("purple"), get_fg_color("reset"));
+        assert_eq!(get_bg_color("ansi(236)"), "\x1b[48;5;236m");
+    }
+
+    #[test]
+    fn test_truecolor_falls_back_to_palette() {
+        let teal = Color::Rgb {
+            r: 0,
+            g: 128,
+            b: 128,
+        };
+        assert_eq!(fit_color(teal, true), teal);
+        assert_eq!(fit_color(teal, false), Color::AnsiValue(30));
+        assert_eq!(fit_color(Color::AnsiValue(30), false), Color::AnsiValue(30));
+        assert_eq!(fit_color(Color::Red, false), Color::Red);
+        assert_eq!(rgb_to_ansi256(255, 255, 255), 231);
+        assert_eq!(
+            rgb_to_ansi256(128, 0, 0),
+            88,
+            "128 is closest to the 135 level"
+        );
+        assert_eq!(rgb_to_ansi256(128, 128, 128), 244, "grays use the ramp");
+        assert_eq!(rgb_to_ansi256(95, 135, 175), 67, "exact cube entries");
+        assert_eq!(rgb_to_ansi256(238, 238, 238), 255);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::components::ComponentDimensions;
 use crate::model::muxbox::MuxBox;
 use crate::pty_manager::PtyManager;
-use crate::{Bounds, Cell, ScreenBuffer};
+use crate::{Bounds, Cell, ScreenBuffer, TextStyle};
 
 /// Border component for rendering box borders with various styles and states
 pub struct Border {
@@ -36,6 +36,17 @@ pub struct BorderCharSet {
 }
 
 impl BorderStyle {
+    /// The style named by a `border_style` value
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "single" => Some(BorderStyle::Single),
+            "double" => Some(BorderStyle::Double),
+            "thick" => Some(BorderStyle::Thick),
+            "rounded" => Some(BorderStyle::Rounded),
+            _ => None,
+        }
+    }
+
     pub fn get_charset(&self) -> BorderCharSet {
         match self {
             BorderStyle::Single => BorderCharSet {
@@ -93,7 +104,7 @@ impl Border {
         let bg_color_u8 = muxbox.bg_color.as_ref().and_then(|s| s.parse::<u8>().ok());
 
         Self {
-            style: BorderStyle::Single, // TODO: Make configurable from muxbox
+            style: muxbox.border_style(),
             color: border_color_u8,
             bg_color: bg_color_u8,
             resize_enabled: !locked,
@@ -151,6 +162,7 @@ impl Border {
                 ch: charset.top_left,
                 fg_color: border_color.clone(),
                 bg_color: bg_color.clone(),
+                style: TextStyle::default(),
             },
         );
 
@@ -161,6 +173,7 @@ impl Border {
                 ch: charset.top_right,
                 fg_color: border_color.clone(),
                 bg_color: bg_color.clone(),
+                style: TextStyle::default(),
             },
         );
 
@@ -171,6 +184,7 @@ impl Border {
                 ch: charset.bottom_left,
                 fg_color: border_color.clone(),
                 bg_color: bg_color.clone(),
+                style: TextStyle::default(),
             },
         );
 
@@ -186,6 +200,7 @@ impl Border {
                 },
                 fg_color: border_color.clone(),
                 bg_color: bg_color.clone(),
+                style: TextStyle::default(),
             },
         );
 
@@ -200,6 +215,7 @@ impl Border {
                     ch: charset.horizontal,
                     fg_color: border_color.clone(),
                     bg_color: bg_color.clone(),
+                    style: TextStyle::default(),
                 },
             );
             buffer.update(
@@ -209,6 +225,7 @@ impl Border {
                     ch: charset.horizontal,
                     fg_color: border_color.clone(),
                     bg_color: bg_color.clone(),
+                    style: TextStyle::default(),
                 },
             );
         }
@@ -222,6 +239,7 @@ impl Border {
                     ch: charset.vertical,
                     fg_color: border_color.clone(),
                     bg_color: bg_color.clone(),
+                    style: TextStyle::default(),
                 },
             );
             buffer.update(
@@ -231,6 +249,7 @@ impl Border {
                     ch: charset.vertical,
                     fg_color: border_color.clone(),
                     bg_color: bg_color.clone(),
+                    style: TextStyle::default(),
                 },
             );
         }
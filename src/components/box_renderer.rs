@@ -2,15 +2,16 @@ use crate::color_utils::{get_bg_color_transparent, get_fg_color_transparent, sho
 use crate::components::choice_menu::ChoiceMenu;
 use crate::components::renderable_content::{RenderableContent, SensitiveZone};
 use crate::components::{
-    ChartComponent, ChartConfig, ChartType, ComponentDimensions, HorizontalScrollbar,
+    BorderStyle, ChartComponent, ChartConfig, ChartType, ComponentDimensions, HorizontalScrollbar,
     VerticalScrollbar,
 };
 use crate::draw_utils::{
     content_size, draw_horizontal_line, draw_horizontal_line_with_tabs, draw_vertical_line,
-    fill_muxbox, print_with_color_and_background_at, render_wrapped_content, wrap_text_to_width,
+    fill_muxbox, print_styled_at, print_with_color_and_background_at, render_wrapped_content,
+    wrap_text_to_width,
 };
 use crate::model::choice::Choice;
-use crate::model::common::{Cell, ChoicesStreamTrait, ContentStreamTrait, StreamType};
+use crate::model::common::{Cell, ChoicesStreamTrait, ContentStreamTrait, StreamType, TextStyle};
 use crate::{AppContext, AppGraph, Bounds, MuxBox, ScreenBuffer};
 use std::collections::HashMap;
 
@@ -73,9 +74,12 @@ impl BoxDimensions {
         // `border_color.is_some()` check used the raw field (None for default boxes),
         // giving border_thickness=0 — so content/choices rendered ON the top
         // border/tab row and overdrew it. The tab bar is drawn ON that same top row
-        // (it replaces the border line), so it never adds a second row.
+        // (it replaces the border line), so it never adds a second row. Sides with
+        // their border turned off give their row or column to the content.
         let border_thickness = 1;
         let tab_height = 0;
+        let sides = muxbox.frame_sides();
+        let inset = |drawn: bool| if drawn { border_thickness } else { 0 };
 
         // Standard scrollbar reservation: always reserve one column on the right
         // and one row on the bottom of the content area.
@@ -83,10 +87,10 @@ impl BoxDimensions {
         let horizontal_scrollbar_height = 1;
 
         // Content bounds: inside the border, below the tab row. Inclusive coords.
-        let content_left = bounds.left() + border_thickness;
-        let content_top = bounds.top() + border_thickness + tab_height;
-        let content_right = bounds.right().saturating_sub(border_thickness);
-        let content_bottom = bounds.bottom().saturating_sub(border_thickness);
+        let content_left = bounds.left() + inset(sides.left);
+        let content_top = bounds.top() + inset(sides.top) + tab_height;
+        let content_right = bounds.right().saturating_sub(inset(sides.right));
+        let content_bottom = bounds.bottom().saturating_sub(inset(sides.bottom));
         let content_bounds = Bounds::new(content_left, content_top, content_right, content_bottom);
 
         // Viewable region excludes the reserved scrollbar column/row.
@@ -439,6 +443,11 @@ impl<'a> BoxRenderer<'a> {
             .unwrap_or(false)
     }
 
+    /// Dimensions of `bounds` for this box, leaving out the sides it draws no border on
+    fn component_dimensions(&self, bounds: &Bounds) -> ComponentDimensions {
+        ComponentDimensions::new(*bounds).with_border_sides(self.muxbox.frame_sides())
+    }
+
     /// Generate chart content if the muxbox has chart configuration
     /// This moves chart rendering responsibility from MuxBox to BoxRenderer
     fn generate_chart_content(&self, bounds: &Bounds) -> Option<String> {
@@ -454,8 +463,8 @@ impl<'a> BoxRenderer<'a> {
 
             let config = ChartConfig {
                 chart_type,
-                width: ComponentDimensions::new(*bounds).content_bounds().width(),
-                height: ComponentDimensions::new(*bounds).content_bounds().height(),
+                width: self.component_dimensions(bounds).content_bounds().width(),
+                height: self.component_dimensions(bounds).content_bounds().height(),
                 title: None, // Don't show chart title since muxbox already has the title
                 color: "blue".to_string(),
                 show_title: false, // Muxbox already shows title
@@ -619,8 +628,10 @@ impl<'a> BoxRenderer<'a> {
     ) {
         // EXACT copy of render_muxbox() logic - preserves ALL functionality
 
-        // Check for chart content first - charts take priority over streams
-        let chart_content = self.generate_chart_content(bounds);
+        // Check for chart content first - charts, then tables, take priority over streams
+        let chart_content = self
+            .generate_chart_content(bounds)
+            .or_else(|| self.muxbox.generate_table_content(bounds));
 
         // F0217: Extract content from streams using trait-based approach
         let (should_render_choices, content_str) = if chart_content.is_some() {
@@ -642,6 +653,7 @@ impl<'a> BoxRenderer<'a> {
                     | StreamType::RedirectedOutput(_)
                     | StreamType::PTY
                     | StreamType::Plugin(_)
+                    | StreamType::PluginLog(_)
                     | StreamType::ChoiceExecution(_)
                     | StreamType::PtySession(_)
                     | StreamType::OwnScript => Some(stream.get_content_lines().join("\n")),
@@ -678,8 +690,10 @@ impl<'a> BoxRenderer<'a> {
             .intersection(&screen_bounds)
             .unwrap_or_else(|| bounds.clone());
 
-        // F0208: Draw top border with tabs
-        if !tab_labels.is_empty() {
+        // F0208: Draw top border with tabs. Without a top border the row is only
+        // kept for a title or several tabs.
+        let border_sides = self.muxbox.border_sides();
+        if self.muxbox.frame_sides().top && !tab_labels.is_empty() {
             draw_horizontal_line_with_tabs(
                 bounds.top(),
                 bounds.left(),
@@ -690,6 +704,7 @@ impl<'a> BoxRenderer<'a> {
                 title_fg_color,
                 title_bg_color,
                 title_position,
+                self.muxbox.title_style.unwrap_or_default(),
                 tab_labels,
                 tab_close_buttons,
                 active_tab_index,
@@ -698,7 +713,9 @@ impl<'a> BoxRenderer<'a> {
                 self.muxbox.hovered_tab_target.as_ref(),
                 buffer,
             );
-        } else if should_draw_color(border_color) || should_draw_color(bg_color) {
+        } else if border_sides.top
+            && (should_draw_color(border_color) || should_draw_color(bg_color))
+        {
             draw_horizontal_line(
                 bounds.top(),
                 bounds.left(),
@@ -771,7 +788,7 @@ impl<'a> BoxRenderer<'a> {
             }
         } else if let Some(content) = content {
             let (content_width, content_height) = content_size(content);
-            let component_dims = ComponentDimensions::new(bounds);
+            let component_dims = self.component_dimensions(&bounds);
             let content_bounds = component_dims.content_bounds();
             let viewable_width = content_bounds.width();
             let viewable_height = content_bounds.height();
@@ -842,7 +859,7 @@ impl<'a> BoxRenderer<'a> {
         buffer: &mut ScreenBuffer,
     ) -> bool {
         let (content_width, content_height) = content_size(content);
-        let component_dims = ComponentDimensions::new(*bounds);
+        let component_dims = self.component_dimensions(bounds);
         let content_bounds = component_dims.content_bounds();
         let viewable_width = content_bounds.width();
         let viewable_height = content_bounds.height();
@@ -937,7 +954,7 @@ impl<'a> BoxRenderer<'a> {
         border_color: &Option<String>,
         buffer: &mut ScreenBuffer,
     ) {
-        let component_dims = ComponentDimensions::new(*bounds);
+        let component_dims = self.component_dimensions(bounds);
         let content_bounds = component_dims.content_bounds();
         let viewable_height = content_bounds.height();
 
@@ -968,38 +985,44 @@ impl<'a> BoxRenderer<'a> {
                 .take(viewable_width)
                 .collect::<String>();
 
-            print_with_color_and_background_at(
+            print_styled_at(
                 content_bounds.top() + line_idx + vertical_padding,
                 content_bounds.left() + 1 + horizontal_padding,
                 fg_color,
                 bg_color,
+                self.muxbox.text_style.unwrap_or_default(),
                 &visible_part,
                 buffer,
             );
         }
 
+        let border_sides = self.muxbox.border_sides();
         if should_draw_color(border_color) || should_draw_color(bg_color) {
-            draw_horizontal_line(
-                bounds.bottom(),
-                bounds.left(),
-                bounds.right(),
-                border_color,
-                bg_color,
-                buffer,
-            );
+            if border_sides.bottom {
+                draw_horizontal_line(
+                    bounds.bottom(),
+                    bounds.left(),
+                    bounds.right(),
+                    border_color,
+                    bg_color,
+                    buffer,
+                );
+            }
 
-            draw_vertical_line(
-                bounds.right(),
-                ComponentDimensions::new(*bounds)
-                    .vertical_scrollbar_track_bounds()
-                    .y1,
-                ComponentDimensions::new(*bounds)
-                    .vertical_scrollbar_track_bounds()
-                    .y2,
-                border_color,
-                bg_color,
-                buffer,
-            );
+            if border_sides.right {
+                draw_vertical_line(
+                    bounds.right(),
+                    self.component_dimensions(bounds)
+                        .vertical_scrollbar_track_bounds()
+                        .y1,
+                    self.component_dimensions(bounds)
+                        .vertical_scrollbar_track_bounds()
+                        .y2,
+                    border_color,
+                    bg_color,
+                    buffer,
+                );
+            }
         }
 
         // Draw scrollbars using components only when needed
@@ -1042,7 +1065,7 @@ impl<'a> BoxRenderer<'a> {
         _parent_bg_color: &Option<String>,
         buffer: &mut ScreenBuffer,
     ) -> bool {
-        let component_dims = ComponentDimensions::new(*bounds);
+        let component_dims = self.component_dimensions(bounds);
         let content_bounds = component_dims.content_bounds();
         let viewable_width = content_bounds.width();
         let wrapped_content = wrap_text_to_width(content, viewable_width);
@@ -1052,10 +1075,11 @@ impl<'a> BoxRenderer<'a> {
 
         render_wrapped_content(
             &wrapped_content,
-            bounds,
+            &content_bounds,
             vertical_scroll,
             fg_color,
             bg_color,
+            self.muxbox.text_style.unwrap_or_default(),
             buffer,
         );
 
@@ -1090,7 +1114,7 @@ impl<'a> BoxRenderer<'a> {
         bg_color: &Option<String>,
         buffer: &mut ScreenBuffer,
     ) {
-        let component_dims = ComponentDimensions::new(*bounds);
+        let component_dims = self.component_dimensions(bounds);
         let content_bounds = component_dims.content_bounds();
         let total_lines = content_lines.len();
         let vertical_padding = (viewable_height.saturating_sub(total_lines)) / 2;
@@ -1099,11 +1123,12 @@ impl<'a> BoxRenderer<'a> {
         for (i, line) in content_lines.iter().enumerate().take(viewable_height) {
             let visible_line = &line.chars().take(viewable_width).collect::<String>();
 
-            print_with_color_and_background_at(
+            print_styled_at(
                 content_bounds.top() + vertical_padding + i,
                 content_bounds.left() + 1 + horizontal_padding,
                 fg_color,
                 bg_color,
+                self.muxbox.text_style.unwrap_or_default(),
                 visible_line,
                 buffer,
             );
@@ -1138,7 +1163,7 @@ impl<'a> BoxRenderer<'a> {
                     .map(|line| line.len())
                     .max()
                     .unwrap_or(0);
-                let component_dims = ComponentDimensions::new(*bounds);
+                let component_dims = self.component_dimensions(bounds);
                 let content_bounds = component_dims.content_bounds();
                 let viewable_width = content_bounds.width();
                 max_content_width > viewable_width
@@ -1147,73 +1172,80 @@ impl<'a> BoxRenderer<'a> {
             }
         };
 
+        let sides = self.muxbox.border_sides();
+        let charset = self.muxbox.border_style().get_charset();
+
         // Draw bottom border
-        if has_horizontal_scrollbar {
-            // Skip middle section for horizontal scrollbar
-            draw_horizontal_line(
-                bounds.bottom(),
-                bounds.left(),
-                ComponentDimensions::new(*bounds)
-                    .horizontal_scrollbar_track_bounds()
-                    .x1,
-                border_color,
-                bg_color,
-                buffer,
-            );
-            draw_horizontal_line(
-                bounds.bottom(),
-                ComponentDimensions::new(*bounds)
-                    .horizontal_scrollbar_track_bounds()
-                    .x2,
-                bounds.right(),
-                border_color,
-                bg_color,
-                buffer,
-            );
-        } else {
-            draw_horizontal_line(
-                bounds.bottom(),
+        if sides.bottom {
+            if has_horizontal_scrollbar {
+                // Skip middle section for horizontal scrollbar
+                draw_horizontal_line(
+                    bounds.bottom(),
+                    bounds.left(),
+                    self.component_dimensions(bounds)
+                        .horizontal_scrollbar_track_bounds()
+                        .x1,
+                    border_color,
+                    bg_color,
+                    buffer,
+                );
+                draw_horizontal_line(
+                    bounds.bottom(),
+                    self.component_dimensions(bounds)
+                        .horizontal_scrollbar_track_bounds()
+                        .x2,
+                    bounds.right(),
+                    border_color,
+                    bg_color,
+                    buffer,
+                );
+            } else {
+                draw_horizontal_line(
+                    bounds.bottom(),
+                    bounds.left(),
+                    bounds.right(),
+                    border_color,
+                    bg_color,
+                    buffer,
+                );
+            }
+        }
+
+        // Draw left border
+        if sides.left {
+            draw_vertical_line(
                 bounds.left(),
-                bounds.right(),
+                self.component_dimensions(bounds)
+                    .vertical_scrollbar_track_bounds()
+                    .y1,
+                self.component_dimensions(bounds)
+                    .vertical_scrollbar_track_bounds()
+                    .y2,
                 border_color,
                 bg_color,
                 buffer,
             );
         }
 
-        // Draw left border
-        draw_vertical_line(
-            bounds.left(),
-            ComponentDimensions::new(*bounds)
-                .vertical_scrollbar_track_bounds()
-                .y1,
-            ComponentDimensions::new(*bounds)
-                .vertical_scrollbar_track_bounds()
-                .y2,
-            border_color,
-            bg_color,
-            buffer,
-        );
-
         // Draw right border - skip if vertical scrollbars are drawn
         let has_vertical_scrollbar = content.is_some() && {
             if let Some(content_str) = content {
                 let content_lines: Vec<&str> = content_str.lines().collect();
                 let content_height = content_lines.len();
-                let viewable_height = ComponentDimensions::new(*bounds).content_bounds().height();
+                let viewable_height = self.component_dimensions(bounds).content_bounds().height();
                 content_height > viewable_height
             } else {
                 false
             }
         };
 
-        if !has_vertical_scrollbar {
+        if sides.right && !has_vertical_scrollbar {
             draw_vertical_line(
                 bounds.right(),
-                ComponentDimensions::new(*bounds)
+                self.component_dimensions(bounds)
                     .vertical_scrollbar_track_bounds()
                     .y1,
-                ComponentDimensions::new(*bounds)
+                self.component_dimensions(bounds)
                     .vertical_scrollbar_track_bounds()
                     .y2,
                 border_color,
@@ -1222,45 +1254,111 @@ impl<'a> BoxRenderer<'a> {
             );
         }
 
-        // Draw corners
-        buffer.update(
-            bounds.left(),
-            bounds.top(),
-            Cell {
-                fg_color: border_color_code.clone(),
-                bg_color: bg_color_code.clone(),
-                ch: '┌',
-            },
-        );
-        buffer.update(
-            bounds.right(),
-            bounds.top(),
-            Cell {
-                fg_color: border_color_code.clone(),
-                bg_color: bg_color_code.clone(),
-                ch: '┐',
-            },
-        );
-        buffer.update(
-            bounds.left(),
-            bounds.bottom(),
-            Cell {
-                fg_color: border_color_code.clone(),
-                bg_color: bg_color_code.clone(),
-                ch: '└',
-            },
-        );
+        // Draw corners. A corner with one of its sides turned off continues the
+        // other side's line; the bottom-right one is the resize knob when unlocked.
+        let corners = [
+            (
+                bounds.left(),
+                bounds.top(),
+                sides.top,
+                sides.left,
+                charset.top_left,
+            ),
+            (
+                bounds.right(),
+                bounds.top(),
+                sides.top,
+                sides.right,
+                charset.top_right,
+            ),
+            (
+                bounds.left(),
+                bounds.bottom(),
+                sides.bottom,
+                sides.left,
+                charset.bottom_left,
+            ),
+            (
+                bounds.right(),
+                bounds.bottom(),
+                sides.bottom,
+                sides.right,
+                if locked {
+                    charset.bottom_right
+                } else {
+                    charset.resize_knob
+                },
+            ),
+        ];
+        for (x, y, horizontal, vertical, corner) in corners {
+            let ch = match (horizontal, vertical) {
+                (true, true) => corner,
+                (true, false) => charset.horizontal,
+                (false, true) => charset.vertical,
+                (false, false) => continue,
+            };
+            buffer.update(
+                x,
+                y,
+                Cell {
+                    fg_color: border_color_code.clone(),
+                    bg_color: bg_color_code.clone(),
+                    ch,
+                    style: TextStyle::default(),
+                },
+            );
+        }
 
-        // Bottom-right corner: show resize knob when unlocked
-        buffer.update(
-            bounds.right(),
-            bounds.bottom(),
-            Cell {
-                fg_color: border_color_code.clone(),
-                bg_color: bg_color_code.clone(),
-                ch: if locked { '┘' } else { '⋱' },
-            },
-        );
+        self.apply_border_glyphs(bounds, &border_color_code, buffer);
+    }
+
+    /// Swap the single-line glyphs drawn along the frame (border lines and tab
+    /// separators) for the box's `border_style`, and blank the line around a tab bar
+    /// kept without a top border
+    fn apply_border_glyphs(
+        &self,
+        bounds: &Bounds,
+        border_color_code: &str,
+        buffer: &mut ScreenBuffer,
+    ) {
+        let sides = self.muxbox.border_sides();
+        if sides.top && self.muxbox.border_style() == BorderStyle::Single {
+            return;
+        }
+        let charset = self.muxbox.border_style().get_charset();
+
+        let mut frame_cells = Vec::new();
+        let (left, top, right, bottom) =
+            (bounds.left(), bounds.top(), bounds.right(), bounds.bottom());
+        if self.muxbox.frame_sides().top {
+            frame_cells.extend((left..=right).map(|x| (x, top)));
+        }
+        if sides.bottom {
+            frame_cells.extend((left..=right).map(|x| (x, bottom)));
+        }
+        if sides.left {
+            frame_cells.extend((top..=bottom).map(|y| (left, y)));
+        }
+        if sides.right {
+            frame_cells.extend((top..=bottom).map(|y| (right, y)));
+        }
+
+        for (x, y) in frame_cells {
+            let Some(cell) = buffer.get(x, y) else {
+                continue;
+            };
+            if cell.fg_color != border_color_code {
+                continue;
+            }
+            let ch = match cell.ch {
+                '─' if y == top && !sides.top => ' ',
+                '─' => charset.horizontal,
+                '│' => charset.vertical,
+                _ => continue,
+            };
+            let cell = Cell { ch, ..cell.clone() };
+            buffer.update(x, y, cell);
+        }
     }
 
     /// Translate box-relative sensitive zones to absolute screen coordinates
@@ -1402,7 +1500,7 @@ impl<'a> BoxRenderer<'a> {
         };
 
         // Forward translate: inbox coordinates -> screen coordinates
-        let component_dims = ComponentDimensions::new(*bounds);
+        let component_dims = self.component_dimensions(bounds);
         let content_bounds = component_dims.content_bounds();
         let screen_x = (content_bounds.left() + 1 + horizontal_padding + inbox_x)
             .wrapping_sub(horizontal_offset);
@@ -1649,14 +1747,37 @@ impl<'a> BoxRenderer<'a> {
         for choice_index in vis_top..last_row {
             let choice = &choices[choice_index];
 
-            // Determine colors based on choice state (priority: selected > hovered > normal)
+            // Determine colors based on choice state (priority: selected > hovered > normal);
+            // the choice's own menu colors win over the box's
             let (fg_color, bg_color) = if choice.selected {
-                (selected_menu_fg_color, selected_menu_bg_color)
+                (
+                    choice
+                        .selected_menu_fg_color
+                        .as_ref()
+                        .or(selected_menu_fg_color.as_ref()),
+                    choice
+                        .selected_menu_bg_color
+                        .as_ref()
+                        .or(selected_menu_bg_color.as_ref()),
+                )
             } else if choice.hovered {
-                (highlighted_menu_fg_color, highlighted_menu_bg_color)
+                (
+                    choice
+                        .highlighted_menu_fg_color
+                        .as_ref()
+                        .or(highlighted_menu_fg_color.as_ref()),
+                    choice
+                        .highlighted_menu_bg_color
+                        .as_ref()
+                        .or(highlighted_menu_bg_color.as_ref()),
+                )
             } else {
-                (menu_fg_color, menu_bg_color)
+                (
+                    choice.menu_fg_color.as_ref().or(menu_fg_color.as_ref()),
+                    choice.menu_bg_color.as_ref().or(menu_bg_color.as_ref()),
+                )
             };
+            let (fg_color, bg_color) = (fg_color.cloned(), bg_color.cloned());
 
             // Screen position of the first visible cell of this choice row, via the
             // shared mapping. Skipped if scrolled out of the viewable window.
@@ -1674,8 +1795,8 @@ impl<'a> BoxRenderer<'a> {
             print_with_color_and_background_at(
                 screen_y,
                 screen_x,
-                fg_color,
-                bg_color,
+                &fg_color,
+                &bg_color,
                 &visible_line,
                 buffer,
             );
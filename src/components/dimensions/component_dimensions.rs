@@ -1,4 +1,5 @@
 use super::{HitRegion, Padding};
+use crate::model::common::BorderSides;
 use crate::Bounds;
 
 /// ComponentDimensions - Centralizes all UI component mathematical operations
@@ -14,6 +15,7 @@ use crate::Bounds;
 pub struct ComponentDimensions {
     bounds: Bounds,
     border_thickness: usize,
+    border_sides: BorderSides,
     padding: Padding,
     has_scrollbar_vertical: bool,
     has_scrollbar_horizontal: bool,
@@ -24,7 +26,8 @@ impl ComponentDimensions {
     pub fn new(bounds: Bounds) -> Self {
         Self {
             bounds,
-            border_thickness: 1,          // Default border thickness
+            border_thickness: 1, // Default border thickness
+            border_sides: BorderSides::all(),
             padding: Padding::uniform(1), // Default padding
             has_scrollbar_vertical: false,
             has_scrollbar_horizontal: false,
@@ -37,6 +40,12 @@ impl ComponentDimensions {
         self
     }
 
+    /// Sides that take up `border_thickness`; the others border the content directly
+    pub fn with_border_sides(mut self, sides: BorderSides) -> Self {
+        self.border_sides = sides;
+        self
+    }
+
     pub fn with_padding(mut self, padding: Padding) -> Self {
         self.padding = padding;
         self
@@ -61,11 +70,10 @@ impl ComponentDimensions {
     /// Get content area bounds (inside border and padding)
     /// Replaces: bounds.left() + 2, bounds.right() - 2, etc.
     pub fn content_bounds(&self) -> Bounds {
-        let border = self.border_thickness;
-        let left_offset = border + self.padding.left;
-        let right_offset = border + self.padding.right;
-        let top_offset = border + self.padding.top;
-        let bottom_offset = border + self.padding.bottom;
+        let left_offset = self.border_width(self.border_sides.left) + self.padding.left;
+        let right_offset = self.border_width(self.border_sides.right) + self.padding.right;
+        let top_offset = self.border_width(self.border_sides.top) + self.padding.top;
+        let bottom_offset = self.border_width(self.border_sides.bottom) + self.padding.bottom;
 
         // Handle scrollbar space
         let scrollbar_vertical_space = if self.has_scrollbar_vertical { 1 } else { 0 };
@@ -145,15 +153,26 @@ impl ComponentDimensions {
     /// Calculate inside border coordinates (border + 1)
     /// Replaces: bounds.left() + 1, bounds.right() - 1, etc.
     pub fn inside_border_bounds(&self) -> Bounds {
-        let thickness = self.border_thickness;
         Bounds::new(
-            self.bounds.x1 + thickness,
-            self.bounds.y1 + thickness,
-            self.bounds.x2.saturating_sub(thickness),
-            self.bounds.y2.saturating_sub(thickness),
+            self.bounds.x1 + self.border_width(self.border_sides.left),
+            self.bounds.y1 + self.border_width(self.border_sides.top),
+            self.bounds
+                .x2
+                .saturating_sub(self.border_width(self.border_sides.right)),
+            self.bounds
+                .y2
+                .saturating_sub(self.border_width(self.border_sides.bottom)),
         )
     }
 
+    fn border_width(&self, drawn: bool) -> usize {
+        if drawn {
+            self.border_thickness
+        } else {
+            0
+        }
+    }
+
     /// Hit test a point against component regions
     /// Centralizes all coordinate hit testing logic
     pub fn hit_test(&self, x: usize, y: usize) -> HitRegion {
@@ -96,7 +96,7 @@ impl HorizontalScrollbar {
             print_with_color_and_background_at(
                 y,
                 x,
-                &Some("bright_black".to_string()),
+                &Some(crate::color_utils::default_scrollbar_track_color()),
                 bg_color,
                 self.track_char,
                 buffer,
@@ -111,6 +111,8 @@ impl HorizontalScrollbar {
                 track_width,
             );
 
+            let knob_color = crate::color_utils::scrollbar_thumb_color(border_color);
+
             // Draw proportional horizontal scroll knob (exact copy from existing implementation)
             for i in 0..knob_size {
                 let knob_x = start_x + knob_position + i;
@@ -118,7 +120,7 @@ impl HorizontalScrollbar {
                     print_with_color_and_background_at(
                         y,
                         knob_x,
-                        border_color,
+                        &knob_color,
                         bg_color,
                         self.knob_char,
                         buffer,
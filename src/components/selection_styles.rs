@@ -641,6 +641,20 @@ mod tests {
             execution_mode: crate::model::common::ExecutionMode::Immediate,
             redirect_output: None,
             append_output: None,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         };
 
         let (fg, bg, text) = renderer.calculate_style_colors_and_text(
@@ -679,6 +693,20 @@ mod tests {
             execution_mode: crate::model::common::ExecutionMode::Immediate,
             redirect_output: None,
             append_output: None,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         };
 
         let (fg, bg, text) = renderer.calculate_style_colors_and_text(
@@ -713,6 +741,20 @@ mod tests {
             execution_mode: crate::model::common::ExecutionMode::Immediate,
             redirect_output: None,
             append_output: None,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         };
 
         let (fg, bg, text) = renderer.calculate_style_colors_and_text(
@@ -743,6 +785,20 @@ mod tests {
             execution_mode: crate::model::common::ExecutionMode::Immediate,
             redirect_output: None,
             append_output: None,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         };
 
         let (fg, bg, text) = renderer.calculate_style_colors_and_text(
@@ -776,6 +832,20 @@ mod tests {
             execution_mode: crate::model::common::ExecutionMode::Immediate,
             redirect_output: None,
             append_output: None,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         };
 
         let (fg, bg, text) = renderer.calculate_style_colors_and_text(
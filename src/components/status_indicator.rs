@@ -23,6 +23,8 @@ pub enum StatusType {
     ScriptCompleted,
     /// Script execution failed
     ScriptFailed,
+    /// A warning-level alert rule matched
+    Warning,
     /// Custom status with user-defined indicator
     Custom(String),
     /// No status indicator
@@ -39,6 +41,7 @@ impl StatusType {
             StatusType::ScriptRunning => "▶️",
             StatusType::ScriptCompleted => "✅",
             StatusType::ScriptFailed => "❌",
+            StatusType::Warning => "⚠️",
             StatusType::Custom(indicator) => indicator,
             StatusType::None => "",
         }
@@ -51,7 +54,8 @@ impl StatusType {
 }
 
 impl StatusIndicator {
-    /// Create status indicator from MuxBox and PTY manager state
+    /// Create status indicator from MuxBox and PTY manager state, or from the box's
+    /// matching alert rule
     pub fn from_muxbox(muxbox: &MuxBox, pty_manager: Option<&PtyManager>) -> Self {
         if muxbox.execution_mode.is_pty() {
             let indicator_type = if let Some(pty_manager) = pty_manager {
@@ -73,6 +77,12 @@ impl StatusIndicator {
                 process_info,
                 custom_text: None,
             }
+        } else if let Some(alert) = &muxbox.alert {
+            Self {
+                indicator_type: crate::alerts::alert_status_type(alert),
+                process_info: None,
+                custom_text: alert.message.clone(),
+            }
         } else {
             Self {
                 indicator_type: StatusType::None,
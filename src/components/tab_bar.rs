@@ -1,7 +1,8 @@
 use crate::draw_utils::{
-    draw_horizontal_line, fill_horizontal_background, print_with_color_and_background_at,
+    draw_horizontal_line, fill_horizontal_background, print_styled_at,
+    print_with_color_and_background_at,
 };
-use crate::model::common::ScreenBuffer;
+use crate::model::common::{ScreenBuffer, TextStyle};
 
 /// Tab bar component for displaying stream tabs with scrolling support
 pub struct TabBar;
@@ -200,6 +201,7 @@ impl TabBar {
         bg_color: &Option<String>,
         title_fg_color: &Option<String>,
         title_bg_color: &Option<String>,
+        title_style: TextStyle,
         tab_labels: &[String],
         tab_close_buttons: &[bool],
         active_tab_index: usize,
@@ -279,6 +281,7 @@ impl TabBar {
                     matches!(hovered_target, Some(TabHoverTarget::CloseButton(index)) if *index == tab.index),
                     title_fg_color,
                     title_bg_color,
+                    title_style,
                     buffer,
                 );
 
@@ -377,6 +380,7 @@ impl TabBar {
         is_close_hovered: bool,
         title_fg_color: &Option<String>,
         title_bg_color: &Option<String>,
+        title_style: TextStyle,
         buffer: &mut ScreenBuffer,
     ) {
         // Active tab uses theme-aware selected-title colors instead of inverting
@@ -384,11 +388,11 @@ impl TabBar {
         // which is white in dark mode (a glaring light title bar). The selected-title
         // defaults keep light's liked dark bar and give dark a dark accent bar. The
         // FOCUSED box's active tab uses a distinct color so the focused box stands out.
-        let active_fg = Some(crate::color_utils::default_selected_title_fg_color().to_string());
+        let active_fg = Some(crate::color_utils::default_selected_title_fg_color());
         let active_bg = Some(if box_selected {
-            crate::color_utils::default_focused_title_bg_color().to_string()
+            crate::color_utils::default_focused_title_bg_color()
         } else {
-            crate::color_utils::default_selected_title_bg_color().to_string()
+            crate::color_utils::default_selected_title_bg_color()
         });
         let (base_tab_fg, base_tab_bg): (&Option<String>, &Option<String>) = if is_active {
             (&active_fg, &active_bg)
@@ -441,7 +445,7 @@ impl TabBar {
 
             // Draw label with left padding
             let label_content = format!(" {}", final_label);
-            print_with_color_and_background_at(y, x, &tab_fg, &tab_bg, &label_content, buffer);
+            print_styled_at(y, x, &tab_fg, &tab_bg, title_style, &label_content, buffer);
 
             // Draw close button with aesthetic spacing
             let (close_fg, close_bg) = Self::hover_colors(&tab_fg, &tab_bg, is_close_hovered);
@@ -479,7 +483,7 @@ impl TabBar {
             };
 
             // Draw tab text
-            print_with_color_and_background_at(y, x, &tab_fg, &tab_bg, &display_text, buffer);
+            print_styled_at(y, x, &tab_fg, &tab_bg, title_style, &display_text, buffer);
         }
     }
 
@@ -647,8 +651,8 @@ impl TabBar {
     ) -> (Option<String>, Option<String>) {
         if is_hovered {
             (
-                Some(crate::color_utils::default_hover_fg_color().to_string()),
-                Some(crate::color_utils::default_hover_bg_color().to_string()),
+                Some(crate::color_utils::default_hover_fg_color()),
+                Some(crate::color_utils::default_hover_bg_color()),
             )
         } else {
             (fg_color.clone(), bg_color.clone())
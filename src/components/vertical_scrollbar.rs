@@ -96,7 +96,7 @@ impl VerticalScrollbar {
             print_with_color_and_background_at(
                 y,
                 x,
-                &Some("bright_black".to_string()),
+                &Some(crate::color_utils::default_scrollbar_track_color()),
                 bg_color,
                 self.track_char,
                 buffer,
@@ -111,6 +111,8 @@ impl VerticalScrollbar {
                 track_height,
             );
 
+            let knob_color = crate::color_utils::scrollbar_thumb_color(border_color);
+
             // Draw proportional vertical scroll knob (exact copy from existing implementation)
             for i in 0..knob_size {
                 let knob_y = start_y + knob_position + i;
@@ -118,7 +120,7 @@ impl VerticalScrollbar {
                     print_with_color_and_background_at(
                         knob_y,
                         x,
-                        border_color,
+                        &knob_color,
                         bg_color,
                         self.knob_char,
                         buffer,
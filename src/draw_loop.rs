@@ -5,7 +5,13 @@ use crate::model::app::{
     save_muxbox_content_to_yaml, save_muxbox_scroll_to_yaml,
 };
 use crate::model::choice::Choice;
-use crate::model::common::{InputBounds, StreamSourceTrait, StreamType};
+use crate::model::common::{
+    BatchSourceState, BatchStatus, ExecutionMode, ExecutionPtyStatus, ExecutionThreadStatus,
+    InputBounds, OutputFormat, PtySourceState, SourceAction, SourceState, StreamSourceTrait,
+    StreamType, StreamUpdate, ThreadSourceState,
+};
+use crate::model::layout::Layout;
+use crate::plugin::{PluginAction, PluginEvent};
 use crate::thread_manager::Runnable;
 use crate::{
     apply_buffer, apply_buffer_if_changed, handle_keypress, AppContext, MuxBox, ScreenBuffer,
@@ -16,6 +22,7 @@ use crossterm::{
     terminal::{enable_raw_mode, EnterAlternateScreen},
     ExecutableCommand,
 };
+use std::collections::HashMap;
 use std::io::stdout;
 use std::io::Stdout;
 use std::sync::{mpsc, Mutex};
@@ -88,6 +95,7 @@ fn compute_render_signature(app_context: &AppContext) -> u64 {
     use std::hash::{Hash, Hasher};
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
     app_context.app.hash(&mut hasher);
+    crate::alerts::hash_flash_phases(&app_context.app, std::time::SystemTime::now(), &mut hasher);
     // Terminal size affects layout even without an explicit resize message.
     let screen = crate::utils::screen_bounds();
     screen.x2.hash(&mut hasher);
@@ -507,7 +515,7 @@ pub fn calculate_new_position(
 // F0188: Helper functions to determine if click is on scroll knob (not just track)
 fn is_on_vertical_knob(muxbox: &MuxBox, click_y: usize) -> bool {
     let muxbox_bounds = muxbox.bounds();
-    let viewable_height = muxbox_bounds.height().saturating_sub(4);
+    let viewable_height = muxbox.content_bounds().height();
 
     // Get content dimensions to calculate knob position and size
     // F0214: Stream-Based Scrollbar Calculations - Use active stream content
@@ -559,7 +567,7 @@ fn is_on_vertical_knob(muxbox: &MuxBox, click_y: usize) -> bool {
 
 fn is_on_horizontal_knob(muxbox: &MuxBox, click_x: usize) -> bool {
     let muxbox_bounds = muxbox.bounds();
-    let viewable_width = muxbox_bounds.width().saturating_sub(4);
+    let viewable_width = muxbox.content_bounds().width();
 
     // Get content width to calculate knob position and size
     // F0214: Stream-Based Scrollbar Calculations - Use active stream content
@@ -615,6 +623,541 @@ fn is_on_horizontal_knob(muxbox: &MuxBox, click_x: usize) -> bool {
 lazy_static! {
     static ref GLOBAL_SCREEN: Mutex<Option<Stdout>> = Mutex::new(None);
     static ref GLOBAL_BUFFER: Mutex<Option<ScreenBuffer>> = Mutex::new(None);
+    static ref PLUGIN_REFRESH_STATE: Mutex<HashMap<String, PluginRefreshState>> =
+        Mutex::new(HashMap::new());
+}
+
+/// When a plugin box was last rendered, at which bounds, which revision of its plugin
+/// process log it shows, and whether a render is still running on a worker thread
+#[derive(Debug, Clone, Copy)]
+pub struct PluginRefreshState {
+    pub rendered_at: std::time::Instant,
+    pub bounds: crate::Bounds,
+    pub log_revision: u64,
+    pub in_flight: bool,
+}
+
+/// Work for the plugin process behind a box, run on a worker thread by
+/// `run_plugin_request`: deliver `event` if any, then render when `render` is set or
+/// the event handler asks to
+#[derive(Debug, Clone)]
+pub struct PluginRequest {
+    pub muxbox_id: String,
+    pub bounds: crate::Bounds,
+    pub event: Option<PluginEvent>,
+    pub render: bool,
+}
+
+/// Re-render the plugin components of the active layout that are due: never rendered,
+/// resized since the last render, or older than their `refresh_interval`. Boxes without
+/// a `refresh_interval` only re-render on resize. Before re-rendering, the plugin gets a
+/// `MuxBoxResize` or `Timer` event for the reason the box is due; the actions its handler
+/// requests are appended to `actions`. Boxes backed by a plugin process are not rendered
+/// here: a `PluginRequest` is appended to `requests` instead, and the box is not due
+/// again until its `PluginOutput` is applied. Returns true if any box was refreshed.
+pub fn refresh_due_plugin_components(
+    app_context: &mut AppContext,
+    refresh_state: &mut HashMap<String, PluginRefreshState>,
+    actions: &mut Vec<PluginAction>,
+    requests: &mut Vec<PluginRequest>,
+) -> bool {
+    let now = std::time::Instant::now();
+    let due: Vec<(String, crate::Bounds, Option<PluginEvent>)> =
+        match app_context.app.get_active_layout() {
+            Some(layout) => layout
+                .get_all_muxboxes()
+                .into_iter()
+                .filter(|muxbox| muxbox.plugin_component.is_some())
+                .filter_map(|muxbox| {
+                    let bounds = muxbox.bounds();
+                    let event = match refresh_state.get(&muxbox.id) {
+                        None => None,
+                        Some(state) if state.in_flight => return None,
+                        Some(state) if state.bounds != bounds => {
+                            Some(PluginEvent::MuxBoxResize { new_bounds: bounds })
+                        }
+                        Some(state) => match muxbox.refresh_interval {
+                            Some(interval)
+                                if now.duration_since(state.rendered_at)
+                                    >= std::time::Duration::from_millis(interval) =>
+                            {
+                                Some(PluginEvent::Timer { interval })
+                            }
+                            _ => return None,
+                        },
+                    };
+                    Some((muxbox.id.clone(), bounds, event))
+                })
+                .collect(),
+            None => return false,
+        };
+
+    let mut refreshed = false;
+    for (muxbox_id, bounds, event) in due {
+        let in_flight = is_process_plugin_box(app_context, &muxbox_id);
+        if in_flight {
+            requests.push(PluginRequest {
+                muxbox_id: muxbox_id.clone(),
+                bounds,
+                event,
+                render: true,
+            });
+        } else {
+            // The plugin sees the app as it was before this refresh pass
+            let plugin_app_context = app_context.clone();
+            if let Some(muxbox) = app_context.app.get_muxbox_by_id_mut(&muxbox_id) {
+                if let Some(event) = event {
+                    match muxbox.dispatch_plugin_event(&plugin_app_context, &bounds, &event) {
+                        Some(Ok(outcome)) => actions.extend(outcome.actions),
+                        Some(Err(e)) => {
+                            log::warn!("Plugin event handler failed in muxbox {}: {}", muxbox_id, e)
+                        }
+                        None => {}
+                    }
+                }
+                refreshed |= muxbox.refresh_plugin_component(&plugin_app_context, &bounds);
+            }
+        }
+        let log_revision = refresh_state
+            .get(&muxbox_id)
+            .map(|state| state.log_revision)
+            .unwrap_or_default();
+        refresh_state.insert(
+            muxbox_id,
+            PluginRefreshState {
+                rendered_at: now,
+                bounds,
+                log_revision,
+                in_flight,
+            },
+        );
+    }
+
+    refreshed | sync_plugin_logs(app_context, refresh_state)
+}
+
+/// Copy new stderr output of process plugins into the log tab of the boxes using them
+fn sync_plugin_logs(
+    app_context: &mut AppContext,
+    refresh_state: &mut HashMap<String, PluginRefreshState>,
+) -> bool {
+    let updates: Vec<(String, u64, Vec<String>)> = {
+        let Some(layout) = app_context.app.get_active_layout() else {
+            return false;
+        };
+        let Ok(registry) = app_context.plugin_registry.lock() else {
+            return false;
+        };
+        layout
+            .get_all_muxboxes()
+            .into_iter()
+            .filter_map(|muxbox| {
+                let component_type = muxbox.plugin_component.as_ref()?;
+                let state = refresh_state.get(&muxbox.id)?;
+                let revision = registry.component_log_revision(component_type)?;
+                (revision != state.log_revision).then(|| {
+                    (
+                        muxbox.id.clone(),
+                        revision,
+                        registry.component_log(component_type),
+                    )
+                })
+            })
+            .collect()
+    };
+
+    let changed = !updates.is_empty();
+    for (muxbox_id, revision, lines) in updates {
+        if let Some(muxbox) = app_context.app.get_muxbox_by_id_mut(&muxbox_id) {
+            muxbox.show_plugin_log(lines);
+        }
+        if let Some(state) = refresh_state.get_mut(&muxbox_id) {
+            state.log_revision = revision;
+        }
+    }
+    changed
+}
+
+/// Drop the refresh state of each box that `old` has and `new` doesn't, e.g. after
+/// `RemoveBox` or a YAML reload
+pub fn forget_removed_plugin_boxes(
+    refresh_state: &mut HashMap<String, PluginRefreshState>,
+    old: &crate::model::app::App,
+    new: &crate::model::app::App,
+) {
+    refresh_state.retain(|muxbox_id, _| {
+        new.get_muxbox_by_id(muxbox_id).is_some() || old.get_muxbox_by_id(muxbox_id).is_none()
+    });
+}
+
+/// [`forget_removed_plugin_boxes`] for the plugin boxes the draw loop renders
+pub fn forget_removed_boxes(old: &crate::model::app::App, new: &crate::model::app::App) {
+    forget_removed_plugin_boxes(&mut PLUGIN_REFRESH_STATE.lock().unwrap(), old, new);
+}
+
+/// Whether the plugin component of a box is answered by a plugin process
+fn is_process_plugin_box(app_context: &AppContext, muxbox_id: &str) -> bool {
+    let Some(component_type) = app_context
+        .app
+        .get_muxbox_by_id(muxbox_id)
+        .and_then(|muxbox| muxbox.plugin_component.as_deref())
+    else {
+        return false;
+    };
+    app_context
+        .plugin_registry
+        .lock()
+        .is_ok_and(|registry| registry.process_plugin(component_type).is_some())
+}
+
+/// Carry out a `PluginRequest`; this is what its worker thread runs. The result is the
+/// `PluginOutput` message to post back to the draw loop.
+pub fn run_plugin_request(app_context: &AppContext, request: &PluginRequest) -> Message {
+    let Some(muxbox) = app_context.app.get_muxbox_by_id(&request.muxbox_id) else {
+        return Message::PluginOutput(request.muxbox_id.clone(), None, Vec::new());
+    };
+    let mut render = request.render;
+    let mut actions = Vec::new();
+    if let Some(event) = &request.event {
+        match muxbox.dispatch_plugin_event(app_context, &request.bounds, event) {
+            Some(Ok(outcome)) => {
+                render |= outcome.rerender;
+                actions = outcome.actions;
+            }
+            // Shown like a render error
+            Some(Err(e)) => {
+                log::warn!(
+                    "Plugin event handler failed in muxbox {}: {}",
+                    request.muxbox_id,
+                    e
+                );
+                return Message::PluginOutput(
+                    request.muxbox_id.clone(),
+                    Some((e.to_string(), false)),
+                    actions,
+                );
+            }
+            None => {}
+        }
+    }
+    let output = if render {
+        muxbox
+            .render_plugin_component(app_context, &request.bounds)
+            .map(|result| match result {
+                Ok(content) => (content, true),
+                Err(e) => {
+                    log::warn!(
+                        "Plugin component failed in muxbox {}: {}",
+                        request.muxbox_id,
+                        e
+                    );
+                    (e.to_string(), false)
+                }
+            })
+    } else {
+        None
+    };
+    Message::PluginOutput(request.muxbox_id.clone(), output, actions)
+}
+
+/// Show the output of a finished `PluginRequest` in its box and make the box due again
+/// on its schedule. Returns true if the box content changed.
+pub fn apply_plugin_output(
+    app_context: &mut AppContext,
+    refresh_state: &mut HashMap<String, PluginRefreshState>,
+    muxbox_id: &str,
+    output: Option<(String, bool)>,
+) -> bool {
+    if let Some(state) = refresh_state.get_mut(muxbox_id) {
+        state.in_flight = false;
+    }
+    let Some((content, success)) = output else {
+        return false;
+    };
+    let Some(muxbox) = app_context.app.get_muxbox_by_id_mut(muxbox_id) else {
+        return false;
+    };
+    muxbox.show_plugin_output(content, success);
+    true
+}
+
+/// Deliver an event to the plugin component of a box, re-rendering it if the handler
+/// asks to, and append the requested actions to `actions`. Returns false when the box
+/// does not exist or has no plugin component.
+pub fn dispatch_plugin_event(
+    app_context: &mut AppContext,
+    muxbox_id: &str,
+    event: &PluginEvent,
+    actions: &mut Vec<PluginAction>,
+) -> bool {
+    let plugin_app_context = app_context.clone();
+    let Some(muxbox) = app_context.app.get_muxbox_by_id_mut(muxbox_id) else {
+        return false;
+    };
+    let bounds = muxbox.bounds();
+    match muxbox.handle_plugin_event(&plugin_app_context, &bounds, event) {
+        Some(requested) => {
+            actions.extend(requested);
+            true
+        }
+        None => false,
+    }
+}
+
+/// The plugin box under a click and the click as a plugin mouse event, with coordinates
+/// relative to the box's top-left corner
+pub fn plugin_click_event(layout: &Layout, x: u16, y: u16) -> Option<(String, PluginEvent)> {
+    let muxbox = layout.find_muxbox_at_coordinates(x, y)?;
+    muxbox.plugin_component.as_ref()?;
+    let bounds = muxbox.bounds();
+    Some((
+        muxbox.id.clone(),
+        PluginEvent::MouseEvent {
+            x: x.saturating_sub(bounds.left() as u16),
+            y: y.saturating_sub(bounds.top() as u16),
+            action: "click".to_string(),
+        },
+    ))
+}
+
+/// Turn plugin actions into the messages that perform them
+/// Create and select the stream an `ExecuteScript` writes to in its target box; returns
+/// `false` when the box doesn't exist. A `choices_script` run gets no stream: its output
+/// replaces the box's choices stream instead.
+pub fn open_execution_stream(
+    app: &mut crate::model::app::App,
+    execute_script: &crate::model::common::ExecuteScript,
+) -> bool {
+    // ExecuteScript already contains the stream_id from source registry
+    let stream_id = execute_script.stream_id.clone();
+    let source_id = execute_script.source.source_id.clone();
+    let Some(target_muxbox) = app.get_muxbox_by_id_mut(&execute_script.target_box_id) else {
+        return false;
+    };
+    if execute_script.source.source_type == crate::model::common::SourceType::ChoicesScript {
+        return true;
+    }
+
+    // Create stream label based on source type
+    let stream_label = match &execute_script.source.source_type {
+        crate::model::common::SourceType::Choice(choice_id) => choice_id.clone(),
+        crate::model::common::SourceType::StaticScript => "Script".to_string(),
+        crate::model::common::SourceType::PeriodicRefresh => "Content".to_string(), // Periodic refresh shows as "Content" tab
+        crate::model::common::SourceType::SocketUpdate => "Socket".to_string(),
+        crate::model::common::SourceType::RedirectedScript => "Redirect".to_string(),
+        crate::model::common::SourceType::HotkeyScript => "Hotkey".to_string(),
+        crate::model::common::SourceType::ScheduledScript => "Scheduled".to_string(),
+        crate::model::common::SourceType::ChoicesScript => "Choices".to_string(),
+    };
+
+    // Create execution stream with appropriate type
+    let stream_type = match execute_script.execution_mode {
+        crate::model::common::ExecutionMode::Immediate => {
+            StreamType::ChoiceExecution(source_id.clone())
+        }
+        crate::model::common::ExecutionMode::Thread => {
+            StreamType::ChoiceExecution(source_id.clone())
+        }
+        crate::model::common::ExecutionMode::Pty => {
+            StreamType::PtySession(format!("PTY-{}", source_id))
+        }
+    };
+
+    let new_stream = crate::model::common::Stream::new(
+        stream_id.clone(),
+        stream_type,
+        stream_label,
+        Vec::new(),
+        None,
+        None,
+    );
+
+    // Set new execution stream as selected so it renders
+    target_muxbox.selected_stream_id = Some(stream_id.clone());
+
+    // Add stream to target muxbox streams HashMap
+    target_muxbox.streams.insert(stream_id.clone(), new_stream);
+
+    log::info!(
+        "Created stream {} in box {} for execution",
+        stream_id,
+        execute_script.target_box_id
+    );
+    true
+}
+
+/// Stream (and its box) that `source_id` writes to: a choice execution stream or a
+/// `PTY-{source_id}` session
+fn find_source_stream(app: &crate::model::app::App, source_id: &str) -> Option<(String, String)> {
+    for layout in &app.layouts {
+        for muxbox in layout.get_all_muxboxes() {
+            for (stream_id, stream) in &muxbox.streams {
+                let stream_source_id = match &stream.stream_type {
+                    StreamType::ChoiceExecution(id) => id.as_str(),
+                    StreamType::PtySession(id) => id.strip_prefix("PTY-").unwrap_or(id),
+                    _ => continue,
+                };
+                if stream_source_id == source_id {
+                    return Some((stream_id.clone(), muxbox.id.clone()));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Handle a Kill `SourceAction`: cancel the scripts running for the source's stream, clean
+/// up the stream's source and return the update that marks the stream terminated
+pub fn kill_source(app_context: &AppContext, source_action: &SourceAction) -> Option<StreamUpdate> {
+    log::info!(
+        "Kill action for source {} (mode: {:?})",
+        source_action.source_id,
+        source_action.execution_mode
+    );
+    let Some((stream_id, muxbox_id)) =
+        find_source_stream(&app_context.app, &source_action.source_id)
+    else {
+        log::warn!(
+            "Source {} not found for kill action",
+            source_action.source_id
+        );
+        return None;
+    };
+    log::info!(
+        "Found stream {} with source_id {} for termination",
+        stream_id,
+        source_action.source_id
+    );
+
+    // Immediate/Thread scripts are tracked by the stream they write to, not the source
+    if source_action.execution_mode != ExecutionMode::Pty {
+        let cancelled = crate::utils::cancel_scripts(&stream_id);
+        if cancelled > 0 {
+            log::info!(
+                "Cancelled {} running script(s) for source {}",
+                cancelled,
+                source_action.source_id
+            );
+        }
+    }
+
+    let stream = app_context
+        .app
+        .get_muxbox_by_id(&muxbox_id)?
+        .streams
+        .get(&stream_id)?;
+    match &stream.source {
+        Some(stream_source) => match stream_source.cleanup() {
+            Ok(()) => log::info!(
+                "Successfully terminated {:?} source {}",
+                source_action.execution_mode,
+                source_action.source_id
+            ),
+            Err(e) => log::warn!(
+                "Failed to cleanup {:?} source {}: {}",
+                source_action.execution_mode,
+                source_action.source_id,
+                e
+            ),
+        },
+        None => log::info!("Stream {} source already terminated or inactive", stream_id),
+    }
+
+    let source_state = match source_action.execution_mode {
+        ExecutionMode::Immediate => SourceState::Batch(BatchSourceState {
+            task_id: source_action.source_id.clone(),
+            queue_wait_time: std::time::Duration::from_secs(0),
+            execution_time: std::time::Duration::from_secs(0),
+            exit_code: Some(1),
+            status: BatchStatus::Failed("Killed by user".to_string()),
+        }),
+        ExecutionMode::Thread => SourceState::Thread(ThreadSourceState {
+            thread_id: source_action.source_id.clone(),
+            execution_time: std::time::Duration::from_secs(0),
+            exit_code: Some(1),
+            status: ExecutionThreadStatus::Failed("Killed by user".to_string()),
+        }),
+        ExecutionMode::Pty => SourceState::Pty(PtySourceState {
+            process_id: 0, // Will be updated by actual PTY termination
+            runtime: std::time::Duration::from_secs(0),
+            exit_code: Some(1),
+            status: ExecutionPtyStatus::Terminated,
+        }),
+    };
+    Some(StreamUpdate {
+        stream_id,
+        target_box_id: muxbox_id,
+        content_update: "\n[Process terminated by user]".to_string(),
+        source_state,
+        execution_mode: source_action.execution_mode.clone(),
+        output_format: OutputFormat::Text,
+    })
+}
+
+/// Run a `PluginRequest` on its own thread, so a slow plugin process never holds up
+/// drawing; the result arrives as a `PluginOutput` message
+fn spawn_plugin_request(inner: &RunnableImpl, app_context: &AppContext, request: PluginRequest) {
+    let Some(sender) = inner.get_message_sender().cloned() else {
+        return;
+    };
+    let app_context = app_context.clone();
+    std::thread::spawn(move || {
+        let message = run_plugin_request(&app_context, &request);
+        // The thread manager passes a message to every thread but its sender's, so a
+        // fresh id gets it back to the draw loop
+        if let Err(e) = sender.send((Uuid::new_v4(), message)) {
+            log::error!("Failed to send plugin output: {}", e);
+        }
+    });
+}
+
+/// Deliver an event from user input to a plugin box: on a worker thread when a plugin
+/// process answers for the box, otherwise right away
+fn deliver_plugin_event(
+    inner: &RunnableImpl,
+    app_context: &mut AppContext,
+    muxbox_id: &str,
+    event: &PluginEvent,
+    actions: &mut Vec<PluginAction>,
+) {
+    if !is_process_plugin_box(app_context, muxbox_id) {
+        dispatch_plugin_event(app_context, muxbox_id, event, actions);
+        return;
+    }
+    let Some(bounds) = app_context
+        .app
+        .get_muxbox_by_id(muxbox_id)
+        .map(|muxbox| muxbox.bounds())
+    else {
+        return;
+    };
+    spawn_plugin_request(
+        inner,
+        app_context,
+        PluginRequest {
+            muxbox_id: muxbox_id.to_string(),
+            bounds,
+            event: Some(event.clone()),
+            render: false,
+        },
+    );
+}
+
+fn send_plugin_actions(inner: &mut RunnableImpl, actions: Vec<PluginAction>) {
+    for action in actions {
+        match action {
+            PluginAction::SwitchLayout(layout_id) => {
+                inner.send_message(Message::SwitchActiveLayout(layout_id))
+            }
+            PluginAction::ExecuteChoice(choice_id) => {
+                inner.send_message(Message::ExecuteHotKeyChoice(choice_id))
+            }
+            // Forgetting the last render makes the box due on the next frame
+            PluginAction::RefreshBox(muxbox_id) => {
+                PLUGIN_REFRESH_STATE.lock().unwrap().remove(&muxbox_id);
+            }
+        }
+    }
 }
 
 create_runnable!(
@@ -663,6 +1206,22 @@ create_runnable!(
             }
         }
 
+        // Plugin components render into their box stream on their own schedule
+        let mut plugin_actions = Vec::new();
+        let mut plugin_requests = Vec::new();
+        if refresh_due_plugin_components(
+            &mut app_context_unwrapped,
+            &mut PLUGIN_REFRESH_STATE.lock().unwrap(),
+            &mut plugin_actions,
+            &mut plugin_requests,
+        ) {
+            inner.update_app_context(app_context_unwrapped.clone());
+        }
+        send_plugin_actions(inner, plugin_actions);
+        for request in plugin_requests {
+            spawn_plugin_request(inner, &app_context_unwrapped, request);
+        }
+
         // Skip the (expensive) full rebuild + sensitive-zone recomputation when
         // nothing observable has changed since the last render. Calibration mode
         // tracks the live cursor cell, so it always renders. A ~1s periodic
@@ -764,82 +1323,12 @@ create_runnable!(
                         log::info!("Processing ExecuteScript from ThreadManager for target_box_id: {}, execution_mode: {:?}", 
                                    execute_script.target_box_id, execute_script.execution_mode);
 
-                        // ExecuteScript already contains the stream_id from source registry
-                        let stream_id = execute_script.stream_id.clone();
-                        let source_id = execute_script.source.source_id.clone();
-
-                        if let Some(target_muxbox) = app_context_unwrapped
-                            .app
-                            .get_muxbox_by_id_mut(&execute_script.target_box_id)
-                        {
-                            // Create stream label based on source type
-                            let stream_label = match &execute_script.source.source_type {
-                                crate::model::common::SourceType::Choice(choice_id) => {
-                                    choice_id.clone()
-                                }
-                                crate::model::common::SourceType::StaticScript => {
-                                    "Script".to_string()
-                                }
-                                crate::model::common::SourceType::PeriodicRefresh => {
-                                    "Content".to_string()
-                                } // Periodic refresh shows as "Content" tab
-                                crate::model::common::SourceType::SocketUpdate => {
-                                    "Socket".to_string()
-                                }
-                                crate::model::common::SourceType::RedirectedScript => {
-                                    "Redirect".to_string()
-                                }
-                                crate::model::common::SourceType::HotkeyScript => {
-                                    "Hotkey".to_string()
-                                }
-                                crate::model::common::SourceType::ScheduledScript => {
-                                    "Scheduled".to_string()
-                                }
-                            };
-
-                            // Create execution stream with appropriate type
-                            let stream_type = match execute_script.execution_mode {
-                                crate::model::common::ExecutionMode::Immediate => {
-                                    StreamType::ChoiceExecution(source_id.clone())
-                                }
-                                crate::model::common::ExecutionMode::Thread => {
-                                    StreamType::ChoiceExecution(source_id.clone())
-                                }
-                                crate::model::common::ExecutionMode::Pty => {
-                                    StreamType::PtySession(format!("PTY-{}", source_id))
-                                }
-                            };
-
-                            let new_stream = crate::model::common::Stream::new(
-                                stream_id.clone(),
-                                stream_type,
-                                stream_label,
-                                Vec::new(),
-                                None,
-                                None,
-                            );
-
-                            // Set new execution stream as selected so it renders
-                            target_muxbox.selected_stream_id = Some(stream_id.clone());
-
-                            // Add stream to target muxbox streams HashMap
-                            target_muxbox.streams.insert(stream_id.clone(), new_stream);
-
-                            log::info!(
-                                "Created stream {} in box {} for execution",
-                                stream_id,
-                                execute_script.target_box_id
-                            );
-
+                        if open_execution_stream(&mut app_context_unwrapped.app, execute_script) {
                             // T0700: Route ALL execution through ThreadManager for unified architecture
-                            // Send ExecuteScript to ThreadManager for consistent handling across all execution modes
-                            let mut execute_script_with_stream = execute_script.clone();
-                            execute_script_with_stream.stream_id = stream_id;
-
                             log::info!("T0700: Unified execution - sending {:?} ExecuteScript to ThreadManager", 
                                       execute_script.execution_mode);
                             inner.send_message(Message::ExecuteScriptMessage(
-                                execute_script_with_stream,
+                                execute_script.clone(),
                             ));
                         } else {
                             log::error!(
@@ -852,54 +1341,113 @@ create_runnable!(
                         log::info!("Processing StreamUpdate for stream_id: {}, target_box: {}, execution_mode: {:?}", 
                                    stream_update.stream_id, stream_update.target_box_id, stream_update.execution_mode);
 
+                        // Finished runs are checked against the target box's alert rules
+                        if let Some(muxbox) = app_context_unwrapped
+                            .app
+                            .get_muxbox_by_id_mut(&stream_update.target_box_id)
+                        {
+                            if let Some(change) = crate::alerts::apply_alerts(
+                                muxbox,
+                                stream_update,
+                                std::time::SystemTime::now(),
+                            ) {
+                                if change.bell {
+                                    use std::io::Write;
+                                    let _ = screen.write_all(b"\x07");
+                                    let _ = screen.flush();
+                                }
+                                inner.send_message(Message::AlertChanged(
+                                    change.box_id,
+                                    change.level,
+                                    change.message,
+                                ));
+                                inner.send_message(Message::RedrawMuxBox(muxbox.id.clone()));
+                            }
+                        }
+
+                        // Finished output of a script with an `output_format` goes to the
+                        // box's table, chart or choices instead of a text stream
+                        let mut structured_applied = false;
+                        let parsed_update;
+                        let stream_update =
+                            match crate::structured_output::parse_stream_update(stream_update) {
+                                Some(crate::structured_output::StructuredOutput::Text(text)) => {
+                                    parsed_update = crate::model::common::StreamUpdate {
+                                        content_update: text,
+                                        ..stream_update.clone()
+                                    };
+                                    &parsed_update
+                                }
+                                Some(structured) => {
+                                    if let Some(muxbox) = app_context_unwrapped
+                                        .app
+                                        .get_muxbox_by_id_mut(&stream_update.target_box_id)
+                                    {
+                                        structured_applied =
+                                            crate::structured_output::apply_structured_output(
+                                                muxbox, structured,
+                                            );
+                                        inner
+                                            .send_message(Message::RedrawMuxBox(muxbox.id.clone()));
+                                    }
+                                    stream_update
+                                }
+                                None => stream_update,
+                            };
+
                         // T0308 ENHANCED: StreamUpdate handler with auto-creation - find or create stream
-                        let mut stream_found = false;
+                        let mut stream_found = structured_applied;
 
                         // First, try to find existing stream across all muxboxes
-                        for layout in &mut app_context_unwrapped.app.layouts {
-                            if let Some(children) = &mut layout.children {
-                                for muxbox in children {
-                                    if let Some(stream) =
-                                        muxbox.streams.get_mut(&stream_update.stream_id)
-                                    {
-                                        // Handle replace vs append based on content prefix
-                                        if !stream_update.content_update.is_empty() {
-                                            if stream_update.content_update.starts_with("REPLACE:")
-                                            {
-                                                // Replace content for full-screen programs
-                                                let new_content = stream_update
+                        if !stream_found {
+                            for layout in &mut app_context_unwrapped.app.layouts {
+                                if let Some(children) = &mut layout.children {
+                                    for muxbox in children {
+                                        if let Some(stream) =
+                                            muxbox.streams.get_mut(&stream_update.stream_id)
+                                        {
+                                            // Handle replace vs append based on content prefix
+                                            if !stream_update.content_update.is_empty() {
+                                                if stream_update
                                                     .content_update
-                                                    .strip_prefix("REPLACE:")
-                                                    .unwrap_or(&stream_update.content_update);
-                                                stream.content = vec![new_content.to_string()];
-                                                log::info!("Replaced content in existing stream {}: {} characters", 
+                                                    .starts_with("REPLACE:")
+                                                {
+                                                    // Replace content for full-screen programs
+                                                    let new_content = stream_update
+                                                        .content_update
+                                                        .strip_prefix("REPLACE:")
+                                                        .unwrap_or(&stream_update.content_update);
+                                                    stream.content = vec![new_content.to_string()];
+                                                    log::info!("Replaced content in existing stream {}: {} characters", 
                                                           stream_update.stream_id, new_content.len());
-                                            } else {
-                                                // Normal append behavior
-                                                stream
-                                                    .content
-                                                    .push(stream_update.content_update.clone());
-                                                log::info!("Appended content to existing stream {}: {} characters", 
+                                                } else {
+                                                    // Normal append behavior
+                                                    stream
+                                                        .content
+                                                        .push(stream_update.content_update.clone());
+                                                    log::info!("Appended content to existing stream {}: {} characters", 
                                                           stream_update.stream_id, stream_update.content_update.len());
+                                                }
                                             }
-                                        }
 
-                                        stream_found = true;
+                                            stream_found = true;
 
-                                        // AUTO_SCROLL_BOTTOM FIX: Apply auto-scroll when stream content is updated
-                                        if muxbox.auto_scroll_bottom == Some(true) {
-                                            muxbox.vertical_scroll = Some(100.0);
-                                            log::debug!("Applied auto-scroll to bottom for muxbox {} after stream update", muxbox.id);
-                                        }
+                                            // AUTO_SCROLL_BOTTOM FIX: Apply auto-scroll when stream content is updated
+                                            if muxbox.auto_scroll_bottom == Some(true) {
+                                                muxbox.vertical_scroll = Some(100.0);
+                                                log::debug!("Applied auto-scroll to bottom for muxbox {} after stream update", muxbox.id);
+                                            }
 
-                                        inner
-                                            .send_message(Message::RedrawMuxBox(muxbox.id.clone()));
+                                            inner.send_message(Message::RedrawMuxBox(
+                                                muxbox.id.clone(),
+                                            ));
+                                            break;
+                                        }
+                                    }
+                                    if stream_found {
                                         break;
                                     }
                                 }
-                                if stream_found {
-                                    break;
-                                }
                             }
                         }
 
@@ -1001,9 +1549,51 @@ create_runnable!(
                             }
                         }
 
+                        // Output arriving for a plugin box is data for its plugin; JSON
+                        // output is passed parsed, anything else as a string
+                        let is_plugin_data = app_context_unwrapped
+                            .app
+                            .get_muxbox_by_id(&stream_update.target_box_id)
+                            .is_some_and(|muxbox| {
+                                muxbox.plugin_stream_id().is_some_and(|plugin_stream_id| {
+                                    plugin_stream_id != stream_update.stream_id
+                                })
+                            });
+                        if is_plugin_data && !stream_update.content_update.is_empty() {
+                            let data = serde_json::from_str(&stream_update.content_update)
+                                .unwrap_or_else(|_| {
+                                    serde_json::Value::String(stream_update.content_update.clone())
+                                });
+                            let event = PluginEvent::DataUpdate {
+                                source: stream_update.stream_id.clone(),
+                                data,
+                            };
+                            let mut plugin_actions = Vec::new();
+                            deliver_plugin_event(
+                                inner,
+                                &mut app_context_unwrapped,
+                                &stream_update.target_box_id,
+                                &event,
+                                &mut plugin_actions,
+                            );
+                            send_plugin_actions(inner, plugin_actions);
+                        }
+
                         // CRITICAL FIX: Update app context to persist all stream changes
                         inner.update_app_context(app_context_unwrapped.clone());
                     }
+                    Message::PluginOutput(muxbox_id, output, actions) => {
+                        if apply_plugin_output(
+                            &mut app_context_unwrapped,
+                            &mut PLUGIN_REFRESH_STATE.lock().unwrap(),
+                            muxbox_id,
+                            output.clone(),
+                        ) {
+                            inner.update_app_context(app_context_unwrapped.clone());
+                            inner.send_message(Message::RedrawMuxBox(muxbox_id.clone()));
+                        }
+                        send_plugin_actions(inner, actions.clone());
+                    }
                     Message::SourceActionMessage(source_action) => {
                         log::info!(
                             "Processing SourceAction: {:?} for source_id: {}, execution_mode: {:?}",
@@ -1015,153 +1605,12 @@ create_runnable!(
                         // T0320: SourceAction handler - Phase 4 source lifecycle management implementation
                         match source_action.action {
                             crate::model::common::ActionType::Kill => {
-                                log::info!(
-                                    "Kill action for source {} (mode: {:?})",
-                                    source_action.source_id,
-                                    source_action.execution_mode
-                                );
-
-                                // Find and terminate the source based on execution mode
-                                let mut source_terminated = false;
-                                let mut stream_to_update: Option<(String, String)> = None; // (stream_id, muxbox_id)
-
-                                // Search all muxboxes for streams with this source_id (read-only first)
-                                for layout in &app_context_unwrapped.app.layouts {
-                                    for muxbox in layout.get_all_muxboxes() {
-                                        for (stream_id, stream) in &muxbox.streams {
-                                            // Check if this stream matches the source_id
-                                            let stream_source_id = match &stream.stream_type {
-                                                StreamType::ChoiceExecution(id) => Some(id.clone()),
-                                                StreamType::PtySession(id) => {
-                                                    // Extract source_id from PTY session format "PTY-{source_id}"
-                                                    if let Some(stripped) = id.strip_prefix("PTY-")
-                                                    {
-                                                        Some(stripped.to_string())
-                                                    } else {
-                                                        Some(id.clone())
-                                                    }
-                                                }
-                                                _ => None,
-                                            };
-
-                                            if let Some(stream_src_id) = stream_source_id {
-                                                if stream_src_id == source_action.source_id {
-                                                    log::info!("Found stream {} with source_id {} for termination", stream_id, source_action.source_id);
-                                                    stream_to_update = Some((
-                                                        stream_id.clone(),
-                                                        muxbox.id.clone(),
-                                                    ));
-                                                    source_terminated = true;
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                        if source_terminated {
-                                            break;
-                                        }
-                                    }
-                                    if source_terminated {
-                                        break;
-                                    }
-                                }
-
-                                // Now perform the actual cleanup if we found the stream
-                                if let Some((stream_id, muxbox_id)) = stream_to_update {
-                                    // Get mutable access to perform cleanup
-                                    if let Some(target_muxbox) =
-                                        app_context_unwrapped.app.get_muxbox_by_id_mut(&muxbox_id)
-                                    {
-                                        if let Some(stream) = target_muxbox.streams.get(&stream_id)
-                                        {
-                                            // Attempt source cleanup based on execution mode
-                                            if let Some(ref stream_source) = stream.source {
-                                                match source_action.execution_mode {
-                                                    crate::model::common::ExecutionMode::Immediate => {
-                                                        // Batch mode - attempt to cancel queued task
-                                                        if let Err(e) = stream_source.cleanup() {
-                                                            log::warn!("Failed to cleanup batch source {}: {}", source_action.source_id, e);
-                                                        } else {
-                                                            log::info!("Successfully terminated batch source {}", source_action.source_id);
-                                                        }
-                                                    }
-                                                    crate::model::common::ExecutionMode::Thread => {
-                                                        // Thread mode - interrupt thread execution
-                                                        if let Err(e) = stream_source.cleanup() {
-                                                            log::warn!("Failed to cleanup thread source {}: {}", source_action.source_id, e);
-                                                        } else {
-                                                            log::info!("Successfully terminated thread source {}", source_action.source_id);
-                                                        }
-                                                    }
-                                                    crate::model::common::ExecutionMode::Pty => {
-                                                        // PTY mode - kill PTY process
-                                                        if let Err(e) = stream_source.cleanup() {
-                                                            log::warn!("Failed to cleanup PTY source {}: {}", source_action.source_id, e);
-                                                        } else {
-                                                            log::info!("Successfully terminated PTY source {}", source_action.source_id);
-                                                        }
-                                                    }
-                                                }
-                                            } else {
-                                                log::info!("Stream {} source already terminated or inactive", stream_id);
-                                            }
-
-                                            // Send StreamUpdate with terminated status
-                                            let terminated_state = match source_action.execution_mode {
-                                                crate::model::common::ExecutionMode::Immediate => {
-                                                    crate::model::common::SourceState::Batch(
-                                                        crate::model::common::BatchSourceState {
-                                                            task_id: source_action.source_id.clone(),
-                                                            queue_wait_time: std::time::Duration::from_secs(0),
-                                                            execution_time: std::time::Duration::from_secs(0),
-                                                            exit_code: Some(1),
-                                                            status: crate::model::common::BatchStatus::Failed("Killed by user".to_string())
-                                                        }
-                                                    )
-                                                }
-                                                crate::model::common::ExecutionMode::Thread => {
-                                                    crate::model::common::SourceState::Thread(
-                                                        crate::model::common::ThreadSourceState {
-                                                            thread_id: source_action.source_id.clone(),
-                                                            execution_time: std::time::Duration::from_secs(0),
-                                                            exit_code: Some(1),
-                                                            status: crate::model::common::ExecutionThreadStatus::Failed("Killed by user".to_string())
-                                                        }
-                                                    )
-                                                }
-                                                crate::model::common::ExecutionMode::Pty => {
-                                                    crate::model::common::SourceState::Pty(
-                                                        crate::model::common::PtySourceState {
-                                                            process_id: 0, // Will be updated by actual PTY termination
-                                                            runtime: std::time::Duration::from_secs(0),
-                                                            exit_code: Some(1),
-                                                            status: crate::model::common::ExecutionPtyStatus::Terminated
-                                                        }
-                                                    )
-                                                }
-                                            };
-
-                                            let termination_update =
-                                                crate::model::common::StreamUpdate {
-                                                    stream_id: stream_id.clone(),
-                                                    target_box_id: muxbox_id.clone(),
-                                                    content_update:
-                                                        "\n[Process terminated by user]".to_string(),
-                                                    source_state: terminated_state,
-                                                    execution_mode: source_action
-                                                        .execution_mode
-                                                        .clone(),
-                                                };
-
-                                            inner.send_message(Message::StreamUpdateMessage(
-                                                termination_update,
-                                            ));
-                                        }
-                                    }
-                                } else {
-                                    log::warn!(
-                                        "Source {} not found for kill action",
-                                        source_action.source_id
-                                    );
+                                if let Some(termination_update) =
+                                    kill_source(&app_context_unwrapped, source_action)
+                                {
+                                    inner.send_message(Message::StreamUpdateMessage(
+                                        termination_update,
+                                    ));
                                 }
                             }
                             crate::model::common::ActionType::Query => {
@@ -1803,7 +2252,9 @@ create_runnable!(
                                 source: ExecutionSource {
                                     source_type: SourceType::HotkeyScript,
                                     source_id: format!("hotkey_choice_{}", choice_id),
-                                    source_reference: SourceReference::Choice(choice.clone()),
+                                    source_reference: SourceReference::Choice(Box::new(
+                                        choice.clone(),
+                                    )),
                                 },
                                 execution_mode: choice.execution_mode.clone(),
                                 target_box_id: muxbox_id.clone(),
@@ -1821,7 +2272,17 @@ create_runnable!(
                                             .iter()
                                             .find(|mb| mb.id == *muxbox_id)
                                     })
-                                    .map(|mb| mb.bounds()),
+                                    .map(|mb| mb.content_bounds()),
+                                environment: app_context_unwrapped
+                                    .app
+                                    .script_environment(&muxbox_id, Some(&choice.id)),
+                                timeout_ms: app_context_unwrapped
+                                    .app
+                                    .script_timeout_ms(&muxbox_id, Some(&choice.id)),
+                                output_format: app_context_unwrapped
+                                    .app
+                                    .script_output_format(&muxbox_id, Some(&choice.id)),
+                                record: app_context_unwrapped.app.script_record(&muxbox_id),
                             };
 
                             // Send ExecuteScript message instead of calling legacy execute_choice_stream_only
@@ -1834,6 +2295,36 @@ create_runnable!(
                         }
                     }
                     Message::KeyPress(pressed_key) => {
+                        // Plugin boxes in focus see every key press
+                        let focused_plugin_boxes: Vec<String> = app_context_unwrapped
+                            .app
+                            .get_active_layout()
+                            .map(|layout| {
+                                layout
+                                    .get_selected_muxboxes()
+                                    .into_iter()
+                                    .filter(|muxbox| muxbox.plugin_component.is_some())
+                                    .map(|muxbox| muxbox.id.clone())
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        if !focused_plugin_boxes.is_empty() {
+                            let mut plugin_actions = Vec::new();
+                            let event = PluginEvent::KeyPress(pressed_key.clone());
+                            for muxbox_id in &focused_plugin_boxes {
+                                deliver_plugin_event(
+                                    inner,
+                                    &mut app_context_unwrapped,
+                                    muxbox_id,
+                                    &event,
+                                    &mut plugin_actions,
+                                );
+                                inner.send_message(Message::RedrawMuxBox(muxbox_id.clone()));
+                            }
+                            inner.update_app_context(app_context_unwrapped.clone());
+                            send_plugin_actions(inner, plugin_actions);
+                        }
+
                         let mut app_context_for_keypress = app_context_unwrapped.clone();
                         let active_layout = app_context_unwrapped.app.get_active_layout().unwrap();
 
@@ -1942,6 +2433,20 @@ create_runnable!(
                                                 append_output: Some(append_output),
                                                 waiting: true,
                                                 hovered: false,
+                                                cwd: None,
+                                                env: None,
+                                                shell: None,
+                                                inherit_env: None,
+                                                timeout_ms: None,
+                                                schedule: None,
+                                                output_format: None,
+                                                class: None,
+                                                menu_fg_color: None,
+                                                menu_bg_color: None,
+                                                selected_menu_fg_color: None,
+                                                selected_menu_bg_color: None,
+                                                highlighted_menu_fg_color: None,
+                                                highlighted_menu_bg_color: None,
                                             };
 
                                             // Register execution source and get stream_id
@@ -1968,7 +2473,7 @@ create_runnable!(
                                                     ),
                                                     source_id: format!("choice_{}", choice_id),
                                                     source_reference: SourceReference::Choice(
-                                                        choice_for_reference,
+                                                        Box::new(choice_for_reference),
                                                     ),
                                                 },
                                                 execution_mode: execution_mode.clone(),
@@ -1977,7 +2482,28 @@ create_runnable!(
                                                 redirect_output: redirect_output.clone(),
                                                 append_output,
                                                 stream_id: stream_id.clone(),
-                                                target_bounds: Some(muxbox.bounds()),
+                                                target_bounds: Some(muxbox.content_bounds()),
+                                                environment: app_context_unwrapped
+                                                    .app
+                                                    .script_environment(
+                                                        &muxbox_id,
+                                                        Some(&choice_id),
+                                                    ),
+                                                timeout_ms: app_context_unwrapped
+                                                    .app
+                                                    .script_timeout_ms(
+                                                        &muxbox_id,
+                                                        Some(&choice_id),
+                                                    ),
+                                                output_format: app_context_unwrapped
+                                                    .app
+                                                    .script_output_format(
+                                                        &muxbox_id,
+                                                        Some(&choice_id),
+                                                    ),
+                                                record: app_context_unwrapped
+                                                    .app
+                                                    .script_record(&muxbox_id),
                                             };
 
                                             // UNIFIED EXECUTION ARCHITECTURE: Route ExecuteScript based on execution mode
@@ -2029,6 +2555,12 @@ create_runnable!(
                                                                     "T0314 FIXED: PTYManager successfully handling ExecuteScript for choice {} (architecture compliant)",
                                                                     choice_id
                                                                 );
+                                                                inner.send_message(
+                                                                    Message::ChoiceExecuted(
+                                                                        muxbox_id.clone(),
+                                                                        choice_id.clone(),
+                                                                    ),
+                                                                );
                                                             }
                                                         } else {
                                                             log::error!("No message sender available for PTY execution - choice {}", choice_id);
@@ -2064,8 +2596,8 @@ create_runnable!(
 
                                 // Create ExecuteScript message for muxbox-level keypress handlers
                                 use crate::model::common::{
-                                    ExecuteScript, ExecutionMode, ExecutionSource, SourceReference,
-                                    SourceType,
+                                    ExecuteScript, ExecutionMode, ExecutionSource, OutputFormat,
+                                    ScriptEnvironment, SourceReference, SourceType,
                                 };
 
                                 // Register execution source and get stream_id
@@ -2106,7 +2638,11 @@ create_runnable!(
                                     redirect_output: muxbox.redirect_output.clone(),
                                     append_output: muxbox.append_output.unwrap_or(false),
                                     stream_id,
-                                    target_bounds: Some(muxbox.bounds()),
+                                    target_bounds: Some(muxbox.content_bounds()),
+                                    environment: ScriptEnvironment::resolve(Some(muxbox), None),
+                                    timeout_ms: muxbox.timeout_ms,
+                                    output_format: OutputFormat::Text,
+                                    record: None,
                                 };
 
                                 inner.send_message(Message::ExecuteScriptMessage(execute_script));
@@ -2203,6 +2739,25 @@ create_runnable!(
                     }
                     Message::MouseClick(x, y) => {
                         log::trace!("Mouse click at ({}, {})", x, y);
+
+                        let plugin_click = app_context_unwrapped
+                            .app
+                            .get_active_layout()
+                            .and_then(|layout| plugin_click_event(layout, *x, *y));
+                        if let Some((muxbox_id, event)) = plugin_click {
+                            let mut plugin_actions = Vec::new();
+                            deliver_plugin_event(
+                                inner,
+                                &mut app_context_unwrapped,
+                                &muxbox_id,
+                                &event,
+                                &mut plugin_actions,
+                            );
+                            inner.update_app_context(app_context_unwrapped.clone());
+                            inner.send_message(Message::RedrawMuxBox(muxbox_id));
+                            send_plugin_actions(inner, plugin_actions);
+                        }
+
                         let mut app_context_for_click = app_context_unwrapped.clone();
                         let active_layout = app_context_unwrapped.app.get_active_layout().unwrap();
 
@@ -2790,6 +3345,20 @@ create_runnable!(
                                                                             ),
                                                                             waiting: true,
                                                                             hovered: false,
+                                                                            cwd: None,
+                                                                            env: None,
+                                                                            shell: None,
+                                                                            inherit_env: None,
+                                                                            timeout_ms: None,
+                                                                            schedule: None,
+                                                                            output_format: None,
+                                                                            class: None,
+                                                                            menu_fg_color: None,
+                                                                            menu_bg_color: None,
+                                                                            selected_menu_fg_color: None,
+                                                                            selected_menu_bg_color: None,
+                                                                            highlighted_menu_fg_color: None,
+                                                                            highlighted_menu_bg_color: None,
                                                                         };
 
                                                                             // Register execution source and get stream_id
@@ -2817,7 +3386,7 @@ create_runnable!(
                                                             ),
                                                             source_reference:
                                                                 SourceReference::Choice(
-                                                                    choice_for_reference,
+                                                                    Box::new(choice_for_reference),
                                                                 ),
                                                         },
                                                         execution_mode: execution_mode.clone(),
@@ -2828,7 +3397,17 @@ create_runnable!(
                                                         stream_id: stream_id.clone(),
                                                         target_bounds: app_context_unwrapped.app.get_active_layout()
                                                             .and_then(|layout| layout.children.as_ref()?.iter().find(|mb| mb.id == *muxbox_id_clone))
-                                                            .map(|mb| mb.bounds()),
+                                                            .map(|mb| mb.content_bounds()),
+                                                        environment: app_context_unwrapped.app.script_environment(
+                                                            &muxbox_id_clone,
+                                                            Some(&choice_id_clone),
+                                                        ),
+                                                        timeout_ms: app_context_unwrapped.app.script_timeout_ms(
+                                                            &muxbox_id_clone,
+                                                            Some(&choice_id_clone),
+                                                        ),
+                                                        output_format: app_context_unwrapped.app.script_output_format(&muxbox_id_clone, Some(&choice_id_clone)),
+                                                        record: app_context_unwrapped.app.script_record(&muxbox_id_clone),
                                                     };
 
                                                                             // Route ExecuteScript based on execution mode
@@ -2864,6 +3443,10 @@ create_runnable!(
                                                                         "T0315 FIXED: PTYManager successfully handling ExecuteScript for choice {} (architecture compliant)",
                                                                         choice_id_clone
                                                                     );
+                                                                    inner.send_message(Message::ChoiceExecuted(
+                                                                        muxbox_id_clone.clone(),
+                                                                        choice_id_clone.clone(),
+                                                                    ));
                                                                 }
                                                                 } else {
                                                                     log::error!("No message sender available for PTY execution - choice {}", choice_id_clone);
@@ -3822,7 +4405,17 @@ create_runnable!(
                                         .iter()
                                         .find(|mb| mb.id == *muxbox_id)
                                 })
-                                .map(|mb| mb.bounds()),
+                                .map(|mb| mb.content_bounds()),
+                            environment: app_context_unwrapped
+                                .app
+                                .script_environment(muxbox_id, None),
+                            timeout_ms: app_context_unwrapped
+                                .app
+                                .script_timeout_ms(muxbox_id, None),
+                            output_format: app_context_unwrapped
+                                .app
+                                .script_output_format(muxbox_id, None),
+                            record: app_context_unwrapped.app.script_record(muxbox_id),
                         };
 
                         // Send ExecuteScript message instead of direct execution
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 
 use crate::ansi_color_processor::{contains_ansi_sequences, process_ansi_text};
 use crate::components::{BoxRenderer, ComponentDimensions};
-use crate::model::common::Cell;
+use crate::model::common::{Cell, TextStyle};
 use crate::utils::screen_bounds;
 
 pub fn content_size(text: &str) -> (usize, usize) {
@@ -120,13 +120,27 @@ pub fn print_with_color_and_background_at(
     bg_color: &Option<String>,
     text: &str,
     buffer: &mut ScreenBuffer,
+) {
+    print_styled_at(y, x, fg_color, bg_color, TextStyle::default(), text, buffer);
+}
+
+/// Like `print_with_color_and_background_at`, with text attributes added to every cell
+pub fn print_styled_at(
+    y: usize,
+    x: usize,
+    fg_color: &Option<String>,
+    bg_color: &Option<String>,
+    style: TextStyle,
+    text: &str,
+    buffer: &mut ScreenBuffer,
 ) {
     // Check if text contains ANSI sequences
     if contains_ansi_sequences(text) {
         // Process ANSI sequences and render directly
         let cells = process_ansi_text(text);
-        for (i, cell) in cells.iter().enumerate() {
-            buffer.update(x + i, y, cell.clone());
+        for (i, cell) in cells.into_iter().enumerate() {
+            let style = cell.style.union(style);
+            buffer.update(x + i, y, Cell { style, ..cell });
         }
     } else {
         // Original behavior for plain text
@@ -137,6 +151,7 @@ pub fn print_with_color_and_background_at(
                 fg_color: fg_color_code.clone(),
                 bg_color: bg_color_code.clone(),
                 ch,
+                style,
             };
             buffer.update(x + i, y, cell);
         }
@@ -166,6 +181,7 @@ pub fn draw_horizontal_line(
             fg_color: border_color_code.clone(),
             bg_color: bg_color_code.clone(),
             ch: '─',
+            style: TextStyle::default(),
         };
         buffer.update(x, y, cell);
     }
@@ -186,6 +202,7 @@ pub fn fill_horizontal_background(
             fg_color: fg_color_code.clone(),
             bg_color: bg_color_code.clone(),
             ch: ' ',
+            style: TextStyle::default(),
         };
         buffer.update(x, y, cell);
     }
@@ -206,6 +223,7 @@ pub fn draw_vertical_line(
             fg_color: border_color_code.clone(),
             bg_color: bg_color_code.clone(),
             ch: '│',
+            style: TextStyle::default(),
         };
         buffer.update(x, y, cell);
     }
@@ -226,6 +244,7 @@ pub fn erase_to_background_color(
                 fg_color: "default".to_string(),
                 bg_color: bg_color_code.clone(),
                 ch: ' ',
+                style: TextStyle::default(),
             };
             buffer.update(x, y, cell);
         }
@@ -353,6 +372,7 @@ pub fn draw_horizontal_line_with_tabs(
     title_fg_color: &Option<String>,
     title_bg_color: &Option<String>,
     title_position: &str,
+    title_style: TextStyle,
     tab_labels: &[String],
     tab_close_buttons: &[bool], // F0219: Close button info for each tab
     active_tab_index: usize,
@@ -373,6 +393,7 @@ pub fn draw_horizontal_line_with_tabs(
             bg_color,
             title_fg_color,
             title_bg_color,
+            title_style,
             tab_labels,
             tab_close_buttons,
             active_tab_index,
@@ -544,6 +565,7 @@ pub fn fill_muxbox(
                     fg_color: get_fg_color_transparent(&None),
                     bg_color: get_bg_color_transparent(&None),
                     ch: ' ',
+                    style: TextStyle::default(),
                 };
                 let existing_cell = buffer.get(x, y).unwrap_or(&default_cell);
                 let cell = Cell {
@@ -558,6 +580,7 @@ pub fn fill_muxbox(
                         existing_cell.bg_color.clone()
                     },
                     ch: fill_char,
+                    style: TextStyle::default(),
                 };
                 buffer.update(x, y, cell);
             }
@@ -643,17 +666,16 @@ pub fn wrap_text_to_width(text: &str, width: usize) -> Vec<String> {
     wrapped_lines
 }
 
-/// Render wrapped text content within bounds
+/// Render wrapped text content within a box's content area
 pub fn render_wrapped_content(
     wrapped_lines: &[String],
-    bounds: &Bounds,
+    content_bounds: &Bounds,
     vertical_scroll: f64,
     fg_color: &Option<String>,
     bg_color: &Option<String>,
+    text_style: TextStyle,
     buffer: &mut ScreenBuffer,
 ) {
-    let component_dims = ComponentDimensions::new(*bounds);
-    let content_bounds = component_dims.content_bounds();
     let viewable_height = content_bounds.height();
     let content_start_x = content_bounds.left() + 1;
     let content_start_y = content_bounds.top();
@@ -670,16 +692,17 @@ pub fn render_wrapped_content(
 
     for (i, line) in visible_lines.enumerate() {
         let render_y = content_start_y + i;
-        if render_y >= bounds.bottom() {
+        if render_y > content_bounds.bottom() {
             break;
         }
 
         // Use the updated function that handles ANSI sequences
-        print_with_color_and_background_at(
+        print_styled_at(
             render_y,
             content_start_x,
             fg_color,
             bg_color,
+            text_style,
             line,
             buffer,
         );
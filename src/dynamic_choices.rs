@@ -0,0 +1,242 @@
+//! Choices generated from a box's `choices_script`.
+//!
+//! The `ChoicesScriptLoop` thread runs each `choices_script` once at startup and again
+//! every `choices_refresh_interval` milliseconds, never starting a run while the previous
+//! one is still going. Boxes added or changed later, e.g. through `AddBox`, are picked up
+//! as the app changes. The output is parsed with the `choices` output format and replaces
+//! the box's choices stream (see [`crate::structured_output`]); generated choices without
+//! a script of their own run the box's `choices_item_script`.
+
+use crate::model::app::App;
+use crate::model::common::{
+    ActionType, ExecuteScript, ExecutionMode, ExecutionSource, ExecutionSourceType, OutputFormat,
+    SourceReference, SourceType,
+};
+use crate::socket_protocol::stream_update_outcome;
+use crate::thread_manager::Runnable;
+use crate::thread_manager::*;
+use crate::AppContext;
+use crate::FieldUpdate;
+use crate::Message;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Whether any box in the app has a `choices_script`
+pub fn has_choices_scripts(app: &App) -> bool {
+    app.layouts.iter().any(|layout| {
+        layout
+            .get_all_muxboxes()
+            .iter()
+            .any(|muxbox| muxbox.choices_script.is_some())
+    })
+}
+
+/// Decides when each box's `choices_script` runs
+#[derive(Debug)]
+pub struct ChoicesRefresher {
+    /// Box ID, script and refresh interval of each box with a `choices_script`
+    boxes: Vec<(String, Vec<String>, Option<Duration>)>,
+    last_runs: HashMap<String, Instant>,
+    /// Stream ID of the run in flight, by box ID
+    running: HashMap<String, String>,
+}
+
+/// Box ID, script and refresh interval of each box with a `choices_script`
+fn choices_boxes(app: &App) -> Vec<(String, Vec<String>, Option<Duration>)> {
+    let mut boxes = Vec::new();
+    for layout in &app.layouts {
+        for muxbox in layout.get_all_muxboxes() {
+            if let Some(script) = &muxbox.choices_script {
+                boxes.push((
+                    muxbox.id.clone(),
+                    script.clone(),
+                    muxbox.choices_refresh_interval.map(Duration::from_millis),
+                ));
+            }
+        }
+    }
+    boxes
+}
+
+impl ChoicesRefresher {
+    pub fn new(app: &App) -> Self {
+        ChoicesRefresher {
+            boxes: choices_boxes(app),
+            last_runs: HashMap::new(),
+            running: HashMap::new(),
+        }
+    }
+
+    /// Follow boxes added, removed or changed since the last call, e.g. by `AddBox` or a
+    /// YAML reload. New boxes and boxes whose script changed run on the next [`Self::due`];
+    /// the others keep their schedule. Returns whether anything changed.
+    pub fn update(&mut self, app: &App) -> bool {
+        let boxes = choices_boxes(app);
+        if boxes == self.boxes {
+            return false;
+        }
+        let unchanged = |box_id: &str| {
+            boxes.iter().any(|(id, script, _)| {
+                id == box_id
+                    && self
+                        .boxes
+                        .iter()
+                        .any(|(old_id, old_script, _)| old_id == id && old_script == script)
+            })
+        };
+        self.last_runs.retain(|box_id, _| unchanged(box_id));
+        self.running
+            .retain(|box_id, _| boxes.iter().any(|(id, _, _)| id == box_id));
+        self.boxes = boxes;
+        true
+    }
+
+    /// Boxes whose choices script should start at `now`: every box on the first call,
+    /// then boxes whose interval has passed and whose last run has finished
+    pub fn due(&mut self, now: Instant) -> Vec<String> {
+        let mut due = Vec::new();
+        for (box_id, _, interval) in &self.boxes {
+            if self.running.contains_key(box_id) {
+                continue;
+            }
+            let is_due = match (self.last_runs.get(box_id), interval) {
+                (None, _) => true,
+                (Some(last_run), Some(interval)) => now.duration_since(*last_run) >= *interval,
+                (Some(_), None) => false,
+            };
+            if is_due {
+                self.last_runs.insert(box_id.clone(), now);
+                due.push(box_id.clone());
+            }
+        }
+        due
+    }
+
+    /// Record that the box's choices script started writing to `stream_id`
+    pub fn started(&mut self, box_id: &str, stream_id: &str) {
+        self.running
+            .insert(box_id.to_string(), stream_id.to_string());
+    }
+
+    /// Notice finished runs. A Kill source action for a box's choices source cancels its
+    /// run, which then ends without an update.
+    pub fn observe(&mut self, message: &Message) {
+        match message {
+            Message::StreamUpdateMessage(update)
+                if stream_update_outcome(update).is_some()
+                    && self.running.get(&update.target_box_id) == Some(&update.stream_id) =>
+            {
+                self.running.remove(&update.target_box_id);
+            }
+            Message::SourceActionMessage(action) if action.action == ActionType::Kill => {
+                let box_id = self
+                    .running
+                    .keys()
+                    .find(|box_id| choices_source_id(box_id) == action.source_id)
+                    .cloned();
+                if let Some(stream_id) = box_id.and_then(|box_id| self.running.remove(&box_id)) {
+                    crate::utils::cancel_scripts(&stream_id);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Source ID of the runs of a box's `choices_script`
+fn choices_source_id(box_id: &str) -> String {
+    format!("choices-{}", box_id)
+}
+
+/// The `ExecuteScript` running a box's `choices_script` on a thread, with its output
+/// parsed as choices. Registers one `ChoicesScript` source per box.
+pub fn choices_script_execution(app: &mut App, box_id: &str) -> Option<ExecuteScript> {
+    let muxbox = app.get_muxbox_by_id(box_id)?;
+    let script = muxbox.choices_script.clone()?;
+    let target_bounds = muxbox.content_bounds();
+    let libs = app.libs.clone().unwrap_or_default();
+    let environment = app.script_environment(box_id, None);
+    let timeout_ms = app.script_timeout_ms(box_id, None);
+
+    let stream_id = app.register_execution_source(
+        ExecutionSourceType::ChoicesScript {
+            script: script.clone(),
+        },
+        box_id.to_string(),
+    );
+
+    Some(ExecuteScript {
+        script,
+        source: ExecutionSource {
+            source_type: SourceType::ChoicesScript,
+            source_id: choices_source_id(box_id),
+            source_reference: SourceReference::PeriodicConfig(box_id.to_string()),
+        },
+        execution_mode: ExecutionMode::Thread,
+        target_box_id: box_id.to_string(),
+        libs,
+        redirect_output: None,
+        append_output: false,
+        stream_id,
+        target_bounds: Some(target_bounds),
+        environment,
+        timeout_ms,
+        output_format: OutputFormat::Choices,
+        record: None,
+    })
+}
+
+create_runnable!(
+    ChoicesScriptLoop,
+    |_inner: &mut RunnableImpl, _app_context: AppContext, _messages: Vec<Message>| -> bool { true },
+    |inner: &mut RunnableImpl,
+     app_context: AppContext,
+     messages: Vec<Message>|
+     -> (bool, AppContext) {
+        // The refresher lives as long as this loop and follows the app as it changes
+        let mut refresher = ChoicesRefresher::new(&app_context.app);
+        let mut pending = messages;
+        loop {
+            if pending
+                .iter()
+                .any(|message| matches!(message, Message::Exit | Message::Terminate))
+            {
+                return (false, app_context);
+            }
+
+            let original_app_context = inner.app_context.clone();
+            let mut app_context_unwrapped = original_app_context.clone();
+            if refresher.update(&app_context_unwrapped.app) {
+                log::info!("Boxes or choices scripts changed, choices scripts reloaded");
+            }
+            for message in &pending {
+                refresher.observe(message);
+            }
+
+            for box_id in refresher.due(Instant::now()) {
+                let Some(execute_script) =
+                    choices_script_execution(&mut app_context_unwrapped.app, &box_id)
+                else {
+                    continue;
+                };
+                log::info!("Running choices_script of box {}", box_id);
+                refresher.started(&box_id, &execute_script.stream_id);
+                inner.send_message(Message::ExecuteScriptMessage(execute_script));
+            }
+
+            // Execution sources registered for the runs reach the other threads
+            if app_context_unwrapped != original_app_context {
+                inner.app_context = app_context_unwrapped;
+                inner.send_app_context_update(original_app_context);
+            }
+
+            std::thread::sleep(Duration::from_millis(inner.app_context.config.frame_delay));
+
+            let (updated_context, new_messages) = inner.receive_updates();
+            inner.app_context = updated_context;
+            pending = new_messages;
+        }
+    }
+);
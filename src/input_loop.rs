@@ -1259,8 +1259,8 @@ create_runnable!(
 
                         // Create ExecuteScript message for app-level keypress handlers
                         use crate::model::common::{
-                            ExecuteScript, ExecutionMode, ExecutionSource, SourceReference,
-                            SourceType,
+                            ExecuteScript, ExecutionMode, ExecutionSource, OutputFormat,
+                            ScriptEnvironment, SourceReference, SourceType,
                         };
 
                         // Register execution source and get stream_id
@@ -1289,6 +1289,10 @@ create_runnable!(
                             append_output: false,
                             stream_id,
                             target_bounds: None, // App-level commands don't target specific muxboxes
+                            environment: ScriptEnvironment::default(),
+                            timeout_ms: None,
+                            output_format: OutputFormat::Text,
+                            record: None,
                         };
 
                         inner.send_message(Message::ExecuteScriptMessage(execute_script));
@@ -1304,8 +1308,8 @@ create_runnable!(
 
                         // Create ExecuteScript message for layout-level keypress handlers
                         use crate::model::common::{
-                            ExecuteScript, ExecutionMode, ExecutionSource, SourceReference,
-                            SourceType,
+                            ExecuteScript, ExecutionMode, ExecutionSource, OutputFormat,
+                            ScriptEnvironment, SourceReference, SourceType,
                         };
 
                         // Register execution source and get stream_id
@@ -1334,6 +1338,10 @@ create_runnable!(
                             append_output: false,
                             stream_id,
                             target_bounds: None, // App-level commands don't target specific muxboxes
+                            environment: ScriptEnvironment::default(),
+                            timeout_ms: None,
+                            output_format: OutputFormat::Text,
+                            record: None,
                         };
 
                         inner.send_message(Message::ExecuteScriptMessage(execute_script));
@@ -3,21 +3,33 @@ pub mod model;
 #[macro_use]
 pub mod thread_manager;
 // pub mod choice_threads; // T311: Removed with ChoiceThreadManager unification
+pub mod alerts;
 pub mod ansi_color_processor;
 pub mod ansi_processor;
+pub mod attach;
 pub mod circular_buffer;
 pub mod color_utils;
 pub mod components;
 pub mod defaults;
 pub mod draw_loop;
 pub mod draw_utils;
+pub mod dynamic_choices;
 pub mod input_loop;
 pub mod live_yaml_sync;
 pub mod plugin;
+pub mod plugin_abi;
+pub mod plugin_process;
 pub mod pty_manager;
+pub mod recording;
+pub mod refresh_scheduler;
 pub mod resize_loop;
+pub mod schedule_loop;
 pub mod socket_loop;
+pub mod socket_protocol;
+pub mod socket_queries;
+pub mod structured_output;
 pub mod table;
+pub mod theme;
 pub mod utils;
 pub mod validation;
 
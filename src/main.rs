@@ -2,11 +2,20 @@
 extern crate lazy_static;
 extern crate clap;
 
+use boxmux_lib::attach;
 use boxmux_lib::create_runnable_with_dynamic_input;
+use boxmux_lib::dynamic_choices::ChoicesScriptLoop;
 use boxmux_lib::pty_manager::PtyManager;
+use boxmux_lib::refresh_scheduler;
 use boxmux_lib::resize_loop::ResizeLoop;
-use boxmux_lib::send_json_to_socket;
-use boxmux_lib::socket_loop::SocketLoop;
+use boxmux_lib::schedule_loop::ScheduleLoop;
+use boxmux_lib::socket_loop::{
+    list_socket_instances, remove_bound_socket, resolve_client_socket_path,
+    resolve_server_socket_path, SocketLoop, SOCKET_ENV_VAR,
+};
+use boxmux_lib::socket_protocol::{
+    send_socket_request, subscribe_to_socket, SocketEventType, SocketRequest,
+};
 use boxmux_lib::thread_manager;
 use boxmux_lib::DrawLoop;
 use boxmux_lib::FieldUpdate;
@@ -26,7 +35,9 @@ use uuid::Uuid;
 use boxmux_lib::model::app::*;
 
 lazy_static! {
-    static ref LAST_EXECUTION_TIMES: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+    // Last run of each periodic box and the delay until its next one
+    static ref LAST_EXECUTION_TIMES: Mutex<HashMap<String, (Instant, Duration)>> =
+        Mutex::new(HashMap::new());
 }
 
 fn run_muxbox_threads(manager: &mut ThreadManager, app_context: &AppContext) {
@@ -59,7 +70,14 @@ fn run_muxbox_threads(manager: &mut ThreadManager, app_context: &AppContext) {
                             let libs = app_context_unwrapped.app.libs.clone();
 
                             // Collect data from muxbox first to avoid borrow conflicts
-                            let (muxbox_id, execution_mode, script_unwrapped, refresh_interval) = {
+                            let (
+                                muxbox_id,
+                                execution_mode,
+                                script_unwrapped,
+                                refresh_interval,
+                                refresh_jitter_ms,
+                                refresh_backoff_max_ms,
+                            ) = {
                                 let muxbox = app_context_unwrapped
                                     .app
                                     .get_muxbox_by_id_mut(&vec[0])
@@ -69,6 +87,8 @@ fn run_muxbox_threads(manager: &mut ThreadManager, app_context: &AppContext) {
                                     muxbox.execution_mode.clone(),
                                     muxbox.script.clone().unwrap(),
                                     muxbox.calc_refresh_interval(&app_context, &app_graph),
+                                    muxbox.refresh_jitter_ms,
+                                    muxbox.refresh_backoff_max_ms,
                                 )
                             };
 
@@ -106,8 +126,18 @@ fn run_muxbox_threads(manager: &mut ThreadManager, app_context: &AppContext) {
                                 libs: libs.unwrap_or_default(),
                                 redirect_output: None,
                                 append_output: false,
-                                stream_id,
+                                stream_id: stream_id.clone(),
                                 target_bounds: None, // CLI-initiated scripts don't have specific target bounds
+                                environment: app_context_unwrapped
+                                    .app
+                                    .script_environment(&muxbox_id, None),
+                                timeout_ms: app_context_unwrapped
+                                    .app
+                                    .script_timeout_ms(&muxbox_id, None),
+                                output_format: app_context_unwrapped
+                                    .app
+                                    .script_output_format(&muxbox_id, None),
+                                record: app_context_unwrapped.app.script_record(&muxbox_id),
                             };
 
                             // Send ExecuteScript message instead of direct execution
@@ -117,7 +147,13 @@ fn run_muxbox_threads(manager: &mut ThreadManager, app_context: &AppContext) {
                                 "T0600: ExecuteScript message sent for background muxbox {} script (unified architecture)",
                                 muxbox_id
                             );
-                            std::thread::sleep(std::time::Duration::from_millis(refresh_interval));
+                            // Backs off after failed runs and adds the box's jitter
+                            std::thread::sleep(refresh_scheduler::next_delay(
+                                &stream_id,
+                                refresh_interval,
+                                refresh_jitter_ms,
+                                refresh_backoff_max_ms,
+                            ));
                             (true, app_context_unwrapped)
                         }
                     );
@@ -157,7 +193,13 @@ fn run_muxbox_threads(manager: &mut ThreadManager, app_context: &AppContext) {
                         let libs = app_context_unwrapped.app.libs.clone();
 
                         // Collect data from muxbox and then register source separately to avoid borrow conflicts
-                        let (refresh_interval, execution_mode, script) = {
+                        let (
+                            refresh_interval,
+                            execution_mode,
+                            script,
+                            refresh_jitter_ms,
+                            refresh_backoff_max_ms,
+                        ) = {
                             let muxbox = app_context_unwrapped
                                 .app
                                 .get_muxbox_by_id_mut(muxbox_id)
@@ -166,15 +208,16 @@ fn run_muxbox_threads(manager: &mut ThreadManager, app_context: &AppContext) {
                                 muxbox.refresh_interval.unwrap_or(1000),
                                 muxbox.execution_mode.clone(),
                                 muxbox.script.clone(),
+                                muxbox.refresh_jitter_ms,
+                                muxbox.refresh_backoff_max_ms,
                             )
                         };
 
-                        let last_execution_time = last_execution_times
+                        let (last_execution_time, delay) = last_execution_times
                             .entry(muxbox_id.clone())
-                            .or_insert(Instant::now());
+                            .or_insert((Instant::now(), Duration::from_millis(refresh_interval)));
 
-                        if last_execution_time.elapsed() >= Duration::from_millis(refresh_interval)
-                        {
+                        if last_execution_time.elapsed() >= *delay {
                             let _sender_for_pty = inner.get_message_sender();
                             let _thread_uuid = inner.get_uuid();
 
@@ -210,8 +253,18 @@ fn run_muxbox_threads(manager: &mut ThreadManager, app_context: &AppContext) {
                                 libs: libs.unwrap_or_default(),
                                 redirect_output: None, // Periodic refresh updates self, no redirection
                                 append_output: false,  // CRITICAL: Replace content, don't append
-                                stream_id,
+                                stream_id: stream_id.clone(),
                                 target_bounds: None, // CLI-initiated scripts don't have specific target bounds
+                                environment: app_context_unwrapped
+                                    .app
+                                    .script_environment(&muxbox_id, None),
+                                timeout_ms: app_context_unwrapped
+                                    .app
+                                    .script_timeout_ms(&muxbox_id, None),
+                                output_format: app_context_unwrapped
+                                    .app
+                                    .script_output_format(&muxbox_id, None),
+                                record: app_context_unwrapped.app.script_record(&muxbox_id),
                             };
 
                             // Send ExecuteScript message instead of direct execution
@@ -223,6 +276,13 @@ fn run_muxbox_threads(manager: &mut ThreadManager, app_context: &AppContext) {
                             );
 
                             *last_execution_time = Instant::now();
+                            // Backs off after failed runs and adds the box's jitter
+                            *delay = refresh_scheduler::next_delay(
+                                &stream_id,
+                                refresh_interval,
+                                refresh_jitter_ms,
+                                refresh_backoff_max_ms,
+                            );
                         }
                     }
 
@@ -240,6 +300,89 @@ fn run_muxbox_threads(manager: &mut ThreadManager, app_context: &AppContext) {
     }
 }
 
+/// Resolve the control socket a client subcommand should target (`--socket`, `$BOXMUX_SOCKET`,
+/// or the only running instance)
+fn client_socket_path(matches: &clap::ArgMatches) -> Result<String, Box<dyn std::error::Error>> {
+    let requested = matches.get_one::<String>("socket").map(|s| s.as_str());
+    let path = resolve_client_socket_path(requested)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Send a socket function as a protocol request and print the JSON response.
+/// Exits with status 1 when boxmux reports an error.
+fn send_socket_function(
+    matches: &clap::ArgMatches,
+    socket_function: SocketFunction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let request = SocketRequest::new(socket_function);
+    let response = send_socket_request(&client_socket_path(matches)?, &request)?;
+    println!("{}", response.to_json());
+    if !response.is_ok() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Load the plugins listed in `app.plugins` into the shared registry. A plugin that fails
+/// to load is reported against the YAML file and skipped; boxmux keeps starting.
+fn load_app_plugins(app_context: &AppContext, yaml_path: &Path) {
+    let Some(plugins) = app_context.app.plugins.as_ref() else {
+        return;
+    };
+    let failures = app_context
+        .plugin_registry
+        .lock()
+        .unwrap()
+        .load_configured_plugins(plugins, yaml_path.parent());
+    if failures.is_empty() {
+        return;
+    }
+
+    let yaml_contents = std::fs::read_to_string(yaml_path).unwrap_or_default();
+    let yaml_path = yaml_path.to_string_lossy();
+    for failure in failures {
+        log::error!("{}", failure.diagnostic(&yaml_path, &yaml_contents));
+    }
+}
+
+/// Play a recording into a full-screen box until the user quits
+fn run_replay(
+    matches: &clap::ArgMatches,
+    frame_delay: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use boxmux_lib::recording::{self, Player, Recording, ReplayLoop};
+    use crossterm::{event, execute, terminal};
+
+    let path = Path::new(matches.get_one::<String>("file").unwrap());
+    let recording = Recording::load(path)?;
+    let app = recording::replay_app(path, &recording);
+    recording::set_player(Player::new(
+        recording,
+        *matches.get_one::<f64>("speed").unwrap(),
+        matches.get_one::<f64>("max_idle").copied(),
+    ));
+    let app_context = AppContext::new(app, boxmux_lib::model::common::Config::new(frame_delay));
+
+    let mut _stdout = std::io::stdout();
+    execute!(_stdout, terminal::EnterAlternateScreen)?;
+    execute!(_stdout, terminal::Clear(terminal::ClearType::All))?;
+    terminal::enable_raw_mode()?;
+    execute!(_stdout, event::EnableMouseCapture)?;
+    setup_signal_handler();
+
+    let mut manager = ThreadManager::new(app_context.clone());
+    manager.spawn_thread(InputLoop::new(app_context.clone()));
+    manager.spawn_thread(DrawLoop::new(app_context.clone()));
+    manager.spawn_thread(ResizeLoop::new(app_context.clone()));
+    manager.spawn_thread(ReplayLoop::new(app_context.clone()));
+    manager.run();
+
+    execute!(_stdout, event::DisableMouseCapture)?;
+    execute!(_stdout, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    Ok(())
+}
+
 /// Setup signal handler to ensure proper terminal cleanup on exit
 fn setup_signal_handler() {
     use signal_hook::{consts::SIGINT, iterator::Signals};
@@ -355,6 +498,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .version(env!("CARGO_PKG_VERSION"))
         .author("jowharshamshiri@gmail.com")
         .about("A terminal multiplexer")
+        .subcommand_negates_reqs(true)
         .arg(
             Arg::new("yaml_file")
                 .required(true)
@@ -410,6 +554,33 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .conflicts_with("dark")
                 .help("Force light theme for default colors (overrides auto-detection)"),
         )
+        .arg(
+            Arg::new("theme")
+                .long("theme")
+                .value_name("NAME|FILE")
+                .help("Color theme: solarized-dark, solarized-light, nord, gruvbox or a theme file (overrides app.theme)"),
+        )
+        .arg(
+            Arg::new("socket")
+                .long("socket")
+                .value_name("PATH")
+                .global(true)
+                .help("Control socket path; for subcommands also an instance name or PID"),
+        )
+        .arg(
+            Arg::new("detached")
+                .long("detached")
+                .action(clap::ArgAction::SetTrue)
+                .help("Run in the background as a session; connect with `boxmux attach`"),
+        )
+        .subcommand(Command::new("list_instances").about("Lists running boxmux instances"))
+        .subcommand(
+            Command::new("attach")
+                .about("Attaches this terminal to a detached session (Ctrl-\\ detaches)"),
+        )
+        .subcommand(
+            Command::new("detach").about("Detaches the terminal attached to a detached session"),
+        )
         .subcommand(
             Command::new("stop_box_refresh")
                 .about("Stops the refresh of the box")
@@ -541,6 +712,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .help("The box id with the PTY process to restart"),
                 ),
         )
+        .subcommand(
+            Command::new("send_pty_signal")
+                .about("Sends a signal to the process group of a box's PTY process")
+                .arg(
+                    Arg::new("box_id")
+                        .required(true)
+                        .index(1)
+                        .help("The box id with the PTY process to signal"),
+                )
+                .arg(
+                    Arg::new("signal")
+                        .required(true)
+                        .index(2)
+                        .help("INT, TERM, HUP, STOP, CONT or KILL (SIG prefix optional)"),
+                ),
+        )
         // F0138: Socket PTY Query - Get PTY status and info
         .subcommand(
             Command::new("query_pty_status")
@@ -596,11 +783,140 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .help("The input text to send to the PTY process"),
                 ),
         )
+        .subcommand(
+            Command::new("get_box_content")
+                .about("Prints a box's static content and streams")
+                .arg(
+                    Arg::new("box_id")
+                        .required(true)
+                        .index(1)
+                        .help("The box id to read"),
+                ),
+        )
+        .subcommand(
+            Command::new("list_boxes")
+                .about("Lists the boxes of a layout with their computed bounds")
+                .arg(
+                    Arg::new("layout")
+                        .long("layout")
+                        .value_name("LAYOUT_ID")
+                        .help("Layout to list (defaults to the active layout)"),
+                ),
+        )
+        .subcommand(Command::new("list_layouts").about("Lists layouts and the active layout"))
+        .subcommand(
+            Command::new("list_execution_sources")
+                .about("Lists registered execution sources and their status"),
+        )
+        .subcommand(
+            Command::new("get_pty_buffer")
+                .about("Prints lines from a PTY box's scrollback buffer")
+                .arg(
+                    Arg::new("box_id")
+                        .required(true)
+                        .index(1)
+                        .help("The box id with the PTY process to read"),
+                )
+                .arg(
+                    Arg::new("start")
+                        .long("start")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("First line to return (defaults to the tail of the buffer)"),
+                )
+                .arg(
+                    Arg::new("count")
+                        .long("count")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of lines to return (default 100)"),
+                ),
+        )
+        .subcommand(
+            Command::new("search_pty_buffer")
+                .about("Searches a PTY box's scrollback buffer (case-insensitive)")
+                .arg(
+                    Arg::new("box_id")
+                        .required(true)
+                        .index(1)
+                        .help("The box id with the PTY process to search"),
+                )
+                .arg(
+                    Arg::new("query")
+                        .required(true)
+                        .index(2)
+                        .help("The text to search for"),
+                ),
+        )
+        .subcommand(
+            Command::new("subscribe")
+                .about("Streams events from a running instance as JSON lines")
+                .arg(
+                    Arg::new("box")
+                        .long("box")
+                        .value_name("BOX_ID")
+                        .action(clap::ArgAction::Append)
+                        .help("Only box-scoped events for this box (repeatable)"),
+                )
+                .arg(
+                    Arg::new("event")
+                        .long("event")
+                        .value_name("TYPE")
+                        .action(clap::ArgAction::Append)
+                        .help("Only events of this type, e.g. stream_finished (repeatable)"),
+                ),
+        )
+        .subcommand(
+            Command::new("replay")
+                .about("Replays an asciicast recording of a PTY box")
+                .arg(
+                    Arg::new("file")
+                        .required(true)
+                        .index(1)
+                        .help("Recording written by a box's `record` option"),
+                )
+                .arg(
+                    Arg::new("speed")
+                        .long("speed")
+                        .value_name("FACTOR")
+                        .value_parser(clap::value_parser!(f64))
+                        .default_value("1")
+                        .help("Playback speed relative to the recording"),
+                )
+                .arg(
+                    Arg::new("max_idle")
+                        .long("max-idle")
+                        .value_name("SECONDS")
+                        .value_parser(clap::value_parser!(f64))
+                        .help("Shorten longer pauses to this (default: the recording's idle_time_limit)"),
+                ),
+        )
         .get_matches();
 
     // Initialize logging framework (F0161/F0162)
     initialize_logging(&matches)?;
 
+    // Handle the list_instances subcommand
+    if matches.subcommand_matches("list_instances").is_some() {
+        for instance in list_socket_instances() {
+            let pid = instance
+                .pid
+                .map(|pid| pid.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            println!("{}\t{}\t{}", instance.name, pid, instance.path.display());
+        }
+        return Ok(());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("attach") {
+        let note = attach::run_attach_client(&client_socket_path(matches)?)?;
+        println!("{}", note);
+        return Ok(());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("detach") {
+        send_socket_function(matches, SocketFunction::Detach {})?;
+        return Ok(());
+    }
+
     // Handle the stop_box_refresh subcommand
     if let Some(matches) = matches.subcommand_matches("stop_box_refresh") {
         if let Some(box_id) = matches.get_one::<String>("box_id") {
@@ -609,10 +925,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 box_id: box_id.clone(),
             };
 
-            let socket_function_json = serde_json::to_string(&socket_function)?;
-
-            // Send the constructed value to the socket function
-            send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+            send_socket_function(matches, socket_function)?;
 
             return Ok(());
         } else {
@@ -628,10 +941,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 box_id: box_id.clone(),
             };
 
-            let socket_function_json = serde_json::to_string(&socket_function)?;
-
-            // Send the constructed value to the socket function
-            send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+            send_socket_function(matches, socket_function)?;
 
             return Ok(());
         } else {
@@ -651,10 +961,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     new_box: submitted_box,
                 };
 
-                let socket_function_json = serde_json::to_string(&socket_function)?;
-
-                // Send the constructed value to the socket function
-                send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+                send_socket_function(matches, socket_function)?;
 
                 return Ok(());
             } else {
@@ -673,10 +980,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 layout_id: layout_id_to_switch_to.to_string(),
             };
 
-            let socket_function_json = serde_json::to_string(&socket_function)?;
-
-            // Send the constructed value to the socket function
-            send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+            send_socket_function(matches, socket_function)?;
 
             return Ok(());
         } else {
@@ -696,10 +1000,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     script: new_box_script,
                 };
 
-                let socket_function_json = serde_json::to_string(&socket_function)?;
-
-                // Send the constructed value to the socket function
-                send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+                send_socket_function(matches, socket_function)?;
 
                 return Ok(());
             } else {
@@ -725,10 +1026,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     content: new_box_content.clone(),
                 };
 
-                let socket_function_json = serde_json::to_string(&socket_function)?;
-
-                // Send the constructed value to the socket function
-                send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+                send_socket_function(matches, socket_function)?;
 
                 return Ok(());
             } else {
@@ -751,10 +1049,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     muxbox: submitted_muxbox,
                 };
 
-                let socket_function_json = serde_json::to_string(&socket_function)?;
-
-                // Send the constructed value to the socket function
-                send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+                send_socket_function(matches, socket_function)?;
 
                 return Ok(());
             } else {
@@ -773,10 +1068,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 box_id: box_id.clone(),
             };
 
-            let socket_function_json = serde_json::to_string(&socket_function)?;
-
-            // Send the constructed value to the socket function
-            send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+            send_socket_function(matches, socket_function)?;
 
             return Ok(());
         } else {
@@ -791,8 +1083,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 box_id: box_id.clone(),
             };
 
-            let socket_function_json = serde_json::to_string(&socket_function)?;
-            send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+            send_socket_function(matches, socket_function)?;
 
             return Ok(());
         } else {
@@ -807,8 +1098,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 box_id: box_id.clone(),
             };
 
-            let socket_function_json = serde_json::to_string(&socket_function)?;
-            send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+            send_socket_function(matches, socket_function)?;
 
             return Ok(());
         } else {
@@ -816,6 +1106,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    if let Some(matches) = matches.subcommand_matches("send_pty_signal") {
+        if let (Some(box_id), Some(signal)) = (
+            matches.get_one::<String>("box_id"),
+            matches.get_one::<String>("signal"),
+        ) {
+            let socket_function = SocketFunction::SendPtySignal {
+                box_id: box_id.clone(),
+                signal: signal.parse()?,
+            };
+
+            send_socket_function(matches, socket_function)?;
+
+            return Ok(());
+        } else {
+            return Err("Box ID and signal are required for send_pty_signal command".into());
+        }
+    }
+
     // F0138: Socket PTY Query - Handle query_pty_status subcommand
     if let Some(matches) = matches.subcommand_matches("query_pty_status") {
         if let Some(box_id) = matches.get_one::<String>("box_id") {
@@ -823,8 +1131,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 box_id: box_id.clone(),
             };
 
-            let socket_function_json = serde_json::to_string(&socket_function)?;
-            send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+            send_socket_function(matches, socket_function)?;
 
             return Ok(());
         } else {
@@ -860,8 +1167,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     redirect_output,
                 };
 
-                let socket_function_json = serde_json::to_string(&socket_function)?;
-                send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+                send_socket_function(matches, socket_function)?;
 
                 return Ok(());
             } else {
@@ -881,8 +1187,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     input: input.clone(),
                 };
 
-                let socket_function_json = serde_json::to_string(&socket_function)?;
-                send_json_to_socket("/tmp/boxmux.sock", &socket_function_json)?;
+                send_socket_function(matches, socket_function)?;
 
                 return Ok(());
             } else {
@@ -893,6 +1198,86 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    // Read-only query subcommands
+    if let Some(matches) = matches.subcommand_matches("get_box_content") {
+        let box_id = matches.get_one::<String>("box_id").unwrap().clone();
+        send_socket_function(matches, SocketFunction::GetBoxContent { box_id })?;
+        return Ok(());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("list_boxes") {
+        let layout_id = matches.get_one::<String>("layout").cloned();
+        send_socket_function(matches, SocketFunction::ListBoxes { layout_id })?;
+        return Ok(());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("list_layouts") {
+        send_socket_function(matches, SocketFunction::ListLayouts {})?;
+        return Ok(());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("list_execution_sources") {
+        send_socket_function(matches, SocketFunction::ListExecutionSources {})?;
+        return Ok(());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("get_pty_buffer") {
+        let socket_function = SocketFunction::GetPtyBuffer {
+            box_id: matches.get_one::<String>("box_id").unwrap().clone(),
+            start: matches.get_one::<usize>("start").copied(),
+            count: matches.get_one::<usize>("count").copied(),
+        };
+        send_socket_function(matches, socket_function)?;
+        return Ok(());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("search_pty_buffer") {
+        let socket_function = SocketFunction::SearchPtyBuffer {
+            box_id: matches.get_one::<String>("box_id").unwrap().clone(),
+            query: matches.get_one::<String>("query").unwrap().clone(),
+        };
+        send_socket_function(matches, socket_function)?;
+        return Ok(());
+    }
+
+    // Handle the subscribe subcommand: print events until the instance exits
+    if let Some(matches) = matches.subcommand_matches("subscribe") {
+        let box_ids = matches
+            .get_many::<String>("box")
+            .map(|values| values.cloned().collect::<Vec<_>>());
+        let events = match matches.get_many::<String>("event") {
+            Some(values) => Some(
+                values
+                    .map(|value| {
+                        serde_json::from_value::<SocketEventType>(serde_json::Value::String(
+                            value.clone(),
+                        ))
+                        .map_err(|_| format!("Unknown event type: {}", value))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+
+        let request = SocketRequest::new(SocketFunction::Subscribe { box_ids, events });
+        subscribe_to_socket(&client_socket_path(matches)?, &request, |line| {
+            use std::io::Write;
+            let mut stdout = std::io::stdout();
+            writeln!(stdout, "{}", line).is_ok() && stdout.flush().is_ok()
+        })?;
+
+        return Ok(());
+    }
+
+    if let Some(replay_matches) = matches.subcommand_matches("replay") {
+        let frame_delay = matches
+            .get_one::<String>("frame_delay")
+            .unwrap()
+            .parse::<u64>()
+            .unwrap_or(100);
+        return run_replay(replay_matches, frame_delay);
+    }
+
     let yaml_path = matches.get_one::<String>("yaml_file").unwrap();
     let frame_delay = matches
         .get_one::<String>("frame_delay")
@@ -926,11 +1311,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .map_err(|e| format!("Failed to resolve absolute path for YAML file: {}", e))?;
 
     // Removed old simplelog - using our new comprehensive logging system instead
-    let config = boxmux_lib::model::common::Config::new_with_lock_and_calibration(
-        frame_delay,
-        locked,
-        calibrate,
-    );
     let app = match load_app_from_yaml_with_lock(yaml_path.to_str().unwrap(), locked) {
         Ok(app) => app,
         Err(e) => {
@@ -941,6 +1321,61 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    // Theme precedence: --theme (relative to the working directory) > app.theme
+    // (relative to the YAML file) > the light/dark defaults
+    let theme = match matches.get_one::<String>("theme") {
+        Some(theme) => Some((theme.clone(), std::env::current_dir()?)),
+        None => app.theme.clone().map(|theme| {
+            let yaml_dir = yaml_path.parent().unwrap_or(Path::new("/")).to_path_buf();
+            (theme, yaml_dir)
+        }),
+    };
+    if let Some((theme, base_dir)) = theme {
+        match boxmux_lib::theme::load_theme(&theme, &base_dir) {
+            Ok(theme) => boxmux_lib::theme::set_active_theme(Some(theme)),
+            Err(e) => {
+                log::error!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Socket path precedence: --socket > app.socket_path > per-instance default
+    let socket_path = matches
+        .get_one::<String>("socket")
+        .cloned()
+        .or_else(|| app.socket_path.clone());
+    let config = boxmux_lib::model::common::Config::new_with_lock_and_calibration(
+        frame_delay,
+        locked,
+        calibrate,
+    )
+    .with_socket_path(socket_path);
+
+    // --detached runs everything below in a session server that `boxmux attach` connects to
+    if matches.get_flag("detached") {
+        match attach::take_session_size() {
+            Some((cols, rows)) => attach::start_session_terminal(cols, rows)
+                .map_err(|e| format!("Failed to set up the session terminal: {}", e))?,
+            None => {
+                let socket_path = attach::spawn_detached_session(
+                    config.socket_path.as_deref(),
+                    yaml_path.to_str().unwrap(),
+                )?;
+                println!(
+                    "Started detached session on {}; attach with: boxmux --socket {} attach",
+                    socket_path.display(),
+                    socket_path.display()
+                );
+                return Ok(());
+            }
+        }
+    }
+
+    if let Some(pty_config) = app.pty.as_ref() {
+        boxmux_lib::pty_manager::set_stop_sequence(pty_config.stop_sequence());
+    }
+
     // Initialize PTY manager for this session
     let pty_manager = match PtyManager::new() {
         Ok(mgr) => {
@@ -970,6 +1405,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
     };
 
+    load_app_plugins(&app_context, &yaml_path);
+
     //create alternate screen in terminal and clear it
     use crossterm::{event, execute, terminal};
     let mut _stdout = std::io::stdout();
@@ -985,17 +1422,33 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Setup signal handler for proper terminal cleanup on exit
     setup_signal_handler();
 
+    // Let scripts and PTY processes address this instance's socket
+    let server_socket_path = resolve_server_socket_path(&app_context);
+    std::env::set_var(SOCKET_ENV_VAR, &server_socket_path);
+
     let mut manager = ThreadManager::new(app_context.clone());
 
     let _input_loop_uuid = manager.spawn_thread(InputLoop::new(app_context.clone()));
     let _draw_loop_uuid = manager.spawn_thread(DrawLoop::new(app_context.clone()));
     let _resize_loop_uuid = manager.spawn_thread(ResizeLoop::new(app_context.clone()));
     let _socket_loop_uuid = manager.spawn_thread(SocketLoop::new(app_context.clone()));
+    // Always running: boxes added through the socket or a reload may bring schedules
+    // and choices scripts
+    manager.spawn_thread(ScheduleLoop::new(app_context.clone()));
+    manager.spawn_thread(ChoicesScriptLoop::new(app_context.clone()));
 
     run_muxbox_threads(&mut manager, &app_context);
 
     manager.run();
 
+    // Give PTY processes the configured stop sequence instead of leaving them behind
+    if let Some(pty_mgr) = pty_manager.as_ref() {
+        pty_mgr.stop_all();
+    }
+
+    // Only this instance's own socket: a shared socket_path may belong to another one
+    remove_bound_socket();
+
     //restore normal terminal state
     execute!(_stdout, event::DisableMouseCapture)?;
     execute!(_stdout, terminal::LeaveAlternateScreen)?;
@@ -29,7 +29,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::validation::SchemaValidator;
-use crate::{calculate_bounds_map, Config, FieldUpdate, Updatable};
+use crate::{calculate_bounds_map, BorderSides, Config, FieldUpdate, StyleClass, Updatable};
 use core::hash::Hash;
 use regex::Regex;
 use std::env;
@@ -151,6 +151,66 @@ pub struct TemplateRoot {
     pub app: App,
 }
 
+/// `app.remote_control`: control listeners for clients on other hosts or containers.
+/// They accept the same requests as the Unix socket, gated by a shared token.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RemoteControlConfig {
+    /// Address for newline-delimited JSON over TCP, e.g. `127.0.0.1:7878`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_address: Option<String>,
+    /// Address for JSON text messages over WebSocket, e.g. `127.0.0.1:7879`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub websocket_address: Option<String>,
+    /// Shared secret; `BOXMUX_REMOTE_TOKEN` takes precedence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Socket functions remote clients may call; only the read-only queries when omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_functions: Option<Vec<String>>,
+}
+
+/// `app.plugins`: plugins loaded into the registry at startup. Relative paths are
+/// resolved against the YAML file's directory and `~` expands to `$HOME`.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PluginsConfig {
+    /// Plugin directories, each holding a `plugin.toml`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
+    /// Directories whose subdirectories are plugins, e.g. `~/.config/boxmux/plugins`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directories: Option<Vec<String>>,
+    /// Commands plugins may declare in `Process` permissions, e.g. `python3`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_commands: Option<Vec<String>>,
+    /// Paths plugins may declare in `FileSystem` permissions (prefix match)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_paths: Option<Vec<String>>,
+}
+
+/// `app.pty`: how PTY processes are stopped on kill, restart, tab close and exit
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PtyConfig {
+    /// Signal sent to the process group first (default `TERM`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_signal: Option<crate::pty_manager::PtySignal>,
+    /// Milliseconds to wait before escalating to `KILL` (default 2000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_timeout_ms: Option<u64>,
+}
+
+impl PtyConfig {
+    pub fn stop_sequence(&self) -> crate::pty_manager::StopSequence {
+        let default = crate::pty_manager::StopSequence::default();
+        crate::pty_manager::StopSequence {
+            signal: self.stop_signal.unwrap_or(default.signal),
+            timeout: self
+                .stop_timeout_ms
+                .map(std::time::Duration::from_millis)
+                .unwrap_or(default.timeout),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct App {
     pub layouts: Vec<Layout>,
@@ -162,6 +222,25 @@ pub struct App {
     pub hot_keys: Option<HashMap<String, String>>,
     #[serde(default)]
     pub variables: Option<HashMap<String, String>>,
+    /// Control socket path; overridden by `--socket` on the command line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket_path: Option<String>,
+    /// Opt-in TCP/WebSocket control listeners
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_control: Option<RemoteControlConfig>,
+    /// Plugins to load at startup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugins: Option<PluginsConfig>,
+    /// PTY process handling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pty: Option<PtyConfig>,
+    /// Built-in theme name or theme file path relative to the YAML file; overridden
+    /// by `--theme` on the command line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Named style classes that boxes, layouts and choices pick up through `class`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub styles: Option<HashMap<String, StyleClass>>,
     #[serde(skip)]
     app_graph: Option<AppGraph>,
     #[serde(skip)]
@@ -197,6 +276,12 @@ impl App {
             on_keypress: None,
             hot_keys: None,
             variables: None,
+            socket_path: None,
+            remote_control: None,
+            plugins: None,
+            pty: None,
+            theme: None,
+            styles: None,
             app_graph: None,
             adjusted_bounds: None,
             execution_sources: HashMap::new(),
@@ -338,6 +423,64 @@ impl App {
         None
     }
 
+    /// A box and, if given, one of its choices
+    fn script_owner(
+        &self,
+        muxbox_id: &str,
+        choice_id: Option<&str>,
+    ) -> (Option<&MuxBox>, Option<&crate::model::choice::Choice>) {
+        let muxbox = self.get_muxbox_by_id(muxbox_id);
+        let choice = choice_id.and_then(|choice_id| {
+            let muxbox = muxbox?;
+            muxbox
+                .choices
+                .iter()
+                .flatten()
+                .chain(muxbox.get_selected_stream_choices().into_iter().flatten())
+                .find(|choice| choice.id == choice_id)
+        });
+        (muxbox, choice)
+    }
+
+    /// Script environment for a box, with the given choice's settings layered on top
+    pub fn script_environment(
+        &self,
+        muxbox_id: &str,
+        choice_id: Option<&str>,
+    ) -> crate::model::common::ScriptEnvironment {
+        let (muxbox, choice) = self.script_owner(muxbox_id, choice_id);
+        crate::model::common::ScriptEnvironment::resolve(muxbox, choice)
+    }
+
+    /// `timeout_ms` of the given choice, falling back to its box's
+    pub fn script_timeout_ms(&self, muxbox_id: &str, choice_id: Option<&str>) -> Option<u64> {
+        let (muxbox, choice) = self.script_owner(muxbox_id, choice_id);
+        choice
+            .and_then(|choice| choice.timeout_ms)
+            .or_else(|| muxbox.and_then(|muxbox| muxbox.timeout_ms))
+    }
+
+    /// Output format of the given choice's script, or of the box's own script when no
+    /// choice is given. Choices don't inherit their box's format.
+    pub fn script_output_format(
+        &self,
+        muxbox_id: &str,
+        choice_id: Option<&str>,
+    ) -> crate::model::common::OutputFormat {
+        let (muxbox, choice) = self.script_owner(muxbox_id, choice_id);
+        match choice_id {
+            Some(_) => choice.and_then(|choice| choice.output_format),
+            None => muxbox.and_then(|muxbox| muxbox.output_format),
+        }
+        .unwrap_or_default()
+    }
+
+    /// `record` path of the box whose PTY runs a script
+    pub fn script_record(&self, muxbox_id: &str) -> Option<String> {
+        self.get_muxbox_by_id(muxbox_id)
+            .and_then(|muxbox| muxbox.record.clone())
+    }
+
     pub fn validate(&mut self) {
         let mut validator = SchemaValidator::new();
         match validator.validate_app(self) {
@@ -546,6 +689,12 @@ impl Clone for App {
             on_keypress: self.on_keypress.clone(),
             hot_keys: self.hot_keys.clone(),
             variables: self.variables.clone(),
+            socket_path: self.socket_path.clone(),
+            remote_control: self.remote_control.clone(),
+            plugins: self.plugins.clone(),
+            pty: self.pty.clone(),
+            theme: self.theme.clone(),
+            styles: self.styles.clone(),
             app_graph: self.app_graph.clone(),
             adjusted_bounds: self.adjusted_bounds.clone(),
             execution_sources: self.execution_sources.clone(),
@@ -1101,6 +1250,9 @@ pub fn load_app_from_yaml_with_lock(
     let mut validator = SchemaValidator::new();
     match validator.validate_app(&app) {
         Ok(_) => {
+            apply_style_classes(&mut app);
+            inherit_border_settings(&mut app);
+
             // Apply the old validation logic for setting up parent relationships and defaults
             apply_post_validation_setup(&mut app)?;
 
@@ -1207,12 +1359,60 @@ fn apply_muxbox_variable_substitution(
         }
     }
 
+    if let Some(ref mut choices_script) = muxbox.choices_script {
+        for (i, script_line) in choices_script.iter_mut().enumerate() {
+            *script_line = local_context
+                .substitute_in_string(script_line, &full_hierarchy)
+                .map_err(|e| {
+                    format!(
+                        "Error in muxbox '{}' choices_script line {}: {}",
+                        muxbox.id,
+                        i + 1,
+                        e
+                    )
+                })?;
+        }
+    }
+
+    // `${item}` stays in place for each generated choice to fill in
+    if let Some(ref mut item_script) = muxbox.choices_item_script {
+        for (i, script_line) in item_script.iter_mut().enumerate() {
+            let parts = script_line
+                .split("${item}")
+                .map(|part| local_context.substitute_in_string(part, &full_hierarchy))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| {
+                    format!(
+                        "Error in muxbox '{}' choices_item_script line {}: {}",
+                        muxbox.id,
+                        i + 1,
+                        e
+                    )
+                })?;
+            *script_line = parts.join("${item}");
+        }
+    }
+
     if let Some(ref mut redirect) = muxbox.redirect_output {
         *redirect = local_context
             .substitute_in_string(redirect, &full_hierarchy)
             .map_err(|e| format!("Error in muxbox '{}' redirect_output: {}", muxbox.id, e))?;
     }
 
+    if let Some(ref mut cwd) = muxbox.cwd {
+        *cwd = local_context
+            .substitute_in_string(cwd, &full_hierarchy)
+            .map_err(|e| format!("Error in muxbox '{}' cwd: {}", muxbox.id, e))?;
+    }
+
+    if let Some(ref mut env) = muxbox.env {
+        for (name, value) in env.iter_mut() {
+            *value = local_context
+                .substitute_in_string(value, &full_hierarchy)
+                .map_err(|e| format!("Error in muxbox '{}' env '{}': {}", muxbox.id, name, e))?;
+        }
+    }
+
     // Apply to choices if present
     if let Some(ref mut choices) = muxbox.choices {
         for choice in choices {
@@ -1242,6 +1442,30 @@ fn apply_muxbox_variable_substitution(
                         })?;
                 }
             }
+
+            if let Some(ref mut choice_cwd) = choice.cwd {
+                *choice_cwd = local_context
+                    .substitute_in_string(choice_cwd, &full_hierarchy)
+                    .map_err(|e| {
+                        format!(
+                            "Error in muxbox '{}' choice '{}' cwd: {}",
+                            muxbox.id, choice.id, e
+                        )
+                    })?;
+            }
+
+            if let Some(ref mut choice_env) = choice.env {
+                for (name, value) in choice_env.iter_mut() {
+                    *value = local_context
+                        .substitute_in_string(value, &full_hierarchy)
+                        .map_err(|e| {
+                            format!(
+                                "Error in muxbox '{}' choice '{}' env '{}': {}",
+                                muxbox.id, choice.id, name, e
+                            )
+                        })?;
+                }
+            }
         }
     }
 
@@ -1257,6 +1481,141 @@ fn apply_muxbox_variable_substitution(
     Ok(())
 }
 
+/// Copy each listed field from a style class wherever the target leaves it unset
+macro_rules! fill_unset {
+    ($target:expr, $class:expr, $($field:ident),+ $(,)?) => {
+        $(
+            if $target.$field.is_none() {
+                $target.$field = $class.$field.clone();
+            }
+        )+
+    };
+}
+
+/// Fill the styling fields boxes and layouts share from a style class
+macro_rules! fill_unset_box_styles {
+    ($target:expr, $class:expr) => {
+        fill_unset!(
+            $target,
+            $class,
+            fill,
+            fill_char,
+            selected_fill_char,
+            highlighted_fill_char,
+            border,
+            border_style,
+            border_color,
+            selected_border_color,
+            highlighted_border_color,
+            bg_color,
+            selected_bg_color,
+            highlighted_bg_color,
+            fg_color,
+            selected_fg_color,
+            highlighted_fg_color,
+            title_fg_color,
+            title_bg_color,
+            selected_title_bg_color,
+            selected_title_fg_color,
+            highlighted_title_bg_color,
+            highlighted_title_fg_color,
+            title_position,
+            menu_fg_color,
+            menu_bg_color,
+            selected_menu_fg_color,
+            selected_menu_bg_color,
+            highlighted_menu_fg_color,
+            highlighted_menu_bg_color,
+            error_border_color,
+            error_bg_color,
+            error_fg_color,
+            error_title_bg_color,
+            error_title_fg_color,
+            error_selected_border_color,
+            error_selected_bg_color,
+            error_selected_fg_color,
+            error_selected_title_bg_color,
+            error_selected_title_fg_color,
+        )
+    };
+}
+
+/// Resolve every `class` into the styling fields of its layout, box or choice.
+/// Fields set on the element win over its classes, and later classes over earlier
+/// ones; whatever stays unset is inherited as usual.
+fn apply_style_classes(app: &mut App) {
+    let Some(styles) = app.styles.clone() else {
+        return;
+    };
+    for layout in &mut app.layouts {
+        for class in named_classes(&layout.class, &styles) {
+            fill_unset_box_styles!(layout, class);
+        }
+        for muxbox in layout.children.iter_mut().flatten() {
+            apply_muxbox_style_classes(muxbox, &styles);
+        }
+    }
+}
+
+fn apply_muxbox_style_classes(muxbox: &mut MuxBox, styles: &HashMap<String, StyleClass>) {
+    for class in named_classes(&muxbox.class, styles) {
+        fill_unset_box_styles!(muxbox, class);
+        fill_unset!(muxbox, class, title_style, text_style);
+    }
+    for choice in muxbox.choices.iter_mut().flatten() {
+        for class in named_classes(&choice.class, styles) {
+            fill_unset!(
+                choice,
+                class,
+                menu_fg_color,
+                menu_bg_color,
+                selected_menu_fg_color,
+                selected_menu_bg_color,
+                highlighted_menu_fg_color,
+                highlighted_menu_bg_color,
+            );
+        }
+    }
+    for child in muxbox.children.iter_mut().flatten() {
+        apply_muxbox_style_classes(child, styles);
+    }
+}
+
+/// The classes `names` refers to, last first, so that filling unset fields in this
+/// order lets later classes win
+fn named_classes<'a>(
+    names: &Option<Vec<String>>,
+    styles: &'a HashMap<String, StyleClass>,
+) -> Vec<&'a StyleClass> {
+    names
+        .iter()
+        .flatten()
+        .rev()
+        .filter_map(|name| styles.get(name))
+        .collect()
+}
+
+/// Give each box without `border` or `border_style` its parent box's, or else its
+/// layout's, so that content and PTY sizes can be worked out from the box alone
+fn inherit_border_settings(app: &mut App) {
+    fn inherit(muxbox: &mut MuxBox, border: Option<BorderSides>, border_style: &Option<String>) {
+        muxbox.border = muxbox.border.or(border);
+        if muxbox.border_style.is_none() {
+            muxbox.border_style = border_style.clone();
+        }
+        let (border, border_style) = (muxbox.border, muxbox.border_style.clone());
+        for child in muxbox.children.iter_mut().flatten() {
+            inherit(child, border, &border_style);
+        }
+    }
+
+    for layout in &mut app.layouts {
+        for muxbox in layout.children.iter_mut().flatten() {
+            inherit(muxbox, layout.border, &layout.border_style);
+        }
+    }
+}
+
 /// Legacy function kept for backward compatibility (now unused in main flow)
 pub fn substitute_variables(content: &str) -> Result<String, Box<dyn std::error::Error>> {
     // This function is deprecated in favor of the new hierarchical system
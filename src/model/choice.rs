@@ -1,8 +1,9 @@
+use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
 
 use serde::{Deserialize, Serialize};
 
-use crate::model::common::deserialize_script;
+use crate::model::common::{deserialize_script, ShellSpec};
 use crate::ExecutionMode;
 
 #[derive(Debug, Deserialize, Serialize, Default)]
@@ -13,6 +14,47 @@ pub struct Choice {
     pub script: Option<Vec<String>>,
     pub redirect_output: Option<String>,
     pub append_output: Option<bool>,
+    /// Overrides the box's `cwd` for this choice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Added to (and overriding) the box's `env` for this choice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<BTreeMap<String, String>>,
+    /// Overrides the box's `shell` for this choice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<ShellSpec>,
+    /// Overrides the box's `inherit_env` for this choice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherit_env: Option<bool>,
+    /// Overrides the box's `timeout_ms` for this choice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Cron, wall-clock and event triggers for the choice's script
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<crate::model::common::Schedule>,
+    /// Parse the choice script's output as structured data (default text)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<crate::model::common::OutputFormat>,
+    /// Style classes from `app.styles` for this choice's menu colors
+    #[serde(
+        default,
+        deserialize_with = "crate::model::common::deserialize_class",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub class: Option<Vec<String>>,
+    /// Override the box's menu colors for this choice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub menu_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub menu_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_menu_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_menu_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlighted_menu_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlighted_menu_bg_color: Option<String>,
     // F0222: Choice ExecutionMode Field - Replace thread+pty boolean flags with single execution_mode enum
     #[serde(default)]
     pub execution_mode: ExecutionMode,
@@ -33,6 +75,20 @@ impl Hash for Choice {
         self.script.hash(state);
         self.redirect_output.hash(state);
         self.append_output.hash(state);
+        self.cwd.hash(state);
+        self.env.hash(state);
+        self.shell.hash(state);
+        self.inherit_env.hash(state);
+        self.timeout_ms.hash(state);
+        self.schedule.hash(state);
+        self.output_format.hash(state);
+        self.class.hash(state);
+        self.menu_fg_color.hash(state);
+        self.menu_bg_color.hash(state);
+        self.selected_menu_fg_color.hash(state);
+        self.selected_menu_bg_color.hash(state);
+        self.highlighted_menu_fg_color.hash(state);
+        self.highlighted_menu_bg_color.hash(state);
         // F0222: Hash ExecutionMode field
         self.execution_mode.hash(state);
         self.selected.hash(state);
@@ -48,6 +104,20 @@ impl PartialEq for Choice {
             && self.script == other.script
             && self.redirect_output == other.redirect_output
             && self.append_output == other.append_output
+            && self.cwd == other.cwd
+            && self.env == other.env
+            && self.shell == other.shell
+            && self.inherit_env == other.inherit_env
+            && self.timeout_ms == other.timeout_ms
+            && self.schedule == other.schedule
+            && self.output_format == other.output_format
+            && self.class == other.class
+            && self.menu_fg_color == other.menu_fg_color
+            && self.menu_bg_color == other.menu_bg_color
+            && self.selected_menu_fg_color == other.selected_menu_fg_color
+            && self.selected_menu_bg_color == other.selected_menu_bg_color
+            && self.highlighted_menu_fg_color == other.highlighted_menu_fg_color
+            && self.highlighted_menu_bg_color == other.highlighted_menu_bg_color
             // F0222: Compare ExecutionMode field
             && self.execution_mode == other.execution_mode
             && self.selected == other.selected
@@ -66,6 +136,20 @@ impl Clone for Choice {
             script: self.script.clone(),
             redirect_output: self.redirect_output.clone(),
             append_output: self.append_output,
+            cwd: self.cwd.clone(),
+            env: self.env.clone(),
+            shell: self.shell.clone(),
+            inherit_env: self.inherit_env,
+            timeout_ms: self.timeout_ms,
+            schedule: self.schedule.clone(),
+            output_format: self.output_format,
+            class: self.class.clone(),
+            menu_fg_color: self.menu_fg_color.clone(),
+            menu_bg_color: self.menu_bg_color.clone(),
+            selected_menu_fg_color: self.selected_menu_fg_color.clone(),
+            selected_menu_bg_color: self.selected_menu_bg_color.clone(),
+            highlighted_menu_fg_color: self.highlighted_menu_fg_color.clone(),
+            highlighted_menu_bg_color: self.highlighted_menu_bg_color.clone(),
             // F0222: Clone ExecutionMode field
             execution_mode: self.execution_mode.clone(),
             selected: self.selected,
@@ -95,7 +95,276 @@ pub struct ExecuteScript {
     pub redirect_output: Option<String>, // Optional output redirection
     pub append_output: bool,             // Append vs replace mode
     pub stream_id: String,               // Stream ID from source registry
-    pub target_bounds: Option<Bounds>,   // Target muxbox bounds for PTY sizing
+    pub target_bounds: Option<Bounds>,   // Target muxbox content area for PTY sizing
+    pub environment: ScriptEnvironment,  // Working directory, env and shell
+    pub timeout_ms: Option<u64>,         // Kill Immediate/Thread scripts running longer
+    pub output_format: OutputFormat,     // How to parse the script's output
+    pub record: Option<String>,          // Asciicast file recording a PTY script's output
+}
+
+/// What a periodic refresh does when its previous run is still in flight
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Hash, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverlapPolicy {
+    /// Drop the new run
+    #[default]
+    Skip,
+    /// Start one more run as soon as the current one finishes
+    QueueOne,
+    /// Kill the running script and start the new one
+    CancelPrevious,
+    /// Run concurrently
+    Allow,
+}
+
+/// How a box or choice parses its script output before display
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Hash, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// Show the output as is
+    #[default]
+    Text,
+    /// A JSON document
+    Json,
+    /// Comma-separated rows with a header line
+    Csv,
+    /// One JSON value per line
+    Ndjson,
+    /// `key=value` or `key: value` lines
+    KeyValue,
+    /// One choice per line, or a JSON array of choice objects
+    Choices,
+}
+
+/// Triggers that run a box or choice script besides `refresh_interval` and clicks; any
+/// trigger that is set fires the script
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Hash, Eq, Default)]
+pub struct Schedule {
+    /// Five-field cron expression (minute hour day-of-month month day-of-week) in local time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cron: Option<String>,
+    /// Daily wall-clock time, `HH:MM` or `HH:MM:SS`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at: Option<String>,
+    /// Run when the layout containing the box becomes active, including at startup
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub on_layout_activated: bool,
+    /// Run when a file matching this glob is created, modified or removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_file_change: Option<String>,
+    /// Run after a script in this box finishes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_box: Option<String>,
+}
+
+/// Severity of a box alert
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Hash, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertLevel {
+    Ok,
+    Warning,
+    #[default]
+    Error,
+}
+
+/// Exit codes an alert rule matches: one code, a list of codes or `nonzero`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Hash, Eq)]
+#[serde(untagged)]
+pub enum ExitCodeMatch {
+    Code(i32),
+    Codes(Vec<i32>),
+    Named(String),
+}
+
+/// Entry of a box's `alerts`: conditions on a finished run of a script writing to the
+/// box, all of which must hold, and how the box shows that the rule matched
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct AlertRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<ExitCodeMatch>,
+    /// Regex that must match somewhere in the output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Regex whose first capture group, or whole match, is read as a number; the last
+    /// match in the output is compared with `above` and `below`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub above: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub below: Option<f64>,
+    #[serde(default)]
+    pub level: AlertLevel,
+    /// Shown after the status indicator; `${value}` is replaced by the extracted number
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Status indicator glyph instead of the level's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indicator: Option<String>,
+    /// Border and title color instead of the level's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Flash the border when the rule starts matching
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub flash: bool,
+    /// Ring the terminal bell when the rule starts matching
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bell: bool,
+}
+
+impl Eq for AlertRule {}
+
+impl Hash for AlertRule {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.exit_code.hash(state);
+        self.pattern.hash(state);
+        self.value.hash(state);
+        self.above.map(f64::to_bits).hash(state);
+        self.below.map(f64::to_bits).hash(state);
+        self.level.hash(state);
+        self.message.hash(state);
+        self.indicator.hash(state);
+        self.color.hash(state);
+        self.flash.hash(state);
+        self.bell.hash(state);
+    }
+}
+
+/// The alert rule that matched a box's latest finished run
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub struct ActiveAlert {
+    /// Index into the box's `alerts`
+    pub rule_index: usize,
+    pub level: AlertLevel,
+    pub message: Option<String>,
+    pub indicator: Option<String>,
+    /// Border and title color; `None` uses the box's error colors
+    pub color: Option<String>,
+    /// When a flashing border started and stops flashing
+    pub flash: Option<(std::time::SystemTime, std::time::SystemTime)>,
+}
+
+/// Shell a script runs under: a name or path run as `<shell> -c <script>`, or an argv
+/// the script is appended to, e.g. `[python3, -c]`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Hash, Eq)]
+#[serde(untagged)]
+pub enum ShellSpec {
+    Program(String),
+    Argv(Vec<String>),
+}
+
+impl ShellSpec {
+    /// Program and leading arguments; the script becomes the last argument
+    pub fn argv(&self) -> Vec<String> {
+        match self {
+            ShellSpec::Program(program) => vec![program.clone(), "-c".to_string()],
+            ShellSpec::Argv(argv) => argv.clone(),
+        }
+    }
+}
+
+/// Variables still passed through with `inherit_env: false`, so the shell can be found
+pub const BASE_ENV_VARS: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TERM",
+    "LANG",
+    "LC_ALL",
+    "TMPDIR",
+    crate::socket_loop::SOCKET_ENV_VAR,
+];
+
+/// Working directory, environment and shell for a script: the box's `cwd`, `env`,
+/// `shell` and `inherit_env`, with a choice's own settings layered on top
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub struct ScriptEnvironment {
+    pub cwd: Option<String>,
+    pub env: std::collections::BTreeMap<String, String>,
+    pub shell: Option<ShellSpec>,
+    pub inherit_env: bool,
+}
+
+impl Default for ScriptEnvironment {
+    fn default() -> Self {
+        ScriptEnvironment {
+            cwd: None,
+            env: std::collections::BTreeMap::new(),
+            shell: None,
+            inherit_env: true,
+        }
+    }
+}
+
+impl ScriptEnvironment {
+    pub fn resolve(muxbox: Option<&MuxBox>, choice: Option<&Choice>) -> Self {
+        let layers = [
+            muxbox.map(|m| (&m.cwd, &m.env, &m.shell, m.inherit_env)),
+            choice.map(|c| (&c.cwd, &c.env, &c.shell, c.inherit_env)),
+        ];
+        let mut environment = ScriptEnvironment::default();
+        for (cwd, env, shell, inherit_env) in layers.into_iter().flatten() {
+            if cwd.is_some() {
+                environment.cwd = cwd.clone();
+            }
+            if let Some(env) = env {
+                environment.env.extend(env.clone());
+            }
+            if shell.is_some() {
+                environment.shell = shell.clone();
+            }
+            if let Some(inherit_env) = inherit_env {
+                environment.inherit_env = inherit_env;
+            }
+        }
+        environment
+    }
+
+    /// Program and arguments running `script`; `default_shell` is used without a `shell`
+    pub fn command_line(&self, default_shell: &str, script: &str) -> (String, Vec<String>) {
+        let mut argv = self
+            .shell
+            .as_ref()
+            .map(ShellSpec::argv)
+            .filter(|argv| !argv.is_empty())
+            .unwrap_or_else(|| vec![default_shell.to_string(), "-c".to_string()]);
+        argv.push(script.to_string());
+        let program = argv.remove(0);
+        (program, argv)
+    }
+
+    /// `cwd` with a leading `~` or `~/` expanded, or an error naming it if it is not a
+    /// directory
+    pub fn working_dir(&self) -> std::io::Result<Option<std::path::PathBuf>> {
+        let Some(cwd) = self.cwd.as_deref() else {
+            return Ok(None);
+        };
+        let path = std::path::PathBuf::from(crate::utils::expand_home(cwd));
+        if path.is_dir() {
+            Ok(Some(path))
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Working directory '{}' does not exist", cwd),
+            ))
+        }
+    }
+
+    /// Variables to set on the child; the whole environment when not inheriting
+    pub fn variables(&self) -> Vec<(String, String)> {
+        let mut variables = Vec::new();
+        if !self.inherit_env {
+            for name in BASE_ENV_VARS {
+                if let Ok(value) = std::env::var(name) {
+                    variables.push((name.to_string(), value));
+                }
+            }
+        }
+        variables.extend(self.env.clone());
+        variables
+    }
 }
 
 /// T0301: ExecutionSource and SourceType enums - Track what triggered the execution
@@ -106,6 +375,17 @@ pub struct ExecutionSource {
     pub source_reference: SourceReference, // Actual source data/object
 }
 
+impl ExecutionSource {
+    /// Choice that triggered this execution, if any (clicked, selected or hotkey)
+    pub fn choice_id(&self) -> Option<String> {
+        match (&self.source_type, &self.source_reference) {
+            (SourceType::Choice(choice_id), _) => Some(choice_id.clone()),
+            (_, SourceReference::Choice(choice)) => Some(choice.id.clone()),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
 pub enum SourceType {
     Choice(String),   // Choice ID that triggered execution
@@ -115,11 +395,19 @@ pub enum SourceType {
     RedirectedScript, // Script with output redirection
     HotkeyScript,     // Hotkey-triggered script
     ScheduledScript,  // Timer/scheduled execution
+    ChoicesScript,    // Box `choices_script` generating its choices
+}
+
+impl SourceType {
+    /// Box scripts re-run by the refresh loops, subject to the box's overlap policy
+    pub fn is_periodic(&self) -> bool {
+        matches!(self, SourceType::StaticScript | SourceType::PeriodicRefresh)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
 pub enum SourceReference {
-    Choice(Choice),         // Full choice object
+    Choice(Box<Choice>),    // Full choice object
     StaticConfig(String),   // YAML configuration reference (one-time)
     PeriodicConfig(String), // YAML configuration for periodic refresh
     SocketCommand(String),  // Socket command that triggered this
@@ -135,6 +423,7 @@ pub struct StreamUpdate {
     pub content_update: String,        // New content to append
     pub source_state: SourceState,     // Current state of execution source
     pub execution_mode: ExecutionMode, // Mode that generated this update
+    pub output_format: OutputFormat,   // How the finished output is parsed
 }
 
 /// T0303: SourceState enums - Track execution status for each mode
@@ -241,6 +530,7 @@ pub enum StreamType {
     RedirectedOutput(String), // Named redirect output
     PTY,
     Plugin(String),
+    PluginLog(String), // stderr of a process plugin
     // F0210: Complete StreamType Enum - Add missing variants for source tracking
     ChoiceExecution(String), // Track choice executions as streams
     RedirectSource(String),  // Track redirect output sources
@@ -284,6 +574,15 @@ pub enum ExecutionSourceType {
         hotkey: String,
         script: Vec<String>,
     },
+    ScheduledScript {
+        // Script run by a box or choice `schedule`
+        choice_id: Option<String>,
+        script: Vec<String>,
+    },
+    ChoicesScript {
+        // Box `choices_script` whose output becomes the box's choices
+        script: Vec<String>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
@@ -716,14 +1015,8 @@ impl StreamSourceTrait for PtySessionExecutionSource {
     fn cleanup(&self) -> Result<(), String> {
         if let Some(pid) = self.process_id {
             if self.is_process_running {
-                let result = std::process::Command::new("kill")
-                    .arg("-9")
-                    .arg(pid.to_string())
-                    .output();
-                match result {
-                    Ok(_) => Ok(()),
-                    Err(e) => Err(format!("Failed to terminate PTY process {}: {}", pid, e)),
-                }
+                crate::pty_manager::stop_process(pid)
+                    .map_err(|e| format!("Failed to terminate PTY process {}: {}", pid, e))
             } else {
                 Ok(()) // Already terminated
             }
@@ -857,18 +1150,9 @@ impl StreamSourceTrait for PTYSource {
         true
     }
     fn cleanup(&self) -> Result<(), String> {
-        // Terminate PTY process
-        let result = std::process::Command::new("kill")
-            .arg("-9")
-            .arg(self.process_id.to_string())
-            .output();
-        match result {
-            Ok(_) => Ok(()),
-            Err(e) => Err(format!(
-                "Failed to terminate PTY process {}: {}",
-                self.process_id, e
-            )),
-        }
+        // Terminate PTY process with the configured stop sequence
+        crate::pty_manager::stop_process(self.process_id)
+            .map_err(|e| format!("Failed to terminate PTY process {}: {}", self.process_id, e))
     }
     fn get_metadata(&self) -> std::collections::HashMap<String, String> {
         let mut meta = std::collections::HashMap::new();
@@ -1337,6 +1621,7 @@ impl ContentStreamTrait for Stream {
             | StreamType::RedirectedOutput(_)
             | StreamType::PTY
             | StreamType::Plugin(_)
+            | StreamType::PluginLog(_)
             | StreamType::ChoiceExecution(_)
             | StreamType::PtySession(_)
             | StreamType::OwnScript => &self.content,
@@ -1353,6 +1638,7 @@ impl ContentStreamTrait for Stream {
             | StreamType::RedirectedOutput(_)
             | StreamType::PTY
             | StreamType::Plugin(_)
+            | StreamType::PluginLog(_)
             | StreamType::ChoiceExecution(_)
             | StreamType::PtySession(_)
             | StreamType::OwnScript => {
@@ -1433,6 +1719,9 @@ pub struct Config {
     pub locked: bool, // Disable muxbox resizing and moving when true
     #[serde(default)]
     pub calibrate: bool,
+    /// Control socket path for this instance; `None` derives a per-instance default
+    #[serde(default)]
+    pub socket_path: Option<String>,
 }
 
 impl Hash for Config {
@@ -1440,6 +1729,7 @@ impl Hash for Config {
         self.frame_delay.hash(state);
         self.locked.hash(state);
         self.calibrate.hash(state);
+        self.socket_path.hash(state);
     }
 }
 
@@ -1449,6 +1739,7 @@ impl Default for Config {
             frame_delay: 30,
             locked: false, // Default to unlocked (resizable/movable)
             calibrate: false,
+            socket_path: None,
         }
     }
 }
@@ -1459,6 +1750,7 @@ impl Config {
             frame_delay,
             locked: false, // Default to unlocked
             calibrate: false,
+            socket_path: None,
         };
         result.validate();
         result
@@ -1469,6 +1761,7 @@ impl Config {
             frame_delay,
             locked,
             calibrate: false,
+            socket_path: None,
         };
         result.validate();
         result
@@ -1479,10 +1772,17 @@ impl Config {
             frame_delay,
             locked,
             calibrate,
+            socket_path: None,
         };
         result.validate();
         result
     }
+
+    pub fn with_socket_path(mut self, socket_path: Option<String>) -> Self {
+        self.socket_path = socket_path;
+        self
+    }
+
     pub fn validate(&self) {
         if self.frame_delay == 0 {
             panic!("Validation error: frame_delay cannot be 0");
@@ -1544,6 +1844,112 @@ pub enum SocketFunction {
         box_id: String,
         input: String,
     },
+    // Deliver a signal to the PTY process group, e.g. INT to interrupt a running query
+    SendPtySignal {
+        box_id: String,
+        signal: crate::pty_manager::PtySignal,
+    },
+    // Keep the connection open and stream events; only valid on an enveloped request
+    Subscribe {
+        box_ids: Option<Vec<String>>,
+        events: Option<Vec<crate::socket_protocol::SocketEventType>>,
+    },
+    // Read-only queries; only valid on an enveloped request, which carries the result
+    GetBoxContent {
+        box_id: String,
+    },
+    ListBoxes {
+        layout_id: Option<String>,
+    },
+    ListLayouts {},
+    ListExecutionSources {},
+    GetPtyBuffer {
+        box_id: String,
+        start: Option<usize>,
+        count: Option<usize>,
+    },
+    SearchPtyBuffer {
+        box_id: String,
+        query: String,
+    },
+    // Turn the connection into the terminal of a detached session; local socket only
+    Attach {
+        cols: u16,
+        rows: u16,
+    },
+    // Let go of the terminal attached to a detached session
+    Detach {},
+}
+
+impl SocketFunction {
+    /// Names of all socket functions, as used on the wire and in remote allow-lists
+    pub const NAMES: &'static [&'static str] = &[
+        "ReplaceBoxContent",
+        "ReplaceBoxScript",
+        "StopBoxRefresh",
+        "StartBoxRefresh",
+        "ReplaceBox",
+        "SwitchActiveLayout",
+        "AddBox",
+        "RemoveBox",
+        "KillPtyProcess",
+        "RestartPtyProcess",
+        "QueryPtyStatus",
+        "SpawnPtyProcess",
+        "SendPtyInput",
+        "SendPtySignal",
+        "Subscribe",
+        "GetBoxContent",
+        "ListBoxes",
+        "ListLayouts",
+        "ListExecutionSources",
+        "GetPtyBuffer",
+        "SearchPtyBuffer",
+        "Attach",
+        "Detach",
+    ];
+
+    /// Functions that only read state or stream events; all that remote clients may call
+    /// unless `remote_control.allowed_functions` says otherwise
+    pub const READ_ONLY: &'static [&'static str] = &[
+        "QueryPtyStatus",
+        "Subscribe",
+        "GetBoxContent",
+        "ListBoxes",
+        "ListLayouts",
+        "ListExecutionSources",
+        "GetPtyBuffer",
+        "SearchPtyBuffer",
+    ];
+
+    /// Wire name of this function's variant
+    pub fn name(&self) -> &'static str {
+        match self {
+            SocketFunction::ReplaceBoxContent { .. } => "ReplaceBoxContent",
+            SocketFunction::ReplaceBoxScript { .. } => "ReplaceBoxScript",
+            SocketFunction::StopBoxRefresh { .. } => "StopBoxRefresh",
+            SocketFunction::StartBoxRefresh { .. } => "StartBoxRefresh",
+            SocketFunction::ReplaceBox { .. } => "ReplaceBox",
+            SocketFunction::SwitchActiveLayout { .. } => "SwitchActiveLayout",
+            SocketFunction::AddBox { .. } => "AddBox",
+            SocketFunction::RemoveBox { .. } => "RemoveBox",
+            SocketFunction::KillPtyProcess { .. } => "KillPtyProcess",
+            SocketFunction::RestartPtyProcess { .. } => "RestartPtyProcess",
+            SocketFunction::QueryPtyStatus { .. } => "QueryPtyStatus",
+            SocketFunction::SpawnPtyProcess { .. } => "SpawnPtyProcess",
+            SocketFunction::SendPtyInput { .. } => "SendPtyInput",
+            SocketFunction::SendPtySignal { .. } => "SendPtySignal",
+            SocketFunction::Subscribe { .. } => "Subscribe",
+            SocketFunction::GetBoxContent { .. } => "GetBoxContent",
+            SocketFunction::ListBoxes { .. } => "ListBoxes",
+            SocketFunction::ListLayouts {} => "ListLayouts",
+            SocketFunction::ListExecutionSources {} => "ListExecutionSources",
+            SocketFunction::GetPtyBuffer { .. } => "GetPtyBuffer",
+            SocketFunction::SearchPtyBuffer { .. } => "SearchPtyBuffer",
+            SocketFunction::Attach { .. } => "Attach",
+            SocketFunction::Detach {} => "Detach",
+        }
+    }
 }
 
 pub fn run_socket_function(
@@ -1580,6 +1986,7 @@ pub fn run_socket_function(
                         },
                     ),
                     execution_mode: crate::model::common::ExecutionMode::Immediate,
+                    output_format: OutputFormat::Text,
                 },
             ));
         }
@@ -1628,6 +2035,7 @@ pub fn run_socket_function(
                                     },
                                 ),
                                 execution_mode: crate::model::common::ExecutionMode::Pty,
+                                output_format: OutputFormat::Text,
                             },
                         ));
                     }
@@ -1648,6 +2056,7 @@ pub fn run_socket_function(
                                     },
                                 ),
                                 execution_mode: crate::model::common::ExecutionMode::Pty,
+                                output_format: OutputFormat::Text,
                             },
                         ));
                     }
@@ -1669,6 +2078,7 @@ pub fn run_socket_function(
                             },
                         ),
                         execution_mode: crate::model::common::ExecutionMode::Pty,
+                        output_format: OutputFormat::Text,
                     },
                 ));
             }
@@ -1693,6 +2103,7 @@ pub fn run_socket_function(
                                     },
                                 ),
                                 execution_mode: crate::model::common::ExecutionMode::Pty,
+                                output_format: OutputFormat::Text,
                             },
                         ));
                     }
@@ -1715,6 +2126,7 @@ pub fn run_socket_function(
                                     },
                                 ),
                                 execution_mode: crate::model::common::ExecutionMode::Pty,
+                                output_format: OutputFormat::Text,
                             },
                         ));
                     }
@@ -1736,6 +2148,7 @@ pub fn run_socket_function(
                             },
                         ),
                         execution_mode: crate::model::common::ExecutionMode::Pty,
+                        output_format: OutputFormat::Text,
                     },
                 ));
             }
@@ -1762,6 +2175,7 @@ pub fn run_socket_function(
                                 },
                             ),
                             execution_mode: crate::model::common::ExecutionMode::Pty,
+                            output_format: OutputFormat::Text,
                         },
                     ));
                 } else {
@@ -1781,6 +2195,7 @@ pub fn run_socket_function(
                                 },
                             ),
                             execution_mode: crate::model::common::ExecutionMode::Pty,
+                            output_format: OutputFormat::Text,
                         },
                     ));
                 }
@@ -1801,6 +2216,7 @@ pub fn run_socket_function(
                             },
                         ),
                         execution_mode: crate::model::common::ExecutionMode::Pty,
+                        output_format: OutputFormat::Text,
                     },
                 ));
             }
@@ -1838,6 +2254,10 @@ pub fn run_socket_function(
                     append_output: false,
                     stream_id,
                     target_bounds: None, // Socket commands don't have direct access to bounds - will use defaults
+                    environment: app_context.app.script_environment(&box_id, None),
+                    timeout_ms: None, // PTY processes are long-lived, so script timeouts don't apply
+                    output_format: OutputFormat::Text,
+                    record: app_context.app.script_record(&box_id),
                 });
 
             // Add ExecuteScript message to be sent via ThreadManager
@@ -1874,6 +2294,7 @@ pub fn run_socket_function(
                                     },
                                 ),
                                 execution_mode: crate::model::common::ExecutionMode::Pty,
+                                output_format: OutputFormat::Text,
                             },
                         ));
                     }
@@ -1897,6 +2318,7 @@ pub fn run_socket_function(
                                     },
                                 ),
                                 execution_mode: crate::model::common::ExecutionMode::Pty,
+                                output_format: OutputFormat::Text,
                             },
                         ));
                     }
@@ -1918,10 +2340,72 @@ pub fn run_socket_function(
                             },
                         ),
                         execution_mode: crate::model::common::ExecutionMode::Pty,
+                        output_format: OutputFormat::Text,
                     },
                 ));
             }
         }
+        SocketFunction::SendPtySignal { box_id, signal } => {
+            let (stream_id, result) = match &app_context.pty_manager {
+                Some(pty_manager) => (
+                    pty_manager
+                        .get_stream_id(&box_id)
+                        .unwrap_or_else(|| format!("error-no-pty-{}", box_id)),
+                    pty_manager
+                        .send_signal(&box_id, signal)
+                        .map_err(|err| err.to_string()),
+                ),
+                None => (
+                    format!("error-no-pty-{}", box_id),
+                    Err("PTY manager not available".to_string()),
+                ),
+            };
+            let (content_update, exit_code, status) = match result {
+                Ok(()) => (
+                    format!("SIG{} sent to PTY process for box {}", signal, box_id),
+                    Some(0),
+                    crate::model::common::ExecutionPtyStatus::Completed,
+                ),
+                Err(err) => (
+                    format!("Failed to send SIG{} to PTY process: {}", signal, err),
+                    Some(1),
+                    crate::model::common::ExecutionPtyStatus::Failed(err),
+                ),
+            };
+            messages.push(Message::StreamUpdateMessage(
+                crate::model::common::StreamUpdate {
+                    target_box_id: box_id.clone(),
+                    stream_id,
+                    content_update,
+                    source_state: crate::model::common::SourceState::Pty(
+                        crate::model::common::PtySourceState {
+                            process_id: 0,
+                            runtime: std::time::Duration::from_millis(0),
+                            exit_code,
+                            status,
+                        },
+                    ),
+                    execution_mode: crate::model::common::ExecutionMode::Pty,
+                    output_format: OutputFormat::Text,
+                },
+            ));
+        }
+        SocketFunction::Subscribe { .. } => {
+            return Err("Subscribe is only available on a persistent socket connection".into());
+        }
+        SocketFunction::GetBoxContent { .. }
+        | SocketFunction::ListBoxes { .. }
+        | SocketFunction::ListLayouts {}
+        | SocketFunction::ListExecutionSources {}
+        | SocketFunction::GetPtyBuffer { .. }
+        | SocketFunction::SearchPtyBuffer { .. } => {
+            return Err("Queries are only available on an enveloped socket request".into());
+        }
+        SocketFunction::Attach { .. } | SocketFunction::Detach {} => {
+            return Err(
+                "Detached sessions are only available on an enveloped socket request".into(),
+            );
+        }
     }
     Ok((app_context, messages))
 }
@@ -1931,6 +2415,331 @@ pub struct Cell {
     pub fg_color: String,
     pub bg_color: String,
     pub ch: char,
+    pub style: TextStyle,
+}
+
+/// Text attributes of a cell; written in YAML as a list such as `[bold, underline]`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "Vec<TextAttribute>", into = "Vec<TextAttribute>")]
+pub struct TextStyle {
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reverse: bool,
+    pub strikethrough: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextAttribute {
+    Bold,
+    Dim,
+    Italic,
+    Underline,
+    Reverse,
+    Strikethrough,
+}
+
+impl From<Vec<TextAttribute>> for TextStyle {
+    fn from(attributes: Vec<TextAttribute>) -> Self {
+        let mut style = TextStyle::default();
+        for attribute in attributes {
+            match attribute {
+                TextAttribute::Bold => style.bold = true,
+                TextAttribute::Dim => style.dim = true,
+                TextAttribute::Italic => style.italic = true,
+                TextAttribute::Underline => style.underline = true,
+                TextAttribute::Reverse => style.reverse = true,
+                TextAttribute::Strikethrough => style.strikethrough = true,
+            }
+        }
+        style
+    }
+}
+
+impl From<TextStyle> for Vec<TextAttribute> {
+    fn from(style: TextStyle) -> Self {
+        [
+            (style.bold, TextAttribute::Bold),
+            (style.dim, TextAttribute::Dim),
+            (style.italic, TextAttribute::Italic),
+            (style.underline, TextAttribute::Underline),
+            (style.reverse, TextAttribute::Reverse),
+            (style.strikethrough, TextAttribute::Strikethrough),
+        ]
+        .into_iter()
+        .filter_map(|(set, attribute)| set.then_some(attribute))
+        .collect()
+    }
+}
+
+impl TextStyle {
+    /// Attributes set in either style
+    pub fn union(self, other: TextStyle) -> TextStyle {
+        TextStyle {
+            bold: self.bold || other.bold,
+            dim: self.dim || other.dim,
+            italic: self.italic || other.italic,
+            underline: self.underline || other.underline,
+            reverse: self.reverse || other.reverse,
+            strikethrough: self.strikethrough || other.strikethrough,
+        }
+    }
+
+    /// SGR sequence switching the terminal from `previous` to this style; with no
+    /// `previous` every attribute is set or cleared explicitly. Colors are untouched.
+    pub fn transition_from(&self, previous: Option<&TextStyle>) -> String {
+        if previous == Some(self) {
+            return String::new();
+        }
+        let previous = previous.copied();
+        let changed = |was: fn(&TextStyle) -> bool| match previous {
+            Some(previous) => was(&previous) != was(self),
+            None => true,
+        };
+
+        let mut codes = Vec::new();
+        // 22 clears both bold and dim, so whichever of them stays on is set again
+        let intensity_changed = changed(|s| s.bold) || changed(|s| s.dim);
+        if intensity_changed {
+            codes.push("22");
+        }
+        for (attribute_changed, on, on_code, off_code) in [
+            (intensity_changed, self.bold, "1", None),
+            (intensity_changed, self.dim, "2", None),
+            (changed(|s| s.italic), self.italic, "3", Some("23")),
+            (changed(|s| s.underline), self.underline, "4", Some("24")),
+            (changed(|s| s.reverse), self.reverse, "7", Some("27")),
+            (
+                changed(|s| s.strikethrough),
+                self.strikethrough,
+                "9",
+                Some("29"),
+            ),
+        ] {
+            if !attribute_changed {
+                continue;
+            }
+            if on {
+                codes.push(on_code);
+            } else if let Some(off_code) = off_code {
+                codes.push(off_code);
+            }
+        }
+        format!("\x1b[{}m", codes.join(";"))
+    }
+}
+
+/// Which sides of a box draw a border. Written in YAML as `true`/`all`, `false`/`none`,
+/// a list of the sides to draw such as `[top, bottom]`, or a map such as
+/// `{left: false}` where unnamed sides keep their border.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "BorderSidesFormat", into = "BorderSidesFormat")]
+pub struct BorderSides {
+    pub top: bool,
+    pub right: bool,
+    pub bottom: bool,
+    pub left: bool,
+}
+
+impl Default for BorderSides {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl BorderSides {
+    pub fn all() -> Self {
+        Self {
+            top: true,
+            right: true,
+            bottom: true,
+            left: true,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            top: false,
+            right: false,
+            bottom: false,
+            left: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BorderSide {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct BorderSideToggles {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    top: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    right: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bottom: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    left: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum BorderSidesFormat {
+    Enabled(bool),
+    Keyword(String),
+    Sides(Vec<BorderSide>),
+    Toggles(BorderSideToggles),
+}
+
+impl TryFrom<BorderSidesFormat> for BorderSides {
+    type Error = String;
+
+    fn try_from(format: BorderSidesFormat) -> Result<Self, Self::Error> {
+        Ok(match format {
+            BorderSidesFormat::Enabled(true) => Self::all(),
+            BorderSidesFormat::Enabled(false) => Self::none(),
+            BorderSidesFormat::Keyword(keyword) => match keyword.as_str() {
+                "all" => Self::all(),
+                "none" => Self::none(),
+                _ => {
+                    return Err(format!(
+                    "unknown border '{}', expected all, none, a list of sides or a map of sides",
+                    keyword
+                ))
+                }
+            },
+            BorderSidesFormat::Sides(sides) => Self {
+                top: sides.contains(&BorderSide::Top),
+                right: sides.contains(&BorderSide::Right),
+                bottom: sides.contains(&BorderSide::Bottom),
+                left: sides.contains(&BorderSide::Left),
+            },
+            BorderSidesFormat::Toggles(toggles) => Self {
+                top: toggles.top.unwrap_or(true),
+                right: toggles.right.unwrap_or(true),
+                bottom: toggles.bottom.unwrap_or(true),
+                left: toggles.left.unwrap_or(true),
+            },
+        })
+    }
+}
+
+impl From<BorderSides> for BorderSidesFormat {
+    fn from(sides: BorderSides) -> Self {
+        if sides == BorderSides::all() {
+            return BorderSidesFormat::Enabled(true);
+        }
+        if sides == BorderSides::none() {
+            return BorderSidesFormat::Keyword("none".to_string());
+        }
+        BorderSidesFormat::Sides(
+            [
+                (sides.top, BorderSide::Top),
+                (sides.right, BorderSide::Right),
+                (sides.bottom, BorderSide::Bottom),
+                (sides.left, BorderSide::Left),
+            ]
+            .into_iter()
+            .filter_map(|(set, side)| set.then_some(side))
+            .collect(),
+        )
+    }
+}
+
+/// A named set of styling fields in `app.styles`. Boxes, layouts and choices take
+/// them through `class`; a field set on the element itself wins over its classes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StyleClass {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill_char: Option<char>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_fill_char: Option<char>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlighted_fill_char: Option<char>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border: Option<BorderSides>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border_style: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_border_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlighted_border_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlighted_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlighted_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_title_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_title_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlighted_title_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlighted_title_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_position: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub menu_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub menu_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_menu_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_menu_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlighted_menu_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlighted_menu_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_border_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_title_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_title_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_selected_border_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_selected_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_selected_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_selected_title_bg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_selected_title_fg_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_style: Option<TextStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_style: Option<TextStyle>,
 }
 
 #[derive(Debug, Clone)]
@@ -1953,9 +2762,10 @@ impl ScreenBuffer {
         // in dark — instead of a hardcoded black that darkened the whole app in
         // light mode.
         let default_cell = Cell {
-            fg_color: get_fg_color(crate::color_utils::default_fg_color(false)),
-            bg_color: get_bg_color(crate::color_utils::default_bg_color(false)),
+            fg_color: get_fg_color(&crate::color_utils::default_fg_color(false)),
+            bg_color: get_bg_color(&crate::color_utils::default_bg_color(false)),
             ch: ' ',
+            style: TextStyle::default(),
         };
         let width = screen_width();
         let height = screen_height();
@@ -1973,9 +2783,10 @@ impl ScreenBuffer {
         // in dark — instead of a hardcoded black that darkened the whole app in
         // light mode.
         let default_cell = Cell {
-            fg_color: get_fg_color(crate::color_utils::default_fg_color(false)),
-            bg_color: get_bg_color(crate::color_utils::default_bg_color(false)),
+            fg_color: get_fg_color(&crate::color_utils::default_fg_color(false)),
+            bg_color: get_bg_color(&crate::color_utils::default_bg_color(false)),
             ch: ' ',
+            style: TextStyle::default(),
         };
         let buffer = vec![vec![default_cell; width]; height];
         ScreenBuffer {
@@ -1991,9 +2802,10 @@ impl ScreenBuffer {
         // in dark — instead of a hardcoded black that darkened the whole app in
         // light mode.
         let default_cell = Cell {
-            fg_color: get_fg_color(crate::color_utils::default_fg_color(false)),
-            bg_color: get_bg_color(crate::color_utils::default_bg_color(false)),
+            fg_color: get_fg_color(&crate::color_utils::default_fg_color(false)),
+            bg_color: get_bg_color(&crate::color_utils::default_bg_color(false)),
             ch: ' ',
+            style: TextStyle::default(),
         };
         self.buffer = vec![vec![default_cell; self.width]; self.height];
     }
@@ -2030,6 +2842,7 @@ impl ScreenBuffer {
                     fg_color: get_fg_color("white"),
                     bg_color: get_bg_color("black"),
                     ch: ' ',
+                    style: TextStyle::default(),
                 };
                 width
             ];
@@ -2042,6 +2855,7 @@ impl ScreenBuffer {
                     fg_color: get_fg_color("white"),
                     bg_color: get_bg_color("black"),
                     ch: ' ',
+                    style: TextStyle::default(),
                 });
             }
         }
@@ -2616,12 +3430,34 @@ where
     }
 }
 
+/// `class` as one name, several space-separated names, or a list of names
+pub fn deserialize_class<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ClassFormat {
+        Names(String),
+        List(Vec<String>),
+    }
+
+    Ok(
+        Option::<ClassFormat>::deserialize(deserializer)?.map(|format| match format {
+            ClassFormat::Names(names) => names.split_whitespace().map(str::to_string).collect(),
+            ClassFormat::List(list) => list,
+        }),
+    )
+}
+
 use std::io::{Read, Write};
 use std::os::unix::net::UnixStream;
 
 pub fn send_json_to_socket(socket_path: &str, json: &str) -> Result<String, Box<dyn Error>> {
     let mut stream = UnixStream::connect(socket_path)?;
     stream.write_all(json.as_bytes())?;
+    // The server reads until EOF, so close our write half before waiting for the reply
+    stream.shutdown(std::net::Shutdown::Write)?;
     let mut response = String::new();
     stream.read_to_string(&mut response)?;
     Ok(response)
@@ -2666,6 +3502,7 @@ mod tests {
             frame_delay: 0,
             locked: false,
             calibrate: false,
+            socket_path: None,
         };
         config.validate();
     }
@@ -2678,6 +3515,7 @@ mod tests {
             frame_delay: 16,
             locked: false,
             calibrate: false,
+            socket_path: None,
         };
         config.validate(); // Should not panic
     }
@@ -3025,6 +3863,7 @@ mod tests {
             fg_color: String::from("red"),
             bg_color: String::from("blue"),
             ch: 'X',
+            style: TextStyle::default(),
         };
         screen_buffer.update(2, 2, test_cell.clone());
         screen_buffer.clear();
@@ -3046,6 +3885,7 @@ mod tests {
             fg_color: String::from("red"),
             bg_color: String::from("blue"),
             ch: 'X',
+            style: TextStyle::default(),
         };
         screen_buffer.update(2, 2, test_cell.clone());
         assert_eq!(screen_buffer.get(2, 2).unwrap(), &test_cell);
@@ -3069,6 +3909,7 @@ mod tests {
             fg_color: String::from("red"),
             bg_color: String::from("blue"),
             ch: 'X',
+            style: TextStyle::default(),
         };
         screen_buffer.update(10, 10, test_cell); // Should not panic
         assert!(screen_buffer.get(10, 10).is_none());
@@ -3178,6 +4019,7 @@ mod tests {
             fg_color: "red".to_string(),
             bg_color: "blue".to_string(),
             ch: 'X',
+            style: TextStyle::default(),
         };
         let cell2 = cell1.clone();
         assert_eq!(cell1, cell2);
@@ -3186,6 +4028,7 @@ mod tests {
             fg_color: "green".to_string(),
             bg_color: "blue".to_string(),
             ch: 'X',
+            style: TextStyle::default(),
         };
         assert_ne!(cell1, cell3);
     }
@@ -1,3 +1,4 @@
+use crate::model::common::BorderSides;
 use crate::{model::muxbox::MuxBox, screen_bounds, Bounds, EntityType, FieldUpdate, Updatable};
 use core::hash::Hash;
 use serde::{Deserialize, Serialize};
@@ -10,10 +11,21 @@ pub struct Layout {
     pub title: Option<String>,
     pub refresh_interval: Option<u64>,
     pub children: Option<Vec<MuxBox>>,
+    /// Style classes from `app.styles`, later ones winning; fields set here win over them
+    #[serde(
+        default,
+        deserialize_with = "crate::model::common::deserialize_class",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub class: Option<Vec<String>>,
     pub fill: Option<bool>,
     pub fill_char: Option<char>,
     pub selected_fill_char: Option<char>,
     pub highlighted_fill_char: Option<char>,
+    /// Default `border` for the layout's boxes
+    pub border: Option<BorderSides>,
+    /// Default `border_style` for the layout's boxes
+    pub border_style: Option<String>,
     pub border_color: Option<String>,
     pub selected_border_color: Option<String>,
     pub highlighted_border_color: Option<String>,
@@ -65,10 +77,13 @@ impl Hash for Layout {
                 muxbox.hash(state);
             }
         }
+        self.class.hash(state);
         self.fill.hash(state);
         self.fill_char.hash(state);
         self.selected_fill_char.hash(state);
         self.highlighted_fill_char.hash(state);
+        self.border.hash(state);
+        self.border_style.hash(state);
         self.border_color.hash(state);
         self.selected_border_color.hash(state);
         self.highlighted_border_color.hash(state);
@@ -114,10 +129,13 @@ impl Layout {
             title: None,
             refresh_interval: None,
             children: None,
+            class: None,
             fill: None,
             fill_char: None,
             selected_fill_char: None,
             highlighted_fill_char: None,
+            border: None,
+            border_style: None,
             border_color: None,
             selected_border_color: None,
             highlighted_border_color: None,
@@ -577,10 +595,13 @@ impl Clone for Layout {
             title: self.title.clone(),
             refresh_interval: self.refresh_interval,
             children: cloned_children,
+            class: self.class.clone(),
             fill: self.fill,
             fill_char: self.fill_char,
             selected_fill_char: self.selected_fill_char,
             highlighted_fill_char: self.highlighted_fill_char,
+            border: self.border,
+            border_style: self.border_style.clone(),
             border_color: self.border_color.clone(),
             selected_border_color: self.selected_border_color.clone(),
             highlighted_border_color: self.highlighted_border_color.clone(),
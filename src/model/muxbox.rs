@@ -20,6 +20,7 @@ pub fn stream_type_priority(stream_type: &crate::model::common::StreamType) -> u
         StreamType::RedirectedOutput(_) => 4, // Redirected outputs
         StreamType::ChoiceExecution(_) => 5,  // Choice executions
         StreamType::Plugin(_) => 6,           // Plugin outputs
+        StreamType::PluginLog(_) => 6,        // Plugin process logs
         StreamType::RedirectSource(_) => 7,   // Redirect sources
         StreamType::ExternalSocket => 8,      // Socket connections
     }
@@ -31,7 +32,7 @@ fn is_zero(n: &usize) -> bool {
 }
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::OpenOptions;
 use std::hash::Hasher;
 use std::io::Write;
@@ -54,13 +55,33 @@ pub struct MuxBox {
     pub max_height: Option<usize>,
     pub overflow_behavior: Option<String>,
     pub refresh_interval: Option<u64>,
+    /// What a refresh does while the previous run is still in flight (default skip)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlap_policy: Option<OverlapPolicy>,
+    /// Random extra delay of up to this many milliseconds before each refresh
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_jitter_ms: Option<u64>,
+    /// Double the refresh interval after each consecutive failure, up to this many milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_backoff_max_ms: Option<u64>,
     pub tab_order: Option<String>,
     pub next_focus_id: Option<String>,
     pub children: Option<Vec<MuxBox>>,
+    /// Style classes from `app.styles`, later ones winning; fields set here win over them
+    #[serde(
+        default,
+        deserialize_with = "deserialize_class",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub class: Option<Vec<String>>,
     pub fill: Option<bool>,
     pub fill_char: Option<char>,
     pub selected_fill_char: Option<char>,
     pub highlighted_fill_char: Option<char>,
+    /// Sides that draw a border; taken from the parent box or layout when unset
+    pub border: Option<BorderSides>,
+    /// `single`, `double`, `thick` or `rounded`; taken from the parent box or layout when unset
+    pub border_style: Option<String>,
     pub border_color: Option<String>,
     pub selected_border_color: Option<String>,
     pub highlighted_border_color: Option<String>,
@@ -77,6 +98,12 @@ pub struct MuxBox {
     pub highlighted_title_bg_color: Option<String>,
     pub highlighted_title_fg_color: Option<String>,
     pub title_position: Option<String>,
+    /// Text attributes of the title bar, e.g. `[bold, underline]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_style: Option<TextStyle>,
+    /// Text attributes of the box's text content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_style: Option<TextStyle>,
     pub error_border_color: Option<String>,
     pub error_bg_color: Option<String>,
     pub error_fg_color: Option<String>,
@@ -98,6 +125,50 @@ pub struct MuxBox {
     pub append_output: Option<bool>,
     #[serde(deserialize_with = "deserialize_script", default)]
     pub script: Option<Vec<String>>,
+    /// Working directory for this box's scripts; `~` expands to `$HOME`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Extra environment variables for this box's scripts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<BTreeMap<String, String>>,
+    /// `bash`, `sh`, `zsh`, a shell path, or an argv the script is appended to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<ShellSpec>,
+    /// Whether scripts see boxmux's own environment (default true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherit_env: Option<bool>,
+    /// Kill immediate and thread scripts still running after this many milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Cron, wall-clock and event triggers for the box's script
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<crate::model::common::Schedule>,
+    /// Parse the box script's output as structured data (default text)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<crate::model::common::OutputFormat>,
+    /// Script whose output (one item per line, or JSON choice objects) becomes the box's choices
+    #[serde(
+        deserialize_with = "deserialize_script",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub choices_script: Option<Vec<String>>,
+    /// Script for generated choices that have none; `${item}` is replaced by the choice's id
+    #[serde(
+        deserialize_with = "deserialize_script",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub choices_item_script: Option<Vec<String>>,
+    /// Re-run `choices_script` every this many milliseconds (default: only at startup)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub choices_refresh_interval: Option<u64>,
+    /// Rules on finished runs that switch the box to ok, warning or error styles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<Vec<crate::model::common::AlertRule>>,
+    /// Record the box's PTY output to this asciicast v2 file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record: Option<String>,
     #[serde(default)]
     pub on_keypress: Option<HashMap<String, Vec<String>>>,
     #[serde(default)]
@@ -141,6 +212,9 @@ pub struct MuxBox {
     pub parent_layout_id: Option<String>,
     #[serde(skip, default)]
     pub error_state: bool,
+    /// The `alerts` rule matching the latest finished run
+    #[serde(skip, default)]
+    pub alert: Option<crate::model::common::ActiveAlert>,
 }
 
 impl Hash for MuxBox {
@@ -156,6 +230,9 @@ impl Hash for MuxBox {
         self.overflow_behavior.hash(state);
         self.content.hash(state);
         self.refresh_interval.hash(state);
+        self.overlap_policy.hash(state);
+        self.refresh_jitter_ms.hash(state);
+        self.refresh_backoff_max_ms.hash(state);
         self.tab_order.hash(state);
         self.next_focus_id.hash(state);
         if let Some(children) = &self.children {
@@ -163,10 +240,13 @@ impl Hash for MuxBox {
                 child.hash(state);
             }
         }
+        self.class.hash(state);
         self.fill.hash(state);
         self.fill_char.hash(state);
         self.selected_fill_char.hash(state);
         self.highlighted_fill_char.hash(state);
+        self.border.hash(state);
+        self.border_style.hash(state);
         self.border_color.hash(state);
         self.selected_border_color.hash(state);
         self.highlighted_border_color.hash(state);
@@ -183,6 +263,8 @@ impl Hash for MuxBox {
         self.highlighted_title_bg_color.hash(state);
         self.highlighted_title_fg_color.hash(state);
         self.title_position.hash(state);
+        self.title_style.hash(state);
+        self.text_style.hash(state);
         self.menu_fg_color.hash(state);
         self.menu_bg_color.hash(state);
         self.selected_menu_fg_color.hash(state);
@@ -207,6 +289,18 @@ impl Hash for MuxBox {
         self.redirect_output.hash(state);
         self.append_output.hash(state);
         self.script.hash(state);
+        self.cwd.hash(state);
+        self.env.hash(state);
+        self.shell.hash(state);
+        self.inherit_env.hash(state);
+        self.timeout_ms.hash(state);
+        self.schedule.hash(state);
+        self.output_format.hash(state);
+        self.choices_script.hash(state);
+        self.choices_item_script.hash(state);
+        self.choices_refresh_interval.hash(state);
+        self.alerts.hash(state);
+        self.record.hash(state);
         self.output.hash(state);
         self.save_in_file.hash(state);
         self.chart_type.hash(state);
@@ -239,6 +333,7 @@ impl Hash for MuxBox {
         self.parent_id.hash(state);
         self.parent_layout_id.hash(state);
         self.error_state.hash(state);
+        self.alert.hash(state);
         self.hovered_tab_target.hash(state);
         // Hash streams by collecting and hashing their keys and values
         for (key, stream) in &self.streams {
@@ -267,13 +362,19 @@ impl Default for MuxBox {
             overflow_behavior: None,
             content: None,
             refresh_interval: None,
+            overlap_policy: None,
+            refresh_jitter_ms: None,
+            refresh_backoff_max_ms: None,
             tab_order: None,
             next_focus_id: None,
             children: None,
+            class: None,
             fill: None,
             fill_char: None,
             selected_fill_char: None,
             highlighted_fill_char: None,
+            border: None,
+            border_style: None,
             border_color: None,
             selected_border_color: None,
             highlighted_border_color: None,
@@ -290,6 +391,8 @@ impl Default for MuxBox {
             highlighted_title_bg_color: None,
             highlighted_title_fg_color: None,
             title_position: None,
+            title_style: None,
+            text_style: None,
             error_border_color: None,
             error_bg_color: None,
             error_fg_color: None,
@@ -310,6 +413,12 @@ impl Default for MuxBox {
             redirect_output: None,
             append_output: None,
             script: None,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
             on_keypress: None,
             variables: None,
             output: "".to_string(),
@@ -336,6 +445,13 @@ impl Default for MuxBox {
             error_state: false,
             streams: IndexMap::new(),
             selected_stream_id: None,
+            output_format: None,
+            choices_script: None,
+            choices_item_script: None,
+            choices_refresh_interval: None,
+            alerts: None,
+            record: None,
+            alert: None,
         }
     }
 }
@@ -352,13 +468,19 @@ impl PartialEq for MuxBox {
             && self.overflow_behavior == other.overflow_behavior
             && self.content == other.content
             && self.refresh_interval == other.refresh_interval
+            && self.overlap_policy == other.overlap_policy
+            && self.refresh_jitter_ms == other.refresh_jitter_ms
+            && self.refresh_backoff_max_ms == other.refresh_backoff_max_ms
             && self.tab_order == other.tab_order
             && self.next_focus_id == other.next_focus_id
             && self.children == other.children
+            && self.class == other.class
             && self.fill == other.fill
             && self.fill_char == other.fill_char
             && self.selected_fill_char == other.selected_fill_char
             && self.highlighted_fill_char == other.highlighted_fill_char
+            && self.border == other.border
+            && self.border_style == other.border_style
             && self.border_color == other.border_color
             && self.selected_border_color == other.selected_border_color
             && self.highlighted_border_color == other.highlighted_border_color
@@ -375,6 +497,8 @@ impl PartialEq for MuxBox {
             && self.highlighted_title_bg_color == other.highlighted_title_bg_color
             && self.highlighted_title_fg_color == other.highlighted_title_fg_color
             && self.title_position == other.title_position
+            && self.title_style == other.title_style
+            && self.text_style == other.text_style
             && self.error_border_color == other.error_border_color
             && self.error_bg_color == other.error_bg_color
             && self.error_fg_color == other.error_fg_color
@@ -395,6 +519,18 @@ impl PartialEq for MuxBox {
             && self.redirect_output == other.redirect_output
             && self.append_output == other.append_output
             && self.script == other.script
+            && self.cwd == other.cwd
+            && self.env == other.env
+            && self.shell == other.shell
+            && self.inherit_env == other.inherit_env
+            && self.timeout_ms == other.timeout_ms
+            && self.schedule == other.schedule
+            && self.output_format == other.output_format
+            && self.choices_script == other.choices_script
+            && self.choices_item_script == other.choices_item_script
+            && self.choices_refresh_interval == other.choices_refresh_interval
+            && self.alerts == other.alerts
+            && self.record == other.record
             && self.horizontal_scroll.map(|hs| hs.to_bits())
                 == other.horizontal_scroll.map(|hs| hs.to_bits())
             && self.vertical_scroll.map(|vs| vs.to_bits())
@@ -415,6 +551,7 @@ impl PartialEq for MuxBox {
             && self.execution_mode == other.execution_mode
             && self.z_index == other.z_index
             && self.error_state == other.error_state
+            && self.alert == other.alert
             && self.streams == other.streams
             && self.hovered_tab_target == other.hovered_tab_target
     }
@@ -436,13 +573,19 @@ impl Clone for MuxBox {
             overflow_behavior: self.overflow_behavior.clone(),
             content: self.content.clone(),
             refresh_interval: self.refresh_interval,
+            overlap_policy: self.overlap_policy,
+            refresh_jitter_ms: self.refresh_jitter_ms,
+            refresh_backoff_max_ms: self.refresh_backoff_max_ms,
             tab_order: self.tab_order.clone(),
             next_focus_id: self.next_focus_id.clone(),
             children: self.children.as_ref().map(|children| children.to_vec()),
+            class: self.class.clone(),
             fill: self.fill,
             fill_char: self.fill_char,
             selected_fill_char: self.selected_fill_char,
             highlighted_fill_char: self.highlighted_fill_char,
+            border: self.border,
+            border_style: self.border_style.clone(),
             border_color: self.border_color.clone(),
             selected_border_color: self.selected_border_color.clone(),
             highlighted_border_color: self.highlighted_border_color.clone(),
@@ -459,6 +602,8 @@ impl Clone for MuxBox {
             highlighted_title_bg_color: self.highlighted_title_bg_color.clone(),
             highlighted_title_fg_color: self.highlighted_title_fg_color.clone(),
             title_position: self.title_position.clone(),
+            title_style: self.title_style,
+            text_style: self.text_style,
             error_border_color: self.error_border_color.clone(),
             error_bg_color: self.error_bg_color.clone(),
             error_fg_color: self.error_fg_color.clone(),
@@ -479,6 +624,18 @@ impl Clone for MuxBox {
             redirect_output: self.redirect_output.clone(),
             append_output: self.append_output,
             script: self.script.clone(),
+            cwd: self.cwd.clone(),
+            env: self.env.clone(),
+            shell: self.shell.clone(),
+            inherit_env: self.inherit_env,
+            timeout_ms: self.timeout_ms,
+            schedule: self.schedule.clone(),
+            output_format: self.output_format,
+            choices_script: self.choices_script.clone(),
+            choices_item_script: self.choices_item_script.clone(),
+            choices_refresh_interval: self.choices_refresh_interval,
+            alerts: self.alerts.clone(),
+            record: self.record.clone(),
             on_keypress: self.on_keypress.clone(),
             variables: self.variables.clone(),
             output: self.output.clone(),
@@ -503,6 +660,7 @@ impl Clone for MuxBox {
             parent_id: self.parent_id.clone(),
             parent_layout_id: self.parent_layout_id.clone(),
             error_state: self.error_state,
+            alert: self.alert.clone(),
             streams: self.streams.clone(),
             selected_stream_id: self.selected_stream_id.clone(),
         }
@@ -555,8 +713,18 @@ impl MuxBox {
         app_context.app.get_layout_by_id(layout_id).cloned()
     }
 
+    /// Whether the box renders with its error colors: a failed script or an error-level
+    /// alert
+    pub fn in_error_state(&self) -> bool {
+        self.error_state
+            || self
+                .alert
+                .as_ref()
+                .is_some_and(|alert| alert.level == crate::model::common::AlertLevel::Error)
+    }
+
     pub fn calc_fg_color(&self, app_context: &AppContext, app_graph: &AppGraph) -> Option<String> {
-        if self.error_state {
+        if self.in_error_state() {
             return self.calc_error_fg_color(app_context, app_graph);
         }
 
@@ -588,12 +756,12 @@ impl MuxBox {
             self_color,
             parent_color.as_ref(),
             parent_layout_color.as_ref(),
-            Some(default_color),
+            Some(&default_color),
         )
     }
 
     pub fn calc_bg_color(&self, app_context: &AppContext, app_graph: &AppGraph) -> Option<String> {
-        if self.error_state {
+        if self.in_error_state() {
             return self.calc_error_bg_color(app_context, app_graph);
         }
 
@@ -625,7 +793,7 @@ impl MuxBox {
             self_color,
             parent_color.as_ref(),
             parent_layout_color.as_ref(),
-            Some(default_color),
+            Some(&default_color),
         )
     }
 
@@ -634,7 +802,15 @@ impl MuxBox {
         app_context: &AppContext,
         app_graph: &AppGraph,
     ) -> Option<String> {
-        if self.error_state {
+        if let Some(alert) = &self.alert {
+            // A flashing border shows the box's usual color in its dark phases
+            if crate::alerts::border_lit(alert, std::time::SystemTime::now()) {
+                return match &alert.color {
+                    Some(color) => Some(color.clone()),
+                    None => self.calc_error_border_color(app_context, app_graph),
+                };
+            }
+        } else if self.error_state {
             return self.calc_error_border_color(app_context, app_graph);
         }
 
@@ -667,7 +843,7 @@ impl MuxBox {
             self_color,
             parent_color.as_ref(),
             parent_layout_color.as_ref(),
-            Some(default_color),
+            Some(&default_color),
         )
     }
 
@@ -676,7 +852,7 @@ impl MuxBox {
         app_context: &AppContext,
         app_graph: &AppGraph,
     ) -> Option<String> {
-        if self.error_state {
+        if self.in_error_state() {
             return self.calc_error_title_bg_color(app_context, app_graph);
         }
 
@@ -709,7 +885,7 @@ impl MuxBox {
             self_color,
             parent_color.as_ref(),
             parent_layout_color.as_ref(),
-            Some(default_color),
+            Some(&default_color),
         )
     }
 
@@ -718,7 +894,10 @@ impl MuxBox {
         app_context: &AppContext,
         app_graph: &AppGraph,
     ) -> Option<String> {
-        if self.error_state {
+        if let Some(color) = self.alert.as_ref().and_then(|alert| alert.color.clone()) {
+            return Some(color);
+        }
+        if self.in_error_state() {
             return self.calc_error_title_fg_color(app_context, app_graph);
         }
 
@@ -751,7 +930,7 @@ impl MuxBox {
             self_color,
             parent_color.as_ref(),
             parent_layout_color.as_ref(),
-            Some(default_color),
+            Some(&default_color),
         )
     }
 
@@ -771,6 +950,38 @@ impl MuxBox {
         )
     }
 
+    /// Whether the box, its parent or its layout sets a panel color, which then
+    /// wins over the theme's menu colors
+    fn panel_color_is_set(
+        &self,
+        app_context: &AppContext,
+        app_graph: &AppGraph,
+        muxbox_color: fn(&MuxBox) -> &Option<String>,
+        layout_color: fn(&Layout) -> &Option<String>,
+    ) -> bool {
+        muxbox_color(self).is_some()
+            || self
+                .get_parent_clone(app_graph)
+                .is_some_and(|p| muxbox_color(&p).is_some())
+            || self
+                .get_parent_layout_clone(app_context)
+                .is_some_and(|l| layout_color(&l).is_some())
+    }
+
+    /// Base color of unhighlighted menu rows: the theme's menu color for panels
+    /// left to the theme, the panel color otherwise
+    fn themed_menu_base(
+        &self,
+        role: crate::theme::ThemeRole,
+        panel_color_is_set: bool,
+        panel_color: impl FnOnce() -> Option<String>,
+    ) -> Option<String> {
+        if panel_color_is_set {
+            return panel_color();
+        }
+        crate::theme::theme_color(role).or_else(panel_color)
+    }
+
     pub fn calc_menu_fg_color(
         &self,
         app_context: &AppContext,
@@ -784,7 +995,11 @@ impl MuxBox {
             .and_then(|pl| pl.menu_fg_color.clone());
 
         // Match the panel's own foreground for the same reason as the background.
-        let panel_fg = self.calc_fg_color(app_context, app_graph);
+        let panel_fg = self.themed_menu_base(
+            crate::theme::ThemeRole::MenuFg,
+            self.panel_color_is_set(app_context, app_graph, |m| &m.fg_color, |l| &l.fg_color),
+            || self.calc_fg_color(app_context, app_graph),
+        );
         inherit_string_transparent(
             self.menu_fg_color.as_ref(),
             parent_position.as_ref(),
@@ -808,8 +1023,13 @@ impl MuxBox {
         // Non-highlighted menu items blend with the panel: base background is the
         // box's OWN background (follows theme + selected state). Using a fixed
         // default_bg_color(false) made items in a selected box keep the unselected
-        // background and visibly differ from the panel fill.
-        let panel_bg = self.calc_bg_color(app_context, app_graph);
+        // background and visibly differ from the panel fill. A theme's menu color
+        // replaces the panel's unless the panel color was set in the YAML.
+        let panel_bg = self.themed_menu_base(
+            crate::theme::ThemeRole::MenuBg,
+            self.panel_color_is_set(app_context, app_graph, |m| &m.bg_color, |l| &l.bg_color),
+            || self.calc_bg_color(app_context, app_graph),
+        );
         inherit_string_transparent(
             self.menu_bg_color.as_ref(),
             parent_position.as_ref(),
@@ -834,7 +1054,7 @@ impl MuxBox {
             self.selected_menu_fg_color.as_ref(),
             parent_position.as_ref(),
             parent_layout_position.as_ref(),
-            Some(crate::color_utils::default_selected_menu_fg_color()),
+            Some(&crate::color_utils::default_selected_menu_fg_color()),
         )
     }
 
@@ -861,7 +1081,7 @@ impl MuxBox {
             self.selected_menu_bg_color.as_ref(),
             parent_position.as_ref(),
             parent_layout_position.as_ref(),
-            Some(default),
+            Some(&default),
         )
     }
 
@@ -881,7 +1101,7 @@ impl MuxBox {
             self.highlighted_menu_fg_color.as_ref(),
             parent_position.as_ref(),
             parent_layout_position.as_ref(),
-            Some(crate::color_utils::default_hover_fg_color()),
+            Some(&crate::color_utils::default_hover_fg_color()),
         )
     }
 
@@ -901,7 +1121,7 @@ impl MuxBox {
             self.highlighted_menu_bg_color.as_ref(),
             parent_position.as_ref(),
             parent_layout_position.as_ref(),
-            Some(crate::color_utils::default_hover_bg_color()),
+            Some(&crate::color_utils::default_hover_bg_color()),
         )
     }
 
@@ -932,17 +1152,14 @@ impl MuxBox {
             self.error_fg_color.as_ref()
         };
 
-        let default_color = if self.selected.unwrap_or(false) {
-            "bright_white"
-        } else {
-            "white"
-        };
+        let default_color =
+            crate::color_utils::default_error_fg_color(self.selected.unwrap_or(false));
 
         crate::utils::inherit_string_transparent(
             self_color,
             parent_color.as_ref(),
             parent_layout_color.as_ref(),
-            Some(default_color),
+            Some(&default_color),
         )
     }
 
@@ -973,17 +1190,14 @@ impl MuxBox {
             self.error_bg_color.as_ref()
         };
 
-        let default_color = if self.selected.unwrap_or(false) {
-            "bright_red"
-        } else {
-            "red"
-        };
+        let default_color =
+            crate::color_utils::default_error_bg_color(self.selected.unwrap_or(false));
 
         crate::utils::inherit_string_transparent(
             self_color,
             parent_color.as_ref(),
             parent_layout_color.as_ref(),
-            Some(default_color),
+            Some(&default_color),
         )
     }
 
@@ -1014,17 +1228,14 @@ impl MuxBox {
             self.error_title_fg_color.as_ref()
         };
 
-        let default_color = if self.selected.unwrap_or(false) {
-            "bright_red"
-        } else {
-            "red"
-        };
+        let default_color =
+            crate::color_utils::default_error_title_fg_color(self.selected.unwrap_or(false));
 
         crate::utils::inherit_string_transparent(
             self_color,
             parent_color.as_ref(),
             parent_layout_color.as_ref(),
-            Some(default_color),
+            Some(&default_color),
         )
     }
 
@@ -1055,17 +1266,14 @@ impl MuxBox {
             self.error_title_bg_color.as_ref()
         };
 
-        let default_color = if self.selected.unwrap_or(false) {
-            "bright_white"
-        } else {
-            "white"
-        };
+        let default_color =
+            crate::color_utils::default_error_title_bg_color(self.selected.unwrap_or(false));
 
         crate::utils::inherit_string_transparent(
             self_color,
             parent_color.as_ref(),
             parent_layout_color.as_ref(),
-            Some(default_color),
+            Some(&default_color),
         )
     }
 
@@ -1096,17 +1304,14 @@ impl MuxBox {
             self.error_border_color.as_ref()
         };
 
-        let default_color = if self.selected.unwrap_or(false) {
-            "bright_white"
-        } else {
-            "white"
-        };
+        let default_color =
+            crate::color_utils::default_error_border_color(self.selected.unwrap_or(false));
 
         crate::utils::inherit_string_transparent(
             self_color,
             parent_color.as_ref(),
             parent_layout_color.as_ref(),
-            Some(default_color),
+            Some(&default_color),
         )
     }
 
@@ -1144,6 +1349,43 @@ impl MuxBox {
         should_draw_color(&self.calc_border_color(app_context, app_graph))
     }
 
+    /// Glyphs named by `border_style`, single lines unless set. Loading fills
+    /// `border_style` and `border` from the parent box or layout, so neither needs
+    /// an inheritance lookup here.
+    pub fn border_style(&self) -> crate::components::BorderStyle {
+        self.border_style
+            .as_deref()
+            .and_then(crate::components::BorderStyle::from_name)
+            .unwrap_or_default()
+    }
+
+    /// Sides that draw a border
+    pub fn border_sides(&self) -> BorderSides {
+        self.border.unwrap_or_default()
+    }
+
+    /// Whether the top row holds a title or tabs, which it keeps without a top border
+    pub fn has_tab_bar(&self) -> bool {
+        self.title.as_ref().is_some_and(|t| !t.is_empty()) || self.streams.len() > 1
+    }
+
+    /// Sides that keep their outer row or column. A side without a border gives it
+    /// to the content, except the top row while it holds the tab bar.
+    pub fn frame_sides(&self) -> BorderSides {
+        let sides = self.border_sides();
+        BorderSides {
+            top: sides.top || self.has_tab_bar(),
+            ..sides
+        }
+    }
+
+    /// Area inside `bounds()` where text and PTY output are drawn
+    pub fn content_bounds(&self) -> Bounds {
+        crate::components::ComponentDimensions::new(self.bounds())
+            .with_border_sides(self.frame_sides())
+            .content_bounds()
+    }
+
     pub fn calc_overflow_behavior(&self, app_context: &AppContext, app_graph: &AppGraph) -> String {
         let parent_overflow_behavior = self
             .get_parent_clone(app_graph)
@@ -1222,6 +1464,7 @@ impl MuxBox {
                     | crate::model::common::StreamType::RedirectedOutput(_)
                     | crate::model::common::StreamType::PTY
                     | crate::model::common::StreamType::Plugin(_)
+                    | crate::model::common::StreamType::PluginLog(_)
                     | crate::model::common::StreamType::ChoiceExecution(_)
                     | crate::model::common::StreamType::PtySession(_)
                     | crate::model::common::StreamType::OwnScript => {
@@ -1586,39 +1829,245 @@ impl MuxBox {
         }
     }
 
-    /// Generate plugin content for the muxbox
-    pub fn generate_plugin_content(
+    /// Component config handed to the plugin, built from `plugin_config` and `refresh_interval`
+    pub fn plugin_component_config(&self) -> Option<crate::plugin::ComponentConfig> {
+        self.plugin_component
+            .as_ref()
+            .map(|component_type| crate::plugin::ComponentConfig {
+                component_type: component_type.clone(),
+                properties: self.plugin_config.clone().unwrap_or_default(),
+                data_source: None,
+                refresh_interval: self.refresh_interval,
+            })
+    }
+
+    /// Stream the plugin component renders into
+    pub fn plugin_stream_id(&self) -> Option<String> {
+        self.plugin_component
+            .as_ref()
+            .map(|component_type| format!("{}_{}", self.id, component_type))
+    }
+
+    /// Render the plugin component through the app's plugin registry.
+    /// Returns None when the box has no `plugin_component`.
+    pub fn render_plugin_component(
         &self,
         app_context: &AppContext,
         bounds: &Bounds,
-    ) -> Option<String> {
-        use crate::plugin::{ComponentConfig, PluginContext, PluginRegistry};
-        use std::collections::HashMap;
+    ) -> Option<Result<String, crate::plugin::PluginError>> {
+        use crate::plugin::PluginContext;
+
+        let config = self.plugin_component_config()?;
+        let registry = match app_context.plugin_registry.lock() {
+            Ok(registry) => registry,
+            Err(_) => {
+                return Some(Err(crate::plugin::PluginError::RuntimeError(
+                    "Plugin registry unavailable".to_string(),
+                )))
+            }
+        };
+        let context = PluginContext {
+            app_context: app_context.clone(),
+            muxbox_bounds: *bounds,
+            plugin_data: registry.plugin_data(&self.id),
+            permissions: registry.component_permissions(&config.component_type),
+        };
 
-        if let Some(component_type) = &self.plugin_component {
-            let registry = PluginRegistry::new();
-            let context = PluginContext {
-                app_context: app_context.clone(),
-                muxbox_bounds: bounds.clone(),
-                plugin_data: HashMap::new(),
-                permissions: Vec::new(),
-            };
-            let config = ComponentConfig {
-                component_type: component_type.clone(),
-                properties: self.plugin_config.clone().unwrap_or_default(),
-                data_source: None,
-                refresh_interval: None,
-            };
+        // A plugin process may take its timeout to answer; don't hold the registry meanwhile
+        if let Some(process) = registry.process_plugin(&config.component_type) {
+            drop(registry);
+            return Some(process.render(&context, &config));
+        }
+        Some(registry.render_component(&config.component_type, &context, &config))
+    }
+
+    /// Deliver an event to the plugin component and keep the plugin data its handler
+    /// returns for this box. Returns None when the box has no `plugin_component`.
+    pub fn dispatch_plugin_event(
+        &self,
+        app_context: &AppContext,
+        bounds: &Bounds,
+        event: &crate::plugin::PluginEvent,
+    ) -> Option<Result<crate::plugin::EventOutcome, crate::plugin::PluginError>> {
+        use crate::plugin::PluginContext;
+
+        let component_type = self.plugin_component.as_ref()?;
+        let mut registry = match app_context.plugin_registry.lock() {
+            Ok(registry) => registry,
+            Err(_) => {
+                return Some(Err(crate::plugin::PluginError::RuntimeError(
+                    "Plugin registry unavailable".to_string(),
+                )))
+            }
+        };
+        let context = PluginContext {
+            app_context: app_context.clone(),
+            muxbox_bounds: *bounds,
+            plugin_data: registry.plugin_data(&self.id),
+            permissions: registry.component_permissions(component_type),
+        };
+
+        let result = match registry.process_plugin(component_type) {
+            Some(process) => {
+                drop(registry);
+                let result = process.handle_event(component_type, &context, event);
+                registry = match app_context.plugin_registry.lock() {
+                    Ok(registry) => registry,
+                    Err(_) => return Some(result),
+                };
+                result
+            }
+            None => registry.handle_event(component_type, &context, event),
+        };
+        if let Ok(crate::plugin::EventOutcome {
+            plugin_data: Some(plugin_data),
+            ..
+        }) = &result
+        {
+            registry.set_plugin_data(&self.id, plugin_data.clone());
+        }
+        Some(result)
+    }
 
-            match registry.render_component(component_type, &context, &config) {
-                Ok(content) => Some(content),
-                Err(_) => {
-                    // Return mock content for testing when component is not found
-                    Some(self.generate_mock_plugin_content(component_type, bounds))
+    /// Deliver an event to the plugin component and re-render it when the handler asks
+    /// for it; a handler error is shown like a render error. Returns the actions the
+    /// handler requested, or None when the box has no `plugin_component`.
+    pub fn handle_plugin_event(
+        &mut self,
+        app_context: &AppContext,
+        bounds: &Bounds,
+        event: &crate::plugin::PluginEvent,
+    ) -> Option<Vec<crate::plugin::PluginAction>> {
+        match self.dispatch_plugin_event(app_context, bounds, event)? {
+            Ok(outcome) => {
+                if outcome.rerender {
+                    self.refresh_plugin_component(app_context, bounds);
                 }
+                Some(outcome.actions)
+            }
+            Err(e) => {
+                log::warn!("Plugin event handler failed in muxbox {}: {}", self.id, e);
+                self.show_plugin_output(e.to_string(), false);
+                Some(Vec::new())
+            }
+        }
+    }
+
+    /// Render the plugin component into its stream. A plugin error replaces the
+    /// stream content with the error text and puts the box in its error state.
+    /// Returns false when the box has no `plugin_component`.
+    pub fn refresh_plugin_component(&mut self, app_context: &AppContext, bounds: &Bounds) -> bool {
+        let Some(result) = self.render_plugin_component(app_context, bounds) else {
+            return false;
+        };
+        let (content, success) = match result {
+            Ok(content) => (content, true),
+            Err(e) => {
+                log::warn!("Plugin component failed in muxbox {}: {}", self.id, e);
+                (e.to_string(), false)
+            }
+        };
+        self.show_plugin_output(content, success);
+        true
+    }
+
+    /// Replace the plugin stream content; failures put the box in its error state
+    pub fn show_plugin_output(&mut self, content: String, success: bool) {
+        let stream_id = self.ensure_plugin_stream();
+        if let Some(stream) = self.streams.get_mut(&stream_id) {
+            stream.content = content.lines().map(|s| s.to_string()).collect();
+        }
+        self.error_state = !success;
+    }
+
+    /// Show the stderr of the plugin process in a log tab, created on first use
+    pub fn show_plugin_log(&mut self, lines: Vec<String>) {
+        let Some(component_type) = self.plugin_component.clone() else {
+            return;
+        };
+        let stream_id = format!("{}_{}_log", self.id, component_type);
+        if !self.streams.contains_key(&stream_id) {
+            self.add_stream_with_source(
+                StreamType::PluginLog(component_type.clone()),
+                format!("{} log", component_type),
+                crate::model::common::StreamSource::StaticContent(
+                    crate::model::common::StaticContentSource {
+                        content_type: "plugin_log".to_string(),
+                        created_at: std::time::SystemTime::now(),
+                    },
+                ),
+            );
+        }
+        if let Some(stream) = self.streams.get_mut(&stream_id) {
+            stream.content = lines;
+        }
+    }
+
+    /// Create the plugin stream if it does not exist yet and return its ID
+    fn ensure_plugin_stream(&mut self) -> String {
+        let stream_id = self.plugin_stream_id().unwrap_or_default();
+        if !self.streams.contains_key(&stream_id) {
+            let component_type = self.plugin_component.clone().unwrap_or_default();
+            let label = self.title.clone().unwrap_or_else(|| component_type.clone());
+            self.add_stream_with_source(
+                StreamType::Plugin(component_type),
+                label,
+                crate::model::common::StreamSource::StaticContent(
+                    crate::model::common::StaticContentSource {
+                        content_type: "plugin".to_string(),
+                        created_at: std::time::SystemTime::now(),
+                    },
+                ),
+            );
+            if self.selected_stream_id.is_none() {
+                self.selected_stream_id = Some(stream_id.clone());
+            }
+        }
+        stream_id
+    }
+
+    /// Replace the box's choices stream with generated choices, creating and selecting the
+    /// stream if the box had none. Choices whose id survives keep their selection.
+    pub fn set_generated_choices(&mut self, mut choices: Vec<Choice>) {
+        let stream_id = format!("{}_choices", self.id);
+        if !self.streams.contains_key(&stream_id) {
+            let label = self.title.clone().unwrap_or_else(|| "Choices".to_string());
+            self.add_stream_with_source(
+                StreamType::Choices,
+                label,
+                crate::model::common::StreamSource::StaticContent(
+                    crate::model::common::StaticContentSource {
+                        content_type: "choices".to_string(),
+                        created_at: std::time::SystemTime::now(),
+                    },
+                ),
+            );
+            self.selected_stream_id = Some(stream_id.clone());
+        }
+        if let Some(stream) = self.streams.get_mut(&stream_id) {
+            for previous in stream.choices.iter().flatten() {
+                if let Some(choice) = choices.iter_mut().find(|c| c.id == previous.id) {
+                    choice.selected = previous.selected;
+                    choice.waiting = previous.waiting;
+                }
+            }
+            stream.set_choices(choices);
+        }
+    }
+
+    /// Generate plugin content for the muxbox
+    pub fn generate_plugin_content(
+        &self,
+        app_context: &AppContext,
+        bounds: &Bounds,
+    ) -> Option<String> {
+        match self.render_plugin_component(app_context, bounds)? {
+            Ok(content) => Some(content),
+            Err(_) => {
+                // Return mock content for testing when component is not found
+                let component_type = self.plugin_component.as_deref().unwrap_or_default();
+                Some(self.generate_mock_plugin_content(component_type, bounds))
             }
-        } else {
-            None
         }
     }
 
@@ -1698,8 +2147,8 @@ impl MuxBox {
 
         if let Some(table_data) = &self.table_data {
             // Parse table data
-            let data = if table_data.trim().starts_with('[') {
-                // JSON format
+            let data = if table_data.trim().starts_with(['[', '{']) {
+                // JSON format: an array of objects or a serialized TableData
                 match parse_table_data_from_json(table_data) {
                     Ok(d) => d,
                     Err(e) => return Some(format!("Table JSON error: {}", e)),
@@ -1709,10 +2158,13 @@ impl MuxBox {
                 parse_table_data(table_data, None)
             };
 
-            // Create table configuration
+            // Create table configuration sized to the content area
+            let content_bounds = crate::components::ComponentDimensions::new(*bounds)
+                .with_border_sides(self.frame_sides())
+                .content_bounds();
             let mut config = TableConfig {
-                width: bounds.width().saturating_sub(4),
-                height: bounds.height().saturating_sub(4),
+                width: content_bounds.width(),
+                height: content_bounds.height(),
                 title: self.title.clone(),
                 ..Default::default()
             };
@@ -1982,6 +2434,11 @@ impl MuxBox {
             self.streams
                 .insert(choices_stream.id.clone(), choices_stream);
         }
+
+        // Plugin components render into their own stream; content arrives on refresh
+        if self.plugin_component.is_some() {
+            self.ensure_plugin_stream();
+        }
     }
 
     /// Add a redirected output stream
@@ -2026,6 +2483,7 @@ impl MuxBox {
             StreamType::RedirectedOutput(source_stream_id) => source_stream_id.clone(),
             StreamType::RedirectSource(source_stream_id) => source_stream_id.clone(),
             StreamType::Plugin(plugin_name) => format!("{}_{}", self.id, plugin_name),
+            StreamType::PluginLog(plugin_name) => format!("{}_{}_log", self.id, plugin_name),
         };
         let stream = Stream::new(
             stream_id.clone(),
@@ -2259,6 +2717,7 @@ impl MuxBox {
                 crate::model::common::StreamType::RedirectedOutput(name) => format!("→{}", name),
                 crate::model::common::StreamType::PTY => "PTY".to_string(),
                 crate::model::common::StreamType::Plugin(name) => format!("Plugin:{}", name),
+                crate::model::common::StreamType::PluginLog(name) => format!("Log:{}", name),
                 crate::model::common::StreamType::ChoiceExecution(choice_id) => {
                     format!("Choice:{}", choice_id)
                 }
@@ -2272,6 +2731,18 @@ impl MuxBox {
             labels.push(label);
         }
 
+        // A matching alert rule leads the first tab with its status indicator
+        if let Some(alert) = &self.alert {
+            let mut status =
+                crate::components::StatusIndicator::new(crate::alerts::alert_status_type(alert));
+            if let Some(message) = &alert.message {
+                status = status.with_custom_text(message.clone());
+            }
+            if let Some(first) = labels.first_mut() {
+                *first = format!("{} {}", status.get_full_status(), first);
+            }
+        }
+
         log::trace!(
             "TAB DEBUG: get_tab_labels() returning {} labels for box {}: {:?}",
             labels.len(),
@@ -2366,11 +2837,12 @@ impl MuxBox {
         let mut cols = bounds.width() as u16;
         let mut rows = bounds.height() as u16;
 
-        // Account for border space if present
+        // Account for border space if present: each side with a border takes a
+        // column or a row
         if should_draw_color(&self.border_color) {
-            // Borders take 2 characters (left + right) and 2 rows (top + bottom)
-            cols = cols.saturating_sub(2);
-            rows = rows.saturating_sub(2);
+            let sides = self.border_sides();
+            cols = cols.saturating_sub(u16::from(sides.left) + u16::from(sides.right));
+            rows = rows.saturating_sub(u16::from(sides.top) + u16::from(sides.bottom));
         }
 
         // Account for title bar space (1 row for title)
@@ -3430,6 +3902,20 @@ mod tests {
             selected: false,
             waiting: false,
             hovered: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         }
     }
 
@@ -1,19 +1,19 @@
+use crate::components::error_display::{ErrorDisplay, ErrorInfo, ErrorSeverity};
+use crate::model::app::PluginsConfig;
 use crate::model::common::Bounds;
+use crate::plugin_abi::NativePlugin;
+use crate::plugin_process::{ProcessCommand, ProcessPlugin};
 use crate::AppContext;
-use libloading::{Library, Symbol};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 // Type aliases for complex plugin function signatures
 type RenderFunction = fn(&PluginContext, &ComponentConfig) -> Result<String, PluginError>;
 type UpdateFunction = fn(&PluginContext, &ComponentConfig) -> Result<ComponentState, PluginError>;
-type EventHandler = fn(&PluginContext, &PluginEvent) -> Result<(), PluginError>;
-
-// Type aliases for Symbol<> function signatures
-type RenderSymbol<'a> = Symbol<'a, RenderFunction>;
-type UpdateSymbol<'a> = Symbol<'a, UpdateFunction>;
-type EventSymbol<'a> = Symbol<'a, EventHandler>;
+type EventHandler = fn(&PluginContext, &PluginEvent) -> Result<EventOutcome, PluginError>;
+type LoadedComponents = (Option<Arc<NativePlugin>>, HashMap<String, PluginComponent>);
 
 /// Plugin manifest structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +26,27 @@ pub struct PluginManifest {
     pub component_types: Vec<String>,
     pub dependencies: Vec<PluginDependency>,
     pub permissions: Vec<PluginPermission>,
+    /// `library` (default) or `process`
+    #[serde(default)]
+    pub kind: PluginKind,
+    /// Program that runs a process plugin's `entry_point`, e.g. `python3`; must be
+    /// declared in a `Process` permission. Without it the entry point is executed directly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpreter: Option<String>,
+    /// How long a process plugin may take to answer one request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// How a plugin's `entry_point` is run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginKind {
+    /// Dynamic library implementing the C plugin ABI
+    #[default]
+    Library,
+    /// Executable speaking JSON lines over stdin/stdout
+    Process,
 }
 
 /// Plugin dependency specification
@@ -48,9 +69,80 @@ pub enum PluginPermission {
 /// Plugin component definition
 pub struct PluginComponent {
     pub component_type: String,
-    pub render_fn: RenderFunction,
-    pub update_fn: Option<UpdateFunction>,
-    pub event_handler: Option<EventHandler>,
+    pub implementation: ComponentImplementation,
+}
+
+/// Code behind a plugin component
+pub enum ComponentImplementation {
+    /// Functions compiled into boxmux (mock components for development and tests)
+    Builtin {
+        render_fn: RenderFunction,
+        update_fn: Option<UpdateFunction>,
+        event_handler: Option<EventHandler>,
+    },
+    /// Dynamic library loaded through the C plugin ABI; shared by the plugin's components
+    Native(Arc<NativePlugin>),
+    /// Child process speaking JSON over stdio; shared by the plugin's components
+    Process(Arc<ProcessPlugin>),
+}
+
+impl PluginComponent {
+    pub fn render(
+        &self,
+        context: &PluginContext,
+        config: &ComponentConfig,
+    ) -> Result<String, PluginError> {
+        match &self.implementation {
+            ComponentImplementation::Builtin { render_fn, .. } => render_fn(context, config),
+            ComponentImplementation::Native(plugin) => plugin.render(context, config),
+            ComponentImplementation::Process(plugin) => plugin.render(context, config),
+        }
+    }
+
+    pub fn update(
+        &self,
+        context: &PluginContext,
+        config: &ComponentConfig,
+    ) -> Result<ComponentState, PluginError> {
+        match &self.implementation {
+            ComponentImplementation::Builtin {
+                update_fn: Some(update_fn),
+                ..
+            } => update_fn(context, config),
+            ComponentImplementation::Builtin {
+                update_fn: None, ..
+            } => Err(PluginError::RuntimeError(format!(
+                "Component '{}' does not support updates",
+                self.component_type
+            ))),
+            ComponentImplementation::Native(plugin) => plugin.update(context, config),
+            ComponentImplementation::Process(plugin) => plugin.update(context, config),
+        }
+    }
+
+    /// Components without an event handler ignore events
+    pub fn handle_event(
+        &self,
+        context: &PluginContext,
+        event: &PluginEvent,
+    ) -> Result<EventOutcome, PluginError> {
+        match &self.implementation {
+            ComponentImplementation::Builtin {
+                event_handler: Some(handler),
+                ..
+            } => handler(context, event),
+            ComponentImplementation::Builtin {
+                event_handler: None,
+                ..
+            } => Ok(EventOutcome::default()),
+            ComponentImplementation::Native(plugin) => {
+                plugin.handle_event(&self.component_type, context, event)
+            }
+            ComponentImplementation::Process(plugin) => {
+                plugin.handle_event(&self.component_type, context, event)
+            }
+        }
+    }
 }
 
 /// Plugin execution context
@@ -100,6 +192,25 @@ pub enum PluginEvent {
     },
 }
 
+/// What an event handler asks boxmux to do after handling an event
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventOutcome {
+    /// Render the component again right away
+    pub rerender: bool,
+    /// Replaces the box's plugin data, passed to every later render and event
+    pub plugin_data: Option<HashMap<String, serde_json::Value>>,
+    pub actions: Vec<PluginAction>,
+}
+
+/// Host actions an event handler can request
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PluginAction {
+    SwitchLayout(String),
+    ExecuteChoice(String),
+    /// Re-render the plugin component of another box
+    RefreshBox(String),
+}
+
 /// Plugin errors
 #[derive(Debug, Clone)]
 pub enum PluginError {
@@ -110,11 +221,30 @@ pub enum PluginError {
     RuntimeError(String),
 }
 
+impl std::fmt::Display for PluginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginError::InitializationFailed(msg) => {
+                write!(f, "Plugin initialization failed: {}", msg)
+            }
+            PluginError::RenderFailed(msg) => write!(f, "Plugin render failed: {}", msg),
+            PluginError::PermissionDenied(msg) => write!(f, "Plugin permission denied: {}", msg),
+            PluginError::InvalidConfiguration(msg) => {
+                write!(f, "Invalid plugin configuration: {}", msg)
+            }
+            PluginError::RuntimeError(msg) => write!(f, "Plugin runtime error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
 /// Plugin registry for managing loaded plugins
 #[derive(Debug)]
 pub struct PluginRegistry {
     plugins: HashMap<String, LoadedPlugin>,
     component_types: HashMap<String, String>, // component_type -> plugin_name
+    plugin_data: HashMap<String, HashMap<String, serde_json::Value>>, // muxbox_id -> data
     security_manager: PluginSecurityManager,
 }
 
@@ -122,7 +252,7 @@ pub struct PluginRegistry {
 struct LoadedPlugin {
     manifest: PluginManifest,
     components: HashMap<String, PluginComponent>,
-    library: Option<Library>,
+    native: Option<Arc<NativePlugin>>,
     is_active: bool,
     load_time: std::time::SystemTime,
 }
@@ -135,7 +265,7 @@ impl std::fmt::Debug for LoadedPlugin {
                 "components",
                 &format!("{} components", self.components.len()),
             )
-            .field("library_loaded", &self.library.is_some())
+            .field("library_loaded", &self.native.is_some())
             .field("is_active", &self.is_active)
             .field("load_time", &self.load_time)
             .finish()
@@ -162,44 +292,84 @@ impl PluginRegistry {
         Self {
             plugins: HashMap::new(),
             component_types: HashMap::new(),
+            plugin_data: HashMap::new(),
             security_manager: PluginSecurityManager::new(),
         }
     }
 
-    /// Load a plugin from a directory
+    /// Load a plugin from a directory. Test builds register mock components for a native
+    /// plugin whose library is missing.
     pub fn load_plugin<P: AsRef<Path>>(&mut self, plugin_path: P) -> Result<(), PluginError> {
-        let manifest_path = plugin_path.as_ref().join("plugin.toml");
+        self.load_plugin_from(plugin_path.as_ref(), cfg!(test))
+    }
+
+    fn load_plugin_from(
+        &mut self,
+        plugin_path: &Path,
+        allow_mock: bool,
+    ) -> Result<(), PluginError> {
+        let manifest_path = plugin_path.join("plugin.toml");
         let manifest = self.load_manifest(&manifest_path)?;
 
         // Validate permissions
         self.security_manager
             .validate_permissions(&manifest.permissions)?;
 
-        // Try to load dynamic library first, fall back to mock if not available
-        let library_path = plugin_path.as_ref().join(&manifest.entry_point);
-        let (library, components) = if library_path.exists() {
+        let library_path = plugin_path.join(&manifest.entry_point);
+        let (native, components) = if manifest.kind == PluginKind::Process {
+            (None, self.start_process_plugin(plugin_path, &manifest)?)
+        } else if library_path.exists() {
             self.load_dynamic_library(&library_path, &manifest)?
-        } else {
-            // Fall back to mock implementation for testing/development
+        } else if allow_mock {
             (None, self.load_mock_components(&manifest)?)
+        } else {
+            return Err(PluginError::InitializationFailed(format!(
+                "Plugin library {} not found",
+                library_path.display()
+            )));
         };
 
-        let loaded_plugin = LoadedPlugin {
-            manifest: manifest.clone(),
-            components,
-            library,
-            is_active: true,
-            load_time: std::time::SystemTime::now(),
-        };
+        self.insert_plugin(manifest, components, native);
+        Ok(())
+    }
 
+    /// Register components implemented inside the host process, e.g. by an application
+    /// embedding boxmux. The manifest's permissions are validated like a loaded plugin's.
+    pub fn register_components(
+        &mut self,
+        manifest: PluginManifest,
+        components: Vec<PluginComponent>,
+    ) -> Result<(), PluginError> {
+        self.security_manager
+            .validate_permissions(&manifest.permissions)?;
+        let components = components
+            .into_iter()
+            .map(|component| (component.component_type.clone(), component))
+            .collect();
+        self.insert_plugin(manifest, components, None);
+        Ok(())
+    }
+
+    fn insert_plugin(
+        &mut self,
+        manifest: PluginManifest,
+        components: HashMap<String, PluginComponent>,
+        native: Option<Arc<NativePlugin>>,
+    ) {
         // Register component types
         for component_type in &manifest.component_types {
             self.component_types
                 .insert(component_type.clone(), manifest.name.clone());
         }
 
-        self.plugins.insert(manifest.name.clone(), loaded_plugin);
-        Ok(())
+        let loaded_plugin = LoadedPlugin {
+            manifest: manifest.clone(),
+            components,
+            native,
+            is_active: true,
+            load_time: std::time::SystemTime::now(),
+        };
+        self.plugins.insert(manifest.name, loaded_plugin);
     }
 
     /// Get a component by type
@@ -212,6 +382,15 @@ impl PluginRegistry {
         None
     }
 
+    /// Permissions declared by the plugin providing a component type
+    pub fn component_permissions(&self, component_type: &str) -> Vec<PluginPermission> {
+        self.component_types
+            .get(component_type)
+            .and_then(|plugin_name| self.plugins.get(plugin_name))
+            .map(|plugin| plugin.manifest.permissions.clone())
+            .unwrap_or_default()
+    }
+
     /// Render a plugin component
     pub fn render_component(
         &self,
@@ -220,7 +399,7 @@ impl PluginRegistry {
         config: &ComponentConfig,
     ) -> Result<String, PluginError> {
         if let Some(component) = self.get_component(component_type) {
-            (component.render_fn)(context, config)
+            component.render(context, config)
         } else {
             Err(PluginError::InvalidConfiguration(format!(
                 "Component type '{}' not found",
@@ -235,13 +414,9 @@ impl PluginRegistry {
         component_type: &str,
         context: &PluginContext,
         event: &PluginEvent,
-    ) -> Result<(), PluginError> {
+    ) -> Result<EventOutcome, PluginError> {
         if let Some(component) = self.get_component(component_type) {
-            if let Some(handler) = &component.event_handler {
-                handler(context, event)
-            } else {
-                Ok(()) // No event handler defined
-            }
+            component.handle_event(context, event)
         } else {
             Err(PluginError::InvalidConfiguration(format!(
                 "Component type '{}' not found",
@@ -250,6 +425,58 @@ impl PluginRegistry {
         }
     }
 
+    /// The process answering for a component type; None when the component is not
+    /// provided by a process plugin
+    pub fn process_plugin(&self, component_type: &str) -> Option<Arc<ProcessPlugin>> {
+        match &self.get_component(component_type)?.implementation {
+            ComponentImplementation::Process(process) => Some(process.clone()),
+            _ => None,
+        }
+    }
+
+    /// Revision of the stderr log of the process behind a component type; None when the
+    /// component is not provided by a process plugin
+    pub fn component_log_revision(&self, component_type: &str) -> Option<u64> {
+        match &self.get_component(component_type)?.implementation {
+            ComponentImplementation::Process(process) => Some(process.log_revision()),
+            _ => None,
+        }
+    }
+
+    /// stderr of the process behind a component type, oldest line first
+    pub fn component_log(&self, component_type: &str) -> Vec<String> {
+        match self
+            .get_component(component_type)
+            .map(|c| &c.implementation)
+        {
+            Some(ComponentImplementation::Process(process)) => process.log_lines(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Let plugins declare `Process` permissions for these commands
+    pub fn allow_commands(&mut self, commands: &[String]) {
+        self.security_manager
+            .allowed_commands
+            .extend(commands.iter().cloned());
+    }
+
+    /// Let plugins declare `FileSystem` permissions below these paths
+    pub fn allow_paths(&mut self, paths: &[String]) {
+        self.security_manager
+            .allowed_paths
+            .extend(paths.iter().cloned());
+    }
+
+    /// Plugin data last returned by an event handler for a box
+    pub fn plugin_data(&self, muxbox_id: &str) -> HashMap<String, serde_json::Value> {
+        self.plugin_data.get(muxbox_id).cloned().unwrap_or_default()
+    }
+
+    pub fn set_plugin_data(&mut self, muxbox_id: &str, data: HashMap<String, serde_json::Value>) {
+        self.plugin_data.insert(muxbox_id.to_string(), data);
+    }
+
     /// List loaded plugins
     pub fn list_plugins(&self) -> Vec<&PluginManifest> {
         self.plugins.values().map(|p| &p.manifest).collect()
@@ -295,62 +522,57 @@ impl PluginRegistry {
                 component_types: vec!["custom_chart".to_string()],
                 dependencies: vec![],
                 permissions: vec![],
+                kind: PluginKind::Library,
+                interpreter: None,
+                timeout_ms: None,
             })
         }
     }
 
-    /// Load dynamic library and extract components
+    /// Load a dynamic library through the C plugin ABI. One instance serves every
+    /// component type listed in the manifest.
     fn load_dynamic_library<P: AsRef<Path>>(
         &self,
         library_path: P,
         manifest: &PluginManifest,
-    ) -> Result<(Option<Library>, HashMap<String, PluginComponent>), PluginError> {
-        unsafe {
-            let library = Library::new(library_path.as_ref()).map_err(|e| {
-                PluginError::InitializationFailed(format!("Failed to load library: {}", e))
-            })?;
-
-            let mut components = HashMap::new();
-
-            // For each component type, try to load the required functions
-            for component_type in &manifest.component_types {
-                let render_fn_name = format!("{}_render", component_type);
-                let update_fn_name = format!("{}_update", component_type);
-                let event_fn_name = format!("{}_event", component_type);
-
-                // Load render function (required)
-                let render_symbol: RenderSymbol =
-                    library.get(render_fn_name.as_bytes()).map_err(|e| {
-                        PluginError::InitializationFailed(format!(
-                            "Failed to load render function '{}': {}",
-                            render_fn_name, e
-                        ))
-                    })?;
-
-                // Load update function (optional)
-                let update_fn = library
-                    .get(update_fn_name.as_bytes())
-                    .ok()
-                    .map(|symbol: UpdateSymbol| *symbol.into_raw());
-
-                // Load event handler (optional)
-                let event_handler = library
-                    .get(event_fn_name.as_bytes())
-                    .ok()
-                    .map(|symbol: EventSymbol| *symbol.into_raw());
+    ) -> Result<LoadedComponents, PluginError> {
+        let native = Arc::new(NativePlugin::load(library_path)?);
 
+        let components = manifest
+            .component_types
+            .iter()
+            .map(|component_type| {
                 let component = PluginComponent {
                     component_type: component_type.clone(),
-                    render_fn: *render_symbol.into_raw(),
-                    update_fn,
-                    event_handler,
+                    implementation: ComponentImplementation::Native(native.clone()),
                 };
+                (component_type.clone(), component)
+            })
+            .collect();
 
-                components.insert(component_type.clone(), component);
-            }
+        Ok((Some(native), components))
+    }
 
-            Ok((Some(library), components))
-        }
+    /// Start a process plugin. One process serves every component type in the manifest.
+    fn start_process_plugin(
+        &self,
+        plugin_path: &Path,
+        manifest: &PluginManifest,
+    ) -> Result<HashMap<String, PluginComponent>, PluginError> {
+        let command = ProcessCommand::from_manifest(plugin_path, manifest)?;
+        let process = Arc::new(ProcessPlugin::start(&manifest.name, command)?);
+
+        Ok(manifest
+            .component_types
+            .iter()
+            .map(|component_type| {
+                let component = PluginComponent {
+                    component_type: component_type.clone(),
+                    implementation: ComponentImplementation::Process(process.clone()),
+                };
+                (component_type.clone(), component)
+            })
+            .collect())
     }
 
     /// Load mock components for testing/fallback
@@ -364,9 +586,11 @@ impl PluginRegistry {
         for component_type in &manifest.component_types {
             let component = PluginComponent {
                 component_type: component_type.clone(),
-                render_fn: mock_render_function,
-                update_fn: Some(mock_update_function),
-                event_handler: Some(mock_event_handler),
+                implementation: ComponentImplementation::Builtin {
+                    render_fn: mock_render_function,
+                    update_fn: Some(mock_update_function),
+                    event_handler: Some(mock_event_handler),
+                },
             };
             components.insert(component_type.clone(), component);
         }
@@ -375,6 +599,245 @@ impl PluginRegistry {
     }
 }
 
+/// A plugin from `app.plugins` that could not be loaded
+#[derive(Debug, Clone)]
+pub struct PluginLoadFailure {
+    /// The `app.plugins` entry the plugin came from, as written in the YAML
+    pub entry: String,
+    /// Resolved plugin directory
+    pub path: PathBuf,
+    pub error: PluginError,
+}
+
+impl PluginLoadFailure {
+    /// Format the failure as a diagnostic pointing at the `app.plugins` entry in the YAML file
+    pub fn diagnostic(&self, yaml_path: &str, yaml_contents: &str) -> String {
+        let (line_number, column_number) = yaml_contents
+            .lines()
+            .enumerate()
+            .find_map(|(i, line)| line.find(&self.entry).map(|col| (i + 1, col + 1)))
+            .unwrap_or((1, 1));
+        let error_info = ErrorInfo {
+            message: format!(
+                "Failed to load plugin '{}': {}",
+                self.path.display(),
+                self.error
+            ),
+            file_path: yaml_path.to_string(),
+            line_number,
+            column_number,
+            severity: ErrorSeverity::Warning,
+            help: Some(
+                "The plugin was skipped; boxes using its components show a render error"
+                    .to_string(),
+            ),
+            note: None,
+            caret_positioning: None,
+        };
+        ErrorDisplay::with_terminal_config("plugin_loader".to_string())
+            .format_error(&error_info, yaml_contents)
+    }
+}
+
+impl PluginRegistry {
+    /// Load every plugin listed in `app.plugins`. Manifests are read first so dependencies
+    /// can be checked against the whole set; a plugin whose required dependency is missing,
+    /// has an incompatible version or failed to load is skipped. Failures are returned
+    /// instead of aborting, so the remaining plugins still load.
+    pub fn load_configured_plugins(
+        &mut self,
+        config: &PluginsConfig,
+        base_dir: Option<&Path>,
+    ) -> Vec<PluginLoadFailure> {
+        self.allow_commands(config.allowed_commands.as_deref().unwrap_or_default());
+        self.allow_paths(config.allowed_paths.as_deref().unwrap_or_default());
+        let (plugin_dirs, mut failures) = discover_plugin_dirs(config, base_dir);
+
+        let mut candidates = Vec::new();
+        for (entry, path) in plugin_dirs {
+            let manifest_path = path.join("plugin.toml");
+            // load_manifest falls back to a mock manifest for a missing file; configured
+            // plugins must ship a real one
+            let manifest = if manifest_path.exists() {
+                self.load_manifest(&manifest_path)
+            } else {
+                Err(PluginError::InitializationFailed(format!(
+                    "No plugin.toml in {}",
+                    path.display()
+                )))
+            };
+            match manifest {
+                Ok(manifest) => candidates.push((entry, path, manifest)),
+                Err(error) => failures.push(PluginLoadFailure { entry, path, error }),
+            }
+        }
+
+        // Drop plugins with unmet required dependencies until the set is stable, since
+        // dropping one plugin can break another that depends on it
+        loop {
+            let available: HashMap<&str, &str> = self
+                .plugins
+                .values()
+                .map(|plugin| &plugin.manifest)
+                .chain(candidates.iter().map(|(_, _, manifest)| manifest))
+                .map(|manifest| (manifest.name.as_str(), manifest.version.as_str()))
+                .collect();
+            let unmet = candidates
+                .iter()
+                .enumerate()
+                .find_map(|(index, (_, _, manifest))| {
+                    unmet_dependency(manifest, &available).map(|error| (index, error))
+                });
+            match unmet {
+                Some((index, error)) => {
+                    let (entry, path, _) = candidates.remove(index);
+                    failures.push(PluginLoadFailure { entry, path, error });
+                }
+                None => break,
+            }
+        }
+
+        for (entry, path, manifest) in candidates {
+            let result = if self.plugins.contains_key(&manifest.name) {
+                Err(PluginError::InitializationFailed(format!(
+                    "A plugin named '{}' is already loaded",
+                    manifest.name
+                )))
+            } else {
+                self.load_plugin_from(&path, false)
+            };
+            match result {
+                Ok(()) => log::info!(
+                    "Loaded plugin {} {} from {}",
+                    manifest.name,
+                    manifest.version,
+                    path.display()
+                ),
+                Err(error) => failures.push(PluginLoadFailure { entry, path, error }),
+            }
+        }
+
+        failures
+    }
+}
+
+/// Resolve `app.plugins` into (entry, plugin directory) pairs. Each `paths` entry is a
+/// plugin; each `directories` entry contributes its subdirectories that hold a `plugin.toml`.
+fn discover_plugin_dirs(
+    config: &PluginsConfig,
+    base_dir: Option<&Path>,
+) -> (Vec<(String, PathBuf)>, Vec<PluginLoadFailure>) {
+    let mut plugin_dirs = Vec::new();
+    let mut failures = Vec::new();
+
+    for entry in config.paths.iter().flatten() {
+        plugin_dirs.push((entry.clone(), resolve_plugin_path(entry, base_dir)));
+    }
+
+    for entry in config.directories.iter().flatten() {
+        let directory = resolve_plugin_path(entry, base_dir);
+        match std::fs::read_dir(&directory) {
+            Ok(read_dir) => {
+                let mut found: Vec<PathBuf> = read_dir
+                    .filter_map(|dir_entry| dir_entry.ok().map(|e| e.path()))
+                    .filter(|path| path.join("plugin.toml").is_file())
+                    .collect();
+                found.sort();
+                plugin_dirs.extend(found.into_iter().map(|path| (entry.clone(), path)));
+            }
+            Err(e) => failures.push(PluginLoadFailure {
+                entry: entry.clone(),
+                path: directory,
+                error: PluginError::InitializationFailed(format!(
+                    "Cannot read plugin directory: {}",
+                    e
+                )),
+            }),
+        }
+    }
+
+    (plugin_dirs, failures)
+}
+
+/// Expand a leading `~` and resolve relative paths against `base_dir`
+fn resolve_plugin_path(entry: &str, base_dir: Option<&Path>) -> PathBuf {
+    let expanded = PathBuf::from(crate::utils::expand_home(entry));
+    match base_dir {
+        Some(base_dir) if expanded.is_relative() => base_dir.join(expanded),
+        _ => expanded,
+    }
+}
+
+/// First required dependency of `manifest` that is missing from `available` (name -> version)
+/// or whose version does not satisfy the requirement. Unmet optional dependencies are logged.
+fn unmet_dependency(
+    manifest: &PluginManifest,
+    available: &HashMap<&str, &str>,
+) -> Option<PluginError> {
+    for dependency in &manifest.dependencies {
+        let problem = match available.get(dependency.name.as_str()) {
+            None => format!("requires plugin '{}', which is not loaded", dependency.name),
+            Some(version) if !version_satisfies(version, &dependency.version) => format!(
+                "requires plugin '{}' {}, found {}",
+                dependency.name, dependency.version, version
+            ),
+            Some(_) => continue,
+        };
+        if dependency.required {
+            return Some(PluginError::InitializationFailed(format!(
+                "Plugin '{}' {}",
+                manifest.name, problem
+            )));
+        }
+        log::warn!(
+            "Optional dependency of plugin '{}' unmet: {}",
+            manifest.name,
+            problem
+        );
+    }
+    None
+}
+
+/// Check a version against a requirement: `*`, `=1.2.3`, `>=1.2`, `>1.2`, `<=1.2`, `<1.2`,
+/// `~1.2` (same minor) or `^1.2` (same major, or same minor below 1.0). A bare version is
+/// treated as `^`, like Cargo. Pre-release and build suffixes are ignored.
+pub fn version_satisfies(version: &str, requirement: &str) -> bool {
+    fn parse(version: &str) -> Option<(u64, u64, u64)> {
+        let core = version.trim().split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(|part| part.parse::<u64>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().transpose().ok()?.unwrap_or(0);
+        let patch = parts.next().transpose().ok()?.unwrap_or(0);
+        Some((major, minor, patch))
+    }
+
+    let requirement = requirement.trim();
+    if requirement.is_empty() || requirement == "*" {
+        return true;
+    }
+    let (operator, required) = [">=", "<=", ">", "<", "=", "~", "^"]
+        .iter()
+        .find_map(|op| requirement.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("^", requirement));
+    let (Some(actual), Some(required)) = (parse(version), parse(required)) else {
+        return false;
+    };
+
+    match operator {
+        ">=" => actual >= required,
+        "<=" => actual <= required,
+        ">" => actual > required,
+        "<" => actual < required,
+        "=" => actual == required,
+        "~" => actual >= required && actual.0 == required.0 && actual.1 == required.1,
+        _ => {
+            actual >= required
+                && actual.0 == required.0
+                && (required.0 > 0 || actual.1 == required.1)
+        }
+    }
+}
+
 impl PluginSecurityManager {
     fn new() -> Self {
         Self {
@@ -460,11 +923,14 @@ fn mock_update_function(
     })
 }
 
-fn mock_event_handler(_context: &PluginContext, event: &PluginEvent) -> Result<(), PluginError> {
+fn mock_event_handler(
+    _context: &PluginContext,
+    event: &PluginEvent,
+) -> Result<EventOutcome, PluginError> {
     if let PluginEvent::KeyPress(key) = event {
         log::debug!("Plugin received key press: {}", key);
     }
-    Ok(())
+    Ok(EventOutcome::default())
 }
 
 #[cfg(test)]
@@ -491,6 +957,9 @@ mod tests {
             permissions: vec![PluginPermission::FileSystem {
                 paths: vec!["/tmp".to_string()],
             }],
+            kind: PluginKind::Library,
+            interpreter: None,
+            timeout_ms: None,
         };
 
         let serialized = serde_json::to_string(&manifest).unwrap();
@@ -528,5 +997,6 @@ mod tests {
     fn test_plugin_error_display() {
         let error = PluginError::PermissionDenied("Test error".to_string());
         assert!(format!("{:?}", error).contains("Test error"));
+        assert_eq!(error.to_string(), "Plugin permission denied: Test error");
     }
 }
@@ -0,0 +1,289 @@
+//! Host side of the C plugin ABI defined by the `boxmux-plugin` crate.
+//!
+//! A native plugin is one instance created by the library's `boxmux_plugin_create` and
+//! driven through `boxmux_plugin_call` with JSON requests. Only `extern "C"` functions and
+//! NUL-terminated strings cross the boundary, so plugins can be built with any toolchain.
+
+use crate::model::common::Bounds;
+use crate::plugin::{
+    ComponentConfig, ComponentState, EventOutcome, PluginAction, PluginContext, PluginError,
+    PluginEvent, PluginPermission,
+};
+use boxmux_plugin as abi;
+use libloading::Library;
+use serde::Serialize;
+use std::ffi::{c_void, CStr, CString};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// The functions a plugin library exports
+#[derive(Clone, Copy)]
+pub struct EntryPoints {
+    pub abi_version: abi::AbiVersionFn,
+    pub create: abi::CreateFn,
+    pub destroy: abi::DestroyFn,
+    pub call: abi::CallFn,
+    pub free_string: abi::FreeStringFn,
+}
+
+/// A plugin instance living in a dynamic library
+pub struct NativePlugin {
+    handle: *mut c_void,
+    entry_points: EntryPoints,
+    /// The ABI promises plugins at most one call at a time per instance
+    call_lock: Mutex<()>,
+    /// Keeps the code behind `entry_points` mapped; `None` for statically linked plugins
+    _library: Option<Library>,
+}
+
+// The instance handle is only touched through `entry_points`, under `call_lock` or in Drop
+unsafe impl Send for NativePlugin {}
+unsafe impl Sync for NativePlugin {}
+
+impl std::fmt::Debug for NativePlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativePlugin")
+            .field("handle", &self.handle)
+            .field("library_loaded", &self._library.is_some())
+            .finish()
+    }
+}
+
+impl NativePlugin {
+    /// Open a plugin library, check its ABI version and create its instance
+    pub fn load<P: AsRef<Path>>(library_path: P) -> Result<Self, PluginError> {
+        let library_path = library_path.as_ref();
+        unsafe {
+            let library = Library::new(library_path).map_err(|e| {
+                PluginError::InitializationFailed(format!("Failed to load library: {}", e))
+            })?;
+
+            let abi_version = *library
+                .get::<abi::AbiVersionFn>(abi::ABI_VERSION_SYMBOL.as_bytes())
+                .map_err(|_| {
+                    PluginError::InitializationFailed(format!(
+                        "{} does not export {}; build it with the boxmux-plugin crate",
+                        library_path.display(),
+                        abi::ABI_VERSION_SYMBOL
+                    ))
+                })?;
+            let entry_points = EntryPoints {
+                abi_version,
+                create: *symbol(&library, abi::CREATE_SYMBOL)?,
+                destroy: *symbol(&library, abi::DESTROY_SYMBOL)?,
+                call: *symbol(&library, abi::CALL_SYMBOL)?,
+                free_string: *symbol(&library, abi::FREE_STRING_SYMBOL)?,
+            };
+
+            Self::from_entry_points(entry_points, Some(library))
+        }
+    }
+
+    /// Create an instance from already resolved entry points.
+    ///
+    /// # Safety
+    /// The entry points must implement the plugin ABI and stay valid as long as the
+    /// returned plugin; pass the library that provides them so it outlives the instance.
+    pub unsafe fn from_entry_points(
+        entry_points: EntryPoints,
+        library: Option<Library>,
+    ) -> Result<Self, PluginError> {
+        let version = (entry_points.abi_version)();
+        if version != abi::ABI_VERSION {
+            return Err(PluginError::InitializationFailed(format!(
+                "Plugin ABI version {} is not supported (boxmux uses version {})",
+                version,
+                abi::ABI_VERSION
+            )));
+        }
+
+        let handle = (entry_points.create)();
+        if handle.is_null() {
+            return Err(PluginError::InitializationFailed(
+                "boxmux_plugin_create returned no instance".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            handle,
+            entry_points,
+            call_lock: Mutex::new(()),
+            _library: library,
+        })
+    }
+
+    pub fn render(
+        &self,
+        context: &PluginContext,
+        config: &ComponentConfig,
+    ) -> Result<String, PluginError> {
+        let value = self.call(abi::Method::Render, &render_request(context, config))?;
+        decode(value)
+    }
+
+    pub fn update(
+        &self,
+        context: &PluginContext,
+        config: &ComponentConfig,
+    ) -> Result<ComponentState, PluginError> {
+        let value = self.call(abi::Method::Update, &render_request(context, config))?;
+        let state: abi::ComponentState = decode(value)?;
+        Ok(ComponentState {
+            content: state.content,
+            metadata: state.metadata,
+            needs_refresh: state.needs_refresh,
+        })
+    }
+
+    pub fn handle_event(
+        &self,
+        component_type: &str,
+        context: &PluginContext,
+        event: &PluginEvent,
+    ) -> Result<EventOutcome, PluginError> {
+        let request = event_request(component_type, context, event);
+        let outcome: abi::EventOutcome = decode(self.call(abi::Method::Event, &request)?)?;
+        Ok(host_outcome(outcome))
+    }
+
+    /// Send one request and decode the `Response` envelope
+    fn call<T: Serialize>(
+        &self,
+        method: abi::Method,
+        request: &T,
+    ) -> Result<serde_json::Value, PluginError> {
+        let method_name = CString::new(method.as_str()).expect("method names contain no NUL");
+        let request = serde_json::to_string(request)
+            .ok()
+            .and_then(|json| CString::new(json).ok())
+            .ok_or_else(|| {
+                PluginError::RuntimeError(format!("Cannot encode {} request", method.as_str()))
+            })?;
+
+        let _guard = self.call_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let json = unsafe {
+            let raw = (self.entry_points.call)(self.handle, method_name.as_ptr(), request.as_ptr());
+            if raw.is_null() {
+                return Err(PluginError::RuntimeError(format!(
+                    "Plugin returned no response to {}",
+                    method.as_str()
+                )));
+            }
+            let json = CStr::from_ptr(raw).to_string_lossy().into_owned();
+            (self.entry_points.free_string)(raw);
+            json
+        };
+
+        let response: abi::Response = serde_json::from_str(&json)
+            .map_err(|e| PluginError::RuntimeError(format!("Malformed plugin response: {}", e)))?;
+        response.map_err(PluginError::from)
+    }
+}
+
+impl Drop for NativePlugin {
+    fn drop(&mut self) {
+        unsafe { (self.entry_points.destroy)(self.handle) };
+    }
+}
+
+unsafe fn symbol<'lib, T>(
+    library: &'lib Library,
+    name: &str,
+) -> Result<libloading::Symbol<'lib, T>, PluginError> {
+    library
+        .get(name.as_bytes())
+        .map_err(|e| PluginError::InitializationFailed(format!("Failed to load '{}': {}", name, e)))
+}
+
+pub(crate) fn decode<T: serde::de::DeserializeOwned>(
+    value: serde_json::Value,
+) -> Result<T, PluginError> {
+    serde_json::from_value(value)
+        .map_err(|e| PluginError::RuntimeError(format!("Unexpected plugin result: {}", e)))
+}
+
+pub(crate) fn render_request(
+    context: &PluginContext,
+    config: &ComponentConfig,
+) -> abi::RenderRequest {
+    abi::RenderRequest {
+        component_type: config.component_type.clone(),
+        properties: config.properties.clone(),
+        data_source: config.data_source.clone(),
+        refresh_interval: config.refresh_interval,
+        bounds: abi_bounds(&context.muxbox_bounds),
+        plugin_data: context.plugin_data.clone(),
+        permissions: context.permissions.iter().map(abi_permission).collect(),
+    }
+}
+
+pub(crate) fn event_request(
+    component_type: &str,
+    context: &PluginContext,
+    event: &PluginEvent,
+) -> abi::EventRequest {
+    abi::EventRequest {
+        component_type: component_type.to_string(),
+        bounds: abi_bounds(&context.muxbox_bounds),
+        plugin_data: context.plugin_data.clone(),
+        event: abi_event(event),
+    }
+}
+
+fn abi_bounds(bounds: &Bounds) -> abi::Bounds {
+    abi::Bounds {
+        x1: bounds.x1,
+        y1: bounds.y1,
+        x2: bounds.x2,
+        y2: bounds.y2,
+    }
+}
+
+fn abi_permission(permission: &PluginPermission) -> abi::Permission {
+    match permission.clone() {
+        PluginPermission::FileSystem { paths } => abi::Permission::FileSystem { paths },
+        PluginPermission::Network { hosts } => abi::Permission::Network { hosts },
+        PluginPermission::Process { commands } => abi::Permission::Process { commands },
+        PluginPermission::Environment { variables } => abi::Permission::Environment { variables },
+    }
+}
+
+fn abi_event(event: &PluginEvent) -> abi::Event {
+    match event.clone() {
+        PluginEvent::KeyPress(key) => abi::Event::KeyPress(key),
+        PluginEvent::MouseEvent { x, y, action } => abi::Event::MouseEvent { x, y, action },
+        PluginEvent::Timer { interval } => abi::Event::Timer { interval },
+        PluginEvent::DataUpdate { source, data } => abi::Event::DataUpdate { source, data },
+        PluginEvent::MuxBoxResize { new_bounds } => abi::Event::MuxBoxResize {
+            new_bounds: abi_bounds(&new_bounds),
+        },
+    }
+}
+
+pub(crate) fn host_outcome(outcome: abi::EventOutcome) -> EventOutcome {
+    EventOutcome {
+        rerender: outcome.rerender,
+        plugin_data: outcome.plugin_data,
+        actions: outcome.actions.into_iter().map(host_action).collect(),
+    }
+}
+
+fn host_action(action: abi::Action) -> PluginAction {
+    match action {
+        abi::Action::SwitchLayout(layout_id) => PluginAction::SwitchLayout(layout_id),
+        abi::Action::ExecuteChoice(choice_id) => PluginAction::ExecuteChoice(choice_id),
+        abi::Action::RefreshBox(muxbox_id) => PluginAction::RefreshBox(muxbox_id),
+    }
+}
+
+impl From<abi::PluginError> for PluginError {
+    fn from(error: abi::PluginError) -> Self {
+        match error {
+            abi::PluginError::InitializationFailed(msg) => PluginError::InitializationFailed(msg),
+            abi::PluginError::RenderFailed(msg) => PluginError::RenderFailed(msg),
+            abi::PluginError::PermissionDenied(msg) => PluginError::PermissionDenied(msg),
+            abi::PluginError::InvalidConfiguration(msg) => PluginError::InvalidConfiguration(msg),
+            abi::PluginError::RuntimeError(msg) => PluginError::RuntimeError(msg),
+        }
+    }
+}
@@ -0,0 +1,539 @@
+//! Out-of-process plugins: an executable written in any language that speaks JSON lines
+//! over stdin/stdout.
+//!
+//! boxmux writes one request per line, `{"method": "render", "request": {...}}`, using the
+//! same `render`/`update`/`event` payloads as the C plugin ABI, and reads one response line,
+//! `{"Ok": <result>}` or `{"Err": <error>}`. A `render` result is either a string or
+//! `{"lines": [...]}` with styled lines. Anything the process writes to stderr is kept as
+//! its log. A process that exits or breaks the protocol is restarted on the next request.
+
+use crate::plugin::{
+    ComponentConfig, ComponentState, EventOutcome, PluginContext, PluginError, PluginEvent,
+    PluginManifest, PluginPermission,
+};
+use crate::plugin_abi::{decode, event_request, host_outcome, render_request};
+use boxmux_plugin as abi;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Time a plugin gets to answer one request unless its manifest sets `timeout_ms`
+pub const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 3000;
+/// Crashes in a row after which the process is left stopped for `RESTART_COOLDOWN`
+const MAX_CONSECUTIVE_CRASHES: u32 = 5;
+const RESTART_COOLDOWN: Duration = Duration::from_secs(30);
+/// stderr lines kept per plugin
+const LOG_CAPACITY: usize = 500;
+/// Variables passed through to every plugin process; anything else must be declared
+/// with an `Environment` permission
+const BASE_ENVIRONMENT: [&str; 6] = ["PATH", "HOME", "LANG", "LC_ALL", "TERM", "TMPDIR"];
+
+/// How to start a plugin process
+#[derive(Debug, Clone)]
+pub struct ProcessCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub working_dir: PathBuf,
+    pub env: Vec<(String, String)>,
+    pub timeout: Duration,
+}
+
+impl ProcessCommand {
+    /// Build the command for a `kind = "process"` manifest. The entry point must lie inside
+    /// the plugin directory, and an `interpreter` must be declared in a `Process` permission.
+    /// The process starts in the plugin directory with only the base and declared
+    /// environment variables. Nothing else is enforced: `FileSystem` paths only reach the
+    /// process as `BOXMUX_PLUGIN_ALLOWED_PATHS`, and an entry point run without an
+    /// interpreter needs no `Process` permission.
+    pub fn from_manifest(
+        plugin_dir: &Path,
+        manifest: &PluginManifest,
+    ) -> Result<Self, PluginError> {
+        let plugin_dir = plugin_dir.canonicalize().map_err(|e| {
+            PluginError::InitializationFailed(format!(
+                "Cannot resolve plugin directory {}: {}",
+                plugin_dir.display(),
+                e
+            ))
+        })?;
+        let entry_point = plugin_dir
+            .join(&manifest.entry_point)
+            .canonicalize()
+            .map_err(|e| {
+                PluginError::InitializationFailed(format!(
+                    "Cannot find entry point '{}': {}",
+                    manifest.entry_point, e
+                ))
+            })?;
+        if !entry_point.starts_with(&plugin_dir) {
+            return Err(PluginError::PermissionDenied(format!(
+                "Entry point '{}' is outside the plugin directory",
+                manifest.entry_point
+            )));
+        }
+
+        let declared_commands: Vec<&String> = manifest
+            .permissions
+            .iter()
+            .filter_map(|permission| match permission {
+                PluginPermission::Process { commands } => Some(commands),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        let (program, args) = match &manifest.interpreter {
+            Some(interpreter) => {
+                if !declared_commands.contains(&interpreter) {
+                    return Err(PluginError::PermissionDenied(format!(
+                        "Interpreter '{}' is not declared in a Process permission",
+                        interpreter
+                    )));
+                }
+                (
+                    PathBuf::from(interpreter),
+                    vec![entry_point.to_string_lossy().to_string()],
+                )
+            }
+            None => (entry_point, Vec::new()),
+        };
+
+        let mut env: Vec<(String, String)> = BASE_ENVIRONMENT
+            .iter()
+            .map(|name| name.to_string())
+            .chain(
+                manifest
+                    .permissions
+                    .iter()
+                    .filter_map(|permission| match permission {
+                        PluginPermission::Environment { variables } => Some(variables.clone()),
+                        _ => None,
+                    })
+                    .flatten(),
+            )
+            .filter_map(|name| std::env::var(&name).ok().map(|value| (name, value)))
+            .collect();
+        let allowed_paths: Vec<String> = manifest
+            .permissions
+            .iter()
+            .filter_map(|permission| match permission {
+                PluginPermission::FileSystem { paths } => Some(paths.clone()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        env.push(("BOXMUX_PLUGIN_NAME".to_string(), manifest.name.clone()));
+        env.push((
+            "BOXMUX_PLUGIN_DIR".to_string(),
+            plugin_dir.to_string_lossy().to_string(),
+        ));
+        env.push((
+            "BOXMUX_PLUGIN_ALLOWED_PATHS".to_string(),
+            allowed_paths.join(":"),
+        ));
+
+        Ok(Self {
+            program,
+            args,
+            working_dir: plugin_dir,
+            env,
+            timeout: Duration::from_millis(
+                manifest.timeout_ms.unwrap_or(DEFAULT_REQUEST_TIMEOUT_MS),
+            ),
+        })
+    }
+}
+
+/// stderr of a plugin process; `revision` changes whenever a line is added
+#[derive(Debug, Default)]
+struct ProcessLog {
+    revision: u64,
+    lines: VecDeque<String>,
+}
+
+impl ProcessLog {
+    fn push(&mut self, line: String) {
+        if self.lines.len() == LOG_CAPACITY {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+        self.revision += 1;
+    }
+}
+
+struct RunningProcess {
+    child: Child,
+    stdin: ChildStdin,
+    responses: Receiver<String>,
+}
+
+#[derive(Default)]
+struct ProcessState {
+    running: Option<RunningProcess>,
+    consecutive_crashes: u32,
+    last_crash: Option<Instant>,
+}
+
+/// Why a request got no response
+enum ExchangeError {
+    Crashed(String),
+    TimedOut,
+}
+
+/// A plugin running as a child process
+pub struct ProcessPlugin {
+    name: String,
+    command: ProcessCommand,
+    state: Mutex<ProcessState>,
+    log: Arc<Mutex<ProcessLog>>,
+}
+
+impl std::fmt::Debug for ProcessPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProcessPlugin")
+            .field("name", &self.name)
+            .field("command", &self.command)
+            .finish()
+    }
+}
+
+/// Request line written to the plugin's stdin
+#[derive(Serialize)]
+struct RequestLine<'a, T> {
+    method: &'static str,
+    request: &'a T,
+}
+
+/// `render` result of a process plugin
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RenderedOutput {
+    Text(String),
+    Lines { lines: Vec<RenderedLine> },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RenderedLine {
+    Plain(String),
+    Styled(StyledText),
+    Spans { spans: Vec<StyledText> },
+}
+
+#[derive(Deserialize)]
+struct StyledText {
+    text: String,
+    #[serde(default)]
+    fg: Option<String>,
+    #[serde(default)]
+    bg: Option<String>,
+    #[serde(default)]
+    bold: bool,
+}
+
+impl ProcessPlugin {
+    /// Start the plugin process
+    pub fn start(name: &str, command: ProcessCommand) -> Result<Self, PluginError> {
+        let plugin = Self {
+            name: name.to_string(),
+            command,
+            state: Mutex::new(ProcessState::default()),
+            log: Arc::new(Mutex::new(ProcessLog::default())),
+        };
+        let running = plugin.spawn()?;
+        plugin.lock_state().running = Some(running);
+        Ok(plugin)
+    }
+
+    pub fn render(
+        &self,
+        context: &PluginContext,
+        config: &ComponentConfig,
+    ) -> Result<String, PluginError> {
+        let value = self.call(abi::Method::Render, &render_request(context, config))?;
+        let output: RenderedOutput = decode(value)?;
+        Ok(match output {
+            RenderedOutput::Text(text) => text,
+            RenderedOutput::Lines { lines } => lines
+                .iter()
+                .map(|line| match line {
+                    RenderedLine::Plain(text) => text.clone(),
+                    RenderedLine::Styled(styled) => styled_text(styled),
+                    RenderedLine::Spans { spans } => spans.iter().map(styled_text).collect(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        })
+    }
+
+    pub fn update(
+        &self,
+        context: &PluginContext,
+        config: &ComponentConfig,
+    ) -> Result<ComponentState, PluginError> {
+        let value = self.call(abi::Method::Update, &render_request(context, config))?;
+        let state: abi::ComponentState = decode(value)?;
+        Ok(ComponentState {
+            content: state.content,
+            metadata: state.metadata,
+            needs_refresh: state.needs_refresh,
+        })
+    }
+
+    pub fn handle_event(
+        &self,
+        component_type: &str,
+        context: &PluginContext,
+        event: &PluginEvent,
+    ) -> Result<EventOutcome, PluginError> {
+        let request = event_request(component_type, context, event);
+        let outcome: abi::EventOutcome = decode(self.call(abi::Method::Event, &request)?)?;
+        Ok(host_outcome(outcome))
+    }
+
+    /// Changes whenever the process writes to stderr or boxmux notes a restart
+    pub fn log_revision(&self) -> u64 {
+        self.log.lock().unwrap_or_else(|e| e.into_inner()).revision
+    }
+
+    pub fn log_lines(&self) -> Vec<String> {
+        let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        log.lines.iter().cloned().collect()
+    }
+
+    /// Send one request, restarting the process first if it is not running. A process
+    /// that dies while answering is restarted and the request retried once.
+    fn call<T: Serialize>(
+        &self,
+        method: abi::Method,
+        request: &T,
+    ) -> Result<serde_json::Value, PluginError> {
+        let line = serde_json::to_string(&RequestLine {
+            method: method.as_str(),
+            request,
+        })
+        .map_err(|e| PluginError::RuntimeError(format!("Cannot encode request: {}", e)))?;
+
+        let mut state = self.lock_state();
+        for attempt in 0..2 {
+            if state.running.is_none() {
+                self.restart(&mut state)?;
+            }
+            let running = state.running.as_mut().expect("process was just started");
+            match exchange(running, &line, self.command.timeout) {
+                Ok(response) => {
+                    state.consecutive_crashes = 0;
+                    return match serde_json::from_str::<abi::Response>(&response) {
+                        Ok(response) => response.map_err(PluginError::from),
+                        // Later responses could be out of step, so start over
+                        Err(e) => {
+                            self.stop(&mut state, "sent a malformed response");
+                            Err(PluginError::RuntimeError(format!(
+                                "Malformed plugin response: {}",
+                                e
+                            )))
+                        }
+                    };
+                }
+                Err(ExchangeError::TimedOut) => {
+                    self.stop(&mut state, "did not answer in time");
+                    return Err(PluginError::RuntimeError(format!(
+                        "Plugin '{}' did not answer '{}' within {}ms",
+                        self.name,
+                        method.as_str(),
+                        self.command.timeout.as_millis()
+                    )));
+                }
+                Err(ExchangeError::Crashed(reason)) => {
+                    self.stop(&mut state, &reason);
+                    state.consecutive_crashes += 1;
+                    state.last_crash = Some(Instant::now());
+                    if attempt == 1 {
+                        return Err(PluginError::RuntimeError(format!(
+                            "Plugin '{}' {}",
+                            self.name, reason
+                        )));
+                    }
+                }
+            }
+        }
+        unreachable!("the second attempt always returns")
+    }
+
+    fn restart(&self, state: &mut ProcessState) -> Result<(), PluginError> {
+        let cooling_down = state
+            .last_crash
+            .is_some_and(|at| at.elapsed() < RESTART_COOLDOWN);
+        if state.consecutive_crashes >= MAX_CONSECUTIVE_CRASHES && cooling_down {
+            return Err(PluginError::RuntimeError(format!(
+                "Plugin '{}' crashed {} times in a row; restarting in {}s",
+                self.name,
+                state.consecutive_crashes,
+                RESTART_COOLDOWN
+                    .saturating_sub(state.last_crash.map(|at| at.elapsed()).unwrap_or_default())
+                    .as_secs()
+            )));
+        }
+        self.note(format!("restarting {}", self.name));
+        state.running = Some(self.spawn()?);
+        Ok(())
+    }
+
+    /// Kill the process and record why
+    fn stop(&self, state: &mut ProcessState, reason: &str) {
+        if let Some(mut running) = state.running.take() {
+            let _ = running.child.kill();
+            let status = running.child.wait().ok();
+            log::warn!("Plugin process '{}' {} ({:?})", self.name, reason, status);
+            self.note(match status {
+                Some(status) => format!("{} {} ({})", self.name, reason, status),
+                None => format!("{} {}", self.name, reason),
+            });
+        }
+    }
+
+    fn spawn(&self) -> Result<RunningProcess, PluginError> {
+        let mut child = Command::new(&self.command.program)
+            .args(&self.command.args)
+            .current_dir(&self.command.working_dir)
+            .env_clear()
+            .envs(self.command.env.iter().cloned())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                PluginError::InitializationFailed(format!(
+                    "Failed to start {}: {}",
+                    self.command.program.display(),
+                    e
+                ))
+            })?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+
+        let (sender, responses) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if !line.trim().is_empty() && sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let log = self.log.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines() {
+                let Ok(line) = line else { break };
+                log.lock().unwrap_or_else(|e| e.into_inner()).push(line);
+            }
+        });
+
+        Ok(RunningProcess {
+            child,
+            stdin,
+            responses,
+        })
+    }
+
+    /// Add a boxmux line to the plugin log
+    fn note(&self, message: String) {
+        self.log
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(format!("[boxmux] {}", message));
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, ProcessState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for ProcessPlugin {
+    fn drop(&mut self) {
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        if let Some(mut running) = state.running.take() {
+            let _ = running.child.kill();
+            let _ = running.child.wait();
+        }
+    }
+}
+
+fn exchange(
+    running: &mut RunningProcess,
+    line: &str,
+    timeout: Duration,
+) -> Result<String, ExchangeError> {
+    if let Ok(Some(status)) = running.child.try_wait() {
+        return Err(ExchangeError::Crashed(format!("exited with {}", status)));
+    }
+    writeln!(running.stdin, "{}", line)
+        .and_then(|_| running.stdin.flush())
+        .map_err(|e| ExchangeError::Crashed(format!("stopped reading requests: {}", e)))?;
+    match running.responses.recv_timeout(timeout) {
+        Ok(response) => Ok(response),
+        Err(RecvTimeoutError::Timeout) => Err(ExchangeError::TimedOut),
+        Err(RecvTimeoutError::Disconnected) => Err(ExchangeError::Crashed(
+            "exited without answering".to_string(),
+        )),
+    }
+}
+
+/// Render styled text with the SGR codes the box content renderer understands
+fn styled_text(styled: &StyledText) -> String {
+    let mut codes: Vec<String> = Vec::new();
+    if styled.bold {
+        codes.push("1".to_string());
+    }
+    if let Some(code) = styled
+        .fg
+        .as_deref()
+        .and_then(|color| sgr_color(color, false))
+    {
+        codes.push(code.to_string());
+    }
+    if let Some(code) = styled
+        .bg
+        .as_deref()
+        .and_then(|color| sgr_color(color, true))
+    {
+        codes.push(code.to_string());
+    }
+    if codes.is_empty() {
+        styled.text.clone()
+    } else {
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), styled.text)
+    }
+}
+
+fn sgr_color(color: &str, background: bool) -> Option<u8> {
+    let (base, bright) = match color.strip_prefix("bright_") {
+        Some(base) => (base, true),
+        None => (color, false),
+    };
+    let offset = match base {
+        "black" => 0,
+        "red" => 1,
+        "green" => 2,
+        "yellow" => 3,
+        "blue" => 4,
+        "magenta" => 5,
+        "cyan" => 6,
+        "white" => 7,
+        "gray" | "grey" | "dark_gray" if !bright => return Some(if background { 100 } else { 90 }),
+        _ => return None,
+    };
+    let first = match (background, bright) {
+        (false, false) => 30,
+        (true, false) => 40,
+        (false, true) => 90,
+        (true, true) => 100,
+    };
+    Some(first + offset)
+}
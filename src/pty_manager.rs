@@ -3,11 +3,14 @@ use log::{debug, error, warn};
 // Use log crate for debugging
 use crate::ansi_processor::AnsiProcessor;
 use crate::circular_buffer::CircularBuffer;
+use crate::recording::{recording_path, AsciicastHeader, Recorder};
 use portable_pty::{CommandBuilder, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::Read;
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 pub struct PtyProcess {
     pub muxbox_id: String,
@@ -17,6 +20,7 @@ pub struct PtyProcess {
     pub can_kill: bool, // Indicates if we can kill the process
     pub output_buffer: Arc<Mutex<CircularBuffer>>, // Scrollback buffer for PTY output
     pub stream_id: String, // Unique stream ID for this PTY process
+    pub recorder: Option<Arc<Mutex<Recorder>>>, // Asciicast recording of the output, if any
 }
 
 impl std::fmt::Debug for PtyProcess {
@@ -32,6 +36,13 @@ impl std::fmt::Debug for PtyProcess {
                 &self.output_buffer.lock().unwrap().len(),
             )
             .field("stream_id", &self.stream_id)
+            .field(
+                "recording",
+                &self
+                    .recorder
+                    .as_ref()
+                    .map(|recorder| recorder.lock().unwrap().path().to_path_buf()),
+            )
             .finish()
     }
 }
@@ -46,6 +57,235 @@ pub enum PtyStatus {
     Dead(String),   // PTY process died unexpectedly with reason
 }
 
+/// Signals that can be delivered to a PTY's process group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE", try_from = "String")]
+pub enum PtySignal {
+    Int,
+    Term,
+    Hup,
+    Stop,
+    Cont,
+    Kill,
+}
+
+impl PtySignal {
+    /// Raw signal number for `kill(2)`
+    #[cfg(unix)]
+    pub fn as_raw(self) -> i32 {
+        match self {
+            PtySignal::Int => libc::SIGINT,
+            PtySignal::Term => libc::SIGTERM,
+            PtySignal::Hup => libc::SIGHUP,
+            PtySignal::Stop => libc::SIGSTOP,
+            PtySignal::Cont => libc::SIGCONT,
+            PtySignal::Kill => libc::SIGKILL,
+        }
+    }
+}
+
+impl std::fmt::Display for PtySignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PtySignal::Int => "INT",
+            PtySignal::Term => "TERM",
+            PtySignal::Hup => "HUP",
+            PtySignal::Stop => "STOP",
+            PtySignal::Cont => "CONT",
+            PtySignal::Kill => "KILL",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::str::FromStr for PtySignal {
+    type Err = String;
+
+    /// Accepts `TERM`, `SIGTERM` or `term`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_ascii_uppercase();
+        match upper.strip_prefix("SIG").unwrap_or(&upper) {
+            "INT" => Ok(PtySignal::Int),
+            "TERM" => Ok(PtySignal::Term),
+            "HUP" => Ok(PtySignal::Hup),
+            "STOP" => Ok(PtySignal::Stop),
+            "CONT" => Ok(PtySignal::Cont),
+            "KILL" => Ok(PtySignal::Kill),
+            _ => Err(format!(
+                "Unknown signal '{}', expected one of INT, TERM, HUP, STOP, CONT, KILL",
+                s
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for PtySignal {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// How PTY processes are stopped on kill, restart, tab close and exit: `signal` goes
+/// to the process group, then SIGKILL if anything is still alive after `timeout`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StopSequence {
+    pub signal: PtySignal,
+    pub timeout: Duration,
+}
+
+pub const DEFAULT_STOP_TIMEOUT_MS: u64 = 2000;
+
+impl Default for StopSequence {
+    fn default() -> Self {
+        DEFAULT_STOP_SEQUENCE
+    }
+}
+
+const DEFAULT_STOP_SEQUENCE: StopSequence = StopSequence {
+    signal: PtySignal::Term,
+    timeout: Duration::from_millis(DEFAULT_STOP_TIMEOUT_MS),
+};
+
+// Process-wide so stream sources, which only know a pid, stop processes the same way
+static STOP_SEQUENCE: Mutex<StopSequence> = Mutex::new(DEFAULT_STOP_SEQUENCE);
+
+/// Set the stop sequence used for every PTY process from now on (`app.pty`)
+pub fn set_stop_sequence(sequence: StopSequence) {
+    *STOP_SEQUENCE.lock().unwrap() = sequence;
+}
+
+/// The stop sequence currently in effect
+pub fn stop_sequence() -> StopSequence {
+    *STOP_SEQUENCE.lock().unwrap()
+}
+
+/// How a stop sequence ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopOutcome {
+    /// The process exited after the stop signal
+    Exited,
+    /// The process outlived the timeout and was sent SIGKILL
+    Killed,
+}
+
+/// Deliver `signal` to the process group led by `pid`, falling back to the process itself
+#[cfg(unix)]
+pub fn signal_process_group(pid: u32, signal: PtySignal) -> Result<()> {
+    let pid = pid as libc::pid_t;
+    // portable_pty starts each child in its own session, so its pid is also the group id
+    if unsafe { libc::killpg(pid, signal.as_raw()) } == 0 {
+        return Ok(());
+    }
+    let group_error = std::io::Error::last_os_error();
+    if unsafe { libc::kill(pid, signal.as_raw()) } == 0 {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "Failed to send SIG{} to process {}: {}",
+        signal,
+        pid,
+        group_error
+    ))
+}
+
+#[cfg(not(unix))]
+pub fn signal_process_group(pid: u32, signal: PtySignal) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Sending SIG{} to process {} is not supported on this platform",
+        signal,
+        pid
+    ))
+}
+
+/// Whether the child `pid` has exited. `kill(pid, 0)` still succeeds for a zombie,
+/// so this asks `waitid` with `WNOWAIT`, which sees the exit without reaping it and
+/// leaves the exit status to the reader thread's `wait`. Once the pid is reaped it is
+/// no longer our child (`ECHILD`), even if the pid is reused.
+#[cfg(unix)]
+fn process_exited(pid: u32) -> bool {
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    let result = unsafe {
+        libc::waitid(
+            libc::P_PID,
+            pid as libc::id_t,
+            &mut info,
+            libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+        )
+    };
+    if result != 0 {
+        return std::io::Error::last_os_error().raw_os_error() == Some(libc::ECHILD);
+    }
+    // With WNOHANG a child that is still running leaves si_pid at zero
+    unsafe { info.si_pid() != 0 }
+}
+
+#[cfg(not(unix))]
+fn process_exited(_pid: u32) -> bool {
+    true
+}
+
+/// Exit code recorded for a process ended by `signal`, negative like the old `Finished(-9)`
+#[cfg(unix)]
+fn signal_exit_code(signal: PtySignal) -> i32 {
+    -signal.as_raw()
+}
+
+#[cfg(not(unix))]
+fn signal_exit_code(_signal: PtySignal) -> i32 {
+    -1
+}
+
+/// Send the stop signal, continuing the group too: a stopped process cannot act on
+/// TERM, HUP or INT until it runs again
+fn deliver_stop_signal(pid: u32, signal: PtySignal) -> Result<()> {
+    signal_process_group(pid, signal)?;
+    if signal != PtySignal::Kill {
+        let _ = signal_process_group(pid, PtySignal::Cont);
+    }
+    Ok(())
+}
+
+/// Wait until `exited` reports true, sending SIGKILL to the group once `timeout` passes.
+/// `exited` is checked right before the kill, and a process seen to exit is never
+/// signalled, so the pid can't have been reused by then.
+fn await_exit_or_kill(
+    pid: u32,
+    timeout: Duration,
+    exited: impl Fn() -> bool,
+) -> Result<StopOutcome> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if exited() {
+            return Ok(StopOutcome::Exited);
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        thread::sleep(STOP_POLL_INTERVAL);
+    }
+    signal_process_group(pid, PtySignal::Kill)?;
+    Ok(StopOutcome::Killed)
+}
+
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Stop a PTY process known only by pid with the configured stop sequence. Returns once
+/// the stop signal is delivered; escalation to SIGKILL happens on a background thread.
+pub fn stop_process(pid: u32) -> Result<()> {
+    let sequence = stop_sequence();
+    deliver_stop_signal(pid, sequence.signal)?;
+    if sequence.signal != PtySignal::Kill {
+        thread::spawn(move || {
+            if let Err(e) = await_exit_or_kill(pid, sequence.timeout, || process_exited(pid)) {
+                debug!("Stop sequence for process {} ended early: {}", pid, e);
+            }
+        });
+    }
+    Ok(())
+}
+
 // F0122: PTY Thread Integration - Now thread-safe by creating PTY system on-demand
 unsafe impl Send for PtyManager {}
 unsafe impl Sync for PtyManager {}
@@ -92,6 +332,8 @@ impl PtyManager {
             execute_script.redirect_output.clone(),
             Some(execute_script.stream_id.clone()),
             execute_script.target_bounds.clone(),
+            &execute_script.environment,
+            execute_script.record.as_deref(),
         )
     }
 
@@ -114,6 +356,8 @@ impl PtyManager {
             None,
             stream_id, // Pass provided stream_id
             None,      // No bounds available for direct PTY script calls
+            &crate::model::common::ScriptEnvironment::default(),
+            None,
         )
     }
 
@@ -127,7 +371,9 @@ impl PtyManager {
         thread_uuid: uuid::Uuid,
         redirect_target: Option<String>,
         stream_id: Option<String>, // Custom stream ID to use for all output
-        target_bounds: Option<crate::model::common::Bounds>, // Target muxbox bounds for PTY sizing
+        target_bounds: Option<crate::model::common::Bounds>, // Target muxbox content area for PTY sizing
+        environment: &crate::model::common::ScriptEnvironment, // Working directory, env and shell
+        record: Option<&str>, // Asciicast file to record the PTY's output to
     ) -> Result<()> {
         // SOURCE OBJECT ARCHITECTURE: stream_id must be provided from source object - no fallbacks
         let pty_stream_id = stream_id.expect(
@@ -144,9 +390,9 @@ impl PtyManager {
 
         // Calculate PTY size from target muxbox bounds
         let pty_size = if let Some(bounds) = &target_bounds {
-            // Calculate content area inside borders (subtract 4 for 2-pixel borders on each side)
-            let content_width = bounds.width().saturating_sub(4);
-            let content_height = bounds.height().saturating_sub(4);
+            // The box's content area, already inside whichever borders it draws
+            let content_width = bounds.width();
+            let content_height = bounds.height();
 
             // Use content area dimensions for PTY terminal size
             let cols = content_width.max(20) as u16; // Minimum 20 columns
@@ -210,10 +456,26 @@ impl PtyManager {
             script_content.push_str(&format!("{}\n", command));
         }
 
-        // Create command to run in PTY
-        let mut cmd = CommandBuilder::new("bash");
-        cmd.arg("-c");
-        cmd.arg(&script_content);
+        // Create command to run in PTY under the configured shell, cwd and environment
+        let (program, args) = environment.command_line("bash", &script_content);
+        let mut cmd = CommandBuilder::new(program);
+        cmd.args(args);
+        let working_dir = match environment.working_dir() {
+            Ok(dir) => dir,
+            Err(e) => {
+                self.record_pty_failure(muxbox_id.clone(), e.to_string());
+                return Err(e.into());
+            }
+        };
+        if let Some(dir) = working_dir {
+            cmd.cwd(dir);
+        }
+        if !environment.inherit_env {
+            cmd.env_clear();
+        }
+        for (key, value) in environment.variables() {
+            cmd.env(key, value);
+        }
 
         // Spawn the process
         let mut child = match writer.spawn_command(cmd) {
@@ -236,6 +498,30 @@ impl PtyManager {
         };
         let process_id = child.process_id();
 
+        // A recording that can't be written is logged and the PTY runs without it
+        let recorder = record.and_then(|record| {
+            let path = recording_path(record);
+            let mut header = AsciicastHeader::new(pty_size.cols, pty_size.rows);
+            header.title = Some(muxbox_id.clone());
+            header.command = Some(script_commands.join("\n"));
+            match Recorder::create(&path, &header) {
+                Ok(recorder) => {
+                    log::info!("Recording PTY of {} to {}", muxbox_id, path.display());
+                    Some(Arc::new(Mutex::new(recorder)))
+                }
+                Err(e) => {
+                    log::error!(
+                        "Cannot record PTY of {} to {}: {}",
+                        muxbox_id,
+                        path.display(),
+                        e
+                    );
+                    None
+                }
+            }
+        });
+        let recorder_clone = recorder.clone();
+
         // Store master PTY for resize operations using Arc<Mutex<>> for thread-safe sharing
         let master_pty_handle = Arc::new(Mutex::new(reader));
         let master_pty_clone = master_pty_handle.clone();
@@ -254,6 +540,7 @@ impl PtyManager {
             can_kill: true, // Process can be killed
             output_buffer,
             stream_id: pty_stream_id.clone(),
+            recorder,
         };
 
         // Store in active PTYs
@@ -345,6 +632,7 @@ impl PtyManager {
                                         }
                                     ),
                                     execution_mode: crate::model::common::ExecutionMode::Pty,
+                                    output_format: crate::model::common::OutputFormat::Text,
                                 }),
                             )) {
                                 error!("Failed to send final PTY output: {}", e);
@@ -372,6 +660,7 @@ impl PtyManager {
                                         },
                                     ),
                                     execution_mode: crate::model::common::ExecutionMode::Pty,
+                                    output_format: crate::model::common::OutputFormat::Text,
                                 },
                             ),
                         )) {
@@ -381,6 +670,11 @@ impl PtyManager {
                     }
                     Ok(bytes_read) => {
                         bytes_processed += bytes_read as u64;
+                        if let Some(recorder) = &recorder_clone {
+                            if let Err(e) = recorder.lock().unwrap().output(&buffer[..bytes_read]) {
+                                warn!("Failed to record PTY output of {}: {}", muxbox_id_clone, e);
+                            }
+                        }
                         log::debug!(
                             "Read {} bytes from PTY (total: {})",
                             bytes_read,
@@ -451,6 +745,7 @@ impl PtyManager {
                                         },
                                     ),
                                     execution_mode: crate::model::common::ExecutionMode::Pty,
+                                    output_format: crate::model::common::OutputFormat::Text,
                                 },
                             );
 
@@ -508,6 +803,7 @@ impl PtyManager {
                                         },
                                     ),
                                     execution_mode: crate::model::common::ExecutionMode::Pty,
+                                    output_format: crate::model::common::OutputFormat::Text,
                                 },
                             ),
                         )) {
@@ -723,14 +1019,12 @@ impl PtyManager {
         }
     }
 
-    /// Resize a PTY to match muxbox content area dimensions
+    /// Resize a PTY to match muxbox content area dimensions, already inside whichever
+    /// borders the box draws
     pub fn resize_pty(&mut self, muxbox_id: &str, rows: u16, cols: u16) -> Result<()> {
-        // Subtract border space from dimensions to get content area
-        let content_cols = cols.saturating_sub(4).max(20);
-        let content_rows = rows.saturating_sub(4).max(5);
         debug!(
-            "Resizing PTY for muxbox {} to content area {}x{} (from total {}x{})",
-            muxbox_id, content_cols, content_rows, cols, rows
+            "Resizing PTY for muxbox {} to content area {}x{}",
+            muxbox_id, cols, rows
         );
 
         let active_ptys = self.active_ptys.lock().unwrap();
@@ -738,17 +1032,22 @@ impl PtyManager {
         if let Some(pty_process) = active_ptys.get(muxbox_id) {
             if let Some(master_pty_handle) = &pty_process.master_pty {
                 let pty_size = PtySize {
-                    rows: content_rows,
-                    cols: content_cols,
+                    rows,
+                    cols,
                     pixel_width: 0,
                     pixel_height: 0,
                 };
 
                 match master_pty_handle.lock().unwrap().resize(pty_size) {
                     Ok(_) => {
+                        if let Some(recorder) = &pty_process.recorder {
+                            if let Err(e) = recorder.lock().unwrap().resize(cols, rows) {
+                                warn!("Failed to record PTY resize of {}: {}", muxbox_id, e);
+                            }
+                        }
                         debug!(
                             "PTY successfully resized for muxbox {} to content area {}x{}",
-                            muxbox_id, content_cols, content_rows
+                            muxbox_id, cols, rows
                         );
                     }
                     Err(e) => {
@@ -766,58 +1065,19 @@ impl PtyManager {
         Ok(())
     }
 
-    /// Kill a PTY process
+    /// Kill a PTY process with the configured stop sequence
     pub fn kill_pty(&mut self, muxbox_id: &str) -> Result<()> {
-        let mut active_ptys = self.active_ptys.lock().unwrap();
+        if !self.active_ptys.lock().unwrap().contains_key(muxbox_id) {
+            return Ok(());
+        }
+        debug!("Killing PTY process for muxbox: {}", muxbox_id);
 
-        if let Some(pty_process) = active_ptys.get_mut(muxbox_id) {
-            debug!("Killing PTY process for muxbox: {}", muxbox_id);
-
-            if pty_process.can_kill {
-                if let Some(pid) = pty_process.process_id {
-                    // Use system kill command for cross-platform killing
-                    #[cfg(unix)]
-                    {
-                        use std::process::Command;
-                        match Command::new("kill").arg("-9").arg(pid.to_string()).output() {
-                            Ok(_) => {
-                                debug!(
-                                    "Successfully killed PTY process {} for muxbox: {}",
-                                    pid, muxbox_id
-                                );
-                                pty_process.status = PtyStatus::Finished(-9); // SIGKILL
-                                pty_process.can_kill = false; // Already killed
-                            }
-                            Err(e) => {
-                                warn!(
-                                    "Failed to kill PTY process {} for muxbox {}: {}",
-                                    pid, muxbox_id, e
-                                );
-                                pty_process.status =
-                                    PtyStatus::Error(format!("Kill failed: {}", e));
-                            }
-                        }
-                    }
-                    #[cfg(not(unix))]
-                    {
-                        warn!(
-                            "Process killing not supported on this platform for muxbox: {}",
-                            muxbox_id
-                        );
-                        pty_process.status = PtyStatus::Error("Kill not supported".to_string());
-                    }
-                } else {
-                    warn!("No process ID available for muxbox: {}", muxbox_id);
-                    pty_process.status = PtyStatus::Error("No process ID".to_string());
-                }
-            } else {
-                debug!(
-                    "Process for muxbox {} already killed or cannot be killed",
-                    muxbox_id
-                );
-            }
+        if let Err(e) = self.stop_pty_process(muxbox_id) {
+            debug!("PTY process for muxbox {} not stopped: {}", muxbox_id, e);
+        }
 
-            // Drop the PTY handle to clean up resources
+        // Drop the PTY handle to clean up resources
+        if let Some(pty_process) = self.active_ptys.lock().unwrap().get_mut(muxbox_id) {
             pty_process.master_pty = None;
         }
 
@@ -851,17 +1111,150 @@ impl PtyManager {
         active_ptys.keys().cloned().collect()
     }
 
-    /// Send signal to a PTY process (future enhancement)
-    pub fn send_signal(&mut self, muxbox_id: &str, _signal: i32) -> Result<()> {
-        debug!(
-            "Signal send request for muxbox: {} (not yet implemented)",
+    /// Send a signal to the process group of a running PTY process
+    pub fn send_signal(&self, muxbox_id: &str, signal: PtySignal) -> Result<()> {
+        let pid = {
+            let active_ptys = self.active_ptys.lock().unwrap();
+            let pty_process = active_ptys
+                .get(muxbox_id)
+                .ok_or_else(|| anyhow::anyhow!("PTY not found for muxbox: {}", muxbox_id))?;
+            if !matches!(pty_process.status, PtyStatus::Running | PtyStatus::Starting) {
+                return Err(anyhow::anyhow!(
+                    "PTY process for muxbox {} is not running",
+                    muxbox_id
+                ));
+            }
+            pty_process.process_id.ok_or_else(|| {
+                anyhow::anyhow!("No process ID available for muxbox {}", muxbox_id)
+            })?
+        };
+        signal_process_group(pid, signal)?;
+        log::info!(
+            "Sent SIG{} to PTY process {} for muxbox {}",
+            signal,
+            pid,
             muxbox_id
         );
-        // TODO: Implement signal sending when portable_pty supports it
-        // For now, only kill() is supported through kill_pty()
         Ok(())
     }
 
+    /// Stop a PTY process with the configured stop sequence. Returns once the stop
+    /// signal is delivered; escalation to SIGKILL happens on a background thread.
+    pub fn stop_pty_process(&self, muxbox_id: &str) -> Result<()> {
+        let sequence = stop_sequence();
+        let pid = self.stoppable_pid(muxbox_id)?;
+        deliver_stop_signal(pid, sequence.signal)?;
+        let manager = self.clone();
+        let muxbox_id = muxbox_id.to_string();
+        thread::spawn(move || {
+            if let Err(e) = manager.finish_stop(&muxbox_id, pid, sequence) {
+                warn!("Failed to stop PTY process for muxbox {}: {}", muxbox_id, e);
+            }
+        });
+        Ok(())
+    }
+
+    /// Stop a PTY process with the configured stop sequence and wait until it is gone
+    pub fn stop_pty_process_and_wait(&self, muxbox_id: &str) -> Result<StopOutcome> {
+        self.stop_pty_process_with(muxbox_id, stop_sequence())
+    }
+
+    /// Stop a PTY process with an explicit stop sequence and wait until it is gone
+    pub fn stop_pty_process_with(
+        &self,
+        muxbox_id: &str,
+        sequence: StopSequence,
+    ) -> Result<StopOutcome> {
+        let pid = self.stoppable_pid(muxbox_id)?;
+        deliver_stop_signal(pid, sequence.signal)?;
+        self.finish_stop(muxbox_id, pid, sequence)
+    }
+
+    /// Stop every running PTY process at once and wait for all of them; used on app exit
+    pub fn stop_all(&self) {
+        let sequence = stop_sequence();
+        let muxbox_ids: Vec<String> = self
+            .active_ptys
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, p)| p.can_kill && matches!(p.status, PtyStatus::Running))
+            .map(|(muxbox_id, _)| muxbox_id.clone())
+            .collect();
+
+        let waiters: Vec<_> = muxbox_ids
+            .into_iter()
+            .filter_map(|muxbox_id| {
+                let pid = self.stoppable_pid(&muxbox_id).ok()?;
+                if let Err(e) = deliver_stop_signal(pid, sequence.signal) {
+                    warn!("Failed to stop PTY process for muxbox {}: {}", muxbox_id, e);
+                    return None;
+                }
+                let manager = self.clone();
+                Some(thread::spawn(move || {
+                    manager.finish_stop(&muxbox_id, pid, sequence)
+                }))
+            })
+            .collect();
+        for waiter in waiters {
+            let _ = waiter.join();
+        }
+    }
+
+    fn stoppable_pid(&self, muxbox_id: &str) -> Result<u32> {
+        let active_ptys = self.active_ptys.lock().unwrap();
+        let pty_process = active_ptys
+            .get(muxbox_id)
+            .ok_or_else(|| anyhow::anyhow!("PTY not found for muxbox: {}", muxbox_id))?;
+        let pid = pty_process
+            .process_id
+            .ok_or_else(|| anyhow::anyhow!("No process ID available for muxbox {}", muxbox_id))?;
+        if !pty_process.can_kill {
+            return Err(anyhow::anyhow!(
+                "Process {} for muxbox {} cannot be killed",
+                pid,
+                muxbox_id
+            ));
+        }
+        Ok(pid)
+    }
+
+    /// Wait out the stop sequence after its signal went out, then record how it ended
+    fn finish_stop(
+        &self,
+        muxbox_id: &str,
+        pid: u32,
+        sequence: StopSequence,
+    ) -> Result<StopOutcome> {
+        let outcome = if sequence.signal == PtySignal::Kill {
+            StopOutcome::Killed
+        } else {
+            await_exit_or_kill(pid, sequence.timeout, || {
+                !self.is_process_running(muxbox_id) || process_exited(pid)
+            })?
+        };
+        log::info!(
+            "PTY process {} for muxbox {} stopped: {:?}",
+            pid,
+            muxbox_id,
+            outcome
+        );
+
+        let mut active_ptys = self.active_ptys.lock().unwrap();
+        if let Some(pty_process) = active_ptys.get_mut(muxbox_id) {
+            pty_process.can_kill = false;
+            // The reader thread records the real exit status when it sees EOF first
+            if matches!(pty_process.status, PtyStatus::Running) {
+                let signal = match outcome {
+                    StopOutcome::Exited => sequence.signal,
+                    StopOutcome::Killed => PtySignal::Kill,
+                };
+                pty_process.status = PtyStatus::Finished(signal_exit_code(signal));
+            }
+        }
+        Ok(outcome)
+    }
+
     /// Get process information for a PTY
     pub fn get_process_info(&self, muxbox_id: &str) -> Option<(u32, PtyStatus)> {
         let active_ptys = self.active_ptys.lock().unwrap();
@@ -1147,71 +1540,19 @@ impl PtyManager {
     /// Kill a PTY process via socket command
     /// F0137: Socket PTY Control - Terminate PTY process remotely
     pub fn kill_pty_process(&self, muxbox_id: &str) -> Result<(), anyhow::Error> {
-        let mut active_ptys = self.active_ptys.lock().unwrap();
-        if let Some(pty_process) = active_ptys.get_mut(muxbox_id) {
-            if let Some(pid) = pty_process.process_id {
-                // Use existing kill functionality
-                if pty_process.can_kill {
-                    // Kill the process using system kill command
-                    let kill_result = if cfg!(target_os = "windows") {
-                        std::process::Command::new("taskkill")
-                            .args(["/F", "/PID", &pid.to_string()])
-                            .output()
-                    } else {
-                        std::process::Command::new("kill")
-                            .args(["-9", &pid.to_string()])
-                            .output()
-                    };
-
-                    match kill_result {
-                        Ok(output) => {
-                            if output.status.success() {
-                                pty_process.status = PtyStatus::Finished(-9); // Killed signal
-                                log::info!("Killed PTY process {} for muxbox {}", pid, muxbox_id);
-                                Ok(())
-                            } else {
-                                let error_msg = format!(
-                                    "Kill command failed: {}",
-                                    String::from_utf8_lossy(&output.stderr)
-                                );
-                                pty_process.status = PtyStatus::Error(error_msg.clone());
-                                Err(anyhow::anyhow!(error_msg))
-                            }
-                        }
-                        Err(e) => {
-                            let error_msg = format!("Failed to execute kill command: {}", e);
-                            pty_process.status = PtyStatus::Error(error_msg.clone());
-                            Err(anyhow::anyhow!(error_msg))
-                        }
-                    }
-                } else {
-                    Err(anyhow::anyhow!(
-                        "Process {} for muxbox {} cannot be killed",
-                        pid,
-                        muxbox_id
-                    ))
-                }
-            } else {
-                Err(anyhow::anyhow!(
-                    "No process ID available for muxbox {}",
-                    muxbox_id
-                ))
-            }
-        } else {
-            Err(anyhow::anyhow!("PTY not found for muxbox: {}", muxbox_id))
-        }
+        self.stop_pty_process(muxbox_id)?;
+        log::info!("Stopping PTY process for muxbox {}", muxbox_id);
+        Ok(())
     }
 
     /// Restart a PTY process via socket command
     /// F0137: Socket PTY Control - Restart PTY process after termination
     pub fn restart_pty_process(&self, muxbox_id: &str) -> Result<(), anyhow::Error> {
-        // First, kill the existing process if it's still running
+        // First, stop the existing process if it's still running
         if self.is_process_running(muxbox_id) {
-            if let Err(e) = self.kill_pty_process(muxbox_id) {
-                log::warn!("Failed to kill existing process during restart: {}", e);
+            if let Err(e) = self.stop_pty_process_and_wait(muxbox_id) {
+                log::warn!("Failed to stop existing process during restart: {}", e);
             }
-            // Give the process time to terminate
-            std::thread::sleep(std::time::Duration::from_millis(100));
         }
 
         // Clear the failure tracking for this muxbox to allow PTY retry
@@ -1240,6 +1581,7 @@ impl PtyManager {
             can_kill: false,
             output_buffer: buffer,
             stream_id: format!("pty-test-{}", &uuid::Uuid::new_v4().to_string()[..8]),
+            recorder: None,
         };
 
         self.active_ptys
@@ -1265,6 +1607,7 @@ impl PtyManager {
             can_kill: false,
             output_buffer: buffer,
             stream_id: format!("pty-test-{}", &uuid::Uuid::new_v4().to_string()[..8]),
+            recorder: None,
         };
 
         self.active_ptys
@@ -0,0 +1,387 @@
+//! Asciicast recordings of PTY boxes.
+//!
+//! A box with `record: path` writes the raw output of its PTY process, with timestamps
+//! and terminal resizes, to `path` in [asciicast v2] format while it runs. Each run of
+//! the box's PTY script starts the file afresh.
+//!
+//! `boxmux replay <file>` plays a recording back: [`Player`] feeds its output through an
+//! [`AnsiProcessor`] the size of the recorded terminal, and the `ReplayLoop` thread sends
+//! the processor's screen into a box at the recorded pace, or faster. The same player
+//! replays a recording to the end in one step for deterministic visual tests.
+//!
+//! [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+
+use crate::ansi_processor::AnsiProcessor;
+use crate::model::app::App;
+use crate::model::common::{
+    ExecutionMode, ExecutionPtyStatus, InputBounds, OutputFormat, PtySourceState, SourceState,
+    StreamUpdate,
+};
+use crate::model::layout::Layout;
+use crate::model::muxbox::MuxBox;
+use crate::thread_manager::Runnable;
+use crate::thread_manager::*;
+use crate::AppContext;
+use crate::FieldUpdate;
+use crate::Message;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// ID of the box and stream a replay is shown in
+pub const REPLAY_BOX_ID: &str = "replay";
+
+/// First line of an asciicast v2 file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AsciicastHeader {
+    pub version: u32,
+    pub width: u16,
+    pub height: u16,
+    /// Unix time the recording started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    /// Longest pause a player should keep, in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_time_limit: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+impl AsciicastHeader {
+    pub fn new(width: u16, height: u16) -> Self {
+        AsciicastHeader {
+            version: 2,
+            width,
+            height,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|now| now.as_secs()),
+            idle_time_limit: None,
+            command: None,
+            title: None,
+        }
+    }
+}
+
+/// What happened at one point of a recording
+#[derive(Debug, Clone, PartialEq)]
+pub enum CastEvent {
+    /// Terminal output (`"o"`)
+    Output(String),
+    /// Keyboard input (`"i"`)
+    Input(String),
+    /// The terminal was resized (`"r"`, `COLSxROWS`)
+    Resize(u16, u16),
+    /// A marker (`"m"`) with its label
+    Marker(String),
+}
+
+/// Resolve a `record` path: a leading `~` or `~/` expands to `$HOME`, relative paths
+/// are relative to boxmux's working directory
+pub fn recording_path(record: &str) -> PathBuf {
+    PathBuf::from(crate::utils::expand_home(record))
+}
+
+/// Writes a PTY's output stream to an asciicast v2 file. Every event is flushed as it
+/// happens, so a recording survives boxmux being killed.
+#[derive(Debug)]
+pub struct Recorder {
+    path: PathBuf,
+    file: LineWriter<File>,
+    started: Instant,
+    /// Bytes of a UTF-8 character split across reads
+    pending: Vec<u8>,
+}
+
+impl Recorder {
+    /// Create (or truncate) the file at `path`, with missing parent directories, and
+    /// write the header
+    pub fn create(path: &Path, header: &AsciicastHeader) -> io::Result<Self> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = LineWriter::new(File::create(path)?);
+        writeln!(file, "{}", serde_json::to_string(header)?)?;
+        Ok(Recorder {
+            path: path.to_path_buf(),
+            file,
+            started: Instant::now(),
+            pending: Vec::new(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record bytes read from the PTY
+    pub fn output(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.pending.extend_from_slice(bytes);
+        let text = crate::attach::take_utf8(&mut self.pending);
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.event("o", &text)
+    }
+
+    /// Record the PTY being resized
+    pub fn resize(&mut self, cols: u16, rows: u16) -> io::Result<()> {
+        self.event("r", &format!("{}x{}", cols, rows))
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> io::Result<()> {
+        let elapsed = self.started.elapsed().as_micros() as f64 / 1_000_000.0;
+        writeln!(
+            self.file,
+            "{}",
+            serde_json::to_string(&(elapsed, code, data))?
+        )
+    }
+}
+
+/// A parsed asciicast v2 recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    pub header: AsciicastHeader,
+    /// Events with their time in seconds since the start of the recording
+    pub events: Vec<(f64, CastEvent)>,
+}
+
+impl Recording {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read recording {}: {}", path.display(), e))?;
+        Recording::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parse a recording; errors name the offending line. Events of unknown types are
+    /// skipped, as the format asks of players.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let (index, first) = lines.next().ok_or("empty recording")?;
+        let header: AsciicastHeader = serde_json::from_str(first)
+            .map_err(|e| format!("line {}: invalid header: {}", index + 1, e))?;
+        if header.version != 2 {
+            return Err(format!(
+                "line {}: unsupported asciicast version {}",
+                index + 1,
+                header.version
+            ));
+        }
+
+        let mut events = Vec::new();
+        for (index, line) in lines {
+            let (time, code, data): (f64, String, String) = serde_json::from_str(line)
+                .map_err(|e| format!("line {}: invalid event: {}", index + 1, e))?;
+            let event = match code.as_str() {
+                "o" => CastEvent::Output(data),
+                "i" => CastEvent::Input(data),
+                "m" => CastEvent::Marker(data),
+                "r" => {
+                    let size = data
+                        .split_once('x')
+                        .and_then(|(cols, rows)| Some((cols.parse().ok()?, rows.parse().ok()?)));
+                    let Some((cols, rows)) = size else {
+                        return Err(format!("line {}: invalid resize {:?}", index + 1, data));
+                    };
+                    CastEvent::Resize(cols, rows)
+                }
+                _ => continue,
+            };
+            events.push((time, event));
+        }
+        Ok(Recording { header, events })
+    }
+}
+
+/// Plays a recording through an [`AnsiProcessor`] the size of the recorded terminal
+pub struct Player {
+    /// Events with their playback time, pauses already shortened
+    events: Vec<(Duration, CastEvent)>,
+    next: usize,
+    ansi_processor: AnsiProcessor,
+}
+
+impl Player {
+    /// Play at `speed` times the recorded pace, keeping no pause longer than `max_idle`
+    /// (default: the recording's `idle_time_limit`)
+    pub fn new(recording: Recording, speed: f64, max_idle: Option<f64>) -> Self {
+        let speed = if speed > 0.0 { speed } else { 1.0 };
+        let max_idle = max_idle.or(recording.header.idle_time_limit);
+
+        let mut events = Vec::with_capacity(recording.events.len());
+        let (mut recorded, mut played) = (0.0_f64, 0.0_f64);
+        for (time, event) in recording.events {
+            let mut pause = (time - recorded).max(0.0);
+            if let Some(max_idle) = max_idle {
+                pause = pause.min(max_idle);
+            }
+            recorded = recorded.max(time);
+            played += pause / speed;
+            events.push((Duration::from_secs_f64(played), event));
+        }
+
+        let mut ansi_processor = AnsiProcessor::with_screen_size(
+            recording.header.width as usize,
+            recording.header.height as usize,
+        );
+        // Line mode at first, as for live PTY output
+        ansi_processor.set_screen_mode(false);
+        Player {
+            events,
+            next: 0,
+            ansi_processor,
+        }
+    }
+
+    /// Apply every event due `elapsed` into playback. Returns the box content to show
+    /// when the screen changed.
+    pub fn advance(&mut self, elapsed: Duration) -> Option<String> {
+        let mut changed = false;
+        while let Some((time, event)) = self.events.get(self.next) {
+            if *time > elapsed {
+                break;
+            }
+            match event {
+                CastEvent::Output(text) => {
+                    self.ansi_processor.process_string(text);
+                    changed = true;
+                }
+                CastEvent::Resize(cols, rows) => {
+                    self.ansi_processor
+                        .resize_screen(*cols as usize, *rows as usize);
+                    changed = true;
+                }
+                CastEvent::Input(_) | CastEvent::Marker(_) => {}
+            }
+            self.next += 1;
+        }
+        changed.then(|| self.ansi_processor.get_screen_content_for_stream())
+    }
+
+    /// Apply every remaining event at once
+    pub fn finish(&mut self) -> Option<String> {
+        self.advance(Duration::MAX)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+
+    /// Playback time of the last event
+    pub fn duration(&self) -> Duration {
+        self.events
+            .last()
+            .map(|(time, _)| *time)
+            .unwrap_or_default()
+    }
+
+    pub fn ansi_processor(&self) -> &AnsiProcessor {
+        &self.ansi_processor
+    }
+}
+
+/// The app `boxmux replay` shows: one full-screen box titled with the recording's name
+pub fn replay_app(path: &Path, recording: &Recording) -> App {
+    let name = recording
+        .header
+        .title
+        .clone()
+        .or_else(|| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+    let muxbox = MuxBox {
+        id: REPLAY_BOX_ID.to_string(),
+        title: Some(format!("Replay: {}", name)),
+        position: InputBounds {
+            x1: "0%".to_string(),
+            y1: "0%".to_string(),
+            x2: "100%".to_string(),
+            y2: "100%".to_string(),
+        },
+        border_color: Some("bright_black".to_string()),
+        auto_scroll_bottom: Some(true),
+        ..Default::default()
+    };
+    let mut layout = Layout::new();
+    layout.id = REPLAY_BOX_ID.to_string();
+    layout.root = Some(true);
+    layout.children = Some(vec![muxbox]);
+
+    let mut app = App::new();
+    app.layouts = vec![layout];
+    app.validate();
+    app
+}
+
+/// Box content update carrying the player's screen
+fn replay_update(content: String, status: ExecutionPtyStatus) -> Message {
+    Message::StreamUpdateMessage(StreamUpdate {
+        stream_id: REPLAY_BOX_ID.to_string(),
+        target_box_id: REPLAY_BOX_ID.to_string(),
+        content_update: format!("REPLACE:{}", content),
+        source_state: SourceState::Pty(PtySourceState {
+            process_id: 0,
+            runtime: Duration::from_millis(0),
+            exit_code: None,
+            status,
+        }),
+        execution_mode: ExecutionMode::Pty,
+        output_format: OutputFormat::Text,
+    })
+}
+
+/// Player for `boxmux replay` and the moment its playback started
+type ActivePlayer = (Player, Option<Instant>);
+
+static PLAYER: once_cell::sync::Lazy<Mutex<Option<ActivePlayer>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(None));
+
+/// Hand the `ReplayLoop` thread the player to run
+pub fn set_player(player: Player) {
+    *PLAYER.lock().unwrap() = Some((player, None));
+}
+
+create_runnable!(
+    ReplayLoop,
+    |_inner: &mut RunnableImpl, _app_context: AppContext, _messages: Vec<Message>| -> bool { true },
+    |inner: &mut RunnableImpl,
+     app_context: AppContext,
+     _messages: Vec<Message>|
+     -> (bool, AppContext) {
+        let mut player = PLAYER.lock().unwrap();
+        let Some((player, started)) = player.as_mut() else {
+            return (false, app_context);
+        };
+        if !player.is_finished() {
+            let started = *started.get_or_insert_with(Instant::now);
+            if let Some(content) = player.advance(started.elapsed()) {
+                let status = if player.is_finished() {
+                    ExecutionPtyStatus::Completed
+                } else {
+                    ExecutionPtyStatus::Running
+                };
+                inner.send_message(replay_update(content, status));
+            }
+        }
+
+        std::thread::sleep(Duration::from_millis(app_context.config.frame_delay));
+
+        (true, app_context)
+    }
+);
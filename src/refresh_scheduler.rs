@@ -0,0 +1,183 @@
+//! Overlap policy and failure backoff for periodic refresh scripts.
+//!
+//! The refresh loops fire an `ExecuteScript` every `refresh_interval`; the thread manager
+//! asks [`admit`] whether a run may start while an earlier one for the same stream is
+//! still in flight, and reports each run's end to [`finish`], which hands back a queued
+//! run if there is one. [`next_delay`] stretches the interval after consecutive failures
+//! and adds jitter. Counters are kept per stream and exposed through [`stats`]; they are
+//! dropped when their box goes away ([`forget_box`], [`forget_removed_boxes`]).
+
+use crate::model::app::App;
+use crate::model::common::{ExecuteScript, OverlapPolicy};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Counters for one periodic source, reported by `ListExecutionSources`
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct RefreshStats {
+    pub overlap_policy: OverlapPolicy,
+    /// Runs currently in flight
+    pub running: usize,
+    /// Whether a run is waiting for the current one to finish
+    pub queued: bool,
+    /// Runs dropped because an earlier one was still in flight
+    pub skipped: u64,
+    /// Times a run was due while an earlier one was still in flight
+    pub overruns: u64,
+    /// Failed runs since the last success; drives the backoff
+    pub consecutive_failures: u32,
+}
+
+/// How a periodic run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    Succeeded,
+    Failed,
+    /// Killed by `cancel-previous` or a Kill source action; leaves the failure count alone
+    Cancelled,
+}
+
+/// What the thread manager should do with a due periodic run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    Run,
+    Skip,
+    Queued,
+}
+
+#[derive(Default)]
+struct RefreshState {
+    box_id: String,
+    stats: RefreshStats,
+    queued: Option<ExecuteScript>,
+}
+
+static REFRESH_STATES: once_cell::sync::Lazy<Mutex<HashMap<String, RefreshState>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Decide whether `execute_script` may start now under `policy`. With `cancel-previous`
+/// the in-flight runs are killed before returning [`Admission::Run`].
+pub fn admit(execute_script: &ExecuteScript, policy: OverlapPolicy) -> Admission {
+    let mut states = REFRESH_STATES.lock().unwrap();
+    let state = states.entry(execute_script.stream_id.clone()).or_default();
+    state.box_id.clone_from(&execute_script.target_box_id);
+    state.stats.overlap_policy = policy;
+
+    if state.stats.running > 0 {
+        state.stats.overruns += 1;
+        match policy {
+            OverlapPolicy::Skip => {
+                state.stats.skipped += 1;
+                return Admission::Skip;
+            }
+            OverlapPolicy::QueueOne => {
+                if state.queued.replace(execute_script.clone()).is_some() {
+                    state.stats.skipped += 1;
+                }
+                state.stats.queued = true;
+                return Admission::Queued;
+            }
+            OverlapPolicy::CancelPrevious => {
+                crate::utils::cancel_scripts(&execute_script.stream_id);
+            }
+            OverlapPolicy::Allow => {}
+        }
+    }
+
+    state.stats.running += 1;
+    Admission::Run
+}
+
+/// Record the end of a run admitted for `stream_id`. Returns the queued run, which the
+/// caller starts right away; it already counts as running.
+pub fn finish(stream_id: &str, outcome: RunOutcome) -> Option<ExecuteScript> {
+    let mut states = REFRESH_STATES.lock().unwrap();
+    let state = states.get_mut(stream_id)?;
+    state.stats.running = state.stats.running.saturating_sub(1);
+    match outcome {
+        RunOutcome::Succeeded => state.stats.consecutive_failures = 0,
+        RunOutcome::Failed => state.stats.consecutive_failures += 1,
+        RunOutcome::Cancelled => {}
+    }
+
+    if state.stats.running > 0 {
+        return None;
+    }
+    let next = state.queued.take()?;
+    state.stats.queued = false;
+    state.stats.running += 1;
+    Some(next)
+}
+
+/// [`admit`] under the policy of the script's box; scripts that aren't periodic refreshes
+/// always run
+pub fn admit_script(app: &App, execute_script: &ExecuteScript) -> Admission {
+    if !execute_script.source.source_type.is_periodic() {
+        return Admission::Run;
+    }
+    let policy = app
+        .get_muxbox_by_id(&execute_script.target_box_id)
+        .and_then(|muxbox| muxbox.overlap_policy)
+        .unwrap_or_default();
+    admit(execute_script, policy)
+}
+
+/// [`finish`] for periodic refreshes; other scripts were never admitted and get `None`
+pub fn finish_script(execute_script: &ExecuteScript, outcome: RunOutcome) -> Option<ExecuteScript> {
+    if !execute_script.source.source_type.is_periodic() {
+        return None;
+    }
+    finish(&execute_script.stream_id, outcome)
+}
+
+/// Counters for `stream_id`, if a periodic run was ever admitted for it
+pub fn stats(stream_id: &str) -> Option<RefreshStats> {
+    REFRESH_STATES
+        .lock()
+        .unwrap()
+        .get(stream_id)
+        .map(|state| state.stats.clone())
+}
+
+/// Drop the counters and any queued run of every stream refreshing `box_id`
+pub fn forget_box(box_id: &str) {
+    REFRESH_STATES
+        .lock()
+        .unwrap()
+        .retain(|_, state| state.box_id != box_id);
+}
+
+/// [`forget_box`] for each box that `old` has and `new` doesn't, e.g. after `RemoveBox`
+/// or a YAML reload
+pub fn forget_removed_boxes(old: &App, new: &App) {
+    REFRESH_STATES.lock().unwrap().retain(|_, state| {
+        new.get_muxbox_by_id(&state.box_id).is_some()
+            || old.get_muxbox_by_id(&state.box_id).is_none()
+    });
+}
+
+/// Delay before the next run: `interval_ms`, doubled for each consecutive failure up to
+/// `backoff_max_ms` (no backoff without it), plus a random `0..=jitter_ms`
+pub fn next_delay(
+    stream_id: &str,
+    interval_ms: u64,
+    jitter_ms: Option<u64>,
+    backoff_max_ms: Option<u64>,
+) -> Duration {
+    let failures = stats(stream_id)
+        .map(|stats| stats.consecutive_failures)
+        .unwrap_or(0);
+    let mut delay = interval_ms;
+    if let Some(max) = backoff_max_ms {
+        if failures > 0 {
+            let factor = 1u64.checked_shl(failures.min(32)).unwrap_or(u64::MAX);
+            delay = interval_ms.saturating_mul(factor).min(max.max(interval_ms));
+        }
+    }
+    if let Some(jitter) = jitter_ms.filter(|jitter| *jitter > 0) {
+        delay += (uuid::Uuid::new_v4().as_u128() % (jitter as u128 + 1)) as u64;
+    }
+    Duration::from_millis(delay)
+}
@@ -0,0 +1,715 @@
+//! Cron, wall-clock and event-driven schedules for box and choice scripts.
+//!
+//! [`Scheduler`] turns the `schedule` keys of an app into [`ScheduleFiring`]s: time-based
+//! triggers are checked on every [`Scheduler::tick`], layout switches and finished streams
+//! arrive as messages through [`Scheduler::observe`]. The `ScheduleLoop` thread turns each
+//! firing into an `ExecuteScript` with source type `ScheduledScript`.
+
+use crate::model::app::App;
+use crate::model::common::{
+    ExecuteScript, ExecutionMode, ExecutionSource, ExecutionSourceType, Schedule, SourceReference,
+    SourceType,
+};
+use crate::socket_protocol::{socket_events_for_message, SocketEvent};
+use crate::thread_manager::Runnable;
+use crate::thread_manager::*;
+use crate::AppContext;
+use crate::FieldUpdate;
+use crate::Message;
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Timelike};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+/// How often `on_file_change` globs are rescanned
+const FILE_POLL_INTERVAL_MS: i64 = 1000;
+/// Upper bound on files tracked per `on_file_change` glob
+const MAX_WATCHED_FILES: usize = 10_000;
+
+const DAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+const MONTH_NAMES: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Parsed five-field cron expression; one bit per allowed value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronExpr {
+    /// Parse `minute hour day-of-month month day-of-week` with `*`, lists, ranges, steps and
+    /// three-letter month and day names, or one of `@hourly`, `@daily`, `@weekly`,
+    /// `@monthly` and `@yearly`
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expanded = match expr.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "expected 5 fields (minute hour day-of-month month day-of-week), got {}",
+                fields.len()
+            ));
+        }
+
+        // Both 0 and 7 mean Sunday
+        let days_of_week = parse_cron_field(fields[4], 0, 7, DAY_NAMES)?;
+        Ok(CronExpr {
+            minutes: parse_cron_field(fields[0], 0, 59, &[])?,
+            hours: parse_cron_field(fields[1], 0, 23, &[])?,
+            days_of_month: parse_cron_field(fields[2], 1, 31, &[])?,
+            months: parse_cron_field(fields[3], 1, 12, MONTH_NAMES)?,
+            days_of_week: (days_of_week | (days_of_week >> 7)) & 0x7f,
+            any_day_of_month: fields[2] == "*",
+            any_day_of_week: fields[4] == "*",
+        })
+    }
+
+    /// Whether the minute containing `time` matches. As in cron, a restricted day of
+    /// month and day of week match when either does.
+    pub fn matches(&self, time: &NaiveDateTime) -> bool {
+        let has = |mask: u64, value: u32| mask & (1 << value) != 0;
+        let day_of_month = has(self.days_of_month, time.day());
+        let day_of_week = has(self.days_of_week, time.weekday().num_days_from_sunday());
+        let day = match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (true, false) => day_of_week,
+            (false, true) => day_of_month,
+            (false, false) => day_of_month || day_of_week,
+        };
+        has(self.minutes, time.minute())
+            && has(self.hours, time.hour())
+            && has(self.months, time.month())
+            && day
+    }
+}
+
+fn parse_cron_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        if let Some(index) = names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(text))
+        {
+            return Ok(min + index as u32);
+        }
+        text.parse::<u32>()
+            .map_err(|_| format!("invalid value '{}' in '{}'", text, field))
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<usize>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("invalid step '{}' in '{}'", step, field)),
+            },
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            let start = value(range)?;
+            // `5/15` means every 15 starting at 5
+            (start, if step > 1 { max } else { start })
+        };
+        if start < min || end > max || start > end {
+            return Err(format!(
+                "'{}' is outside {}-{} in '{}'",
+                range, min, max, field
+            ));
+        }
+        for v in (start..=end).step_by(step) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+/// Parse an `at` time, `HH:MM` or `HH:MM:SS`
+pub fn parse_daily_time(at: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(at.trim(), "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(at.trim(), "%H:%M"))
+        .map_err(|_| format!("expected HH:MM or HH:MM:SS, got '{}'", at))
+}
+
+/// Files matching a glob and their modification times, rescanned on demand
+#[derive(Debug)]
+pub struct FileWatch {
+    root: PathBuf,
+    pattern: Regex,
+    /// Directory levels below `root` to scan; `None` with `**`
+    depth: Option<usize>,
+    snapshot: BTreeMap<PathBuf, SystemTime>,
+}
+
+impl FileWatch {
+    /// Watch `glob` (`*`, `?`, `[...]` and `**`); relative globs resolve against `base`
+    pub fn new(glob: &str, base: Option<&Path>) -> Result<Self, String> {
+        let expanded = crate::utils::expand_home(glob);
+        if expanded.trim().is_empty() {
+            return Err("empty glob".to_string());
+        }
+
+        let is_wild = |component: &str| component.contains(['*', '?', '[']);
+        let components: Vec<&str> = expanded.split('/').collect();
+        let mut literal = components
+            .iter()
+            .take_while(|component| !is_wild(component))
+            .count();
+        if literal == components.len() {
+            // A plain path: watch its parent for that one name
+            literal -= 1;
+        }
+
+        let mut root = PathBuf::from(if expanded.starts_with('/') { "/" } else { "" });
+        for component in &components[..literal] {
+            root.push(component);
+        }
+        if root.is_relative() {
+            root = base
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(root);
+        }
+
+        let rest = &components[literal..];
+        let pattern = Regex::new(&glob_to_regex(&rest.join("/")))
+            .map_err(|err| format!("invalid glob '{}': {}", glob, err))?;
+        let depth = if rest.iter().any(|component| component.contains("**")) {
+            None
+        } else {
+            Some(rest.len())
+        };
+
+        let mut watch = FileWatch {
+            root,
+            pattern,
+            depth,
+            snapshot: BTreeMap::new(),
+        };
+        watch.snapshot = watch.scan();
+        Ok(watch)
+    }
+
+    /// Rescan; true if a matching file appeared, changed or disappeared since the last call
+    pub fn changed(&mut self) -> bool {
+        let snapshot = self.scan();
+        let changed = snapshot != self.snapshot;
+        self.snapshot = snapshot;
+        changed
+    }
+
+    fn scan(&self) -> BTreeMap<PathBuf, SystemTime> {
+        let mut files = BTreeMap::new();
+        let mut pending = vec![(self.root.clone(), 0usize)];
+        while let Some((dir, level)) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    if self.depth.is_none_or(|depth| level + 1 < depth) {
+                        pending.push((path, level + 1));
+                    }
+                    continue;
+                }
+                let Ok(relative) = path.strip_prefix(&self.root) else {
+                    continue;
+                };
+                if self.pattern.is_match(&relative.to_string_lossy()) {
+                    files.insert(path, metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH));
+                    if files.len() >= MAX_WATCHED_FILES {
+                        return files;
+                    }
+                }
+            }
+        }
+        files
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                regex.push(']');
+            }
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+/// Whether any box or choice in the app has a schedule
+pub fn has_schedules(app: &App) -> bool {
+    app.layouts.iter().any(|layout| {
+        layout.get_all_muxboxes().iter().any(|muxbox| {
+            muxbox.schedule.is_some()
+                || muxbox
+                    .choices
+                    .iter()
+                    .flatten()
+                    .any(|c| c.schedule.is_some())
+        })
+    })
+}
+
+/// A scheduled script that is due
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleFiring {
+    pub box_id: String,
+    pub choice_id: Option<String>,
+    /// What fired, e.g. `cron */5 * * * *` or `after box build`
+    pub trigger: String,
+}
+
+#[derive(Debug)]
+struct ScheduledJob {
+    box_id: String,
+    choice_id: Option<String>,
+    layout_id: String,
+    cron: Option<(String, CronExpr)>,
+    at: Option<(String, NaiveTime)>,
+    on_layout_activated: bool,
+    file_watch: Option<(String, FileWatch)>,
+    after_box: Option<String>,
+}
+
+impl ScheduledJob {
+    fn new(
+        app: &App,
+        layout_id: &str,
+        box_id: &str,
+        choice_id: Option<&str>,
+        schedule: &Schedule,
+    ) -> Result<Self, String> {
+        let cron = match &schedule.cron {
+            Some(cron) => Some((cron.clone(), CronExpr::parse(cron)?)),
+            None => None,
+        };
+        let at = match &schedule.at {
+            Some(at) => Some((at.clone(), parse_daily_time(at)?)),
+            None => None,
+        };
+        let file_watch = match &schedule.on_file_change {
+            Some(glob) => {
+                let base = app
+                    .script_environment(box_id, choice_id)
+                    .working_dir()
+                    .map_err(|err| err.to_string())?;
+                Some((glob.clone(), FileWatch::new(glob, base.as_deref())?))
+            }
+            None => None,
+        };
+        Ok(ScheduledJob {
+            box_id: box_id.to_string(),
+            choice_id: choice_id.map(str::to_string),
+            layout_id: layout_id.to_string(),
+            cron,
+            at,
+            on_layout_activated: schedule.on_layout_activated,
+            file_watch,
+            after_box: schedule.after_box.clone(),
+        })
+    }
+
+    fn firing(&self, trigger: String) -> ScheduleFiring {
+        ScheduleFiring {
+            box_id: self.box_id.clone(),
+            choice_id: self.choice_id.clone(),
+            trigger,
+        }
+    }
+}
+
+/// Jobs for the schedules of every box and choice that has a script
+fn collect_jobs(app: &App) -> Vec<ScheduledJob> {
+    let mut jobs = Vec::new();
+    for layout in &app.layouts {
+        for muxbox in layout.get_all_muxboxes() {
+            let mut schedules = Vec::new();
+            if let (Some(schedule), Some(_)) = (&muxbox.schedule, &muxbox.script) {
+                schedules.push((None, schedule));
+            }
+            for choice in muxbox.choices.iter().flatten() {
+                if let (Some(schedule), Some(_)) = (&choice.schedule, &choice.script) {
+                    schedules.push((Some(choice.id.as_str()), schedule));
+                }
+            }
+            for (choice_id, schedule) in schedules {
+                match ScheduledJob::new(app, &layout.id, &muxbox.id, choice_id, schedule) {
+                    Ok(job) => jobs.push(job),
+                    Err(err) => log::error!(
+                        "Ignoring schedule of box {} choice {:?}: {}",
+                        muxbox.id,
+                        choice_id,
+                        err
+                    ),
+                }
+            }
+        }
+    }
+    jobs
+}
+
+/// Hash of everything `collect_jobs` reads, to notice boxes, scripts or schedules that
+/// were added, removed or edited while the app runs
+fn schedule_signature(app: &App) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for layout in &app.layouts {
+        layout.id.hash(&mut hasher);
+        for muxbox in layout.get_all_muxboxes() {
+            let script = muxbox.script.is_some();
+            (&muxbox.id, &muxbox.schedule, script, &muxbox.cwd).hash(&mut hasher);
+            for choice in muxbox.choices.iter().flatten() {
+                let script = choice.script.is_some();
+                (&choice.id, &choice.schedule, script, &choice.cwd).hash(&mut hasher);
+            }
+        }
+    }
+    hasher.finish()
+}
+
+/// Schedule state of one app: which jobs exist and when time-based triggers last ran
+#[derive(Debug)]
+pub struct Scheduler {
+    jobs: Vec<ScheduledJob>,
+    /// `schedule_signature` of the app the jobs were collected from
+    signature: u64,
+    last_tick: NaiveDateTime,
+    last_file_poll: NaiveDateTime,
+    pending: Vec<ScheduleFiring>,
+}
+
+impl Scheduler {
+    /// Collect the schedules of every box and choice that has a script. Jobs for the
+    /// active layout's `on_layout_activated` triggers are returned by the first `tick`.
+    pub fn new(app: &App, now: NaiveDateTime) -> Self {
+        let mut scheduler = Scheduler {
+            jobs: collect_jobs(app),
+            signature: schedule_signature(app),
+            last_tick: now,
+            last_file_poll: now,
+            pending: Vec::new(),
+        };
+        if let Some(layout) = app.get_active_layout() {
+            scheduler.pending = scheduler.layout_activated(&layout.id);
+        }
+        scheduler
+    }
+
+    /// Collect the jobs again when boxes, scripts or schedules changed since the last
+    /// call, e.g. after `AddBox` or a YAML reload. Tick times are kept, so triggers
+    /// that already ran don't run again. Returns whether the jobs were replaced.
+    pub fn update(&mut self, app: &App) -> bool {
+        let signature = schedule_signature(app);
+        if signature == self.signature {
+            return false;
+        }
+        self.jobs = collect_jobs(app);
+        self.signature = signature;
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Firings caused by a message: a layout switch or a finished stream
+    pub fn observe(&mut self, message: &Message) -> Vec<ScheduleFiring> {
+        let mut firings = Vec::new();
+        for event in socket_events_for_message(message) {
+            match event {
+                SocketEvent::LayoutSwitched { layout_id } => {
+                    firings.extend(self.layout_activated(&layout_id));
+                }
+                SocketEvent::StreamFinished { box_id, .. } => {
+                    firings.extend(
+                        self.jobs
+                            .iter()
+                            .filter(|job| job.after_box.as_deref() == Some(box_id.as_str()))
+                            .map(|job| job.firing(format!("after box {}", box_id))),
+                    );
+                }
+                _ => {}
+            }
+        }
+        firings
+    }
+
+    /// Firings whose cron minute or `at` time passed since the last tick, and those whose
+    /// watched files changed
+    pub fn tick(&mut self, now: NaiveDateTime) -> Vec<ScheduleFiring> {
+        let mut firings = std::mem::take(&mut self.pending);
+        let last_tick = self.last_tick;
+        if now <= last_tick {
+            return firings;
+        }
+        self.last_tick = now;
+
+        let poll_files = (now - self.last_file_poll).num_milliseconds() >= FILE_POLL_INTERVAL_MS;
+        if poll_files {
+            self.last_file_poll = now;
+        }
+
+        for job in &mut self.jobs {
+            if let Some((expr, cron)) = &job.cron {
+                if cron_due(cron, last_tick, now) {
+                    firings.push(job.firing(format!("cron {}", expr)));
+                }
+            }
+            if let Some((text, at)) = &job.at {
+                let mut candidate = now.date().and_time(*at);
+                if candidate > now {
+                    candidate -= chrono::Duration::days(1);
+                }
+                if candidate > last_tick {
+                    firings.push(job.firing(format!("at {}", text)));
+                }
+            }
+            if poll_files {
+                let mut changed = None;
+                if let Some((glob, watch)) = &mut job.file_watch {
+                    if watch.changed() {
+                        changed = Some(glob.clone());
+                    }
+                }
+                if let Some(glob) = changed {
+                    firings.push(job.firing(format!("file changed {}", glob)));
+                }
+            }
+        }
+        firings
+    }
+
+    fn layout_activated(&self, layout_id: &str) -> Vec<ScheduleFiring> {
+        self.jobs
+            .iter()
+            .filter(|job| job.on_layout_activated && job.layout_id == layout_id)
+            .map(|job| job.firing(format!("layout {} activated", layout_id)))
+            .collect()
+    }
+}
+
+/// Whether a cron minute starts in `(last, now]`; looks back at most one day
+fn cron_due(cron: &CronExpr, last: NaiveDateTime, now: NaiveDateTime) -> bool {
+    let start_of_minute = |time: NaiveDateTime| {
+        time.with_second(0)
+            .and_then(|time| time.with_nanosecond(0))
+            .unwrap_or(time)
+    };
+    let earliest = last.max(now - chrono::Duration::days(1));
+    let mut minute = start_of_minute(earliest);
+    if minute <= earliest {
+        minute += chrono::Duration::minutes(1);
+    }
+    while minute <= now {
+        if cron.matches(&minute) {
+            return true;
+        }
+        minute += chrono::Duration::minutes(1);
+    }
+    false
+}
+
+/// The `ExecuteScript` for a firing, registering a stable `ScheduledScript` source per box
+/// or choice. `None` if the box, choice or script no longer exists.
+pub fn scheduled_script(app: &mut App, firing: &ScheduleFiring) -> Option<ExecuteScript> {
+    let muxbox = app.get_muxbox_by_id(&firing.box_id)?;
+    let (script, execution_mode, redirect_output, append_output) = match &firing.choice_id {
+        Some(choice_id) => {
+            let choice = muxbox
+                .choices
+                .iter()
+                .flatten()
+                .find(|choice| &choice.id == choice_id)?;
+            (
+                choice.script.clone()?,
+                choice.execution_mode.clone(),
+                choice.redirect_output.clone(),
+                choice.append_output.unwrap_or(false),
+            )
+        }
+        None => (
+            muxbox.script.clone()?,
+            muxbox.execution_mode.clone(),
+            muxbox.redirect_output.clone(),
+            muxbox.append_output.unwrap_or(false),
+        ),
+    };
+    let target_bounds = muxbox.content_bounds();
+    let libs = app.libs.clone().unwrap_or_default();
+    let environment = app.script_environment(&firing.box_id, firing.choice_id.as_deref());
+    let timeout_ms = app.script_timeout_ms(&firing.box_id, firing.choice_id.as_deref());
+
+    let stream_id = app.register_execution_source(
+        ExecutionSourceType::ScheduledScript {
+            choice_id: firing.choice_id.clone(),
+            script: script.clone(),
+        },
+        firing.box_id.clone(),
+    );
+
+    Some(ExecuteScript {
+        script,
+        source: ExecutionSource {
+            source_type: SourceType::ScheduledScript,
+            source_id: format!(
+                "schedule-{}",
+                firing.choice_id.as_deref().unwrap_or(&firing.box_id)
+            ),
+            source_reference: SourceReference::Schedule(firing.trigger.clone()),
+        },
+        execution_mode,
+        target_box_id: firing.box_id.clone(),
+        libs,
+        redirect_output,
+        append_output,
+        stream_id,
+        target_bounds: Some(target_bounds),
+        environment,
+        timeout_ms,
+        output_format: app.script_output_format(&firing.box_id, firing.choice_id.as_deref()),
+        record: app.script_record(&firing.box_id),
+    })
+}
+
+/// Hand a due script to the thread manager, or to the PTY manager for PTY boxes
+fn run_firing(inner: &mut RunnableImpl, app_context: &mut AppContext, firing: &ScheduleFiring) {
+    let Some(execute_script) = scheduled_script(&mut app_context.app, firing) else {
+        return;
+    };
+    log::info!(
+        "Schedule '{}' running script of box {} (mode: {:?})",
+        firing.trigger,
+        firing.box_id,
+        execute_script.execution_mode
+    );
+
+    match execute_script.execution_mode {
+        ExecutionMode::Immediate | ExecutionMode::Thread => {
+            inner.send_message(Message::ExecuteScriptMessage(execute_script));
+        }
+        ExecutionMode::Pty => {
+            // PTY scripts go to the PTY manager, never to the ThreadManager
+            match (&app_context.pty_manager, inner.get_message_sender()) {
+                (Some(pty_manager), Some(sender)) => {
+                    if let Err(err) = pty_manager.handle_execute_script(
+                        &execute_script,
+                        sender.clone(),
+                        Uuid::new_v4(),
+                    ) {
+                        log::error!(
+                            "Scheduled PTY script for box {} failed: {}",
+                            firing.box_id,
+                            err
+                        );
+                    }
+                }
+                _ => log::error!(
+                    "Scheduled PTY script for box {} needs a PTY manager",
+                    firing.box_id
+                ),
+            }
+        }
+    }
+}
+
+create_runnable!(
+    ScheduleLoop,
+    |_inner: &mut RunnableImpl, _app_context: AppContext, _messages: Vec<Message>| -> bool { true },
+    |inner: &mut RunnableImpl,
+     app_context: AppContext,
+     messages: Vec<Message>|
+     -> (bool, AppContext) {
+        // The scheduler lives as long as this loop and follows the app as it changes
+        let mut scheduler = Scheduler::new(&app_context.app, chrono::Local::now().naive_local());
+        let mut pending = messages;
+        loop {
+            if pending
+                .iter()
+                .any(|message| matches!(message, Message::Exit | Message::Terminate))
+            {
+                return (false, app_context);
+            }
+
+            let original_app_context = inner.app_context.clone();
+            let mut app_context_unwrapped = original_app_context.clone();
+            if scheduler.update(&app_context_unwrapped.app) {
+                log::info!("Boxes or schedules changed, schedules reloaded");
+            }
+
+            let now = chrono::Local::now().naive_local();
+            let mut firings = Vec::new();
+            for message in &pending {
+                firings.extend(scheduler.observe(message));
+            }
+            firings.extend(scheduler.tick(now));
+            for firing in &firings {
+                run_firing(inner, &mut app_context_unwrapped, firing);
+            }
+
+            // Execution sources registered for the firings reach the other threads
+            if app_context_unwrapped != original_app_context {
+                inner.app_context = app_context_unwrapped;
+                inner.send_app_context_update(original_app_context);
+            }
+
+            std::thread::sleep(Duration::from_millis(inner.app_context.config.frame_delay));
+
+            let (updated_context, new_messages) = inner.receive_updates();
+            inner.app_context = updated_context;
+            pending = new_messages;
+        }
+    }
+);
@@ -1,127 +1,1070 @@
+use crate::attach::AttachFrame;
+use crate::model::app::RemoteControlConfig;
 use crate::model::common::{run_socket_function, SocketFunction};
+use crate::socket_protocol::{
+    execute_socket_function, invalid_json_response, parse_socket_message, parse_socket_value,
+    socket_events_for_message, IncomingSocketMessage, SocketError, SocketErrorCode, SocketEvent,
+    SocketEventLine, SocketResponse, SocketResult, SocketSubscription, LEGACY_SUCCESS_RESPONSE,
+};
 use crate::thread_manager::Runnable;
 use crate::{AppContext, FieldUpdate};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{Read, Write};
-use std::os::unix::net::UnixListener;
-use std::sync::mpsc;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use tungstenite::Message as WsMessage;
 
 use crate::thread_manager::*;
 
 use uuid::Uuid;
 
-create_runnable!(
-    SocketLoop,
-    |_inner: &mut RunnableImpl, _app_context: AppContext, _messages: Vec<Message>| -> bool { true },
-    |inner: &mut RunnableImpl,
-     app_context: AppContext,
-     _messages: Vec<Message>|
-     -> (bool, AppContext) {
-        let socket_path = "/tmp/boxmux.sock";
-        // Remove the stale socket file if it exists
-        if std::path::Path::new(socket_path).exists() {
-            let _ = fs::remove_file(socket_path);
+/// Environment variable naming the control socket of the running instance.
+/// Exported to child scripts and consulted by the CLI client subcommands.
+pub const SOCKET_ENV_VAR: &str = "BOXMUX_SOCKET";
+
+const SOCKET_FILE_PREFIX: &str = "boxmux-";
+const SOCKET_FILE_SUFFIX: &str = ".sock";
+
+/// A running (or stale) boxmux instance discovered in the socket runtime directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketInstance {
+    pub name: String,
+    pub pid: Option<u32>,
+    pub path: PathBuf,
+}
+
+/// Directory holding per-instance control sockets: `$XDG_RUNTIME_DIR/boxmux`,
+/// falling back to a per-user directory under the system temp dir.
+pub fn socket_runtime_dir() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("boxmux"),
+        _ => {
+            let uid = unsafe { libc::getuid() };
+            std::env::temp_dir().join(format!("boxmux-{}", uid))
         }
+    }
+}
 
-        let listener = match UnixListener::bind(socket_path) {
-            Ok(listener) => {
-                log::info!("Listening on socket: {}", socket_path);
-                listener
+/// Default socket path for an instance, derived from the YAML file name and PID,
+/// e.g. `$XDG_RUNTIME_DIR/boxmux/boxmux-dashboard-4242.sock`.
+pub fn default_socket_path(yaml_path: Option<&str>, pid: u32) -> PathBuf {
+    let name = yaml_path
+        .and_then(|path| Path::new(path).file_stem())
+        .map(|stem| {
+            stem.to_string_lossy()
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect::<String>()
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "app".to_string());
+
+    socket_runtime_dir().join(format!(
+        "{}{}-{}{}",
+        SOCKET_FILE_PREFIX, name, pid, SOCKET_FILE_SUFFIX
+    ))
+}
+
+/// Socket path the server should bind: configured path, else the per-instance default.
+pub fn resolve_server_socket_path(app_context: &AppContext) -> PathBuf {
+    match app_context.config.socket_path {
+        Some(ref path) => PathBuf::from(path),
+        None => default_socket_path(app_context.yaml_file_path.as_deref(), std::process::id()),
+    }
+}
+
+/// Parse `boxmux-<name>-<pid>.sock` into its instance name and PID.
+fn parse_socket_file_name(file_name: &str) -> Option<(String, Option<u32>)> {
+    let stem = file_name
+        .strip_prefix(SOCKET_FILE_PREFIX)?
+        .strip_suffix(SOCKET_FILE_SUFFIX)?;
+    match stem.rsplit_once('-') {
+        Some((name, pid)) => match pid.parse::<u32>() {
+            Ok(pid) => Some((name.to_string(), Some(pid))),
+            Err(_) => Some((stem.to_string(), None)),
+        },
+        None => Some((stem.to_string(), None)),
+    }
+}
+
+/// List instances with a control socket in the runtime directory.
+/// Socket files whose instance PID no longer exists are left over from crashed
+/// instances and are removed; a socket of a running instance is never touched, even if
+/// a connection attempt fails.
+pub fn list_socket_instances() -> Vec<SocketInstance> {
+    list_socket_instances_in(&socket_runtime_dir())
+}
+
+pub fn list_socket_instances_in(dir: &Path) -> Vec<SocketInstance> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut instances: Vec<SocketInstance> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let file_name = path.file_name()?.to_str()?.to_string();
+            let (name, pid) = parse_socket_file_name(&file_name)?;
+            match pid {
+                Some(pid) if process_is_gone(pid) => {
+                    log::debug!("Removing stale control socket: {}", path.display());
+                    let _ = fs::remove_file(&path);
+                    return None;
+                }
+                Some(_) => {}
+                // Without a PID only a listening socket shows the instance is running
+                None if UnixStream::connect(&path).is_err() => return None,
+                None => {}
             }
-            Err(err) => {
-                log::error!("Failed to bind to socket {}: {}", socket_path, err);
-                return (false, app_context);
+            Some(SocketInstance { name, pid, path })
+        })
+        .collect();
+    instances.sort_by(|a, b| a.name.cmp(&b.name).then(a.pid.cmp(&b.pid)));
+    instances
+}
+
+/// Whether no process with this PID exists (`ESRCH`); a process we may not signal
+/// (`EPERM`) still exists
+fn process_is_gone(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    let result = unsafe { libc::kill(pid, 0) };
+    result != 0 && std::io::Error::last_os_error().raw_os_error() == Some(libc::ESRCH)
+}
+
+/// Resolve which socket a CLI client should talk to.
+///
+/// `requested` may be a socket path, an instance name or a PID. Without it,
+/// `$BOXMUX_SOCKET` is used, then the only running instance if there is exactly one.
+pub fn resolve_client_socket_path(requested: Option<&str>) -> Result<PathBuf, String> {
+    let instances = list_socket_instances();
+
+    if let Some(requested) = requested {
+        let matches: Vec<&SocketInstance> = instances
+            .iter()
+            .filter(|instance| {
+                instance.name == requested
+                    || instance.pid.map(|pid| pid.to_string()).as_deref() == Some(requested)
+            })
+            .collect();
+        return match matches.len() {
+            0 => Ok(PathBuf::from(requested)),
+            1 => Ok(matches[0].path.clone()),
+            _ => Err(format!(
+                "Instance name '{}' is ambiguous, use one of: {}",
+                requested,
+                describe_instances(&matches)
+            )),
+        };
+    }
+
+    if let Ok(path) = std::env::var(SOCKET_ENV_VAR) {
+        if !path.is_empty() {
+            return Ok(PathBuf::from(path));
+        }
+    }
+
+    match instances.len() {
+        0 => Err(format!(
+            "No running boxmux instances found in {} (use --socket to select one)",
+            socket_runtime_dir().display()
+        )),
+        1 => Ok(instances[0].path.clone()),
+        _ => Err(format!(
+            "Multiple boxmux instances are running, select one with --socket: {}",
+            describe_instances(&instances.iter().collect::<Vec<_>>())
+        )),
+    }
+}
+
+fn describe_instances(instances: &[&SocketInstance]) -> String {
+    instances
+        .iter()
+        .map(|instance| match instance.pid {
+            Some(pid) => format!("{} (pid {})", instance.name, pid),
+            None => instance.name.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Handle one socket payload, returning the response text and the messages to forward.
+///
+/// Envelope requests get a JSON `SocketResponse`; bare `SocketFunction`s keep the
+/// original free-text replies.
+pub fn handle_socket_payload(payload: &str, app_context: &AppContext) -> (String, Vec<Message>) {
+    handle_incoming_message(parse_socket_message(payload), app_context)
+}
+
+fn handle_incoming_message(
+    parsed: Result<IncomingSocketMessage, Box<SocketResponse>>,
+    app_context: &AppContext,
+) -> (String, Vec<Message>) {
+    let incoming = match parsed {
+        Ok(incoming) => incoming,
+        Err(response) => {
+            log::error!("Rejected socket message: {}", response.to_json());
+            return (response.to_json(), Vec::new());
+        }
+    };
+
+    match incoming {
+        IncomingSocketMessage::Request(request) => {
+            log::debug!("Parsed socket request: {:?}", request);
+            match execute_socket_function(request.function, app_context) {
+                Ok((result, messages)) => {
+                    (SocketResponse::ok(request.id, result).to_json(), messages)
+                }
+                Err(error) => {
+                    log::error!("Socket function execution failed: {}", error);
+                    (
+                        SocketResponse::error(request.id, error).to_json(),
+                        Vec::new(),
+                    )
+                }
+            }
+        }
+        IncomingSocketMessage::Legacy(socket_function) => {
+            log::debug!("Parsed socket function: {:?}", socket_function);
+            match run_socket_function(socket_function, app_context) {
+                Ok((_updated_context, messages)) => (LEGACY_SUCCESS_RESPONSE.to_string(), messages),
+                Err(err) => {
+                    let error_msg = format!("Socket function execution failed: {}", err);
+                    log::error!("{}", error_msg);
+                    (error_msg, Vec::new())
+                }
             }
+        }
+    }
+}
+
+/// Make `path` ready for binding: create its directory and clear a stale socket file.
+/// Fails if another instance is still listening there instead of stealing its socket.
+pub fn prepare_socket_path(path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(parent)
+                .map_err(|e| {
+                    format!(
+                        "Failed to create socket directory {}: {}",
+                        parent.display(),
+                        e
+                    )
+                })?;
+        }
+    }
+
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(format!(
+                "Socket {} is in use by another boxmux instance",
+                path.display()
+            ));
+        }
+        fs::remove_file(path)
+            .map_err(|e| format!("Failed to remove stale socket {}: {}", path.display(), e))?;
+    }
+
+    Ok(())
+}
+
+/// Socket file this process bound, the only one it removes on exit
+static BOUND_SOCKET_PATH: once_cell::sync::Lazy<std::sync::Mutex<Option<PathBuf>>> =
+    once_cell::sync::Lazy::new(|| std::sync::Mutex::new(None));
+
+/// Prepare and bind the instance socket at `path`, remembering it for
+/// [`remove_bound_socket`]
+pub fn bind_instance_socket(path: &Path) -> Result<SocketServer, String> {
+    prepare_socket_path(path)?;
+    let server = SocketServer::bind(path)
+        .map_err(|err| format!("Failed to bind to socket {}: {}", path.display(), err))?;
+    *BOUND_SOCKET_PATH.lock().unwrap() = Some(path.to_path_buf());
+    Ok(server)
+}
+
+/// Remove the socket file this process bound, if any. A configured `socket_path` that
+/// another instance kept is left alone.
+pub fn remove_bound_socket() {
+    if let Some(path) = BOUND_SOCKET_PATH.lock().unwrap().take() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Environment variable holding the remote control token; overrides `app.remote_control.token`
+pub const REMOTE_TOKEN_ENV_VAR: &str = "BOXMUX_REMOTE_TOKEN";
+
+/// Token and function allow-list applied to TCP and WebSocket connections
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteAccess {
+    token: String,
+    allowed_functions: Option<HashSet<String>>,
+}
+
+impl RemoteAccess {
+    pub fn new(token: &str, allowed_functions: Option<&[String]>) -> Result<Self, String> {
+        if token.is_empty() {
+            return Err("Remote control token must not be empty".to_string());
+        }
+        if let Some(unknown) = allowed_functions
+            .unwrap_or_default()
+            .iter()
+            .find(|name| !SocketFunction::NAMES.contains(&name.as_str()))
+        {
+            return Err(format!(
+                "Unknown socket function in remote_control.allowed_functions: {}",
+                unknown
+            ));
+        }
+        Ok(RemoteAccess {
+            token: token.to_string(),
+            allowed_functions: allowed_functions
+                .map(|names| names.iter().cloned().collect::<HashSet<_>>()),
+        })
+    }
+
+    /// Build the policy for `config`, taking the token from `BOXMUX_REMOTE_TOKEN` if set
+    pub fn from_config(config: &RemoteControlConfig) -> Result<Self, String> {
+        let token = std::env::var(REMOTE_TOKEN_ENV_VAR)
+            .ok()
+            .filter(|token| !token.is_empty())
+            .or_else(|| config.token.clone())
+            .ok_or_else(|| {
+                format!(
+                    "Remote control needs a token: set app.remote_control.token or {}",
+                    REMOTE_TOKEN_ENV_VAR
+                )
+            })?;
+        Self::new(&token, config.allowed_functions.as_deref())
+    }
+
+    /// Compare in constant time so the token cannot be guessed byte by byte
+    pub fn token_matches(&self, token: &str) -> bool {
+        let expected = self.token.as_bytes();
+        let given = token.as_bytes();
+        expected.len() == given.len()
+            && expected
+                .iter()
+                .zip(given)
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    /// Without an allow-list remote clients may only read: a token holder must not get
+    /// a shell through `SpawnPtyProcess` or `SendPtyInput` unless that was asked for
+    pub fn allows(&self, function: &SocketFunction) -> bool {
+        match &self.allowed_functions {
+            Some(allowed) => allowed.contains(function.name()),
+            None => SocketFunction::READ_ONLY.contains(&function.name()),
+        }
+    }
+}
+
+/// Write half of a client connection
+enum ConnectionWriter {
+    Unix(UnixStream),
+    Tcp(TcpStream),
+    /// Lines are handed to the connection's thread, which owns the WebSocket
+    WebSocket(mpsc::Sender<String>),
+}
+
+impl ConnectionWriter {
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        match self {
+            ConnectionWriter::Unix(stream) => write_stream_line(stream, line),
+            ConnectionWriter::Tcp(stream) => write_stream_line(stream, line),
+            ConnectionWriter::WebSocket(sender) => sender.send(line.to_string()).map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::BrokenPipe, "WebSocket closed")
+            }),
+        }
+    }
+
+    /// A second handle on a local connection, for attaching it to a detached session
+    fn try_clone_unix(&self) -> Result<UnixStream, SocketError> {
+        match self {
+            ConnectionWriter::Unix(stream) => stream
+                .try_clone()
+                .map_err(|e| SocketError::new(SocketErrorCode::ExecutionFailed, e.to_string())),
+            _ => Err(SocketError::new(
+                SocketErrorCode::Forbidden,
+                "Attach is only available on the local socket",
+            )),
+        }
+    }
+
+    /// Whether the peer has hung up entirely rather than only closing its write half.
+    /// A TCP peer's close only shows once a write to it has failed.
+    fn peer_hung_up(&self) -> bool {
+        use std::os::fd::AsRawFd;
+        let fd = match self {
+            ConnectionWriter::Unix(stream) => stream.as_raw_fd(),
+            ConnectionWriter::Tcp(stream) => stream.as_raw_fd(),
+            // Its thread has exited by the time the connection reports being closed
+            ConnectionWriter::WebSocket(_) => return true,
+        };
+        let mut poll_fd = libc::pollfd {
+            fd,
+            events: 0,
+            revents: 0,
+        };
+        // SAFETY: a single valid pollfd and no timeout
+        let ready = unsafe { libc::poll(&mut poll_fd, 1, 0) };
+        ready > 0 && poll_fd.revents & (libc::POLLHUP | libc::POLLERR) != 0
+    }
+
+    fn close(&self) {
+        match self {
+            ConnectionWriter::Unix(stream) => {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+            ConnectionWriter::Tcp(stream) => {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+            // Dropping the sender makes the connection thread close the WebSocket
+            ConnectionWriter::WebSocket(_) => {}
+        }
+    }
+}
+
+/// Activity on a client connection, reported by the acceptor and reader threads
+enum ConnectionEvent {
+    Opened(u64, ConnectionWriter, Option<Arc<RemoteAccess>>),
+    Payload(u64, Result<Value, serde_json::Error>),
+    Closed(u64),
+}
+
+struct SocketConnection {
+    writer: ConnectionWriter,
+    subscription: Option<SocketSubscription>,
+    /// Set for TCP and WebSocket connections
+    remote_access: Option<Arc<RemoteAccess>>,
+    authenticated: bool,
+    /// Carries attach frames for the detached session instead of requests
+    attached: bool,
+    /// The peer closed its write half; kept only while subscribed and listening
+    read_closed: bool,
+}
+
+/// Write timeout for responses and events, so a stuck client cannot stall the socket loop
+const CONNECTION_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How often a WebSocket connection thread stops reading to send queued lines
+const WEBSOCKET_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Control socket server with persistent connections.
+///
+/// Connections are read on their own threads and may carry any number of JSON
+/// requests; all requests are executed on the thread that calls `poll`. A connection
+/// that sent a Subscribe request also receives events passed to `publish`.
+///
+/// Besides the Unix socket it was bound to, the server can accept TCP and WebSocket
+/// connections, which must authenticate against a `RemoteAccess` policy.
+pub struct SocketServer {
+    events: mpsc::Receiver<ConnectionEvent>,
+    sender: mpsc::Sender<ConnectionEvent>,
+    next_id: Arc<AtomicU64>,
+    connections: HashMap<u64, SocketConnection>,
+}
+
+impl SocketServer {
+    /// Bind `path` (which must be prepared) and start accepting connections
+    pub fn bind(path: &Path) -> std::io::Result<Self> {
+        let listener = UnixListener::bind(path)?;
+        let (sender, events) = mpsc::channel();
+        let server = SocketServer {
+            events,
+            sender,
+            next_id: Arc::new(AtomicU64::new(0)),
+            connections: HashMap::new(),
         };
 
-        for stream in listener.incoming() {
-            match stream {
-                Ok(mut stream) => {
-                    let mut buffer = String::new();
-                    match stream.read_to_string(&mut buffer) {
-                        Ok(_size) => {
-                            let trimmed_message = buffer.trim();
-                            log::debug!("Received socket message: {}", trimmed_message);
-
-                            // Parse JSON message as SocketFunction and execute directly
-                            if !trimmed_message.is_empty() {
-                                match serde_json::from_str::<SocketFunction>(trimmed_message) {
-                                    Ok(socket_function) => {
-                                        log::debug!(
-                                            "Parsed socket function: {:?}",
-                                            socket_function
-                                        );
-
-                                        // Execute socket function and send resulting messages
-                                        match run_socket_function(socket_function, &app_context) {
-                                            Ok((_updated_context, messages)) => {
-                                                // Update app_context if it was modified
-                                                // Note: app_context is typically not modified by socket functions
-                                                // but we maintain the pattern for consistency
-
-                                                // Send all resulting messages to the thread manager
-                                                for message in messages {
-                                                    inner.send_message(message);
-                                                }
-
-                                                // Send success acknowledgment
-                                                if let Err(err) = stream.write_all(
-                                                    b"Socket function executed successfully.",
-                                                ) {
-                                                    log::error!(
-                                                        "Error sending success response: {}",
-                                                        err
-                                                    );
-                                                }
-                                            }
-                                            Err(err) => {
-                                                let error_msg = format!(
-                                                    "Socket function execution failed: {}",
-                                                    err
-                                                );
-                                                log::error!("{}", error_msg);
-
-                                                // Send error response to client
-                                                if let Err(write_err) =
-                                                    stream.write_all(error_msg.as_bytes())
-                                                {
-                                                    log::error!(
-                                                        "Error sending error response: {}",
-                                                        write_err
-                                                    );
-                                                }
-                                            }
-                                        }
-                                    }
-                                    Err(parse_err) => {
-                                        let error_msg =
-                                            format!("Invalid socket function JSON: {}", parse_err);
-                                        log::error!("{}", error_msg);
-
-                                        // Send parse error response to client
-                                        if let Err(write_err) =
-                                            stream.write_all(error_msg.as_bytes())
-                                        {
-                                            log::error!(
-                                                "Error sending parse error response: {}",
-                                                write_err
-                                            );
-                                        }
-                                    }
-                                }
+        let sender = server.sender.clone();
+        let next_id = server.next_id.clone();
+        spawn_acceptor("boxmux-socket-acceptor", move || {
+            let (stream, _) = listener.accept()?;
+            let writer = stream.try_clone()?;
+            let _ = writer.set_write_timeout(Some(CONNECTION_WRITE_TIMEOUT));
+            let id = next_id.fetch_add(1, Ordering::Relaxed) + 1;
+            Ok(open_stream_connection(
+                id,
+                stream,
+                ConnectionWriter::Unix(writer),
+                None,
+                &sender,
+            ))
+        })?;
+
+        Ok(server)
+    }
+
+    /// Accept newline-delimited JSON requests over TCP on `address`.
+    /// Returns the bound address, which differs from `address` when its port is 0.
+    pub fn listen_tcp(
+        &self,
+        address: &str,
+        access: Arc<RemoteAccess>,
+    ) -> std::io::Result<SocketAddr> {
+        let listener = TcpListener::bind(address)?;
+        let local_address = listener.local_addr()?;
+        let sender = self.sender.clone();
+        let next_id = self.next_id.clone();
+        spawn_acceptor("boxmux-tcp-acceptor", move || {
+            let (stream, peer) = listener.accept()?;
+            log::info!("Remote control connection from {}", peer);
+            let writer = stream.try_clone()?;
+            let _ = writer.set_write_timeout(Some(CONNECTION_WRITE_TIMEOUT));
+            let id = next_id.fetch_add(1, Ordering::Relaxed) + 1;
+            Ok(open_stream_connection(
+                id,
+                stream,
+                ConnectionWriter::Tcp(writer),
+                Some(access.clone()),
+                &sender,
+            ))
+        })?;
+        Ok(local_address)
+    }
+
+    /// Accept WebSocket connections on `address`; each text message carries one request
+    /// and each response or event is sent as one text message.
+    pub fn listen_websocket(
+        &self,
+        address: &str,
+        access: Arc<RemoteAccess>,
+    ) -> std::io::Result<SocketAddr> {
+        let listener = TcpListener::bind(address)?;
+        let local_address = listener.local_addr()?;
+        let sender = self.sender.clone();
+        let next_id = self.next_id.clone();
+        spawn_acceptor("boxmux-websocket-acceptor", move || {
+            let (stream, peer) = listener.accept()?;
+            log::info!("Remote control WebSocket connection from {}", peer);
+            let id = next_id.fetch_add(1, Ordering::Relaxed) + 1;
+            spawn_websocket_connection(id, stream, access.clone(), sender.clone());
+            Ok(true)
+        })?;
+        Ok(local_address)
+    }
+
+    /// Handle pending connection activity, waiting up to `timeout` for the first event.
+    /// Returns the messages produced by requests, to be forwarded to the thread manager.
+    pub fn poll(&mut self, app_context: &AppContext, timeout: Duration) -> Vec<Message> {
+        let mut messages = Vec::new();
+        let mut next = self.events.recv_timeout(timeout).ok();
+        while let Some(event) = next {
+            match event {
+                ConnectionEvent::Opened(id, writer, remote_access) => {
+                    self.connections.insert(
+                        id,
+                        SocketConnection {
+                            writer,
+                            subscription: None,
+                            remote_access,
+                            authenticated: false,
+                            attached: false,
+                            read_closed: false,
+                        },
+                    );
+                }
+                ConnectionEvent::Payload(id, payload) => {
+                    messages.extend(self.handle_payload(id, payload, app_context));
+                }
+                ConnectionEvent::Closed(id) => {
+                    let attached = self
+                        .connections
+                        .get(&id)
+                        .is_some_and(|connection| connection.attached);
+                    if attached {
+                        if let Some(session) = crate::attach::session() {
+                            session.client_closed(id);
+                        }
+                    }
+                    // Subscribers may close their write half and keep listening
+                    let listening = self.connections.get_mut(&id).is_some_and(|connection| {
+                        connection.read_closed = true;
+                        connection.subscription.is_some() && !connection.writer.peer_hung_up()
+                    });
+                    if !listening {
+                        self.connections.remove(&id);
+                    }
+                }
+            }
+            next = self.events.try_recv().ok();
+        }
+
+        // A subscriber that only listens can hang up at any time
+        self.connections.retain(|id, connection| {
+            let gone = connection.read_closed && connection.writer.peer_hung_up();
+            if gone {
+                log::debug!("Dropping socket subscriber {}", id);
+            }
+            !gone
+        });
+
+        // The thread manager does not echo our own messages back to us, so publish
+        // them here. Their StreamUpdates only acknowledge the request and must not be
+        // reported as a process finishing.
+        for message in &messages {
+            for event in socket_events_for_message(message) {
+                if !event.is_lifecycle() {
+                    self.publish_event(&event);
+                }
+            }
+        }
+
+        messages
+    }
+
+    /// Deliver the events derived from `message` to matching subscribers
+    pub fn publish(&mut self, message: &Message) {
+        for event in socket_events_for_message(message) {
+            self.publish_event(&event);
+        }
+    }
+
+    pub fn publish_event(&mut self, event: &SocketEvent) {
+        let line = SocketEventLine::new(event.clone()).to_json();
+        let mut dropped = Vec::new();
+        for (id, connection) in self.connections.iter_mut() {
+            let wants_event = connection
+                .subscription
+                .as_ref()
+                .map(|subscription| subscription.matches(event))
+                .unwrap_or(false);
+            if wants_event && connection.writer.write_line(&line).is_err() {
+                dropped.push(*id);
+            }
+        }
+        for id in dropped {
+            log::debug!("Dropping socket subscriber {}", id);
+            self.connections.remove(&id);
+        }
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.connections
+            .values()
+            .filter(|connection| connection.subscription.is_some())
+            .count()
+    }
+
+    /// Check a remote connection's token and allow-list before anything is executed.
+    /// Local connections are always authorized.
+    fn authorize(
+        &mut self,
+        id: u64,
+        parsed: &Result<IncomingSocketMessage, Box<SocketResponse>>,
+    ) -> Result<(), SocketError> {
+        let Some(connection) = self.connections.get_mut(&id) else {
+            return Ok(());
+        };
+        let Some(access) = connection.remote_access.clone() else {
+            return Ok(());
+        };
+
+        let request = match parsed {
+            Ok(IncomingSocketMessage::Request(request)) => request,
+            Ok(IncomingSocketMessage::Legacy(_)) => {
+                return Err(SocketError::new(
+                    SocketErrorCode::Unauthorized,
+                    "Remote connections require a versioned request with a token",
+                ));
+            }
+            // Nothing will be executed; the parse error is reported as usual
+            Err(_) => return Ok(()),
+        };
+
+        match request.token.as_deref() {
+            Some(token) if access.token_matches(token) => connection.authenticated = true,
+            None if connection.authenticated => {}
+            _ => {
+                connection.authenticated = false;
+                return Err(SocketError::new(
+                    SocketErrorCode::Unauthorized,
+                    "Missing or invalid token",
+                ));
+            }
+        }
+
+        if !access.allows(&request.function) {
+            return Err(SocketError::new(
+                SocketErrorCode::Forbidden,
+                format!(
+                    "{} is not allowed for remote clients",
+                    request.function.name()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn handle_payload(
+        &mut self,
+        id: u64,
+        payload: Result<Value, serde_json::Error>,
+        app_context: &AppContext,
+    ) -> Vec<Message> {
+        if self
+            .connections
+            .get(&id)
+            .is_some_and(|connection| connection.attached)
+        {
+            handle_attach_frame(id, payload);
+            return Vec::new();
+        }
+
+        let parsed = payload
+            .map_err(|e| invalid_json_response(&e))
+            .and_then(parse_socket_value);
+        log::debug!("Received socket message: {:?}", parsed);
+
+        if let Err(error) = self.authorize(id, &parsed) {
+            log::warn!("Rejected remote socket request: {}", error);
+            let unauthorized = error.code == SocketErrorCode::Unauthorized;
+            let request_id = match &parsed {
+                Ok(IncomingSocketMessage::Request(request)) => request.id.clone(),
+                _ => None,
+            };
+            let response = SocketResponse::error(request_id, error);
+            if let Some(connection) = self.connections.get_mut(&id) {
+                let _ = connection.writer.write_line(&response.to_json());
+                // Make guessing tokens expensive: one attempt per connection
+                if unauthorized {
+                    connection.writer.close();
+                    self.connections.remove(&id);
+                }
+            }
+            return Vec::new();
+        }
+
+        let (response, messages) = match parsed {
+            Ok(IncomingSocketMessage::Request(request)) => match request.function {
+                SocketFunction::Subscribe { box_ids, events } => {
+                    if let Some(connection) = self.connections.get_mut(&id) {
+                        connection.subscription = Some(SocketSubscription {
+                            box_ids: box_ids.clone(),
+                            events: events.clone(),
+                        });
+                    }
+                    let result = SocketResult::Subscribed { box_ids, events };
+                    (SocketResponse::ok(request.id, result).to_json(), Vec::new())
+                }
+                SocketFunction::Attach { cols, rows } => {
+                    return self.attach(id, request.id, cols, rows);
+                }
+                function => handle_incoming_message(
+                    Ok(IncomingSocketMessage::Request(
+                        crate::socket_protocol::SocketRequest {
+                            function,
+                            ..request
+                        },
+                    )),
+                    app_context,
+                ),
+            },
+            other => handle_incoming_message(other, app_context),
+        };
+
+        if let Some(connection) = self.connections.get_mut(&id) {
+            if let Err(err) = connection.writer.write_line(&response) {
+                log::error!("Error sending socket response: {}", err);
+                self.connections.remove(&id);
+            }
+        }
+        messages
+    }
+
+    /// Hand connection `id` to the detached session as its terminal. The response is
+    /// written before the session starts sending output, so it stays the first line.
+    fn attach(
+        &mut self,
+        id: u64,
+        request_id: Option<String>,
+        cols: u16,
+        rows: u16,
+    ) -> Vec<Message> {
+        let Some(connection) = self.connections.get_mut(&id) else {
+            return Vec::new();
+        };
+        let attachable = connection
+            .writer
+            .try_clone_unix()
+            .and_then(|stream| Ok((crate::attach::require_session()?, stream)));
+        let (session, stream) = match attachable {
+            Ok(attachable) => attachable,
+            Err(error) => {
+                log::warn!("Rejected attach: {}", error);
+                let response = SocketResponse::error(request_id, error);
+                let _ = connection.writer.write_line(&response.to_json());
+                return Vec::new();
+            }
+        };
+
+        let response = SocketResponse::ok(request_id, SocketResult::Attached { cols, rows });
+        if let Err(err) = connection.writer.write_line(&response.to_json()) {
+            log::error!("Error sending socket response: {}", err);
+            self.connections.remove(&id);
+            return Vec::new();
+        }
+        if let Err(err) = session.attach(id, stream, cols, rows) {
+            log::error!("Failed to attach client {}: {}", id, err);
+            connection.writer.close();
+            return Vec::new();
+        }
+        connection.attached = true;
+        log::info!("Client {} attached ({}x{})", id, cols, rows);
+        // The client's terminal starts out blank
+        vec![Message::RedrawApp]
+    }
+}
+
+/// Pass a frame from an attached client to the session
+fn handle_attach_frame(id: u64, payload: Result<Value, serde_json::Error>) {
+    let frame = match payload.and_then(serde_json::from_value::<AttachFrame>) {
+        Ok(frame) => frame,
+        Err(e) => {
+            log::warn!("Ignoring invalid attach frame from client {}: {}", id, e);
+            return;
+        }
+    };
+    if let Some(session) = crate::attach::session() {
+        if let Err(e) = session.handle_client_frame(frame) {
+            log::error!("Failed to pass attach frame to the session: {}", e);
+        }
+    }
+}
+
+fn write_stream_line(stream: &mut impl Write, line: &str) -> std::io::Result<()> {
+    stream.write_all(line.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.flush()
+}
+
+/// Run `accept` on its own thread until it reports that the server was dropped
+fn spawn_acceptor(
+    name: &str,
+    mut accept: impl FnMut() -> std::io::Result<bool> + Send + 'static,
+) -> std::io::Result<()> {
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || loop {
+            match accept() {
+                Ok(true) => {}
+                // Server was dropped
+                Ok(false) => break,
+                Err(err) => log::error!("Error accepting connection: {}", err),
+            }
+        })?;
+    Ok(())
+}
+
+/// Register a Unix or TCP connection and start reading from it.
+/// Returns false if the server is gone.
+fn open_stream_connection(
+    id: u64,
+    stream: impl Read + Send + 'static,
+    writer: ConnectionWriter,
+    remote_access: Option<Arc<RemoteAccess>>,
+    sender: &mpsc::Sender<ConnectionEvent>,
+) -> bool {
+    if sender
+        .send(ConnectionEvent::Opened(id, writer, remote_access))
+        .is_err()
+    {
+        return false;
+    }
+    spawn_connection_reader(id, stream, sender.clone());
+    true
+}
+
+/// Read consecutive JSON values from a connection until EOF or invalid input
+fn spawn_connection_reader(
+    id: u64,
+    stream: impl Read + Send + 'static,
+    sender: mpsc::Sender<ConnectionEvent>,
+) {
+    let spawned = thread::Builder::new()
+        .name(format!("boxmux-socket-conn-{}", id))
+        .spawn(move || {
+            let values = serde_json::Deserializer::from_reader(stream).into_iter::<Value>();
+            for value in values {
+                let failed = value.is_err();
+                if sender.send(ConnectionEvent::Payload(id, value)).is_err() || failed {
+                    break;
+                }
+            }
+            let _ = sender.send(ConnectionEvent::Closed(id));
+        });
+    if let Err(err) = spawned {
+        log::error!("Failed to spawn socket connection reader: {}", err);
+    }
+}
+
+/// Complete the WebSocket handshake and relay messages in both directions.
+/// The thread owns the socket, so it alternates between short reads and sending
+/// the lines queued by the server.
+fn spawn_websocket_connection(
+    id: u64,
+    stream: TcpStream,
+    access: Arc<RemoteAccess>,
+    sender: mpsc::Sender<ConnectionEvent>,
+) {
+    let spawned = thread::Builder::new()
+        .name(format!("boxmux-websocket-conn-{}", id))
+        .spawn(move || {
+            let mut socket = match tungstenite::accept(stream) {
+                Ok(socket) => socket,
+                Err(err) => {
+                    log::error!("WebSocket handshake failed: {}", err);
+                    return;
+                }
+            };
+            let _ = socket
+                .get_ref()
+                .set_read_timeout(Some(WEBSOCKET_POLL_INTERVAL));
+
+            let (line_sender, lines) = mpsc::channel::<String>();
+            if sender
+                .send(ConnectionEvent::Opened(
+                    id,
+                    ConnectionWriter::WebSocket(line_sender),
+                    Some(access),
+                ))
+                .is_err()
+            {
+                return;
+            }
+
+            'connection: loop {
+                loop {
+                    match lines.try_recv() {
+                        Ok(line) => {
+                            if socket.send(WsMessage::Text(line)).is_err() {
+                                break 'connection;
                             }
                         }
-                        Err(err) => {
-                            log::error!("Error receiving message: {}", err);
+                        Err(mpsc::TryRecvError::Empty) => break,
+                        Err(mpsc::TryRecvError::Disconnected) => {
+                            let _ = socket.close(None);
+                            let _ = socket.flush();
+                            break 'connection;
                         }
                     }
                 }
-                Err(err) => {
-                    log::error!("Error accepting connection: {}", err);
+
+                let payload = match socket.read() {
+                    Ok(WsMessage::Text(text)) => serde_json::from_str::<Value>(&text),
+                    Ok(WsMessage::Binary(bytes)) => serde_json::from_slice::<Value>(&bytes),
+                    Ok(WsMessage::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(tungstenite::Error::Io(err))
+                        if matches!(
+                            err.kind(),
+                            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                        ) =>
+                    {
+                        continue
+                    }
+                    Err(_) => break,
+                };
+                if sender.send(ConnectionEvent::Payload(id, payload)).is_err() {
+                    break;
                 }
             }
+            let _ = sender.send(ConnectionEvent::Closed(id));
+        });
+    if let Err(err) = spawned {
+        log::error!("Failed to spawn WebSocket connection thread: {}", err);
+    }
+}
+
+/// Start the listeners configured in `app.remote_control`, if any
+fn start_remote_listeners(server: &SocketServer, config: &RemoteControlConfig) {
+    if config.tcp_address.is_none() && config.websocket_address.is_none() {
+        return;
+    }
+    let access = match RemoteAccess::from_config(config) {
+        Ok(access) => Arc::new(access),
+        Err(err) => {
+            log::error!("Remote control disabled: {}", err);
+            return;
+        }
+    };
+
+    if let Some(address) = &config.tcp_address {
+        match server.listen_tcp(address, access.clone()) {
+            Ok(bound) => log::info!("Listening for remote control on tcp://{}", bound),
+            Err(err) => log::error!("Failed to listen on {}: {}", address, err),
+        }
+    }
+    if let Some(address) = &config.websocket_address {
+        match server.listen_websocket(address, access) {
+            Ok(bound) => log::info!("Listening for remote control on ws://{}", bound),
+            Err(err) => log::error!("Failed to listen on {}: {}", address, err),
+        }
+    }
+}
+
+create_runnable!(
+    SocketLoop,
+    |_inner: &mut RunnableImpl, _app_context: AppContext, _messages: Vec<Message>| -> bool { true },
+    |inner: &mut RunnableImpl,
+     app_context: AppContext,
+     messages: Vec<Message>|
+     -> (bool, AppContext) {
+        let socket_path = resolve_server_socket_path(&app_context);
+        let mut server = match bind_instance_socket(&socket_path) {
+            Ok(server) => {
+                log::info!("Listening on socket: {}", socket_path.display());
+                server
+            }
+            Err(err) => {
+                log::error!("{}", err);
+                return (false, app_context);
+            }
+        };
+        if let Some(remote_control) = &app_context.app.remote_control {
+            start_remote_listeners(&server, remote_control);
         }
 
-        (true, app_context)
+        // Serve requests and relay events until the thread manager shuts us down
+        let mut pending = messages;
+        loop {
+            for message in &pending {
+                server.publish(message);
+                if matches!(message, Message::Exit | Message::Terminate) {
+                    return (false, app_context);
+                }
+            }
+
+            let current_context = inner.app_context.clone();
+            let poll_timeout = Duration::from_millis(current_context.config.frame_delay.max(1));
+            for message in server.poll(&current_context, poll_timeout) {
+                inner.send_message(message);
+            }
+
+            let (updated_context, new_messages) = inner.receive_updates();
+            inner.app_context = updated_context;
+            pending = new_messages;
+        }
     }
 );
@@ -0,0 +1,798 @@
+// Control socket protocol: versioned request/response envelope around SocketFunction.
+//
+// Request:  {"version": 1, "id": "req-1", "function": {"ReplaceBoxContent": {...}}}
+// Response: {"version": 1, "id": "req-1", "status": "ok", "result": {"type": "accepted", ...}}
+//           {"version": 1, "id": "req-1", "status": "error", "error": {"code": "not_found", ...}}
+//
+// A bare SocketFunction (the original wire format) is still accepted and answered
+// with the original free-text acknowledgement.
+//
+// Connections stay open until the client closes them, so several requests can be sent
+// back to back, each answered by one response line. After a Subscribe request the
+// connection also receives event lines:
+//           {"version": 1, "event": "layout_switched", "layout_id": "main"}
+// After an Attach request to a detached session the connection carries attach frames
+// instead (see crate::attach):
+//           {"output": "..."}  {"input": "..."}  {"resize": {"cols": 80, "rows": 24}}
+//
+// Query functions (GetBoxContent, ListBoxes, ListLayouts, ...) are read-only: they
+// return a snapshot in `result` and produce no internal messages.
+
+use crate::model::common::{
+    run_socket_function, send_json_to_socket, AlertLevel, BatchStatus, ExecutionPtyStatus,
+    ExecutionThreadStatus, SocketFunction, SourceState, StreamUpdate,
+};
+use crate::pty_manager::PtyStatus;
+use crate::socket_queries::{
+    query_box_content, query_boxes, query_execution_sources, query_layouts, query_pty_buffer,
+    query_pty_search, BoxContentReport, BoxSummary, ExecutionSourceReport, LayoutSummary,
+    PtySearchMatch,
+};
+use crate::{AppContext, Message};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+
+/// Current envelope version. Requests without a version are treated as this version.
+pub const SOCKET_PROTOCOL_VERSION: u32 = 1;
+
+/// Legacy acknowledgement sent for bare SocketFunction requests
+pub const LEGACY_SUCCESS_RESPONSE: &str = "Socket function executed successfully.";
+
+fn default_protocol_version() -> u32 {
+    SOCKET_PROTOCOL_VERSION
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub struct SocketRequest {
+    #[serde(default = "default_protocol_version")]
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Shared secret, required on TCP and WebSocket connections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    pub function: SocketFunction,
+}
+
+// Requests are logged, so keep the token out of their Debug output
+impl std::fmt::Debug for SocketRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SocketRequest")
+            .field("version", &self.version)
+            .field("id", &self.id)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("function", &self.function)
+            .finish()
+    }
+}
+
+impl SocketRequest {
+    pub fn new(function: SocketFunction) -> Self {
+        SocketRequest {
+            version: SOCKET_PROTOCOL_VERSION,
+            id: Some(uuid::Uuid::new_v4().to_string()),
+            token: None,
+            function,
+        }
+    }
+}
+
+/// A message read from the control socket, in either wire format
+#[derive(Debug, Clone, PartialEq)]
+pub enum IncomingSocketMessage {
+    Request(SocketRequest),
+    Legacy(SocketFunction),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SocketErrorCode {
+    /// Payload is not valid JSON
+    InvalidJson,
+    /// JSON is valid but not a known request or function
+    InvalidRequest,
+    /// Envelope version is not supported by this server
+    UnsupportedVersion,
+    /// Target box or process does not exist
+    NotFound,
+    /// Function needs the PTY manager, which is not running
+    PtyUnavailable,
+    /// Function was understood but failed while executing
+    ExecutionFailed,
+    /// Remote connection sent no token or a wrong one
+    Unauthorized,
+    /// Function is not in the remote allow-list
+    Forbidden,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct SocketError {
+    pub code: SocketErrorCode,
+    pub message: String,
+}
+
+impl SocketError {
+    pub fn new(code: SocketErrorCode, message: impl Into<String>) -> Self {
+        SocketError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for SocketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = serde_json::to_value(self.code)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        write!(f, "{}: {}", code, self.message)
+    }
+}
+
+impl Error for SocketError {}
+
+/// Machine-readable PTY process status
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct PtyStatusReport {
+    pub box_id: String,
+    pub process_id: Option<u32>,
+    /// One of starting, running, finished, error, failed_fallback, dead
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub running: bool,
+    pub can_kill: bool,
+    pub buffer_lines: usize,
+}
+
+/// Typed success payloads
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SocketResult {
+    /// Function was accepted and turned into `messages` internal messages
+    Accepted {
+        messages: usize,
+    },
+    PtyStatus(PtyStatusReport),
+    /// Connection is now subscribed; events follow as separate lines
+    Subscribed {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        box_ids: Option<Vec<String>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        events: Option<Vec<SocketEventType>>,
+    },
+    BoxContent(BoxContentReport),
+    Boxes {
+        layout_id: String,
+        boxes: Vec<BoxSummary>,
+    },
+    Layouts {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        active_layout_id: Option<String>,
+        layouts: Vec<LayoutSummary>,
+    },
+    ExecutionSources {
+        sources: Vec<ExecutionSourceReport>,
+    },
+    /// Scrollback lines `start..start + lines.len()` out of `total_lines`
+    PtyBuffer {
+        box_id: String,
+        start: usize,
+        total_lines: usize,
+        lines: Vec<String>,
+    },
+    PtySearch {
+        box_id: String,
+        query: String,
+        matches: Vec<PtySearchMatch>,
+    },
+    /// Connection is now the session's terminal; attach frames follow as separate lines
+    Attached {
+        cols: u16,
+        rows: u16,
+    },
+    /// Number of attached clients let go of
+    Detached {
+        clients: usize,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SocketOutcome {
+    Ok { result: SocketResult },
+    Error { error: SocketError },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SocketResponse {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(flatten)]
+    pub outcome: SocketOutcome,
+}
+
+impl SocketResponse {
+    pub fn ok(id: Option<String>, result: SocketResult) -> Self {
+        SocketResponse {
+            version: SOCKET_PROTOCOL_VERSION,
+            id,
+            outcome: SocketOutcome::Ok { result },
+        }
+    }
+
+    pub fn error(id: Option<String>, error: SocketError) -> Self {
+        SocketResponse {
+            version: SOCKET_PROTOCOL_VERSION,
+            id,
+            outcome: SocketOutcome::Error { error },
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        matches!(self.outcome, SocketOutcome::Ok { .. })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|e| {
+            format!(
+                r#"{{"version":{},"status":"error","error":{{"code":"execution_failed","message":"Failed to encode response: {}"}}}}"#,
+                SOCKET_PROTOCOL_VERSION, e
+            )
+        })
+    }
+}
+
+/// Parse raw socket input. On failure returns the error response to send back,
+/// carrying the request id when one could be read.
+pub fn parse_socket_message(raw: &str) -> Result<IncomingSocketMessage, Box<SocketResponse>> {
+    let value: Value = serde_json::from_str(raw).map_err(|e| invalid_json_response(&e))?;
+    parse_socket_value(value)
+}
+
+/// Error response for input that could not be parsed as JSON
+pub fn invalid_json_response(error: &serde_json::Error) -> Box<SocketResponse> {
+    Box::new(SocketResponse::error(
+        None,
+        SocketError::new(
+            SocketErrorCode::InvalidJson,
+            format!("Invalid JSON: {}", error),
+        ),
+    ))
+}
+
+/// Interpret an already-parsed JSON value as an envelope request or a bare SocketFunction
+pub fn parse_socket_value(value: Value) -> Result<IncomingSocketMessage, Box<SocketResponse>> {
+    let is_envelope = value
+        .as_object()
+        .map(|object| object.contains_key("function"))
+        .unwrap_or(false);
+
+    if !is_envelope {
+        return serde_json::from_value::<SocketFunction>(value)
+            .map(IncomingSocketMessage::Legacy)
+            .map_err(|e| {
+                Box::new(SocketResponse::error(
+                    None,
+                    SocketError::new(
+                        SocketErrorCode::InvalidRequest,
+                        format!("Unknown socket function: {}", e),
+                    ),
+                ))
+            });
+    }
+
+    let id = value
+        .get("id")
+        .and_then(|id| id.as_str())
+        .map(str::to_string);
+
+    if let Some(version) = value.get("version") {
+        if version.as_u64() != Some(SOCKET_PROTOCOL_VERSION as u64) {
+            return Err(Box::new(SocketResponse::error(
+                id,
+                SocketError::new(
+                    SocketErrorCode::UnsupportedVersion,
+                    format!(
+                        "Unsupported protocol version {}, expected {}",
+                        version, SOCKET_PROTOCOL_VERSION
+                    ),
+                ),
+            )));
+        }
+    }
+
+    serde_json::from_value::<SocketRequest>(value)
+        .map(IncomingSocketMessage::Request)
+        .map_err(|e| {
+            Box::new(SocketResponse::error(
+                id,
+                SocketError::new(
+                    SocketErrorCode::InvalidRequest,
+                    format!("Invalid request: {}", e),
+                ),
+            ))
+        })
+}
+
+fn pty_status_report(info: crate::pty_manager::ProcessInfo) -> PtyStatusReport {
+    let (status, exit_code, reason) = match info.status {
+        PtyStatus::Starting => ("starting", None, None),
+        PtyStatus::Running => ("running", None, None),
+        PtyStatus::Finished(code) => ("finished", Some(code), None),
+        PtyStatus::Error(msg) => ("error", None, Some(msg)),
+        PtyStatus::FailedFallback => ("failed_fallback", None, None),
+        PtyStatus::Dead(msg) => ("dead", None, Some(msg)),
+    };
+    PtyStatusReport {
+        box_id: info.muxbox_id,
+        process_id: info.process_id,
+        status: status.to_string(),
+        exit_code,
+        reason,
+        running: info.is_running,
+        can_kill: info.can_kill,
+        buffer_lines: info.buffer_lines,
+    }
+}
+
+/// Check that a box-targeting function names a box that exists
+fn require_box(app_context: &AppContext, box_id: &str) -> Result<(), SocketError> {
+    if app_context.app.get_muxbox_by_id(box_id).is_none() {
+        return Err(SocketError::new(
+            SocketErrorCode::NotFound,
+            format!("Box {} not found", box_id),
+        ));
+    }
+    Ok(())
+}
+
+/// Check that a PTY-targeting function has a PTY manager and a process to act on
+fn require_pty_process(app_context: &AppContext, box_id: &str) -> Result<(), SocketError> {
+    let pty_manager = app_context.pty_manager.as_ref().ok_or_else(|| {
+        SocketError::new(SocketErrorCode::PtyUnavailable, "PTY manager not available")
+    })?;
+    if pty_manager.get_process_info(box_id).is_none() {
+        return Err(SocketError::new(
+            SocketErrorCode::NotFound,
+            format!("No PTY process found for box {}", box_id),
+        ));
+    }
+    Ok(())
+}
+
+/// Execute a socket function for an envelope request, returning a typed result
+/// plus the messages to forward to the thread manager.
+pub fn execute_socket_function(
+    socket_function: SocketFunction,
+    app_context: &AppContext,
+) -> Result<(SocketResult, Vec<Message>), SocketError> {
+    match &socket_function {
+        SocketFunction::QueryPtyStatus { box_id } => {
+            require_pty_process(app_context, box_id)?;
+            let info = app_context
+                .pty_manager
+                .as_ref()
+                .and_then(|pty_manager| pty_manager.get_detailed_process_info(box_id))
+                .ok_or_else(|| {
+                    SocketError::new(
+                        SocketErrorCode::NotFound,
+                        format!("No PTY process found for box {}", box_id),
+                    )
+                })?;
+            return Ok((SocketResult::PtyStatus(pty_status_report(info)), Vec::new()));
+        }
+        SocketFunction::KillPtyProcess { box_id }
+        | SocketFunction::RestartPtyProcess { box_id }
+        | SocketFunction::SendPtyInput { box_id, .. }
+        | SocketFunction::SendPtySignal { box_id, .. } => {
+            require_pty_process(app_context, box_id)?;
+        }
+        SocketFunction::Subscribe { .. } => {
+            return Err(SocketError::new(
+                SocketErrorCode::InvalidRequest,
+                "Subscribe is only available on a persistent socket connection",
+            ));
+        }
+        SocketFunction::GetBoxContent { box_id } => {
+            let report = query_box_content(app_context, box_id)?;
+            return Ok((SocketResult::BoxContent(report), Vec::new()));
+        }
+        SocketFunction::ListBoxes { layout_id } => {
+            let (layout_id, boxes) = query_boxes(app_context, layout_id.as_deref())?;
+            return Ok((SocketResult::Boxes { layout_id, boxes }, Vec::new()));
+        }
+        SocketFunction::ListLayouts {} => {
+            let (active_layout_id, layouts) = query_layouts(app_context);
+            return Ok((
+                SocketResult::Layouts {
+                    active_layout_id,
+                    layouts,
+                },
+                Vec::new(),
+            ));
+        }
+        SocketFunction::ListExecutionSources {} => {
+            let sources = query_execution_sources(app_context);
+            return Ok((SocketResult::ExecutionSources { sources }, Vec::new()));
+        }
+        SocketFunction::GetPtyBuffer {
+            box_id,
+            start,
+            count,
+        } => {
+            let (start, lines, total_lines) =
+                query_pty_buffer(app_context, box_id, *start, *count)?;
+            return Ok((
+                SocketResult::PtyBuffer {
+                    box_id: box_id.clone(),
+                    start,
+                    total_lines,
+                    lines,
+                },
+                Vec::new(),
+            ));
+        }
+        SocketFunction::SearchPtyBuffer { box_id, query } => {
+            let matches = query_pty_search(app_context, box_id, query)?;
+            return Ok((
+                SocketResult::PtySearch {
+                    box_id: box_id.clone(),
+                    query: query.clone(),
+                    matches,
+                },
+                Vec::new(),
+            ));
+        }
+        SocketFunction::Attach { .. } => {
+            return Err(SocketError::new(
+                SocketErrorCode::InvalidRequest,
+                "Attach is only available on a persistent local socket connection",
+            ));
+        }
+        SocketFunction::Detach {} => {
+            let session = crate::attach::require_session()?;
+            let clients = usize::from(session.detach("detached by boxmux detach"));
+            return Ok((SocketResult::Detached { clients }, Vec::new()));
+        }
+        SocketFunction::ReplaceBoxContent { box_id, .. }
+        | SocketFunction::ReplaceBoxScript { box_id, .. }
+        | SocketFunction::StopBoxRefresh { box_id }
+        | SocketFunction::StartBoxRefresh { box_id }
+        | SocketFunction::ReplaceBox { box_id, .. }
+        | SocketFunction::RemoveBox { box_id } => {
+            require_box(app_context, box_id)?;
+        }
+        SocketFunction::SpawnPtyProcess { box_id, .. } => {
+            if app_context.pty_manager.is_none() {
+                return Err(SocketError::new(
+                    SocketErrorCode::PtyUnavailable,
+                    "PTY manager not available",
+                ));
+            }
+            require_box(app_context, box_id)?;
+        }
+        _ => {}
+    }
+
+    let (_, messages) = run_socket_function(socket_function, app_context)
+        .map_err(|e| SocketError::new(SocketErrorCode::ExecutionFailed, e.to_string()))?;
+    Ok((
+        SocketResult::Accepted {
+            messages: messages.len(),
+        },
+        messages,
+    ))
+}
+
+/// Send an envelope request and parse the JSON response
+pub fn send_socket_request(
+    socket_path: &str,
+    request: &SocketRequest,
+) -> Result<SocketResponse, Box<dyn Error>> {
+    let request_json = serde_json::to_string(request)?;
+    let response_json = send_json_to_socket(socket_path, &request_json)?;
+    let response = serde_json::from_str::<SocketResponse>(response_json.trim()).map_err(|e| {
+        format!(
+            "Invalid response from boxmux ({}): {}",
+            e,
+            response_json.trim()
+        )
+    })?;
+    Ok(response)
+}
+
+/// Event types a subscriber can filter on
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SocketEventType {
+    ChoiceExecuted,
+    StreamFinished,
+    PtyExited,
+    LayoutSwitched,
+    BoxContentChanged,
+    BoxAdded,
+    BoxRemoved,
+    RefreshStarted,
+    RefreshStopped,
+    AlertChanged,
+    Shutdown,
+}
+
+/// Event pushed to subscribed connections, derived from internal `Message`s
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SocketEvent {
+    ChoiceExecuted {
+        box_id: String,
+        choice_id: String,
+    },
+    StreamFinished {
+        box_id: String,
+        stream_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+        success: bool,
+    },
+    PtyExited {
+        box_id: String,
+        stream_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+        success: bool,
+    },
+    LayoutSwitched {
+        layout_id: String,
+    },
+    BoxContentChanged {
+        box_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stream_id: Option<String>,
+    },
+    BoxAdded {
+        box_id: String,
+        layout_id: String,
+    },
+    BoxRemoved {
+        box_id: String,
+    },
+    RefreshStarted {
+        box_id: String,
+    },
+    RefreshStopped {
+        box_id: String,
+    },
+    /// The box's matching `alerts` rule changed; no level once none matches
+    AlertChanged {
+        box_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        level: Option<AlertLevel>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    Shutdown,
+}
+
+impl SocketEvent {
+    pub fn event_type(&self) -> SocketEventType {
+        match self {
+            SocketEvent::ChoiceExecuted { .. } => SocketEventType::ChoiceExecuted,
+            SocketEvent::StreamFinished { .. } => SocketEventType::StreamFinished,
+            SocketEvent::PtyExited { .. } => SocketEventType::PtyExited,
+            SocketEvent::LayoutSwitched { .. } => SocketEventType::LayoutSwitched,
+            SocketEvent::BoxContentChanged { .. } => SocketEventType::BoxContentChanged,
+            SocketEvent::BoxAdded { .. } => SocketEventType::BoxAdded,
+            SocketEvent::BoxRemoved { .. } => SocketEventType::BoxRemoved,
+            SocketEvent::RefreshStarted { .. } => SocketEventType::RefreshStarted,
+            SocketEvent::RefreshStopped { .. } => SocketEventType::RefreshStopped,
+            SocketEvent::AlertChanged { .. } => SocketEventType::AlertChanged,
+            SocketEvent::Shutdown => SocketEventType::Shutdown,
+        }
+    }
+
+    /// Box the event is about; None for app-wide events
+    pub fn box_id(&self) -> Option<&str> {
+        match self {
+            SocketEvent::ChoiceExecuted { box_id, .. }
+            | SocketEvent::StreamFinished { box_id, .. }
+            | SocketEvent::PtyExited { box_id, .. }
+            | SocketEvent::BoxContentChanged { box_id, .. }
+            | SocketEvent::BoxAdded { box_id, .. }
+            | SocketEvent::BoxRemoved { box_id }
+            | SocketEvent::RefreshStarted { box_id }
+            | SocketEvent::RefreshStopped { box_id }
+            | SocketEvent::AlertChanged { box_id, .. } => Some(box_id),
+            SocketEvent::LayoutSwitched { .. } | SocketEvent::Shutdown => None,
+        }
+    }
+
+    /// Only lifecycle events that report a process actually finishing
+    pub fn is_lifecycle(&self) -> bool {
+        matches!(
+            self,
+            SocketEvent::StreamFinished { .. } | SocketEvent::PtyExited { .. }
+        )
+    }
+}
+
+/// One line on a subscribed connection
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SocketEventLine {
+    pub version: u32,
+    #[serde(flatten)]
+    pub event: SocketEvent,
+}
+
+impl SocketEventLine {
+    pub fn new(event: SocketEvent) -> Self {
+        SocketEventLine {
+            version: SOCKET_PROTOCOL_VERSION,
+            event,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Event filter registered by a Subscribe request. `None` means no filtering.
+/// The box filter only applies to box-scoped events; app-wide events such as
+/// `layout_switched` are filtered by type alone.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SocketSubscription {
+    pub box_ids: Option<Vec<String>>,
+    pub events: Option<Vec<SocketEventType>>,
+}
+
+impl SocketSubscription {
+    pub fn matches(&self, event: &SocketEvent) -> bool {
+        if let Some(events) = &self.events {
+            if !events.contains(&event.event_type()) {
+                return false;
+            }
+        }
+        match (&self.box_ids, event.box_id()) {
+            (Some(box_ids), Some(box_id)) => box_ids.iter().any(|id| id == box_id),
+            _ => true,
+        }
+    }
+}
+
+/// Exit code and success of an update that finishes its stream, `None` while more
+/// updates are expected
+pub(crate) fn stream_update_outcome(update: &StreamUpdate) -> Option<(Option<i32>, bool)> {
+    if update.source_state.expects_more_updates() {
+        return None;
+    }
+    match &update.source_state {
+        // A failure such as a timeout finishes the stream even without an exit code
+        SourceState::Batch(state) => match &state.status {
+            BatchStatus::Failed(_) => Some((state.exit_code, false)),
+            _ => state.exit_code.map(|code| (Some(code), code == 0)),
+        },
+        SourceState::Thread(state) => match &state.status {
+            ExecutionThreadStatus::Failed(_) => Some((state.exit_code, false)),
+            _ => state.exit_code.map(|code| (Some(code), code == 0)),
+        },
+        SourceState::Pty(state) => match &state.status {
+            // A completed PTY without an exit code is the trailing screen flush, not the exit
+            ExecutionPtyStatus::Completed => state.exit_code.map(|code| (Some(code), code == 0)),
+            ExecutionPtyStatus::Failed(_) | ExecutionPtyStatus::Terminated => {
+                Some((state.exit_code, false))
+            }
+            ExecutionPtyStatus::Starting | ExecutionPtyStatus::Running => None,
+        },
+    }
+}
+
+/// Map an internal message to the events subscribers can see
+pub fn socket_events_for_message(message: &Message) -> Vec<SocketEvent> {
+    match message {
+        Message::ChoiceExecuted(box_id, choice_id) => vec![SocketEvent::ChoiceExecuted {
+            box_id: box_id.clone(),
+            choice_id: choice_id.clone(),
+        }],
+        Message::SwitchActiveLayout(layout_id) => vec![SocketEvent::LayoutSwitched {
+            layout_id: layout_id.clone(),
+        }],
+        Message::AddBox(layout_id, muxbox) => vec![SocketEvent::BoxAdded {
+            box_id: muxbox.id.clone(),
+            layout_id: layout_id.clone(),
+        }],
+        Message::RemoveBox(box_id) => vec![SocketEvent::BoxRemoved {
+            box_id: box_id.clone(),
+        }],
+        Message::StartBoxRefresh(box_id) => vec![SocketEvent::RefreshStarted {
+            box_id: box_id.clone(),
+        }],
+        Message::StopBoxRefresh(box_id) => vec![SocketEvent::RefreshStopped {
+            box_id: box_id.clone(),
+        }],
+        Message::ReplaceMuxBox(box_id, _) => vec![SocketEvent::BoxContentChanged {
+            box_id: box_id.clone(),
+            stream_id: None,
+        }],
+        Message::UpdateStreamContent(box_id, stream_id, _) => {
+            vec![SocketEvent::BoxContentChanged {
+                box_id: box_id.clone(),
+                stream_id: Some(stream_id.clone()),
+            }]
+        }
+        Message::StreamUpdateMessage(update) => {
+            let mut events = Vec::new();
+            if !update.content_update.is_empty() {
+                events.push(SocketEvent::BoxContentChanged {
+                    box_id: update.target_box_id.clone(),
+                    stream_id: Some(update.stream_id.clone()),
+                });
+            }
+            if let Some((exit_code, success)) = stream_update_outcome(update) {
+                events.push(SocketEvent::StreamFinished {
+                    box_id: update.target_box_id.clone(),
+                    stream_id: update.stream_id.clone(),
+                    exit_code,
+                    success,
+                });
+                if matches!(update.source_state, SourceState::Pty(_)) {
+                    events.push(SocketEvent::PtyExited {
+                        box_id: update.target_box_id.clone(),
+                        stream_id: update.stream_id.clone(),
+                        exit_code,
+                        success,
+                    });
+                }
+            }
+            events
+        }
+        Message::AlertChanged(box_id, level, message) => vec![SocketEvent::AlertChanged {
+            box_id: box_id.clone(),
+            level: *level,
+            message: message.clone(),
+        }],
+        Message::Exit | Message::Terminate => vec![SocketEvent::Shutdown],
+        _ => Vec::new(),
+    }
+}
+
+/// Send a Subscribe request and pass each event line to `on_line` until the server
+/// closes the connection or `on_line` returns false. Returns the Subscribe response
+/// if it was an error.
+pub fn subscribe_to_socket(
+    socket_path: &str,
+    request: &SocketRequest,
+    mut on_line: impl FnMut(&str) -> bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut stream = UnixStream::connect(socket_path)?;
+    stream.write_all(serde_json::to_string(request)?.as_bytes())?;
+    stream.write_all(b"\n")?;
+    // Nothing more to send; the server keeps subscribed connections open after EOF
+    stream.shutdown(std::net::Shutdown::Write)?;
+
+    let reader = BufReader::new(stream);
+    let mut lines = reader.lines();
+    if let Some(first) = lines.next() {
+        let first = first?;
+        let response = serde_json::from_str::<SocketResponse>(first.trim())
+            .map_err(|e| format!("Invalid response from boxmux ({}): {}", e, first.trim()))?;
+        if !response.is_ok() {
+            return Err(response.to_json().into());
+        }
+        if !on_line(&first) {
+            return Ok(());
+        }
+    }
+    for line in lines {
+        if !on_line(&line?) {
+            break;
+        }
+    }
+    Ok(())
+}
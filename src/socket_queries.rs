@@ -0,0 +1,315 @@
+// Read-only control socket queries: snapshots of boxes, layouts, execution sources
+// and PTY scrollback, taken from the socket loop's copy of the AppContext.
+
+use crate::model::common::{ExecutionSourceStatus, ExecutionSourceType, StreamType};
+use crate::model::muxbox::MuxBox;
+use crate::refresh_scheduler::{self, RefreshStats};
+use crate::socket_protocol::{SocketError, SocketErrorCode};
+use crate::{AppContext, Bounds};
+use serde::{Deserialize, Serialize};
+
+/// Lines returned by `GetPtyBuffer` when no count is given
+pub const DEFAULT_PTY_BUFFER_LINES: usize = 100;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct StreamReport {
+    pub id: String,
+    pub label: String,
+    /// content, choices, redirected_output, pty, plugin, choice_execution,
+    /// redirect_source, external_socket, pty_session or own_script
+    pub kind: String,
+    pub selected: bool,
+    pub content: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct BoxContentReport {
+    pub box_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout_id: Option<String>,
+    /// Static `content` from the YAML, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_stream_id: Option<String>,
+    pub streams: Vec<StreamReport>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct BoxSummary {
+    pub box_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// Screen cells the box occupies at the current terminal size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<Bounds>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct LayoutSummary {
+    pub layout_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub active: bool,
+    pub root: bool,
+    pub box_count: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ExecutionSourceReport {
+    pub source_id: String,
+    pub stream_id: String,
+    pub box_id: String,
+    /// static_content, periodic_script, choice_execution, pty_process,
+    /// socket_update, hotkey_script, scheduled_script or choices_script
+    pub kind: String,
+    /// Choice ID, command or hotkey that started the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// pending, running, completed, failed or terminated
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Milliseconds since the Unix epoch
+    pub created_at_ms: u64,
+    /// Overlap and backoff counters of a periodic refresh script
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh: Option<RefreshStats>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct PtySearchMatch {
+    pub line: usize,
+    pub text: String,
+}
+
+fn stream_kind(stream_type: &StreamType) -> &'static str {
+    match stream_type {
+        StreamType::Content => "content",
+        StreamType::Choices => "choices",
+        StreamType::RedirectedOutput(_) => "redirected_output",
+        StreamType::PTY => "pty",
+        StreamType::Plugin(_) => "plugin",
+        StreamType::PluginLog(_) => "plugin_log",
+        StreamType::ChoiceExecution(_) => "choice_execution",
+        StreamType::RedirectSource(_) => "redirect_source",
+        StreamType::ExternalSocket => "external_socket",
+        StreamType::PtySession(_) => "pty_session",
+        StreamType::OwnScript => "own_script",
+    }
+}
+
+fn box_not_found(box_id: &str) -> SocketError {
+    SocketError::new(
+        SocketErrorCode::NotFound,
+        format!("Box {} not found", box_id),
+    )
+}
+
+/// Streams and static content of one box
+pub fn query_box_content(
+    app_context: &AppContext,
+    box_id: &str,
+) -> Result<BoxContentReport, SocketError> {
+    let muxbox = app_context
+        .app
+        .get_muxbox_by_id(box_id)
+        .ok_or_else(|| box_not_found(box_id))?;
+
+    let streams = muxbox
+        .streams
+        .values()
+        .map(|stream| StreamReport {
+            id: stream.id.clone(),
+            label: stream.label.clone(),
+            kind: stream_kind(&stream.stream_type).to_string(),
+            selected: muxbox.selected_stream_id.as_deref() == Some(stream.id.as_str()),
+            content: stream.content.clone(),
+        })
+        .collect();
+
+    Ok(BoxContentReport {
+        box_id: muxbox.id.clone(),
+        title: muxbox.title.clone(),
+        layout_id: muxbox.parent_layout_id.clone(),
+        content: muxbox.content.clone(),
+        selected_stream_id: muxbox.selected_stream_id.clone(),
+        streams,
+    })
+}
+
+/// Boxes of a layout (the active one by default) with their computed bounds.
+/// Returns the layout ID that was listed.
+pub fn query_boxes(
+    app_context: &AppContext,
+    layout_id: Option<&str>,
+) -> Result<(String, Vec<BoxSummary>), SocketError> {
+    let layout = match layout_id {
+        Some(layout_id) => app_context.app.get_layout_by_id(layout_id).ok_or_else(|| {
+            SocketError::new(
+                SocketErrorCode::NotFound,
+                format!("Layout {} not found", layout_id),
+            )
+        })?,
+        None => app_context
+            .app
+            .get_active_layout()
+            .ok_or_else(|| SocketError::new(SocketErrorCode::NotFound, "No active layout"))?,
+    };
+
+    // Bounds are computed on a copy so the query stays read-only
+    let mut app = app_context.app.clone();
+    let bounds = app.calculate_bounds();
+    let layout_bounds = bounds.get(&layout.id);
+
+    let boxes = layout
+        .get_all_muxboxes()
+        .into_iter()
+        .map(|muxbox: &MuxBox| BoxSummary {
+            box_id: muxbox.id.clone(),
+            title: muxbox.title.clone(),
+            parent_id: muxbox.parent_id.clone(),
+            bounds: layout_bounds.and_then(|bounds| bounds.get(&muxbox.id).copied()),
+        })
+        .collect();
+
+    Ok((layout.id.clone(), boxes))
+}
+
+/// All layouts plus the ID of the active one
+pub fn query_layouts(app_context: &AppContext) -> (Option<String>, Vec<LayoutSummary>) {
+    let layouts: Vec<LayoutSummary> = app_context
+        .app
+        .layouts
+        .iter()
+        .map(|layout| LayoutSummary {
+            layout_id: layout.id.clone(),
+            title: layout.title.clone(),
+            active: layout.active.unwrap_or(false),
+            root: layout.root.unwrap_or(false),
+            box_count: layout.get_all_muxboxes().len(),
+        })
+        .collect();
+    let active = layouts
+        .iter()
+        .find(|layout| layout.active)
+        .map(|layout| layout.layout_id.clone());
+    (active, layouts)
+}
+
+/// Registered execution sources, oldest first
+pub fn query_execution_sources(app_context: &AppContext) -> Vec<ExecutionSourceReport> {
+    let mut sources: Vec<_> = app_context.app.execution_sources.values().collect();
+    sources.sort_by(|a, b| {
+        a.created_at
+            .cmp(&b.created_at)
+            .then_with(|| a.source_id.cmp(&b.source_id))
+    });
+
+    sources
+        .into_iter()
+        .map(|source| {
+            let (kind, detail) = match &source.source_type {
+                ExecutionSourceType::StaticContent(_) => ("static_content", None),
+                ExecutionSourceType::PeriodicScript(_) => ("periodic_script", None),
+                ExecutionSourceType::ChoiceExecution { choice_id, .. } => {
+                    ("choice_execution", Some(choice_id.clone()))
+                }
+                ExecutionSourceType::PtyProcess { command, .. } => {
+                    ("pty_process", Some(command.join(" ")))
+                }
+                ExecutionSourceType::SocketUpdate { command_type } => {
+                    ("socket_update", Some(command_type.clone()))
+                }
+                ExecutionSourceType::HotkeyScript { hotkey, .. } => {
+                    ("hotkey_script", Some(hotkey.clone()))
+                }
+                ExecutionSourceType::ScheduledScript { choice_id, .. } => {
+                    ("scheduled_script", choice_id.clone())
+                }
+                ExecutionSourceType::ChoicesScript { .. } => ("choices_script", None),
+            };
+            let (status, error) = match &source.status {
+                ExecutionSourceStatus::Pending => ("pending", None),
+                ExecutionSourceStatus::Running => ("running", None),
+                ExecutionSourceStatus::Completed => ("completed", None),
+                ExecutionSourceStatus::Failed(error) => ("failed", Some(error.clone())),
+                ExecutionSourceStatus::Terminated => ("terminated", None),
+            };
+            ExecutionSourceReport {
+                source_id: source.source_id.clone(),
+                stream_id: source.stream_id.clone(),
+                box_id: source.target_box_id.clone(),
+                kind: kind.to_string(),
+                detail,
+                status: status.to_string(),
+                error,
+                created_at_ms: source
+                    .created_at
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|duration| duration.as_millis() as u64)
+                    .unwrap_or(0),
+                refresh: refresh_scheduler::stats(&source.stream_id),
+            }
+        })
+        .collect()
+}
+
+fn pty_buffer_unavailable(app_context: &AppContext, box_id: &str) -> SocketError {
+    if app_context.pty_manager.is_none() {
+        SocketError::new(SocketErrorCode::PtyUnavailable, "PTY manager not available")
+    } else {
+        SocketError::new(
+            SocketErrorCode::NotFound,
+            format!("No PTY process found for box {}", box_id),
+        )
+    }
+}
+
+/// Scrollback lines of a PTY box. Without `start`, returns the last `count` lines.
+/// Returns the start index used, the lines, and the total buffered line count.
+pub fn query_pty_buffer(
+    app_context: &AppContext,
+    box_id: &str,
+    start: Option<usize>,
+    count: Option<usize>,
+) -> Result<(usize, Vec<String>, usize), SocketError> {
+    let pty_manager = app_context
+        .pty_manager
+        .as_ref()
+        .ok_or_else(|| pty_buffer_unavailable(app_context, box_id))?;
+    let total = pty_manager
+        .get_detailed_process_info(box_id)
+        .map(|info| info.buffer_lines)
+        .ok_or_else(|| pty_buffer_unavailable(app_context, box_id))?;
+
+    let count = count.unwrap_or(DEFAULT_PTY_BUFFER_LINES);
+    let start = start.unwrap_or_else(|| total.saturating_sub(count));
+    let lines = pty_manager
+        .get_buffer_range(box_id, start, count)
+        .ok_or_else(|| pty_buffer_unavailable(app_context, box_id))?;
+    Ok((start, lines, total))
+}
+
+/// Case-insensitive search of a PTY box's scrollback
+pub fn query_pty_search(
+    app_context: &AppContext,
+    box_id: &str,
+    query: &str,
+) -> Result<Vec<PtySearchMatch>, SocketError> {
+    let pty_manager = app_context
+        .pty_manager
+        .as_ref()
+        .ok_or_else(|| pty_buffer_unavailable(app_context, box_id))?;
+    let matches = pty_manager
+        .search_buffer(box_id, query)
+        .ok_or_else(|| pty_buffer_unavailable(app_context, box_id))?;
+    Ok(matches
+        .into_iter()
+        .map(|(line, text)| PtySearchMatch { line, text })
+        .collect())
+}
@@ -0,0 +1,336 @@
+//! Structured script output.
+//!
+//! A box or choice with an `output_format` other than `text` has its finished output
+//! parsed here instead of being shown as is. JSON arrays of objects with a `content`
+//! field become the box's choices, label/value data becomes a chart, other arrays,
+//! objects and CSV become a table. The `choices` format turns each line into a choice.
+//! [`parse_stream_update`] is called by the draw loop for each finished update and
+//! [`apply_structured_output`] hands the result to the box's chart, table or choices
+//! renderer.
+
+use crate::components::DataPoint;
+use crate::model::choice::Choice;
+use crate::model::common::{OutputFormat, StreamUpdate};
+use crate::model::muxbox::MuxBox;
+use crate::socket_protocol::stream_update_outcome;
+use crate::table::{parse_table_data, parse_table_data_from_json, TableData};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Script output after parsing by its `output_format`
+#[derive(Debug, Clone)]
+pub enum StructuredOutput {
+    /// Shown as a text stream
+    Text(String),
+    Table(TableData),
+    Chart(Vec<DataPoint>),
+    Choices(Vec<Choice>),
+}
+
+/// Parse `output` according to `format`
+pub fn parse_output(format: OutputFormat, output: &str) -> Result<StructuredOutput, String> {
+    match format {
+        OutputFormat::Text => Ok(StructuredOutput::Text(output.to_string())),
+        OutputFormat::Json => {
+            let value = serde_json::from_str(output).map_err(|e| format!("invalid JSON: {}", e))?;
+            structure_json(value)
+        }
+        OutputFormat::Ndjson => {
+            let mut values = Vec::new();
+            for (index, line) in output.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let value = serde_json::from_str(line)
+                    .map_err(|e| format!("line {}: invalid JSON: {}", index + 1, e))?;
+                values.push(value);
+            }
+            structure_json(Value::Array(values))
+        }
+        OutputFormat::Csv => Ok(StructuredOutput::Table(parse_table_data(output, None))),
+        OutputFormat::KeyValue => parse_key_values(output),
+        OutputFormat::Choices => parse_choices(output).map(StructuredOutput::Choices),
+    }
+}
+
+/// Parse a finished, successful update of a script with a structured `output_format`.
+/// Returns `None` for text output, failed runs and updates still streaming. Output that
+/// fails to parse comes back as text led by the parse error.
+pub fn parse_stream_update(update: &StreamUpdate) -> Option<StructuredOutput> {
+    if update.output_format == OutputFormat::Text
+        || !matches!(stream_update_outcome(update), Some((_, true)))
+    {
+        return None;
+    }
+    match parse_output(update.output_format, &update.content_update) {
+        Ok(structured) => Some(structured),
+        Err(e) => {
+            log::warn!(
+                "Output of stream {} is not valid {:?}: {}",
+                update.stream_id,
+                update.output_format,
+                e
+            );
+            Some(StructuredOutput::Text(format!(
+                "Output format error: {}\n{}",
+                e, update.content_update
+            )))
+        }
+    }
+}
+
+/// Show structured output in `muxbox`: tables and charts through its `table_data` and
+/// `chart_data` (a bar chart unless `chart_type` is set), choices through its choices
+/// stream, with the box's `choices_item_script` for choices that have no script.
+/// Returns false for text, which the caller shows as a stream.
+pub fn apply_structured_output(muxbox: &mut MuxBox, output: StructuredOutput) -> bool {
+    match output {
+        StructuredOutput::Text(_) => return false,
+        StructuredOutput::Table(table) => match serde_json::to_string(&table) {
+            Ok(table_data) => muxbox.table_data = Some(table_data),
+            Err(e) => log::error!("Failed to store table for box {}: {}", muxbox.id, e),
+        },
+        StructuredOutput::Chart(points) => {
+            let chart_data: Vec<String> = points
+                .iter()
+                .map(|point| format!("{},{}", point.label.replace(',', " "), point.value))
+                .collect();
+            muxbox.chart_data = Some(chart_data.join("\n"));
+            if muxbox.chart_type.is_none() {
+                muxbox.chart_type = Some("bar".to_string());
+            }
+        }
+        StructuredOutput::Choices(mut choices) => {
+            if let Some(template) = &muxbox.choices_item_script {
+                for choice in choices.iter_mut().filter(|choice| choice.script.is_none()) {
+                    choice.script = Some(item_script(template, &choice.id));
+                }
+            }
+            muxbox.set_generated_choices(choices);
+        }
+    }
+    true
+}
+
+/// Build a choice from a JSON object with `content` and optional `id`, `script`,
+/// `execution_mode` and other choice fields. Choices without an `id` get `item_<index>`.
+pub fn choice_from_json(value: &Value, index: usize) -> Result<Choice, String> {
+    let Value::Object(map) = value else {
+        return Err(format!("item {}: expected an object", index));
+    };
+    let mut map = map.clone();
+    match map.get("id") {
+        Some(Value::String(_)) => {}
+        Some(Value::Number(id)) => {
+            let id = id.to_string();
+            map.insert("id".to_string(), Value::String(id));
+        }
+        _ => {
+            map.insert("id".to_string(), Value::String(format!("item_{}", index)));
+        }
+    }
+    serde_json::from_value(Value::Object(map)).map_err(|e| format!("item {}: {}", index, e))
+}
+
+/// `template` with each `${item}` replaced by `item`, quoted for the shell when needed
+pub fn item_script(template: &[String], item: &str) -> Vec<String> {
+    let quoted = shell_quote(item);
+    template
+        .iter()
+        .map(|line| line.replace("${item}", &quoted))
+        .collect()
+}
+
+fn shell_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:@%+=,".contains(c));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// A JSON array of choice objects or strings, or one choice per line where lines starting
+/// with `{` are choice objects. Items without an `id` use their content and vice versa;
+/// of items sharing an id only the first is kept.
+fn parse_choices(output: &str) -> Result<Vec<Choice>, String> {
+    let trimmed = output.trim_start();
+    let mut choices = Vec::new();
+    if trimmed.starts_with('[') {
+        let items: Vec<Value> =
+            serde_json::from_str(trimmed).map_err(|e| format!("invalid JSON: {}", e))?;
+        for (index, item) in items.iter().enumerate() {
+            choices.push(choice_item(item, index)?);
+        }
+    } else {
+        for (index, line) in output.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let item = if line.starts_with('{') {
+                serde_json::from_str(line)
+                    .map_err(|e| format!("line {}: invalid JSON: {}", index + 1, e))?
+            } else {
+                Value::String(line.to_string())
+            };
+            choices.push(choice_item(&item, index)?);
+        }
+    }
+    let mut seen = HashSet::new();
+    choices.retain(|choice| seen.insert(choice.id.clone()));
+    Ok(choices)
+}
+
+fn choice_item(item: &Value, index: usize) -> Result<Choice, String> {
+    let Value::Object(map) = item else {
+        let text = cell_text(item);
+        return Ok(Choice {
+            id: text.clone(),
+            content: Some(text),
+            ..Default::default()
+        });
+    };
+    let mut map = map.clone();
+    match (
+        map.get("id").map(cell_text),
+        map.get("content").map(cell_text),
+    ) {
+        (None, Some(content)) => {
+            map.insert("id".to_string(), Value::String(content));
+        }
+        (Some(id), None) => {
+            map.insert("content".to_string(), Value::String(id));
+        }
+        _ => {}
+    }
+    choice_from_json(&Value::Object(map), index)
+}
+
+fn structure_json(value: Value) -> Result<StructuredOutput, String> {
+    match value {
+        Value::Array(items) => {
+            if !items.is_empty() && items.iter().all(is_choice_object) {
+                let choices = items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| choice_from_json(item, index))
+                    .collect::<Result<_, _>>()?;
+                return Ok(StructuredOutput::Choices(choices));
+            }
+            if let Some(points) = chart_points(&items) {
+                return Ok(StructuredOutput::Chart(points));
+            }
+            if items.iter().all(Value::is_object) {
+                let table = parse_table_data_from_json(&Value::Array(items).to_string())
+                    .map_err(|e| e.to_string())?;
+                return Ok(StructuredOutput::Table(table));
+            }
+            if items.iter().all(Value::is_array) {
+                let mut rows = items.iter().map(|row| match row {
+                    Value::Array(cells) => cells.iter().map(cell_text).collect::<Vec<_>>(),
+                    _ => Vec::new(),
+                });
+                let headers = rows.next().unwrap_or_default();
+                return Ok(StructuredOutput::Table(table(headers, rows.collect())));
+            }
+            let lines: Vec<String> = items.iter().map(cell_text).collect();
+            Ok(StructuredOutput::Text(lines.join("\n")))
+        }
+        Value::Object(map) => Ok(structure_pairs(
+            map.iter()
+                .map(|(key, value)| (key.clone(), cell_text(value)))
+                .collect(),
+        )),
+        scalar => Ok(StructuredOutput::Text(cell_text(&scalar))),
+    }
+}
+
+/// `key=value` or `key: value` lines, whichever separator comes first
+fn parse_key_values(output: &str) -> Result<StructuredOutput, String> {
+    let mut pairs = Vec::new();
+    for (index, line) in output.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some(separator) = line.find(['=', ':']) else {
+            return Err(format!(
+                "line {}: expected key=value or key: value",
+                index + 1
+            ));
+        };
+        pairs.push((
+            line[..separator].trim().to_string(),
+            line[separator + 1..].trim().to_string(),
+        ));
+    }
+    Ok(structure_pairs(pairs))
+}
+
+/// A chart when every value is a number, otherwise a Key/Value table
+fn structure_pairs(pairs: Vec<(String, String)>) -> StructuredOutput {
+    let points: Option<Vec<DataPoint>> = pairs
+        .iter()
+        .map(|(label, value)| {
+            value.parse::<f64>().ok().map(|value| DataPoint {
+                label: label.clone(),
+                value,
+            })
+        })
+        .collect();
+    match points {
+        Some(points) if !points.is_empty() => StructuredOutput::Chart(points),
+        _ => StructuredOutput::Table(table(
+            vec!["Key".to_string(), "Value".to_string()],
+            pairs
+                .into_iter()
+                .map(|(key, value)| vec![key, value])
+                .collect(),
+        )),
+    }
+}
+
+fn is_choice_object(value: &Value) -> bool {
+    value.get("content").is_some_and(Value::is_string)
+}
+
+/// `{"label": .., "value": <number>}` objects or `[label, <number>]` pairs
+fn chart_points(items: &[Value]) -> Option<Vec<DataPoint>> {
+    if items.is_empty() {
+        return None;
+    }
+    items
+        .iter()
+        .map(|item| {
+            let (label, value) = match item {
+                Value::Object(map) if map.len() == 2 => (map.get("label")?, map.get("value")?),
+                Value::Array(pair) if pair.len() == 2 => (&pair[0], &pair[1]),
+                _ => return None,
+            };
+            Some(DataPoint {
+                label: cell_text(label),
+                value: value.as_f64()?,
+            })
+        })
+        .collect()
+}
+
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn table(headers: Vec<String>, rows: Vec<Vec<String>>) -> TableData {
+    TableData {
+        headers,
+        rows,
+        metadata: HashMap::new(),
+    }
+}
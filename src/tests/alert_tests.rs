@@ -0,0 +1,372 @@
+#[cfg(test)]
+mod alert_tests {
+    use crate::alerts::{apply_alerts, border_lit, extract_value, matching_rule, FLASH_DURATION};
+    use crate::model::common::{
+        AlertLevel, AlertRule, BatchSourceState, BatchStatus, ExecuteScript, ExecutionMode,
+        ExecutionSource, ExitCodeMatch, OutputFormat, ScriptEnvironment, SourceReference,
+        SourceState, SourceType, StreamUpdate,
+    };
+    use crate::refresh_scheduler::RunOutcome;
+    use crate::socket_protocol::{socket_events_for_message, SocketEventLine};
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::thread_manager::{run_immediate_script, run_threaded_script, Message};
+    use crate::{AppContext, Config};
+    use std::time::{Duration, SystemTime};
+
+    const ALERTS_YAML: &str = r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'load'
+          title: 'Load'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          border_color: 'white'
+          error_border_color: 'bright_red'
+          script: ['uptime']
+          alerts:
+            - exit_code: nonzero
+              message: 'uptime failed'
+            - value: 'load average: ([0-9.]+)'
+              above: 8
+              level: error
+              color: 'magenta'
+              flash: true
+              bell: true
+              message: 'load ${value}'
+            - value: 'load average: ([0-9.]+)'
+              above: 4
+              level: warning
+              message: 'load ${value}'
+            - level: ok
+"#;
+
+    fn finished(content: &str, exit_code: i32) -> StreamUpdate {
+        StreamUpdate {
+            stream_id: "stream".to_string(),
+            target_box_id: "load".to_string(),
+            content_update: content.to_string(),
+            source_state: SourceState::Batch(BatchSourceState {
+                task_id: "stream".to_string(),
+                queue_wait_time: Duration::from_millis(0),
+                execution_time: Duration::from_millis(5),
+                exit_code: Some(exit_code),
+                status: BatchStatus::Completed,
+            }),
+            execution_mode: ExecutionMode::Immediate,
+            output_format: OutputFormat::Text,
+        }
+    }
+
+    fn rule(exit_code: Option<ExitCodeMatch>, pattern: Option<&str>) -> AlertRule {
+        AlertRule {
+            exit_code,
+            pattern: pattern.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_exit_code_and_pattern_conditions() {
+        let rules = vec![
+            rule(Some(ExitCodeMatch::Codes(vec![2, 3])), None),
+            rule(Some(ExitCodeMatch::Code(1)), Some("disk full")),
+            rule(Some(ExitCodeMatch::Named("nonzero".to_string())), None),
+            rule(None, Some(r"WARN\b")),
+        ];
+        assert_eq!(matching_rule(&rules, Some(3), false, ""), Some((0, None)));
+        assert_eq!(
+            matching_rule(&rules, Some(1), false, "error: disk full"),
+            Some((1, None))
+        );
+        assert_eq!(
+            matching_rule(&rules, Some(1), false, "oops"),
+            Some((2, None))
+        );
+        assert_eq!(
+            matching_rule(&rules, None, false, "killed"),
+            Some((2, None)),
+            "runs killed on timeout count as nonzero"
+        );
+        assert_eq!(
+            matching_rule(&rules, Some(0), true, "WARN low"),
+            Some((3, None))
+        );
+        assert_eq!(matching_rule(&rules, Some(0), true, "WARNING"), None);
+    }
+
+    #[test]
+    fn test_value_thresholds_use_the_last_match() {
+        assert_eq!(extract_value(r"cpu=(\d+)", "cpu=10\ncpu=95\n"), Some(95.0));
+        assert_eq!(extract_value(r"\d+\.\d+", "took 1.25s"), Some(1.25));
+        assert_eq!(extract_value(r"cpu=(\w+)", "cpu=high"), None);
+
+        let rules = vec![AlertRule {
+            value: Some(r"temp (\d+)".to_string()),
+            above: Some(60.0),
+            below: Some(90.0),
+            ..Default::default()
+        }];
+        assert_eq!(
+            matching_rule(&rules, Some(0), true, "temp 75"),
+            Some((0, Some(75.0)))
+        );
+        assert_eq!(matching_rule(&rules, Some(0), true, "temp 60"), None);
+        assert_eq!(matching_rule(&rules, Some(0), true, "temp 95"), None);
+        assert_eq!(matching_rule(&rules, Some(0), true, "no reading"), None);
+    }
+
+    #[test]
+    fn test_apply_alerts_reports_only_rule_changes() {
+        let mut app = TestDataFactory::load_app_from_yaml_str(ALERTS_YAML).unwrap();
+        let muxbox = app.get_muxbox_by_id_mut("load").unwrap();
+        let now = SystemTime::now();
+
+        let change = apply_alerts(muxbox, &finished("load average: 5.5", 0), now).unwrap();
+        assert_eq!(change.level, Some(AlertLevel::Warning));
+        assert_eq!(change.message.as_deref(), Some("load 5.5"));
+        assert!(!change.bell);
+        let alert = muxbox.alert.clone().unwrap();
+        assert_eq!(alert.color.as_deref(), Some("yellow"));
+        assert_eq!(alert.flash, None);
+
+        assert_eq!(
+            apply_alerts(muxbox, &finished("load average: 6.25", 0), now),
+            None,
+            "the same rule matching again is not a change"
+        );
+        assert_eq!(
+            muxbox.alert.as_ref().unwrap().message.as_deref(),
+            Some("load 6.25")
+        );
+
+        let change = apply_alerts(muxbox, &finished("load average: 9", 0), now).unwrap();
+        assert_eq!(change.level, Some(AlertLevel::Error));
+        assert!(change.bell);
+        let alert = muxbox.alert.clone().unwrap();
+        assert_eq!(alert.color.as_deref(), Some("magenta"));
+        assert_eq!(alert.flash, Some((now, now + FLASH_DURATION)));
+
+        let change = apply_alerts(muxbox, &finished("", 127), now).unwrap();
+        assert_eq!(change.message.as_deref(), Some("uptime failed"));
+        assert_eq!(muxbox.alert.as_ref().unwrap().color, None);
+
+        let mut still_running = finished("load average: 1", 0);
+        still_running.source_state = SourceState::Batch(BatchSourceState {
+            exit_code: None,
+            status: BatchStatus::Executing,
+            ..match still_running.source_state {
+                SourceState::Batch(state) => state,
+                _ => unreachable!(),
+            }
+        });
+        assert_eq!(apply_alerts(muxbox, &still_running, now), None);
+
+        let change = apply_alerts(muxbox, &finished("load average: 1", 0), now).unwrap();
+        assert_eq!(change.level, Some(AlertLevel::Ok));
+    }
+
+    /// Box script run the way the thread manager runs it
+    fn script_run(script: &str, execution_mode: ExecutionMode) -> ExecuteScript {
+        ExecuteScript {
+            script: vec![script.to_string()],
+            source: ExecutionSource {
+                source_type: SourceType::StaticScript,
+                source_id: "job_script".to_string(),
+                source_reference: SourceReference::StaticConfig("job".to_string()),
+            },
+            execution_mode,
+            target_box_id: "job".to_string(),
+            libs: vec![],
+            redirect_output: None,
+            append_output: false,
+            stream_id: uuid::Uuid::new_v4().to_string(),
+            target_bounds: None,
+            environment: ScriptEnvironment::default(),
+            timeout_ms: None,
+            output_format: OutputFormat::Text,
+            record: None,
+        }
+    }
+
+    #[test]
+    fn test_exit_code_rules_fire_for_real_script_runs() {
+        let mut app = TestDataFactory::load_app_from_yaml_str(
+            r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'job'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          script: ['exit 2']
+          alerts:
+            - exit_code: 3
+              message: 'three'
+            - exit_code: [2, 4]
+              message: 'two or four'
+            - exit_code: nonzero
+              message: 'failed'
+            - level: ok
+"#,
+        )
+        .unwrap();
+        let muxbox = app.get_muxbox_by_id_mut("job").unwrap();
+        let now = SystemTime::now();
+
+        for execution_mode in [ExecutionMode::Immediate, ExecutionMode::Thread] {
+            let run = |script: &str| {
+                let execute_script = script_run(script, execution_mode.clone());
+                let (update, outcome) = match execution_mode {
+                    ExecutionMode::Thread => run_threaded_script(&execute_script),
+                    _ => run_immediate_script(&execute_script),
+                };
+                let expected = if script == "true" {
+                    RunOutcome::Succeeded
+                } else {
+                    RunOutcome::Failed
+                };
+                assert_eq!(outcome, expected, "{}", script);
+                update.unwrap()
+            };
+
+            let change = apply_alerts(muxbox, &run("exit 2"), now).unwrap();
+            assert_eq!(
+                change.message.as_deref(),
+                Some("two or four"),
+                "{:?}",
+                execution_mode
+            );
+            let change = apply_alerts(muxbox, &run("exit 5"), now).unwrap();
+            assert_eq!(
+                change.message.as_deref(),
+                Some("failed"),
+                "{:?}",
+                execution_mode
+            );
+            let change = apply_alerts(muxbox, &run("true"), now).unwrap();
+            assert_eq!(change.level, Some(AlertLevel::Ok), "{:?}", execution_mode);
+        }
+    }
+
+    #[test]
+    fn test_alert_styles_border_title_and_tab_indicator() {
+        let mut app = TestDataFactory::load_app_from_yaml_str(ALERTS_YAML).unwrap();
+        let muxbox = app.get_muxbox_by_id_mut("load").unwrap();
+        muxbox.streams.clear();
+        muxbox.add_stream_with_source(
+            crate::model::common::StreamType::Content,
+            "Load".to_string(),
+            crate::model::common::StreamSource::create_immediate_execution_source(
+                "stream".to_string(),
+                "load".to_string(),
+                vec!["uptime".to_string()],
+            ),
+        );
+        apply_alerts(muxbox, &finished("load average: 5", 0), SystemTime::now());
+        assert_eq!(muxbox.get_tab_labels()[0], "⚠️ load 5 Load");
+        assert!(!muxbox.in_error_state());
+
+        let app_context = AppContext::new(app.clone(), Config::default());
+        let app_graph = app.generate_graph();
+        let muxbox = app.get_muxbox_by_id("load").unwrap();
+        assert_eq!(
+            muxbox
+                .calc_border_color(&app_context, &app_graph)
+                .as_deref(),
+            Some("yellow")
+        );
+        assert_eq!(
+            muxbox
+                .calc_title_fg_color(&app_context, &app_graph)
+                .as_deref(),
+            Some("yellow")
+        );
+
+        let mut app = app;
+        let muxbox = app.get_muxbox_by_id_mut("load").unwrap();
+        apply_alerts(muxbox, &finished("", 1), SystemTime::now());
+        assert!(muxbox.in_error_state());
+        assert!(muxbox.get_tab_labels()[0].starts_with("❌ uptime failed"));
+        let app_context = AppContext::new(app.clone(), Config::default());
+        let app_graph = app.generate_graph();
+        let muxbox = app.get_muxbox_by_id("load").unwrap();
+        assert_eq!(
+            muxbox
+                .calc_border_color(&app_context, &app_graph)
+                .as_deref(),
+            Some("bright_red"),
+            "error rules without a color use the box's error colors"
+        );
+    }
+
+    #[test]
+    fn test_flashing_border_alternates_then_stays_lit() {
+        let mut app = TestDataFactory::load_app_from_yaml_str(ALERTS_YAML).unwrap();
+        let muxbox = app.get_muxbox_by_id_mut("load").unwrap();
+        let start = SystemTime::now();
+        apply_alerts(muxbox, &finished("load average: 12", 0), start);
+        let alert = muxbox.alert.clone().unwrap();
+
+        assert!(border_lit(&alert, start));
+        assert!(!border_lit(&alert, start + Duration::from_millis(600)));
+        assert!(border_lit(&alert, start + Duration::from_millis(1100)));
+        assert!(border_lit(
+            &alert,
+            start + FLASH_DURATION + Duration::from_millis(600)
+        ));
+    }
+
+    #[test]
+    fn test_alert_changes_become_socket_events() {
+        let events = socket_events_for_message(&Message::AlertChanged(
+            "load".to_string(),
+            Some(AlertLevel::Warning),
+            Some("load 5".to_string()),
+        ));
+        assert_eq!(
+            SocketEventLine::new(events[0].clone()).to_json(),
+            r#"{"version":1,"event":"alert_changed","box_id":"load","level":"warning","message":"load 5"}"#
+        );
+
+        let cleared =
+            socket_events_for_message(&Message::AlertChanged("load".to_string(), None, None));
+        assert_eq!(
+            SocketEventLine::new(cleared[0].clone()).to_json(),
+            r#"{"version":1,"event":"alert_changed","box_id":"load"}"#
+        );
+    }
+
+    #[test]
+    fn test_invalid_alert_rules_fail_to_load() {
+        let yaml = |rule: &str| {
+            format!(
+                r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'load'
+          position: {{x1: 0%, y1: 0%, x2: 100%, y2: 100%}}
+          script: ['uptime']
+          alerts:
+            - {}
+"#,
+                rule
+            )
+        };
+
+        let err =
+            TestDataFactory::load_app_from_yaml_str(&yaml("pattern: '(unclosed'")).unwrap_err();
+        assert!(err.contains("alerts[0].pattern"), "{}", err);
+        let err =
+            TestDataFactory::load_app_from_yaml_str(&yaml("exit_code: sometimes")).unwrap_err();
+        assert!(err.contains("alerts/0/exit_code"), "{}", err);
+        let err = TestDataFactory::load_app_from_yaml_str(&yaml("above: 3")).unwrap_err();
+        assert!(err.contains("alerts[0].value"), "{}", err);
+        assert!(TestDataFactory::load_app_from_yaml_str(&yaml("level: ok")).is_ok());
+    }
+}
@@ -0,0 +1,185 @@
+#[cfg(test)]
+mod attach_tests {
+    use crate::attach::{open_pty, take_utf8, AttachFrame, AttachSession};
+    use crate::model::common::SocketFunction;
+    use crate::socket_loop::SocketServer;
+    use crate::socket_protocol::*;
+    use crate::tests::test_utils::TestDataFactory;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    fn read_frame(reader: &mut BufReader<UnixStream>) -> AttachFrame {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn client_pair() -> (UnixStream, BufReader<UnixStream>) {
+        let (server_side, client_side) = UnixStream::pair().unwrap();
+        client_side
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        (server_side, BufReader::new(client_side))
+    }
+
+    fn terminal_size(fd: i32) -> (u16, u16) {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        assert_eq!(unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) }, 0);
+        (size.ws_col, size.ws_row)
+    }
+
+    /// Deliver keystrokes to the slave unbuffered and unechoed, as the TUI's raw mode does
+    fn make_raw(fd: i32) {
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            assert_eq!(libc::tcgetattr(fd, &mut termios), 0);
+            libc::cfmakeraw(&mut termios);
+            assert_eq!(libc::tcsetattr(fd, libc::TCSANOW, &termios), 0);
+        }
+    }
+
+    #[test]
+    fn test_attach_frames_are_json_lines() {
+        assert_eq!(
+            AttachFrame::Output("\x1b[2Jhi".to_string()).to_line(),
+            r#"{"output":"\u001b[2Jhi"}"#
+        );
+        assert_eq!(
+            AttachFrame::Resize {
+                cols: 120,
+                rows: 40
+            }
+            .to_line(),
+            r#"{"resize":{"cols":120,"rows":40}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<AttachFrame>(r#"{"input":"q"}"#).unwrap(),
+            AttachFrame::Input("q".to_string())
+        );
+    }
+
+    #[test]
+    fn test_take_utf8_keeps_cut_off_characters() {
+        let mut pending = "ab€".as_bytes()[..4].to_vec();
+        assert_eq!(take_utf8(&mut pending), "ab");
+        assert_eq!(pending.len(), 2);
+        pending.extend_from_slice(&"€".as_bytes()[2..]);
+        assert_eq!(take_utf8(&mut pending), "€");
+        assert!(pending.is_empty());
+
+        let mut invalid = vec![b'x', 0xff, b'y'];
+        assert_eq!(take_utf8(&mut invalid), "x\u{fffd}y");
+        assert!(invalid.is_empty());
+    }
+
+    #[test]
+    fn test_session_relays_terminal_to_attached_client() {
+        let (master, mut slave) = open_pty(80, 24).unwrap();
+        make_raw(slave.as_raw_fd());
+        let session = AttachSession::start(master).unwrap();
+        assert!(!session.is_attached());
+
+        let (server_side, mut client) = client_pair();
+        session.attach(1, server_side, 100, 30).unwrap();
+        assert!(session.is_attached());
+        assert_eq!(terminal_size(slave.as_raw_fd()), (100, 30));
+
+        slave.write_all(b"hello").unwrap();
+        let mut output = String::new();
+        while output.len() < 5 {
+            match read_frame(&mut client) {
+                AttachFrame::Output(text) => output.push_str(&text),
+                other => panic!("Expected output, got {:?}", other),
+            }
+        }
+        assert_eq!(output, "hello");
+
+        session
+            .handle_client_frame(AttachFrame::Input("q".to_string()))
+            .unwrap();
+        let mut key = [0u8; 1];
+        slave.read_exact(&mut key).unwrap();
+        assert_eq!(&key, b"q");
+
+        session
+            .handle_client_frame(AttachFrame::Resize { cols: 90, rows: 20 })
+            .unwrap();
+        assert_eq!(terminal_size(slave.as_raw_fd()), (90, 20));
+
+        assert!(session.detach("detached by boxmux detach"));
+        assert_eq!(
+            read_frame(&mut client),
+            AttachFrame::Detached("detached by boxmux detach".to_string())
+        );
+        assert!(!session.is_attached());
+        assert!(!session.detach("again"));
+    }
+
+    #[test]
+    fn test_new_client_replaces_attached_client() {
+        let (master, _slave) = open_pty(80, 24).unwrap();
+        let session = AttachSession::start(master).unwrap();
+
+        let (first_side, mut first) = client_pair();
+        session.attach(1, first_side, 80, 24).unwrap();
+        let (second_side, _second) = client_pair();
+        session.attach(2, second_side, 80, 24).unwrap();
+        assert_eq!(
+            read_frame(&mut first),
+            AttachFrame::Detached("attached from another terminal".to_string())
+        );
+
+        session.client_closed(1);
+        assert!(
+            session.is_attached(),
+            "closing the old client keeps the new one"
+        );
+        session.client_closed(2);
+        assert!(!session.is_attached());
+    }
+
+    #[test]
+    fn test_attach_and_detach_need_a_detached_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("boxmux-attach-test.sock");
+        let mut server = SocketServer::bind(&path).unwrap();
+        let app_context = TestDataFactory::create_test_app_context();
+        let stream = UnixStream::connect(&path).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+        for (id, function) in [
+            ("attach", SocketFunction::Attach { cols: 80, rows: 24 }),
+            ("detach", SocketFunction::Detach {}),
+        ] {
+            let request = SocketRequest {
+                version: SOCKET_PROTOCOL_VERSION,
+                id: Some(id.to_string()),
+                token: None,
+                function,
+            };
+            writeln!(&stream, "{}", serde_json::to_string(&request).unwrap()).unwrap();
+            let mut messages = Vec::new();
+            for _ in 0..10 {
+                messages.extend(server.poll(&app_context, Duration::from_millis(20)));
+            }
+            assert!(messages.is_empty());
+
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let response: SocketResponse = serde_json::from_str(&line).unwrap();
+            assert_eq!(response.id.as_deref(), Some(id));
+            match response.outcome {
+                SocketOutcome::Error { error } => {
+                    assert_eq!(error.code, SocketErrorCode::InvalidRequest);
+                    assert!(error.message.contains("--detached"), "{}", error.message);
+                }
+                other => panic!("Expected error outcome, got {:?}", other),
+            }
+        }
+    }
+}
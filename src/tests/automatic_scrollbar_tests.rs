@@ -91,6 +91,20 @@ mod tests {
                 selected: false,
                 hovered: false,
                 waiting: false,
+                cwd: None,
+                env: None,
+                shell: None,
+                inherit_env: None,
+                timeout_ms: None,
+                schedule: None,
+                output_format: None,
+                class: None,
+                menu_fg_color: None,
+                menu_bg_color: None,
+                selected_menu_fg_color: None,
+                selected_menu_bg_color: None,
+                highlighted_menu_fg_color: None,
+                highlighted_menu_bg_color: None,
             });
         }
         muxbox.choices = Some(choices);
@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod border_style_tests {
+    use crate::components::{BorderStyle, BoxRenderer, ComponentDimensions};
+    use crate::model::common::{BorderSides, Bounds};
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::{AppContext, Config, ScreenBuffer};
+    use std::collections::HashMap;
+
+    const BORDERED_YAML: &str = r#"
+app:
+  styles:
+    panel:
+      border_style: 'rounded'
+  layouts:
+    - id: 'main'
+      root: true
+      border_style: 'double'
+      border: [top, bottom]
+      children:
+        - id: 'outer'
+          position: {x1: 0%, y1: 0%, x2: 50%, y2: 100%}
+          border: {left: false}
+          children:
+            - id: 'inner'
+              position: {x1: 0%, y1: 0%, x2: 100%, y2: 50%}
+        - id: 'styled'
+          position: {x1: 50%, y1: 0%, x2: 100%, y2: 50%}
+          class: 'panel'
+          border: true
+        - id: 'bare'
+          position: {x1: 50%, y1: 50%, x2: 100%, y2: 100%}
+          border: none
+          content: 'edge'
+"#;
+
+    fn render(app: &mut crate::App, id: &str) -> Vec<String> {
+        let context = AppContext::new(app.clone(), Config::default());
+        let graph = app.generate_graph();
+        let muxbox = app.get_muxbox_by_id(id).unwrap().clone();
+        let adjusted_bounds = HashMap::from([(
+            "main".to_string(),
+            HashMap::from([(id.to_string(), Bounds::new(0, 0, 19, 5))]),
+        )]);
+        let mut buffer = ScreenBuffer::new_custom(24, 8);
+        let layout = app.get_layout_by_id("main").unwrap().clone();
+        BoxRenderer::new(&muxbox, id.to_string()).render(
+            &context,
+            &graph,
+            &adjusted_bounds,
+            &layout,
+            &mut buffer,
+        );
+        buffer
+            .buffer
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.ch).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_border_sides_yaml_forms() {
+        let parse = |yaml: &str| serde_yaml::from_str::<BorderSides>(yaml);
+        assert_eq!(parse("true").unwrap(), BorderSides::all());
+        assert_eq!(parse("all").unwrap(), BorderSides::all());
+        assert_eq!(parse("false").unwrap(), BorderSides::none());
+        assert_eq!(parse("none").unwrap(), BorderSides::none());
+        let top_bottom = BorderSides {
+            top: true,
+            right: false,
+            bottom: true,
+            left: false,
+        };
+        assert_eq!(parse("[top, bottom]").unwrap(), top_bottom);
+        assert_eq!(
+            parse("{right: false, left: false}").unwrap(),
+            top_bottom,
+            "unnamed sides keep their border"
+        );
+        assert!(parse("sides").is_err());
+        assert!(parse("[top, middle]").is_err());
+        assert!(parse("{up: true}").is_err());
+
+        for sides in [BorderSides::all(), BorderSides::none(), top_bottom] {
+            let yaml = serde_yaml::to_string(&sides).unwrap();
+            assert_eq!(parse(&yaml).unwrap(), sides, "{}", yaml);
+        }
+    }
+
+    #[test]
+    fn test_border_style_names() {
+        assert_eq!(BorderStyle::from_name("double"), Some(BorderStyle::Double));
+        assert_eq!(BorderStyle::from_name("thick"), Some(BorderStyle::Thick));
+        assert_eq!(
+            BorderStyle::from_name("rounded"),
+            Some(BorderStyle::Rounded)
+        );
+        assert_eq!(BorderStyle::from_name("single"), Some(BorderStyle::Single));
+        assert_eq!(BorderStyle::from_name("dotted"), None);
+    }
+
+    #[test]
+    fn test_border_settings_are_inherited() {
+        let app = TestDataFactory::load_app_from_yaml_str(BORDERED_YAML).unwrap();
+        let muxbox = |id: &str| app.get_muxbox_by_id(id).unwrap();
+
+        let outer = muxbox("outer");
+        assert_eq!(outer.border_style(), BorderStyle::Double, "from the layout");
+        assert_eq!(
+            outer.border_sides(),
+            BorderSides {
+                left: false,
+                ..BorderSides::all()
+            }
+        );
+        let inner = muxbox("inner");
+        assert_eq!(inner.border_style(), BorderStyle::Double);
+        assert_eq!(
+            inner.border_sides(),
+            outer.border_sides(),
+            "from the parent box before the layout"
+        );
+        assert_eq!(muxbox("styled").border_style(), BorderStyle::Rounded);
+        assert_eq!(muxbox("styled").border_sides(), BorderSides::all());
+        assert_eq!(muxbox("bare").border_sides(), BorderSides::none());
+    }
+
+    #[test]
+    fn test_border_style_glyphs_are_drawn() {
+        let mut app = TestDataFactory::load_app_from_yaml_str(BORDERED_YAML).unwrap();
+        let rows = render(&mut app, "styled");
+        let row = |y: usize| rows[y].chars().collect::<Vec<_>>();
+        assert_eq!(row(0)[0], '╭', "{:#?}", rows);
+        assert_eq!(row(5)[0], '╰', "{:#?}", rows);
+        assert_eq!(row(2)[0], '│', "{:#?}", rows);
+
+        let mut double = app.clone();
+        double.get_muxbox_by_id_mut("styled").unwrap().border_style = Some("double".into());
+        let rows = render(&mut double, "styled");
+        let row = |y: usize| rows[y].chars().collect::<Vec<_>>();
+        assert_eq!(row(5)[0], '╚', "{:#?}", rows);
+        assert_eq!(row(5)[5], '═', "{:#?}", rows);
+        assert_eq!(row(2)[0], '║', "{:#?}", rows);
+    }
+
+    #[test]
+    fn test_boxes_without_border_give_the_space_to_content() {
+        let mut app = TestDataFactory::load_app_from_yaml_str(BORDERED_YAML).unwrap();
+        let rows = render(&mut app, "bare");
+        assert!(rows.iter().any(|row| row.contains("edge")), "{:#?}", rows);
+        assert!(
+            !rows.iter().any(|row| row.contains(['─', '│', '┌', '┘'])),
+            "{:#?}",
+            rows
+        );
+    }
+
+    #[test]
+    fn test_content_bounds_follow_border_sides() {
+        let bounds = Bounds::new(0, 0, 20, 10);
+        let content = |sides: BorderSides| {
+            ComponentDimensions::new(bounds)
+                .with_border_sides(sides)
+                .content_bounds()
+        };
+        assert_eq!(content(BorderSides::all()), Bounds::new(2, 2, 18, 8));
+        assert_eq!(content(BorderSides::none()), Bounds::new(1, 1, 19, 9));
+        assert_eq!(
+            content(BorderSides {
+                top: false,
+                left: false,
+                ..BorderSides::all()
+            }),
+            Bounds::new(1, 1, 18, 8)
+        );
+
+        let app = TestDataFactory::load_app_from_yaml_str(BORDERED_YAML).unwrap();
+        let bare = app.get_muxbox_by_id("bare").unwrap();
+        let full = bare.bounds();
+        assert_eq!(
+            bare.content_bounds(),
+            Bounds::new(full.x1 + 1, full.y1 + 1, full.x2 - 1, full.y2 - 1),
+            "only the padding is left"
+        );
+        let mut titled = bare.clone();
+        titled.title = Some("Logs".into());
+        assert_eq!(
+            titled.content_bounds().top(),
+            full.top() + 2,
+            "the title keeps the top row"
+        );
+    }
+
+    #[test]
+    fn test_unknown_border_style_is_rejected() {
+        let error = TestDataFactory::load_app_from_yaml_str(
+            r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'box'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          border_style: 'dotted'
+"#,
+        )
+        .unwrap_err();
+        assert!(error.contains("border_style"), "{}", error);
+    }
+}
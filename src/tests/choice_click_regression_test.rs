@@ -21,6 +21,20 @@ fn test_choice_sensitive_zones_generation() {
             selected: false,
             hovered: false,
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         },
         Choice {
             id: "choice2".to_string(),
@@ -32,6 +46,20 @@ fn test_choice_sensitive_zones_generation() {
             selected: false,
             hovered: false,
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         },
     ];
 
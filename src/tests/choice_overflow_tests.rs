@@ -14,6 +14,13 @@ mod choice_overflow_tests {
             append_output: None,
             selected,
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
         }
     }
 
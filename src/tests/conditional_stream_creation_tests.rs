@@ -120,6 +120,20 @@ mod conditional_stream_creation_tests {
             selected: false,
             hovered: false,
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         }]);
 
         muxbox.initialize_streams();
@@ -158,6 +172,20 @@ mod conditional_stream_creation_tests {
             selected: false,
             hovered: false,
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         }]);
 
         muxbox.initialize_streams();
@@ -195,6 +223,20 @@ mod conditional_stream_creation_tests {
             selected: false,
             hovered: false,
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         }]);
 
         muxbox.initialize_streams();
@@ -257,6 +299,20 @@ mod conditional_stream_creation_tests {
             selected: false,
             hovered: false,
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         }]);
 
         muxbox.initialize_streams();
@@ -0,0 +1,326 @@
+#[cfg(test)]
+mod dynamic_choices_tests {
+    use crate::draw_loop::open_execution_stream;
+    use crate::dynamic_choices::{choices_script_execution, has_choices_scripts, ChoicesRefresher};
+    use crate::model::app::App;
+    use crate::model::common::{
+        ActionType, BatchSourceState, BatchStatus, ExecutionMode, OutputFormat, SourceAction,
+        SourceState, SourceType, StreamUpdate,
+    };
+    use crate::structured_output::{
+        apply_structured_output, item_script, parse_output, parse_stream_update, StructuredOutput,
+    };
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::thread_manager::Message;
+    use std::time::{Duration, Instant};
+
+    fn dynamic_app() -> App {
+        TestDataFactory::load_app_from_yaml_str(
+            r#"
+app:
+  variables:
+    NAMESPACE: 'prod'
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'branches'
+          position: {x1: 0%, y1: 0%, x2: 50%, y2: 100%}
+          choices_script: git branch --format='%(refname:short)'
+          choices_item_script: git checkout ${item}
+        - id: 'pods'
+          position: {x1: 50%, y1: 0%, x2: 100%, y2: 100%}
+          timeout_ms: 4000
+          choices_script: ['kubectl get pods -n ${NAMESPACE} -o name']
+          choices_item_script: ['kubectl logs -n ${NAMESPACE} ${item}']
+          choices_refresh_interval: 5000
+"#,
+        )
+        .unwrap()
+    }
+
+    fn choices(output: &str) -> Vec<(String, Option<String>, Option<Vec<String>>)> {
+        match parse_output(OutputFormat::Choices, output).unwrap() {
+            StructuredOutput::Choices(choices) => choices
+                .into_iter()
+                .map(|choice| (choice.id, choice.content, choice.script))
+                .collect(),
+            other => panic!("Expected choices, got {:?}", other),
+        }
+    }
+
+    fn finished(box_id: &str, stream_id: &str) -> Message {
+        Message::StreamUpdateMessage(StreamUpdate {
+            stream_id: stream_id.to_string(),
+            target_box_id: box_id.to_string(),
+            content_update: "main\n".to_string(),
+            source_state: SourceState::Batch(BatchSourceState {
+                task_id: stream_id.to_string(),
+                queue_wait_time: Duration::from_millis(0),
+                execution_time: Duration::from_millis(20),
+                exit_code: Some(0),
+                status: BatchStatus::Completed,
+            }),
+            execution_mode: ExecutionMode::Thread,
+            output_format: OutputFormat::Choices,
+        })
+    }
+
+    #[test]
+    fn test_lines_become_choices_without_duplicates() {
+        assert_eq!(
+            choices("main\n\n  feature/login \nmain\n"),
+            vec![
+                ("main".to_string(), Some("main".to_string()), None),
+                (
+                    "feature/login".to_string(),
+                    Some("feature/login".to_string()),
+                    None
+                ),
+            ]
+        );
+
+        let mixed = choices(
+            "plain\n{\"id\": \"api-1\", \"content\": \"api-1 Running\", \"script\": \"kubectl describe pod api-1\"}\n",
+        );
+        assert_eq!(mixed[1].0, "api-1");
+        assert_eq!(mixed[1].1.as_deref(), Some("api-1 Running"));
+        assert_eq!(
+            mixed[1].2,
+            Some(vec!["kubectl describe pod api-1".to_string()])
+        );
+
+        let err = parse_output(OutputFormat::Choices, "ok\n{broken").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{}", err);
+    }
+
+    #[test]
+    fn test_json_array_items_fill_in_id_and_content() {
+        assert_eq!(
+            choices(
+                r#"["web", {"id": "db"}, {"content": "cache"}, {"id": 3, "content": "Three"}]"#
+            ),
+            vec![
+                ("web".to_string(), Some("web".to_string()), None),
+                ("db".to_string(), Some("db".to_string()), None),
+                ("cache".to_string(), Some("cache".to_string()), None),
+                ("3".to_string(), Some("Three".to_string()), None),
+            ]
+        );
+        assert!(parse_output(OutputFormat::Choices, "[\"unterminated\"")
+            .unwrap_err()
+            .starts_with("invalid JSON"));
+    }
+
+    #[test]
+    fn test_item_script_quotes_items_for_the_shell() {
+        let template = vec![
+            "kubectl logs ${item}".to_string(),
+            "echo ${item}".to_string(),
+        ];
+        assert_eq!(
+            item_script(&template, "pod/api-1"),
+            vec!["kubectl logs pod/api-1", "echo pod/api-1"]
+        );
+        assert_eq!(
+            item_script(&template, "it's; rm -rf"),
+            vec!["kubectl logs 'it'\\''s; rm -rf'", "echo 'it'\\''s; rm -rf'"]
+        );
+        assert_eq!(
+            item_script(&template, ""),
+            vec!["kubectl logs ''", "echo ''"]
+        );
+    }
+
+    #[test]
+    fn test_refresh_applies_item_scripts_and_keeps_selection() {
+        let mut muxbox = TestDataFactory::create_test_muxbox("branches");
+        muxbox.choices_item_script = Some(vec!["git checkout ${item}".to_string()]);
+
+        let output = parse_output(
+            OutputFormat::Choices,
+            "main\nfeature\n{\"id\": \"dev\", \"script\": \"make dev\"}",
+        )
+        .unwrap();
+        assert!(apply_structured_output(&mut muxbox, output));
+        assert_eq!(
+            muxbox.selected_stream_id.as_deref(),
+            Some("branches_choices")
+        );
+        {
+            let generated = muxbox.get_selected_stream_choices().unwrap();
+            assert_eq!(
+                generated[1].script,
+                Some(vec!["git checkout feature".to_string()])
+            );
+            assert_eq!(generated[2].script, Some(vec!["make dev".to_string()]));
+        }
+
+        let stream = muxbox.streams.get_mut("branches_choices").unwrap();
+        stream.choices.as_mut().unwrap()[1].selected = true;
+        muxbox.selected_stream_id = Some("elsewhere".to_string());
+
+        let refreshed = parse_output(OutputFormat::Choices, "release\nfeature\nmain").unwrap();
+        assert!(apply_structured_output(&mut muxbox, refreshed));
+        assert_eq!(
+            muxbox.selected_stream_id.as_deref(),
+            Some("elsewhere"),
+            "a refresh doesn't switch tabs"
+        );
+        let generated = muxbox.streams["branches_choices"].choices.clone().unwrap();
+        let selected: Vec<&str> = generated
+            .iter()
+            .filter(|choice| choice.selected)
+            .map(|choice| choice.id.as_str())
+            .collect();
+        assert_eq!(selected, vec!["feature"]);
+    }
+
+    #[test]
+    fn test_refresher_runs_at_startup_then_on_interval_without_overlap() {
+        let app = dynamic_app();
+        assert!(has_choices_scripts(&app));
+
+        let start = Instant::now();
+        let mut refresher = ChoicesRefresher::new(&app);
+        assert_eq!(refresher.due(start), vec!["branches", "pods"]);
+        refresher.started("pods", "pods-stream");
+        assert!(refresher.due(start + Duration::from_secs(6)).is_empty());
+
+        refresher.observe(&finished("pods", "other-stream"));
+        assert!(refresher.due(start + Duration::from_secs(7)).is_empty());
+
+        refresher.observe(&finished("pods", "pods-stream"));
+        assert!(refresher.due(start + Duration::from_secs(4)).is_empty());
+        assert_eq!(refresher.due(start + Duration::from_secs(8)), vec!["pods"]);
+        assert!(refresher.due(start + Duration::from_secs(9)).is_empty());
+        assert_eq!(
+            refresher.due(start + Duration::from_secs(60)),
+            vec!["pods"],
+            "boxes without an interval only run once"
+        );
+    }
+
+    #[test]
+    fn test_refresher_follows_added_and_changed_boxes() {
+        let mut app = dynamic_app();
+        let start = Instant::now();
+        let mut refresher = ChoicesRefresher::new(&app);
+        assert_eq!(refresher.due(start), vec!["branches", "pods"]);
+        assert!(!refresher.update(&app));
+
+        let mut tags = app.get_muxbox_by_id("branches").unwrap().clone();
+        tags.id = "tags".to_string();
+        tags.choices_script = Some(vec!["git tag".to_string()]);
+        app.layouts[0].children.as_mut().unwrap().push(tags);
+        app.get_muxbox_by_id_mut("branches").unwrap().choices_script =
+            Some(vec!["git branch -r".to_string()]);
+        assert!(refresher.update(&app));
+        assert_eq!(
+            refresher.due(start + Duration::from_secs(1)),
+            vec!["branches", "tags"],
+            "new and changed scripts run; pods keeps its interval"
+        );
+
+        app.layouts[0]
+            .children
+            .as_mut()
+            .unwrap()
+            .retain(|b| b.id != "pods");
+        assert!(refresher.update(&app));
+        assert!(refresher.due(start + Duration::from_secs(60)).is_empty());
+    }
+
+    #[test]
+    fn test_killed_run_lets_the_box_refresh_again() {
+        let app = dynamic_app();
+        let start = Instant::now();
+        let mut refresher = ChoicesRefresher::new(&app);
+        refresher.due(start);
+        refresher.started("pods", "pods-stream");
+
+        // A cancelled run ends without a finishing update
+        refresher.observe(&Message::SourceActionMessage(SourceAction {
+            action: ActionType::Kill,
+            source_id: "choices-pods".to_string(),
+            execution_mode: ExecutionMode::Thread,
+        }));
+        assert_eq!(refresher.due(start + Duration::from_secs(6)), vec!["pods"]);
+    }
+
+    #[test]
+    fn test_choices_script_runs_keep_the_choices_tab_selected() {
+        let mut app = TestDataFactory::load_app_from_yaml_str(
+            r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'branches'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          choices_script: git branch --format='%(refname:short)'
+          choices:
+            - id: 'fetch'
+              content: 'Fetch'
+              script: ['git fetch']
+"#,
+        )
+        .unwrap();
+        let streams_before = app.get_muxbox_by_id("branches").unwrap().streams.len();
+
+        for output in ["main\nfeature", "main\nrelease"] {
+            // The draw loop's handling of the run, then of its finished output
+            let execute_script = choices_script_execution(&mut app, "branches").unwrap();
+            assert!(open_execution_stream(&mut app, &execute_script));
+            let Message::StreamUpdateMessage(mut update) =
+                finished("branches", &execute_script.stream_id)
+            else {
+                unreachable!()
+            };
+            update.content_update = output.to_string();
+            let muxbox = app.get_muxbox_by_id_mut("branches").unwrap();
+            assert!(apply_structured_output(
+                muxbox,
+                parse_stream_update(&update).unwrap()
+            ));
+
+            assert_eq!(muxbox.streams.len(), streams_before, "{}", output);
+            assert_eq!(
+                muxbox.selected_stream_id.as_deref(),
+                Some("branches_choices"),
+                "{}",
+                output
+            );
+            let choices = muxbox.get_selected_stream_choices().unwrap();
+            assert_eq!(choices.last().unwrap().id, output.lines().last().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_choices_script_execution_uses_box_settings() {
+        let mut app = dynamic_app();
+
+        let execute_script = choices_script_execution(&mut app, "pods").unwrap();
+        assert_eq!(
+            execute_script.script,
+            vec!["kubectl get pods -n prod -o name".to_string()]
+        );
+        assert_eq!(execute_script.source.source_type, SourceType::ChoicesScript);
+        assert_eq!(execute_script.execution_mode, ExecutionMode::Thread);
+        assert_eq!(execute_script.output_format, OutputFormat::Choices);
+        assert_eq!(execute_script.timeout_ms, Some(4000));
+        assert_eq!(execute_script.redirect_output, None);
+
+        let again = choices_script_execution(&mut app, "pods").unwrap();
+        assert_eq!(again.stream_id, execute_script.stream_id);
+
+        let pods = app.get_muxbox_by_id("pods").unwrap();
+        assert_eq!(
+            pods.choices_item_script,
+            Some(vec!["kubectl logs -n prod ${item}".to_string()]),
+            "variables are substituted around the item placeholder"
+        );
+        assert!(choices_script_execution(&mut app, "missing").is_none());
+    }
+}
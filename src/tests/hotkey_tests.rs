@@ -56,6 +56,20 @@ mod hotkey_tests {
             selected: false,
             hovered: false,
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         };
 
         let mut muxbox = TestDataFactory::create_test_muxbox("test_muxbox");
@@ -170,7 +170,7 @@ mod tests {
                     content,
                 } => {
                     use crate::model::common::{
-                        BatchSourceState, ExecutionMode, SourceState, StreamUpdate,
+                        BatchSourceState, ExecutionMode, OutputFormat, SourceState, StreamUpdate,
                     };
                     let stream_update = StreamUpdate {
                         stream_id: format!("{}_default", box_id),
@@ -188,6 +188,7 @@ mod tests {
                             },
                         }),
                         execution_mode: ExecutionMode::Thread,
+                        output_format: OutputFormat::Text,
                     };
                     Message::StreamUpdateMessage(stream_update)
                 }
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod integration_yaml_tests {
     use crate::model::app::load_app_from_yaml;
+    use crate::tests::test_utils::TestDataFactory;
     use std::env;
     use std::fs;
     use tempfile::NamedTempFile;
@@ -171,6 +172,38 @@ app:
         );
     }
 
+    #[test]
+    fn test_schema_validation_accepts_extended_colors() {
+        let yaml_for = |color: &str| {
+            format!(
+                r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      bg_color: '#1e1e2e'
+      children:
+        - id: 'muxbox1'
+          position: {{x1: 10%, y1: 10%, x2: 90%, y2: 90%}}
+          fg_color: 'rgb(205, 214, 244)'
+          border_color: '{}'
+"#,
+                color
+            )
+        };
+        let load = |color: &str| TestDataFactory::load_app_from_yaml_str(&yaml_for(color));
+
+        for color in ["ansi(208)", "#89B4FA", "bright_blue"] {
+            let app = load(color).unwrap_or_else(|e| panic!("{}: {}", color, e));
+            let muxbox = &app.layouts[0].children.as_ref().unwrap()[0];
+            assert_eq!(muxbox.border_color.as_deref(), Some(color));
+        }
+        for color in ["#89b4f", "rgb(256, 0, 0)", "ansi(300)", "purple"] {
+            let error = load(color).expect_err(color);
+            assert!(error.contains("border_color"), "{}: {}", color, error);
+        }
+    }
+
     #[test]
     fn test_full_yaml_pipeline_integration() {
         env::set_var("INTEGRATION_TEST_VAR", "integration_value");
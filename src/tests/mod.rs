@@ -48,6 +48,18 @@ pub mod execution_mode_schema_validation_tests; // F0230: ExecutionMode YAML Sch
 #[cfg(test)]
 pub mod socket_tests;
 
+#[cfg(test)]
+pub mod socket_protocol_tests;
+
+#[cfg(test)]
+pub mod socket_subscription_tests;
+
+#[cfg(test)]
+pub mod socket_query_tests;
+
+#[cfg(test)]
+pub mod remote_control_tests;
+
 #[cfg(test)]
 pub mod variable_tests;
 
@@ -66,6 +78,16 @@ pub mod plugin_integration_tests;
 #[cfg(test)]
 pub mod plugin_dynamic_tests;
 
+#[cfg(test)]
+pub mod plugin_discovery_tests;
+
+#[cfg(test)]
+pub mod plugin_abi_tests;
+#[cfg(test)]
+pub mod plugin_event_tests;
+#[cfg(test)]
+pub mod plugin_process_tests;
+
 #[cfg(test)]
 pub mod table_tests;
 
@@ -221,3 +243,32 @@ pub mod interactive_visual_tests; // Enhanced visual testing with interaction su
 
 #[cfg(test)]
 pub mod user_behavioral_component_tests; // User behavioral expectation testing (unit-test style)
+
+#[cfg(test)]
+pub mod alert_tests;
+#[cfg(test)]
+pub mod attach_tests;
+#[cfg(test)]
+pub mod border_style_tests;
+#[cfg(test)]
+pub mod dynamic_choices_tests;
+#[cfg(test)]
+pub mod pty_signal_tests;
+#[cfg(test)]
+pub mod recording_tests;
+#[cfg(test)]
+pub mod refresh_scheduler_tests;
+#[cfg(test)]
+pub mod schedule_tests;
+#[cfg(test)]
+pub mod script_environment_tests;
+#[cfg(test)]
+pub mod script_timeout_tests;
+#[cfg(test)]
+pub mod structured_output_tests;
+#[cfg(test)]
+pub mod style_class_tests;
+#[cfg(test)]
+pub mod text_style_tests;
+#[cfg(test)]
+pub mod theme_tests;
@@ -88,6 +88,20 @@ fn test_muxbox_with_choices_bounds_validation() {
             selected: false,
             hovered: false,
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         });
     }
     muxbox.choices = Some(choices);
@@ -204,6 +218,20 @@ fn test_minimal_muxbox_bounds_handling() {
         selected: false,
         hovered: false,
         waiting: false,
+        cwd: None,
+        env: None,
+        shell: None,
+        inherit_env: None,
+        timeout_ms: None,
+        schedule: None,
+        output_format: None,
+        class: None,
+        menu_fg_color: None,
+        menu_bg_color: None,
+        selected_menu_fg_color: None,
+        selected_menu_bg_color: None,
+        highlighted_menu_fg_color: None,
+        highlighted_menu_bg_color: None,
     }]);
 
     // Calculate bounds
@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod plugin_abi_tests {
+    use crate::model::app::App;
+    use crate::model::common::Bounds;
+    use crate::plugin::*;
+    use crate::plugin_abi::{EntryPoints, NativePlugin};
+    use crate::{AppContext, Config};
+    use boxmux_plugin as abi;
+    use std::collections::HashMap;
+
+    /// Plugin exported through the real ABI functions, linked into the test binary
+    #[derive(Default)]
+    struct CounterPlugin {
+        keys: Vec<String>,
+    }
+
+    impl abi::Plugin for CounterPlugin {
+        fn render(&mut self, request: &abi::RenderRequest) -> Result<String, abi::PluginError> {
+            if request.properties.contains_key("fail") {
+                return Err(abi::PluginError::RenderFailed("bad data".to_string()));
+            }
+            Ok(format!(
+                "{} {}x{} keys={}",
+                request.component_type,
+                request.bounds.width(),
+                request.bounds.height(),
+                self.keys.join(",")
+            ))
+        }
+
+        fn handle_event(
+            &mut self,
+            request: &abi::EventRequest,
+        ) -> Result<abi::EventOutcome, abi::PluginError> {
+            match &request.event {
+                abi::Event::KeyPress(key) => {
+                    self.keys.push(key.clone());
+                    Ok(abi::EventOutcome {
+                        rerender: true,
+                        plugin_data: Some(HashMap::from([(
+                            "keys".to_string(),
+                            serde_json::json!(self.keys.len()),
+                        )])),
+                        actions: vec![abi::Action::RefreshBox("other_box".to_string())],
+                    })
+                }
+                _ => Ok(abi::EventOutcome::default()),
+            }
+        }
+    }
+
+    abi::export_plugin!(CounterPlugin::default);
+
+    fn entry_points() -> EntryPoints {
+        EntryPoints {
+            abi_version: boxmux_plugin_abi_version,
+            create: boxmux_plugin_create,
+            destroy: boxmux_plugin_destroy,
+            call: boxmux_plugin_call,
+            free_string: boxmux_plugin_free_string,
+        }
+    }
+
+    fn context() -> PluginContext {
+        PluginContext {
+            app_context: AppContext::new(App::default(), Config::default()),
+            muxbox_bounds: Bounds::new(0, 0, 19, 4),
+            plugin_data: HashMap::new(),
+            permissions: vec![],
+        }
+    }
+
+    fn config(properties: HashMap<String, serde_json::Value>) -> ComponentConfig {
+        ComponentConfig {
+            component_type: "counter".to_string(),
+            properties,
+            data_source: None,
+            refresh_interval: None,
+        }
+    }
+
+    #[test]
+    fn test_native_plugin_render_and_events() {
+        let plugin = unsafe { NativePlugin::from_entry_points(entry_points(), None) }.unwrap();
+        let context = context();
+
+        assert_eq!(
+            plugin.render(&context, &config(HashMap::new())).unwrap(),
+            "counter 20x5 keys="
+        );
+
+        let outcome = plugin
+            .handle_event("counter", &context, &PluginEvent::KeyPress("j".to_string()))
+            .unwrap();
+        assert!(outcome.rerender);
+        assert_eq!(outcome.plugin_data.unwrap()["keys"], serde_json::json!(1));
+        assert_eq!(
+            outcome.actions,
+            vec![PluginAction::RefreshBox("other_box".to_string())]
+        );
+        assert_eq!(
+            plugin.render(&context, &config(HashMap::new())).unwrap(),
+            "counter 20x5 keys=j"
+        );
+    }
+
+    #[test]
+    fn test_native_plugin_errors_map_to_plugin_error() {
+        let plugin = unsafe { NativePlugin::from_entry_points(entry_points(), None) }.unwrap();
+        let context = context();
+
+        let mut properties = HashMap::new();
+        properties.insert("fail".to_string(), serde_json::json!(true));
+        match plugin.render(&context, &config(properties)) {
+            Err(PluginError::RenderFailed(msg)) => assert_eq!(msg, "bad data"),
+            other => panic!("Expected RenderFailed, got {:?}", other),
+        }
+
+        // CounterPlugin does not implement update
+        assert!(matches!(
+            plugin.update(&context, &config(HashMap::new())),
+            Err(PluginError::RuntimeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_abi_version_mismatch_is_rejected() {
+        extern "C" fn future_abi_version() -> u32 {
+            abi::ABI_VERSION + 1
+        }
+
+        let entry_points = EntryPoints {
+            abi_version: future_abi_version,
+            ..entry_points()
+        };
+        match unsafe { NativePlugin::from_entry_points(entry_points, None) } {
+            Err(PluginError::InitializationFailed(msg)) => {
+                assert!(msg.contains("ABI version"));
+            }
+            other => panic!("Expected InitializationFailed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_library_without_abi_symbols_is_rejected() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let library_path = temp_dir.path().join("libnot_a_plugin.so");
+        std::fs::write(&library_path, b"not a shared library").unwrap();
+
+        assert!(matches!(
+            NativePlugin::load(&library_path),
+            Err(PluginError::InitializationFailed(_))
+        ));
+    }
+}
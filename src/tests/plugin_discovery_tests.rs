@@ -0,0 +1,303 @@
+#[cfg(test)]
+mod plugin_discovery_tests {
+    use crate::model::app::PluginsConfig;
+    use crate::plugin::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    const NO_DEPS_OR_PERMISSIONS: &str = "dependencies = []\npermissions = []";
+
+    /// Write a process plugin directory with a manifest; `tables` holds its dependencies and
+    /// permissions
+    fn write_plugin(root: &Path, dir: &str, name: &str, version: &str, tables: &str) {
+        let plugin_dir = root.join(dir);
+        fs::create_dir_all(&plugin_dir).unwrap();
+        let script_path = plugin_dir.join("plugin.sh");
+        fs::write(&script_path, "#!/bin/sh\ncat > /dev/null\n").unwrap();
+        fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755)).unwrap();
+        let manifest = format!(
+            r#"
+name = "{name}"
+version = "{version}"
+author = "Test Author"
+description = "Discovery test plugin"
+entry_point = "plugin.sh"
+component_types = ["{name}_widget"]
+kind = "process"
+{tables}
+"#
+        );
+        fs::write(plugin_dir.join("plugin.toml"), manifest).unwrap();
+    }
+
+    fn loaded_names(registry: &PluginRegistry) -> Vec<String> {
+        let mut names: Vec<String> = registry
+            .list_plugins()
+            .iter()
+            .map(|manifest| manifest.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_load_plugins_from_paths_and_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(
+            temp_dir.path(),
+            "single",
+            "single",
+            "1.0.0",
+            NO_DEPS_OR_PERMISSIONS,
+        );
+        write_plugin(
+            temp_dir.path(),
+            "plugins/alpha",
+            "alpha",
+            "1.0.0",
+            NO_DEPS_OR_PERMISSIONS,
+        );
+        write_plugin(
+            temp_dir.path(),
+            "plugins/beta",
+            "beta",
+            "0.3.1",
+            NO_DEPS_OR_PERMISSIONS,
+        );
+        fs::create_dir_all(temp_dir.path().join("plugins/not_a_plugin")).unwrap();
+
+        let config = PluginsConfig {
+            paths: Some(vec!["single".to_string()]),
+            directories: Some(vec!["plugins".to_string()]),
+            ..Default::default()
+        };
+        let mut registry = PluginRegistry::new();
+        let failures = registry.load_configured_plugins(&config, Some(temp_dir.path()));
+
+        assert!(failures.is_empty(), "unexpected failures: {:?}", failures);
+        assert_eq!(loaded_names(&registry), vec!["alpha", "beta", "single"]);
+        assert!(registry.get_component("beta_widget").is_some());
+    }
+
+    #[test]
+    fn test_missing_manifest_and_directory_are_reported() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("empty")).unwrap();
+
+        let config = PluginsConfig {
+            paths: Some(vec!["empty".to_string()]),
+            directories: Some(vec!["does_not_exist".to_string()]),
+            ..Default::default()
+        };
+        let mut registry = PluginRegistry::new();
+        let failures = registry.load_configured_plugins(&config, Some(temp_dir.path()));
+
+        assert_eq!(failures.len(), 2);
+        assert!(failures
+            .iter()
+            .any(|f| f.entry == "empty" && f.error.to_string().contains("No plugin.toml")));
+        assert!(failures.iter().any(|f| f.entry == "does_not_exist"));
+        assert!(registry.list_plugins().is_empty());
+    }
+
+    #[test]
+    fn test_native_plugin_without_library_is_reported() {
+        let temp_dir = TempDir::new().unwrap();
+        let plugin_dir = temp_dir.path().join("native");
+        fs::create_dir_all(&plugin_dir).unwrap();
+        fs::write(
+            plugin_dir.join("plugin.toml"),
+            r#"
+name = "native"
+version = "1.0.0"
+author = "Test Author"
+description = "Native plugin without its library"
+entry_point = "libnative.so"
+component_types = ["native_widget"]
+dependencies = []
+permissions = []
+"#,
+        )
+        .unwrap();
+
+        let config = PluginsConfig {
+            paths: Some(vec!["native".to_string()]),
+            ..Default::default()
+        };
+        let mut registry = PluginRegistry::new();
+        let failures = registry.load_configured_plugins(&config, Some(temp_dir.path()));
+
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(
+            failures[0].error,
+            PluginError::InitializationFailed(ref message) if message.contains("libnative.so")
+        ));
+        assert!(registry.list_plugins().is_empty());
+        assert!(registry.get_component("native_widget").is_none());
+    }
+
+    #[test]
+    fn test_disallowed_permission_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(
+            temp_dir.path(),
+            "plugins/shell",
+            "shell",
+            "1.0.0",
+            "dependencies = []\n[[permissions]]\n[permissions.Process]\ncommands = [\"rm\"]",
+        );
+        write_plugin(
+            temp_dir.path(),
+            "plugins/ok",
+            "ok",
+            "1.0.0",
+            NO_DEPS_OR_PERMISSIONS,
+        );
+
+        let config = PluginsConfig {
+            paths: None,
+            directories: Some(vec!["plugins".to_string()]),
+            ..Default::default()
+        };
+        let mut registry = PluginRegistry::new();
+        let failures = registry.load_configured_plugins(&config, Some(temp_dir.path()));
+
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(
+            failures[0].error,
+            PluginError::PermissionDenied(_)
+        ));
+        assert_eq!(loaded_names(&registry), vec!["ok"]);
+    }
+
+    #[test]
+    fn test_dependency_versions_are_checked() {
+        let temp_dir = TempDir::new().unwrap();
+        let depends_on = |name: &str, version: &str, required: bool| {
+            format!(
+                "permissions = []\n[[dependencies]]\nname = \"{}\"\nversion = \"{}\"\nrequired = {}",
+                name, version, required
+            )
+        };
+        write_plugin(
+            temp_dir.path(),
+            "plugins/base",
+            "base",
+            "1.4.2",
+            NO_DEPS_OR_PERMISSIONS,
+        );
+        write_plugin(
+            temp_dir.path(),
+            "plugins/compatible",
+            "compatible",
+            "1.0.0",
+            &depends_on("base", "^1.2", true),
+        );
+        write_plugin(
+            temp_dir.path(),
+            "plugins/too_new",
+            "too_new",
+            "1.0.0",
+            &depends_on("base", ">=2.0", true),
+        );
+        // Depends on a plugin that is itself rejected
+        write_plugin(
+            temp_dir.path(),
+            "plugins/transitive",
+            "transitive",
+            "1.0.0",
+            &depends_on("too_new", "1.0", true),
+        );
+        write_plugin(
+            temp_dir.path(),
+            "plugins/optional",
+            "optional",
+            "1.0.0",
+            &depends_on("absent", "1.0", false),
+        );
+        let config = PluginsConfig {
+            paths: None,
+            directories: Some(vec!["plugins".to_string()]),
+            ..Default::default()
+        };
+        let mut registry = PluginRegistry::new();
+        let failures = registry.load_configured_plugins(&config, Some(temp_dir.path()));
+
+        let mut failed: Vec<String> = failures
+            .iter()
+            .map(|f| f.path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        failed.sort();
+        assert_eq!(failed, vec!["too_new", "transitive"]);
+        assert_eq!(
+            loaded_names(&registry),
+            vec!["base", "compatible", "optional"]
+        );
+    }
+
+    #[test]
+    fn test_version_satisfies() {
+        assert!(version_satisfies("1.4.2", "*"));
+        assert!(version_satisfies("1.4.2", "1.2"));
+        assert!(!version_satisfies("2.0.0", "1.2"));
+        assert!(version_satisfies("0.3.5", "^0.3.1"));
+        assert!(!version_satisfies("0.4.0", "^0.3.1"));
+        assert!(version_satisfies("1.2.9", "~1.2.3"));
+        assert!(!version_satisfies("1.3.0", "~1.2.3"));
+        assert!(version_satisfies("3.0.0", ">=2.0"));
+        assert!(!version_satisfies("1.9.9", ">=2.0"));
+        assert!(version_satisfies("1.0.0", "<2"));
+        assert!(version_satisfies("1.0.0-beta", "=1.0.0"));
+        assert!(!version_satisfies("abc", "1.0"));
+    }
+
+    #[test]
+    fn test_load_failure_diagnostic_points_at_yaml_entry() {
+        let failure = PluginLoadFailure {
+            entry: "./plugins/broken".to_string(),
+            path: "/app/plugins/broken".into(),
+            error: PluginError::InitializationFailed("No plugin.toml".to_string()),
+        };
+        let yaml = "app:\n  plugins:\n    paths:\n      - ./plugins/broken\n  layouts: []\n";
+
+        let diagnostic = failure.diagnostic("dashboard.yaml", yaml);
+
+        assert!(diagnostic.contains("dashboard.yaml:4:9"));
+        assert!(diagnostic.contains("/app/plugins/broken"));
+        assert!(diagnostic.contains("No plugin.toml"));
+    }
+
+    #[test]
+    fn test_plugins_section_parses_from_yaml() {
+        let temp_dir = TempDir::new().unwrap();
+        let yaml_path = temp_dir.path().join("app.yaml");
+        fs::write(
+            &yaml_path,
+            r#"
+app:
+  plugins:
+    paths:
+      - './plugins/metrics'
+    directories:
+      - '~/.config/boxmux/plugins'
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'box'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+"#,
+        )
+        .unwrap();
+
+        let app = crate::model::app::load_app_from_yaml(yaml_path.to_str().unwrap()).unwrap();
+        let plugins = app.plugins.unwrap();
+        assert_eq!(plugins.paths, Some(vec!["./plugins/metrics".to_string()]));
+        assert_eq!(
+            plugins.directories,
+            Some(vec!["~/.config/boxmux/plugins".to_string()])
+        );
+    }
+}
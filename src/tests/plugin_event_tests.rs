@@ -0,0 +1,315 @@
+#[cfg(test)]
+mod plugin_event_tests {
+    use crate::draw_loop::{
+        dispatch_plugin_event, plugin_click_event, refresh_due_plugin_components,
+    };
+    use crate::plugin::*;
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::{AppContext, InputBounds};
+    use std::collections::{BTreeMap, HashMap};
+
+    /// Renders its plugin data as sorted `key=value` lines
+    fn render_data(
+        context: &PluginContext,
+        _config: &ComponentConfig,
+    ) -> Result<String, PluginError> {
+        let data: BTreeMap<_, _> = context.plugin_data.iter().collect();
+        Ok(data
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// Records every event in its plugin data
+    fn record_events(
+        context: &PluginContext,
+        event: &PluginEvent,
+    ) -> Result<EventOutcome, PluginError> {
+        let mut data = context.plugin_data.clone();
+        let mut actions = Vec::new();
+        match event {
+            PluginEvent::KeyPress(key) if key == "x" => {
+                return Err(PluginError::RuntimeError("x is not allowed".to_string()))
+            }
+            PluginEvent::KeyPress(key) if key == "l" => {
+                actions.push(PluginAction::SwitchLayout("other_layout".to_string()));
+            }
+            PluginEvent::KeyPress(_) => {
+                let count = data.get("keys").and_then(|v| v.as_u64()).unwrap_or(0);
+                data.insert("keys".to_string(), serde_json::json!(count + 1));
+            }
+            PluginEvent::MouseEvent { x, y, action } => {
+                data.insert(action.clone(), serde_json::json!(format!("{},{}", x, y)));
+            }
+            PluginEvent::Timer { interval } => {
+                data.insert("timer".to_string(), serde_json::json!(interval));
+            }
+            PluginEvent::DataUpdate {
+                source,
+                data: value,
+            } => {
+                data.insert(source.clone(), value.clone());
+            }
+            PluginEvent::MuxBoxResize { new_bounds } => {
+                data.insert("width".to_string(), serde_json::json!(new_bounds.width()));
+            }
+        }
+        Ok(EventOutcome {
+            rerender: true,
+            plugin_data: Some(data),
+            actions,
+        })
+    }
+
+    fn half_screen(x1: &str, x2: &str) -> InputBounds {
+        InputBounds {
+            x1: x1.to_string(),
+            y1: "0%".to_string(),
+            x2: x2.to_string(),
+            y2: "100%".to_string(),
+        }
+    }
+
+    /// Active layout with a `recorder` box on the left and a plain box on the right
+    fn app_context_with_recorder() -> AppContext {
+        let mut plugin_box =
+            TestDataFactory::create_test_muxbox_with_parent("plugin_box", "test_layout");
+        plugin_box.plugin_component = Some("recorder".to_string());
+        plugin_box.position = half_screen("0%", "49%");
+        let mut plain_box =
+            TestDataFactory::create_test_muxbox_with_parent("plain_box", "test_layout");
+        plain_box.position = half_screen("51%", "100%");
+
+        let mut app_context = TestDataFactory::create_test_app_context();
+        let mut layout =
+            TestDataFactory::create_root_layout("test_layout", Some(vec![plugin_box, plain_box]));
+        layout.active = Some(true);
+        app_context.app.layouts = vec![layout];
+
+        let manifest = PluginManifest {
+            name: "recorder".to_string(),
+            version: "1.0.0".to_string(),
+            author: "Test Author".to_string(),
+            description: "Records events".to_string(),
+            entry_point: String::new(),
+            component_types: vec!["recorder".to_string()],
+            dependencies: vec![],
+            permissions: vec![],
+            kind: PluginKind::Library,
+            interpreter: None,
+            timeout_ms: None,
+        };
+        let component = PluginComponent {
+            component_type: "recorder".to_string(),
+            implementation: ComponentImplementation::Builtin {
+                render_fn: render_data,
+                update_fn: None,
+                event_handler: Some(record_events),
+            },
+        };
+        app_context
+            .plugin_registry
+            .lock()
+            .unwrap()
+            .register_components(manifest, vec![component])
+            .unwrap();
+        app_context
+    }
+
+    fn plugin_output(app_context: &AppContext) -> Vec<String> {
+        let muxbox = app_context.app.get_muxbox_by_id("plugin_box").unwrap();
+        muxbox.streams[&muxbox.plugin_stream_id().unwrap()]
+            .content
+            .clone()
+    }
+
+    #[test]
+    fn test_key_press_updates_plugin_data_and_rerenders() {
+        let mut app_context = app_context_with_recorder();
+        let mut actions = Vec::new();
+        let event = PluginEvent::KeyPress("j".to_string());
+
+        assert!(dispatch_plugin_event(
+            &mut app_context,
+            "plugin_box",
+            &event,
+            &mut actions
+        ));
+        assert!(dispatch_plugin_event(
+            &mut app_context,
+            "plugin_box",
+            &event,
+            &mut actions
+        ));
+
+        assert_eq!(plugin_output(&app_context), vec!["keys=2"]);
+        assert!(actions.is_empty());
+        assert_eq!(
+            app_context
+                .plugin_registry
+                .lock()
+                .unwrap()
+                .plugin_data("plugin_box")["keys"],
+            serde_json::json!(2)
+        );
+    }
+
+    #[test]
+    fn test_handler_actions_are_collected() {
+        let mut app_context = app_context_with_recorder();
+        let mut actions = Vec::new();
+
+        dispatch_plugin_event(
+            &mut app_context,
+            "plugin_box",
+            &PluginEvent::KeyPress("l".to_string()),
+            &mut actions,
+        );
+
+        assert_eq!(
+            actions,
+            vec![PluginAction::SwitchLayout("other_layout".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_handler_error_puts_box_in_error_state() {
+        let mut app_context = app_context_with_recorder();
+
+        dispatch_plugin_event(
+            &mut app_context,
+            "plugin_box",
+            &PluginEvent::KeyPress("x".to_string()),
+            &mut Vec::new(),
+        );
+
+        let muxbox = app_context.app.get_muxbox_by_id("plugin_box").unwrap();
+        assert!(muxbox.error_state);
+        assert!(plugin_output(&app_context)[0].contains("x is not allowed"));
+    }
+
+    #[test]
+    fn test_boxes_without_plugin_component_are_skipped() {
+        let mut app_context = app_context_with_recorder();
+        let event = PluginEvent::KeyPress("j".to_string());
+
+        assert!(!dispatch_plugin_event(
+            &mut app_context,
+            "plain_box",
+            &event,
+            &mut Vec::new()
+        ));
+        assert!(!dispatch_plugin_event(
+            &mut app_context,
+            "missing_box",
+            &event,
+            &mut Vec::new()
+        ));
+    }
+
+    #[test]
+    fn test_click_event_is_relative_to_the_box() {
+        let app_context = app_context_with_recorder();
+        let layout = app_context.app.get_active_layout().unwrap();
+        let plugin_bounds = app_context
+            .app
+            .get_muxbox_by_id("plugin_box")
+            .unwrap()
+            .bounds();
+        let plain_bounds = app_context
+            .app
+            .get_muxbox_by_id("plain_box")
+            .unwrap()
+            .bounds();
+
+        let (muxbox_id, event) = plugin_click_event(
+            layout,
+            plugin_bounds.left() as u16 + 3,
+            plugin_bounds.top() as u16 + 2,
+        )
+        .unwrap();
+        assert_eq!(muxbox_id, "plugin_box");
+        match event {
+            PluginEvent::MouseEvent { x, y, action } => {
+                assert_eq!((x, y), (3, 2));
+                assert_eq!(action, "click");
+            }
+            other => panic!("Expected MouseEvent, got {:?}", other),
+        }
+
+        assert!(plugin_click_event(
+            layout,
+            plain_bounds.left() as u16 + 1,
+            plain_bounds.top() as u16 + 1
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_refresh_pass_sends_timer_and_resize_events() {
+        let mut app_context = app_context_with_recorder();
+        let mut refresh_state = HashMap::new();
+        let mut actions = Vec::new();
+
+        // The first render is not caused by an event
+        refresh_due_plugin_components(
+            &mut app_context,
+            &mut refresh_state,
+            &mut actions,
+            &mut Vec::new(),
+        );
+        assert_eq!(plugin_output(&app_context), Vec::<String>::new());
+
+        app_context
+            .app
+            .get_muxbox_by_id_mut("plugin_box")
+            .unwrap()
+            .refresh_interval = Some(0);
+        refresh_due_plugin_components(
+            &mut app_context,
+            &mut refresh_state,
+            &mut actions,
+            &mut Vec::new(),
+        );
+        assert_eq!(plugin_output(&app_context), vec!["timer=0"]);
+
+        let mut moved = refresh_state["plugin_box"];
+        moved.bounds.x2 += 5;
+        refresh_state.insert("plugin_box".to_string(), moved);
+        refresh_due_plugin_components(
+            &mut app_context,
+            &mut refresh_state,
+            &mut actions,
+            &mut Vec::new(),
+        );
+        let width = app_context
+            .app
+            .get_muxbox_by_id("plugin_box")
+            .unwrap()
+            .bounds()
+            .width();
+        assert_eq!(
+            plugin_output(&app_context),
+            vec!["timer=0".to_string(), format!("width={}", width)]
+        );
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn test_data_update_event_reaches_plugin() {
+        let mut app_context = app_context_with_recorder();
+
+        dispatch_plugin_event(
+            &mut app_context,
+            "plugin_box",
+            &PluginEvent::DataUpdate {
+                source: "feed".to_string(),
+                data: serde_json::json!({"cpu": 42}),
+            },
+            &mut Vec::new(),
+        );
+
+        assert_eq!(plugin_output(&app_context), vec![r#"feed={"cpu":42}"#]);
+    }
+}
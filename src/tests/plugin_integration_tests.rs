@@ -196,4 +196,142 @@ mod plugin_integration_tests {
         assert!(content.contains("metrics"));
         assert!(content.contains("60x20"));
     }
+
+    #[test]
+    fn test_refresh_plugin_component_renders_into_stream() {
+        let mut muxbox = MuxBox::default();
+        muxbox.id = "plugin_box".to_string();
+        muxbox.plugin_component = Some("custom_chart".to_string());
+
+        let app_context = AppContext::new(App::default(), Config::default());
+        app_context
+            .plugin_registry
+            .lock()
+            .unwrap()
+            .load_plugin("/nonexistent/plugin")
+            .unwrap();
+        let bounds = Bounds::new(0, 0, 29, 9);
+
+        assert!(muxbox.refresh_plugin_component(&app_context, &bounds));
+        assert!(!muxbox.error_state);
+
+        let stream_id = muxbox.plugin_stream_id().unwrap();
+        assert_eq!(stream_id, "plugin_box_custom_chart");
+        assert_eq!(
+            muxbox.selected_stream_id.as_deref(),
+            Some("plugin_box_custom_chart")
+        );
+        assert_eq!(
+            muxbox.streams[&stream_id].content,
+            vec!["Custom component: custom_chart".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_refresh_plugin_component_error_sets_error_state() {
+        let mut muxbox = MuxBox::default();
+        muxbox.id = "broken_plugin".to_string();
+        muxbox.plugin_component = Some("missing_component".to_string());
+
+        let app_context = AppContext::new(App::default(), Config::default());
+        let bounds = Bounds::new(0, 0, 20, 5);
+
+        assert!(muxbox.refresh_plugin_component(&app_context, &bounds));
+        assert!(muxbox.error_state);
+
+        let stream_id = muxbox.plugin_stream_id().unwrap();
+        assert!(muxbox.streams[&stream_id].content[0].contains("missing_component"));
+    }
+
+    #[test]
+    fn test_refresh_plugin_component_without_component() {
+        let mut muxbox = MuxBox::default();
+        muxbox.id = "plain_box".to_string();
+
+        let app_context = AppContext::new(App::default(), Config::default());
+        let bounds = Bounds::new(0, 0, 20, 5);
+
+        assert!(!muxbox.refresh_plugin_component(&app_context, &bounds));
+        assert!(muxbox.streams.is_empty());
+    }
+
+    #[test]
+    fn test_plugin_component_config_uses_refresh_interval() {
+        let mut muxbox = MuxBox::default();
+        muxbox.plugin_component = Some("custom_chart".to_string());
+        muxbox.refresh_interval = Some(2500);
+
+        let config = muxbox.plugin_component_config().unwrap();
+        assert_eq!(config.component_type, "custom_chart");
+        assert_eq!(config.refresh_interval, Some(2500));
+        assert!(config.properties.is_empty());
+    }
+
+    #[test]
+    fn test_refresh_due_plugin_components_schedule() {
+        use crate::draw_loop::refresh_due_plugin_components;
+        use crate::tests::test_utils::TestDataFactory;
+
+        let mut plugin_box =
+            TestDataFactory::create_test_muxbox_with_parent("plugin_box", "test_layout");
+        plugin_box.plugin_component = Some("custom_chart".to_string());
+        let mut app_context = TestDataFactory::create_test_app_context();
+        let mut layout = TestDataFactory::create_root_layout("test_layout", Some(vec![plugin_box]));
+        layout.active = Some(true);
+        app_context.app.layouts = vec![layout];
+        let mut refresh_state = HashMap::new();
+
+        // First pass renders, second pass has nothing due without a refresh_interval
+        assert!(refresh_due_plugin_components(
+            &mut app_context,
+            &mut refresh_state,
+            &mut Vec::new(),
+            &mut Vec::new()
+        ));
+        assert!(refresh_state.contains_key("plugin_box"));
+        assert!(!refresh_due_plugin_components(
+            &mut app_context,
+            &mut refresh_state,
+            &mut Vec::new(),
+            &mut Vec::new()
+        ));
+
+        // A zero interval makes the box due on every pass
+        app_context
+            .app
+            .get_muxbox_by_id_mut("plugin_box")
+            .unwrap()
+            .refresh_interval = Some(0);
+        assert!(refresh_due_plugin_components(
+            &mut app_context,
+            &mut refresh_state,
+            &mut Vec::new(),
+            &mut Vec::new()
+        ));
+    }
+
+    #[test]
+    fn test_refresh_state_goes_away_with_its_box() {
+        use crate::draw_loop::{forget_removed_plugin_boxes, PluginRefreshState};
+        use crate::tests::test_utils::TestDataFactory;
+
+        let state = PluginRefreshState {
+            rendered_at: std::time::Instant::now(),
+            bounds: Bounds::new(0, 0, 20, 5),
+            log_revision: 0,
+            in_flight: false,
+        };
+        // "unknown" stands for a box the old app never had, e.g. one added meanwhile
+        let mut refresh_state: HashMap<String, PluginRefreshState> = ["removed", "kept", "unknown"]
+            .into_iter()
+            .map(|muxbox_id| (muxbox_id.to_string(), state))
+            .collect();
+
+        let old = TestDataFactory::create_test_app_context_with_boxes(&["removed", "kept"]).app;
+        let new = TestDataFactory::create_test_app_context_with_boxes(&["kept"]).app;
+        forget_removed_plugin_boxes(&mut refresh_state, &old, &new);
+        let mut remaining: Vec<&str> = refresh_state.keys().map(String::as_str).collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["kept", "unknown"]);
+    }
 }
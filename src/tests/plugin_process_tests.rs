@@ -0,0 +1,411 @@
+#[cfg(test)]
+mod plugin_process_tests {
+    use crate::model::app::{App, PluginsConfig};
+    use crate::model::common::Bounds;
+    use crate::plugin::*;
+    use crate::{AppContext, Config};
+    use std::collections::HashMap;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// Answers render requests with styled lines and events with a re-render
+    const STYLED_PLUGIN: &str = r#"#!/bin/sh
+echo "styled plugin ready" >&2
+while read -r line; do
+  case "$line" in
+    *'"method":"render"'*)
+      echo '{"Ok":{"lines":["plain",{"text":"alert","fg":"red","bold":true},{"spans":[{"text":"a","bg":"bright_blue"},{"text":"b"}]}]}}' ;;
+    *'"method":"event"'*)
+      echo '{"Ok":{"rerender":true,"actions":[{"SwitchLayout":"details"}]}}' ;;
+    *)
+      echo '{"Err":{"RuntimeError":"unsupported"}}' ;;
+  esac
+done
+"#;
+
+    fn write_plugin(root: &Path, name: &str, script: &str, extra_manifest: &str) {
+        let plugin_dir = root.join(name);
+        fs::create_dir_all(&plugin_dir).unwrap();
+        let script_path = plugin_dir.join("plugin.sh");
+        fs::write(&script_path, script).unwrap();
+        fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755)).unwrap();
+        let manifest = format!(
+            r#"
+name = "{name}"
+version = "1.0.0"
+author = "Test Author"
+description = "Process test plugin"
+entry_point = "plugin.sh"
+component_types = ["{name}_widget"]
+kind = "process"
+dependencies = []
+{extra_manifest}
+"#
+        );
+        fs::write(plugin_dir.join("plugin.toml"), manifest).unwrap();
+    }
+
+    fn context() -> PluginContext {
+        PluginContext {
+            app_context: AppContext::new(App::default(), Config::default()),
+            muxbox_bounds: Bounds::new(0, 0, 19, 4),
+            plugin_data: HashMap::new(),
+            permissions: vec![],
+        }
+    }
+
+    fn config(component_type: &str) -> ComponentConfig {
+        ComponentConfig {
+            component_type: component_type.to_string(),
+            properties: HashMap::new(),
+            data_source: None,
+            refresh_interval: None,
+        }
+    }
+
+    /// Poll until the plugin's stderr log contains `needle`; stderr is read on its own thread
+    fn wait_for_log(registry: &PluginRegistry, component_type: &str, needle: &str) -> Vec<String> {
+        for _ in 0..100 {
+            let log = registry.component_log(component_type);
+            if log.iter().any(|line| line.contains(needle)) {
+                return log;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        panic!(
+            "'{}' never appeared in {:?}",
+            needle,
+            registry.component_log(component_type)
+        );
+    }
+
+    #[test]
+    fn test_process_plugin_renders_styled_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(temp_dir.path(), "styled", STYLED_PLUGIN, "permissions = []");
+        let mut registry = PluginRegistry::new();
+        registry
+            .load_plugin(temp_dir.path().join("styled"))
+            .unwrap();
+
+        let output = registry
+            .render_component("styled_widget", &context(), &config("styled_widget"))
+            .unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            vec!["plain", "\x1b[1;31malert\x1b[0m", "\x1b[104ma\x1b[0mb"]
+        );
+
+        let outcome = registry
+            .handle_event(
+                "styled_widget",
+                &context(),
+                &PluginEvent::KeyPress("j".to_string()),
+            )
+            .unwrap();
+        assert!(outcome.rerender);
+        assert_eq!(
+            outcome.actions,
+            vec![PluginAction::SwitchLayout("details".to_string())]
+        );
+
+        assert!(registry.component_log_revision("styled_widget").is_some());
+        wait_for_log(&registry, "styled_widget", "styled plugin ready");
+    }
+
+    #[test]
+    fn test_crashed_process_is_restarted() {
+        // Answers a single request, then exits
+        let one_shot = r#"#!/bin/sh
+read -r line
+echo '{"Ok":"rendered once"}'
+echo "exiting" >&2
+exit 3
+"#;
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(temp_dir.path(), "one_shot", one_shot, "permissions = []");
+        let mut registry = PluginRegistry::new();
+        registry
+            .load_plugin(temp_dir.path().join("one_shot"))
+            .unwrap();
+
+        for _ in 0..3 {
+            let output = registry
+                .render_component("one_shot_widget", &context(), &config("one_shot_widget"))
+                .unwrap();
+            assert_eq!(output, "rendered once");
+        }
+        wait_for_log(&registry, "one_shot_widget", "[boxmux] restarting one_shot");
+    }
+
+    #[test]
+    fn test_unresponsive_process_times_out() {
+        let silent = "#!/bin/sh\nwhile read -r line; do :; done\n";
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(
+            temp_dir.path(),
+            "silent",
+            silent,
+            "permissions = []\ntimeout_ms = 200",
+        );
+        let mut registry = PluginRegistry::new();
+        registry
+            .load_plugin(temp_dir.path().join("silent"))
+            .unwrap();
+
+        match registry.render_component("silent_widget", &context(), &config("silent_widget")) {
+            Err(PluginError::RuntimeError(msg)) => assert!(msg.contains("within 200ms")),
+            other => panic!("Expected a timeout, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_environment_is_limited_to_declared_variables() {
+        std::env::set_var("BOXMUX_PROCESS_TEST_DECLARED", "visible");
+        std::env::set_var("BOXMUX_PROCESS_TEST_SECRET", "leaked");
+        let env_plugin = r#"#!/bin/sh
+while read -r line; do
+  echo "{\"Ok\":\"${BOXMUX_PROCESS_TEST_DECLARED:-unset} ${BOXMUX_PROCESS_TEST_SECRET:-unset} $BOXMUX_PLUGIN_NAME $BOXMUX_PLUGIN_ALLOWED_PATHS\"}"
+done
+"#;
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(
+            temp_dir.path(),
+            "env",
+            env_plugin,
+            r#"
+[[permissions]]
+[permissions.Environment]
+variables = ["BOXMUX_PROCESS_TEST_DECLARED"]
+
+[[permissions]]
+[permissions.FileSystem]
+paths = ["/tmp/boxmux"]
+"#,
+        );
+        let mut registry = PluginRegistry::new();
+        registry.load_plugin(temp_dir.path().join("env")).unwrap();
+
+        let output = registry
+            .render_component("env_widget", &context(), &config("env_widget"))
+            .unwrap();
+        assert_eq!(output, "visible unset env /tmp/boxmux");
+    }
+
+    #[test]
+    fn test_interpreter_must_be_declared_and_allowed() {
+        let script = "while read -r line; do echo '{\"Ok\":\"via sh\"}'; done\n";
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(
+            temp_dir.path(),
+            "undeclared",
+            script,
+            "permissions = []\ninterpreter = \"sh\"",
+        );
+        let declared =
+            "interpreter = \"sh\"\n[[permissions]]\n[permissions.Process]\ncommands = [\"sh\"]";
+        write_plugin(temp_dir.path(), "declared", script, declared);
+
+        let mut registry = PluginRegistry::new();
+        assert!(matches!(
+            registry.load_plugin(temp_dir.path().join("undeclared")),
+            Err(PluginError::PermissionDenied(_))
+        ));
+        // Declared, but `sh` is not on the security manager's allow-list yet
+        assert!(matches!(
+            registry.load_plugin(temp_dir.path().join("declared")),
+            Err(PluginError::PermissionDenied(_))
+        ));
+
+        let config = PluginsConfig {
+            paths: Some(vec!["declared".to_string()]),
+            allowed_commands: Some(vec!["sh".to_string()]),
+            ..Default::default()
+        };
+        let failures = registry.load_configured_plugins(&config, Some(temp_dir.path()));
+        assert!(failures.is_empty(), "unexpected failures: {:?}", failures);
+        let output = registry
+            .render_component(
+                "declared_widget",
+                &context(),
+                &self::config("declared_widget"),
+            )
+            .unwrap();
+        assert_eq!(output, "via sh");
+    }
+
+    #[test]
+    fn test_entry_point_outside_plugin_directory_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(temp_dir.path(), "escape", STYLED_PLUGIN, "permissions = []");
+        fs::copy(
+            temp_dir.path().join("escape/plugin.sh"),
+            temp_dir.path().join("outside.sh"),
+        )
+        .unwrap();
+        let manifest_path = temp_dir.path().join("escape/plugin.toml");
+        let manifest = fs::read_to_string(&manifest_path).unwrap().replace(
+            "entry_point = \"plugin.sh\"",
+            "entry_point = \"../outside.sh\"",
+        );
+        fs::write(&manifest_path, manifest).unwrap();
+
+        let mut registry = PluginRegistry::new();
+        assert!(matches!(
+            registry.load_plugin(temp_dir.path().join("escape")),
+            Err(PluginError::PermissionDenied(_))
+        ));
+    }
+
+    /// An app whose active layout has one box showing `component_type`, with the
+    /// process plugin in `plugin_dir` loaded
+    fn plugin_box_app_context(plugin_dir: &Path, component_type: &str) -> AppContext {
+        use crate::tests::test_utils::TestDataFactory;
+
+        let mut plugin_box =
+            TestDataFactory::create_test_muxbox_with_parent("plugin_box", "test_layout");
+        plugin_box.plugin_component = Some(component_type.to_string());
+        let mut app_context = TestDataFactory::create_test_app_context();
+        let mut layout = TestDataFactory::create_root_layout("test_layout", Some(vec![plugin_box]));
+        layout.active = Some(true);
+        app_context.app.layouts = vec![layout];
+        app_context
+            .plugin_registry
+            .lock()
+            .unwrap()
+            .load_plugin(plugin_dir)
+            .unwrap();
+        app_context
+    }
+
+    #[test]
+    fn test_process_log_is_shown_in_a_log_stream() {
+        use crate::draw_loop::{
+            apply_plugin_output, refresh_due_plugin_components, run_plugin_request,
+        };
+        use crate::thread_manager::Message;
+
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(temp_dir.path(), "styled", STYLED_PLUGIN, "permissions = []");
+        let mut app_context =
+            plugin_box_app_context(&temp_dir.path().join("styled"), "styled_widget");
+        wait_for_log(
+            &app_context.plugin_registry.lock().unwrap(),
+            "styled_widget",
+            "styled plugin ready",
+        );
+
+        let mut refresh_state = HashMap::new();
+        let mut requests = Vec::new();
+        assert!(refresh_due_plugin_components(
+            &mut app_context,
+            &mut refresh_state,
+            &mut Vec::new(),
+            &mut requests
+        ));
+        assert_eq!(requests.len(), 1);
+        let Message::PluginOutput(muxbox_id, output, _) =
+            run_plugin_request(&app_context, &requests[0])
+        else {
+            panic!("Expected plugin output");
+        };
+        assert!(apply_plugin_output(
+            &mut app_context,
+            &mut refresh_state,
+            &muxbox_id,
+            output
+        ));
+
+        let muxbox = app_context.app.get_muxbox_by_id("plugin_box").unwrap();
+        let log_stream = &muxbox.streams["plugin_box_styled_widget_log"];
+        assert_eq!(log_stream.content, vec!["styled plugin ready".to_string()]);
+        // The rendered output stays the selected tab
+        assert_eq!(
+            muxbox.selected_stream_id.as_deref(),
+            Some("plugin_box_styled_widget")
+        );
+        // Nothing new on stderr, nothing to do
+        assert!(!refresh_due_plugin_components(
+            &mut app_context,
+            &mut refresh_state,
+            &mut Vec::new(),
+            &mut Vec::new()
+        ));
+    }
+
+    #[test]
+    fn test_process_plugin_renders_off_the_draw_thread() {
+        use crate::draw_loop::{
+            apply_plugin_output, refresh_due_plugin_components, run_plugin_request,
+        };
+        use crate::thread_manager::Message;
+        use std::time::{Duration, Instant};
+
+        let slow =
+            "#!/bin/sh\nwhile read -r line; do sleep 1; echo '{\"Ok\":\"slow answer\"}'; done\n";
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(temp_dir.path(), "slow", slow, "permissions = []");
+        let mut app_context = plugin_box_app_context(&temp_dir.path().join("slow"), "slow_widget");
+
+        let mut refresh_state = HashMap::new();
+        let mut requests = Vec::new();
+        let started = Instant::now();
+        refresh_due_plugin_components(
+            &mut app_context,
+            &mut refresh_state,
+            &mut Vec::new(),
+            &mut requests,
+        );
+        assert!(started.elapsed() < Duration::from_millis(500));
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].muxbox_id, "plugin_box");
+        assert!(requests[0].render);
+
+        // Not due again while its render runs
+        let mut more_requests = Vec::new();
+        refresh_due_plugin_components(
+            &mut app_context,
+            &mut refresh_state,
+            &mut Vec::new(),
+            &mut more_requests,
+        );
+        assert!(more_requests.is_empty());
+
+        let worker_context = app_context.clone();
+        let request = requests[0].clone();
+        let worker = std::thread::spawn(move || run_plugin_request(&worker_context, &request));
+        std::thread::sleep(Duration::from_millis(200));
+        // The registry stays usable while the process answers
+        let locked_at = Instant::now();
+        drop(app_context.plugin_registry.lock().unwrap());
+        assert!(locked_at.elapsed() < Duration::from_millis(500));
+
+        let message = worker.join().unwrap();
+        assert_eq!(
+            message,
+            Message::PluginOutput(
+                "plugin_box".to_string(),
+                Some(("slow answer".to_string(), true)),
+                Vec::new()
+            )
+        );
+        let Message::PluginOutput(muxbox_id, output, _) = message else {
+            unreachable!()
+        };
+        assert!(apply_plugin_output(
+            &mut app_context,
+            &mut refresh_state,
+            &muxbox_id,
+            output
+        ));
+        assert!(!refresh_state["plugin_box"].in_flight);
+        let muxbox = app_context.app.get_muxbox_by_id("plugin_box").unwrap();
+        assert_eq!(
+            muxbox.streams["plugin_box_slow_widget"].content,
+            vec!["slow answer".to_string()]
+        );
+    }
+}
@@ -59,6 +59,53 @@ mod pty_resize_tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_pty_resize_uses_the_given_content_area() {
+        use crate::model::common::{
+            ExecuteScript, ExecutionMode, ExecutionSource, OutputFormat, ScriptEnvironment,
+            SourceReference, SourceType,
+        };
+        use crate::thread_manager::Message;
+        use std::time::{Duration, Instant};
+
+        let execute_script = ExecuteScript {
+            script: vec!["sleep 0.5; stty size".to_string()],
+            source: ExecutionSource {
+                source_type: SourceType::StaticScript,
+                source_id: "resized".to_string(),
+                source_reference: SourceReference::StaticConfig("resized".to_string()),
+            },
+            execution_mode: ExecutionMode::Pty,
+            target_box_id: "resized".to_string(),
+            libs: Vec::new(),
+            redirect_output: None,
+            append_output: false,
+            stream_id: "resized-stream".to_string(),
+            target_bounds: None,
+            environment: ScriptEnvironment::default(),
+            timeout_ms: None,
+            output_format: OutputFormat::Text,
+            record: None,
+        };
+        let mut manager = PtyManager::new().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        manager
+            .handle_execute_script(&execute_script, sender, uuid::Uuid::new_v4())
+            .unwrap();
+        manager.resize_pty("resized", 30, 100).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut output = String::new();
+        while Instant::now() < deadline && !output.contains("30 100") {
+            if let Ok((_, Message::StreamUpdateMessage(update))) =
+                receiver.recv_timeout(Duration::from_millis(100))
+            {
+                output.push_str(&update.content_update);
+            }
+        }
+        assert!(output.contains("30 100"), "stty reported {:?}", output);
+    }
+
     #[test]
     fn test_pty_manager_cleanup() {
         let mut manager = PtyManager::new().unwrap();
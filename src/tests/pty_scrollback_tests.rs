@@ -199,6 +199,7 @@ mod pty_scrollback_tests {
             tab_order: None,
             next_focus_id: None,
             children: None,
+            class: None,
             fill: None,
             fill_char: None,
             selected_fill_char: None,
@@ -213,6 +214,8 @@ mod pty_scrollback_tests {
             selected_title_bg_color: None,
             selected_title_fg_color: None,
             title_position: None,
+            title_style: None,
+            text_style: None,
             error_border_color: None,
             error_bg_color: None,
             error_fg_color: None,
@@ -224,6 +227,8 @@ mod pty_scrollback_tests {
             error_selected_title_bg_color: None,
             error_selected_title_fg_color: None,
             highlighted_fill_char: None,
+            border: None,
+            border_style: None,
             highlighted_border_color: None,
             highlighted_bg_color: None,
             highlighted_fg_color: None,
@@ -265,6 +270,22 @@ mod pty_scrollback_tests {
             hovered_tab_target: None,
             streams: indexmap::IndexMap::new(),
             selected_stream_id: None,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            overlap_policy: None,
+            refresh_jitter_ms: None,
+            refresh_backoff_max_ms: None,
+            schedule: None,
+            output_format: None,
+            choices_script: None,
+            choices_item_script: None,
+            choices_refresh_interval: None,
+            alerts: None,
+            record: None,
+            alert: None,
         }
     }
 
@@ -0,0 +1,235 @@
+#[cfg(test)]
+mod pty_signal_tests {
+    use crate::circular_buffer::CircularBuffer;
+    use crate::model::app::PtyConfig;
+    use crate::model::common::{run_socket_function, SocketFunction};
+    use crate::pty_manager::*;
+    use crate::thread_manager::Message;
+    use std::os::unix::process::{CommandExt, ExitStatusExt};
+    use std::path::Path;
+    use std::process::{Command, ExitStatus};
+    use std::sync::{Arc, Mutex};
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// Writes `ready` to `$1` once the trap is in place, and `hup` there on SIGHUP
+    const HUP_RECORDER: &str = r#"trap 'echo hup > "$1"; exit 0' HUP
+echo ready > "$1"
+while :; do sleep 0.05; done 2>/dev/null"#;
+
+    /// Start `script` as the leader of its own process group, like portable_pty does,
+    /// and register it as the box's PTY process. The handle yields its exit status.
+    fn spawn_registered(
+        manager: &PtyManager,
+        muxbox_id: &str,
+        script: &str,
+        args: &[&str],
+    ) -> JoinHandle<ExitStatus> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .arg("sh")
+            .args(args)
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        manager.add_test_pty_process_with_status(
+            muxbox_id.to_string(),
+            Arc::new(Mutex::new(CircularBuffer::new(10))),
+            PtyStatus::Running,
+            child.id(),
+        );
+        manager.set_pty_killable(muxbox_id, true);
+
+        // Stands in for the PTY reader thread, which reaps the child on EOF
+        let reaper = manager.clone();
+        let muxbox_id = muxbox_id.to_string();
+        std::thread::spawn(move || {
+            let status = child.wait().unwrap();
+            reaper
+                .mark_pty_dead(&muxbox_id, status.to_string())
+                .unwrap();
+            status
+        })
+    }
+
+    fn wait_for_file(path: &Path, contents: &str) {
+        for _ in 0..250 {
+            if std::fs::read_to_string(path).unwrap_or_default().trim() == contents {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        panic!("{} never contained '{}'", path.display(), contents);
+    }
+
+    fn sequence(signal: PtySignal, timeout_ms: u64) -> StopSequence {
+        StopSequence {
+            signal,
+            timeout: Duration::from_millis(timeout_ms),
+        }
+    }
+
+    #[test]
+    fn test_signal_names_parse() {
+        assert_eq!("TERM".parse::<PtySignal>(), Ok(PtySignal::Term));
+        assert_eq!("sigint".parse::<PtySignal>(), Ok(PtySignal::Int));
+        assert_eq!(" SIGHUP ".parse::<PtySignal>(), Ok(PtySignal::Hup));
+        assert!("USR1".parse::<PtySignal>().is_err());
+
+        let function: SocketFunction =
+            serde_json::from_str(r#"{"SendPtySignal":{"box_id":"db","signal":"SIGCONT"}}"#)
+                .unwrap();
+        assert_eq!(
+            function,
+            SocketFunction::SendPtySignal {
+                box_id: "db".to_string(),
+                signal: PtySignal::Cont,
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&PtySignal::Stop).unwrap(),
+            r#""STOP""#
+        );
+        assert!(SocketFunction::NAMES.contains(&function.name()));
+    }
+
+    #[test]
+    fn test_pty_config_stop_sequence() {
+        assert_eq!(
+            PtyConfig::default().stop_sequence(),
+            StopSequence::default()
+        );
+        assert_eq!(
+            StopSequence::default(),
+            sequence(PtySignal::Term, DEFAULT_STOP_TIMEOUT_MS)
+        );
+
+        let config: PtyConfig =
+            serde_yaml::from_str("stop_signal: HUP\nstop_timeout_ms: 500").unwrap();
+        assert_eq!(config.stop_sequence(), sequence(PtySignal::Hup, 500));
+        assert!(serde_yaml::from_str::<PtyConfig>("stop_signal: LOUDLY").is_err());
+    }
+
+    #[test]
+    fn test_send_signal_reaches_the_process_group() {
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("marker");
+        let manager = PtyManager::new().unwrap();
+        let reaper = spawn_registered(&manager, "shell", HUP_RECORDER, &[marker.to_str().unwrap()]);
+        wait_for_file(&marker, "ready");
+
+        manager.send_signal("shell", PtySignal::Hup).unwrap();
+
+        wait_for_file(&marker, "hup");
+        assert!(reaper.join().unwrap().success());
+        assert!(manager.send_signal("shell", PtySignal::Int).is_err());
+        assert!(manager.send_signal("missing", PtySignal::Int).is_err());
+    }
+
+    #[test]
+    fn test_stop_lets_the_process_exit_on_its_own() {
+        let manager = PtyManager::new().unwrap();
+        let reaper = spawn_registered(&manager, "server", "exec sleep 30", &[]);
+
+        let outcome = manager
+            .stop_pty_process_with("server", sequence(PtySignal::Term, 5000))
+            .unwrap();
+
+        assert_eq!(outcome, StopOutcome::Exited);
+        assert_eq!(reaper.join().unwrap().signal(), Some(libc::SIGTERM));
+    }
+
+    #[test]
+    fn test_stop_escalates_to_kill_after_the_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("marker");
+        let manager = PtyManager::new().unwrap();
+        // `exec` keeps the ignored TERM disposition in the single remaining process
+        let reaper = spawn_registered(
+            &manager,
+            "stubborn",
+            "trap '' TERM; echo ready > \"$1\"; exec sleep 30",
+            &[marker.to_str().unwrap()],
+        );
+        wait_for_file(&marker, "ready");
+
+        let outcome = manager
+            .stop_pty_process_with("stubborn", sequence(PtySignal::Term, 200))
+            .unwrap();
+
+        assert_eq!(outcome, StopOutcome::Killed);
+        assert_eq!(reaper.join().unwrap().signal(), Some(libc::SIGKILL));
+        // Stopped processes are not stopped twice
+        assert!(manager.stop_pty_process("stubborn").is_err());
+    }
+
+    #[test]
+    fn test_stop_sees_an_unreaped_exit() {
+        let manager = PtyManager::new().unwrap();
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        manager.add_test_pty_process_with_status(
+            "zombie".to_string(),
+            Arc::new(Mutex::new(CircularBuffer::new(10))),
+            PtyStatus::Running,
+            child.id(),
+        );
+        manager.set_pty_killable("zombie", true);
+
+        // Nothing reaps the child, so it stays a zombie after TERM
+        let started = std::time::Instant::now();
+        let outcome = manager
+            .stop_pty_process_with("zombie", sequence(PtySignal::Term, 5000))
+            .unwrap();
+
+        assert_eq!(outcome, StopOutcome::Exited);
+        assert!(started.elapsed() < Duration::from_secs(4));
+        assert_eq!(child.wait().unwrap().signal(), Some(libc::SIGTERM));
+    }
+
+    #[test]
+    fn test_stop_all_stops_every_running_process() {
+        let manager = PtyManager::new().unwrap();
+        let first = spawn_registered(&manager, "first", "exec sleep 30", &[]);
+        let second = spawn_registered(&manager, "second", "exec sleep 30", &[]);
+
+        manager.stop_all();
+
+        assert_eq!(first.join().unwrap().signal(), Some(libc::SIGTERM));
+        assert_eq!(second.join().unwrap().signal(), Some(libc::SIGTERM));
+        assert!(!manager.is_process_running("first"));
+        assert!(!manager.is_process_running("second"));
+    }
+
+    #[test]
+    fn test_send_pty_signal_socket_function_reports_failures() {
+        use crate::tests::test_utils::TestDataFactory;
+        let mut app_context = TestDataFactory::create_test_app_context();
+        app_context.pty_manager = Some(Arc::new(PtyManager::new().unwrap()));
+
+        let (_, messages) = run_socket_function(
+            SocketFunction::SendPtySignal {
+                box_id: "missing".to_string(),
+                signal: PtySignal::Int,
+            },
+            &app_context,
+        )
+        .unwrap();
+
+        match &messages[..] {
+            [Message::StreamUpdateMessage(update)] => {
+                assert_eq!(update.target_box_id, "missing");
+                assert!(update
+                    .content_update
+                    .starts_with("Failed to send SIGINT to PTY process"));
+                assert!(update.content_update.contains("PTY not found"));
+            }
+            other => panic!("Expected one stream update, got {:?}", other),
+        }
+    }
+}
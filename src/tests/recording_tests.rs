@@ -0,0 +1,248 @@
+#[cfg(test)]
+mod recording_tests {
+    use crate::model::common::{
+        ExecuteScript, ExecutionMode, ExecutionSource, OutputFormat, ScriptEnvironment,
+        SourceReference, SourceType,
+    };
+    use crate::pty_manager::PtyManager;
+    use crate::recording::{
+        recording_path, replay_app, AsciicastHeader, CastEvent, Player, Recorder, Recording,
+        REPLAY_BOX_ID,
+    };
+    use crate::tests::test_utils::TestDataFactory;
+    use std::fs;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    fn cast(lines: &[&str]) -> Recording {
+        Recording::parse(&lines.join("\n")).unwrap()
+    }
+
+    #[test]
+    fn test_recorder_writes_asciicast_v2() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested/dir/session.cast");
+        let mut header = AsciicastHeader::new(80, 24);
+        header.title = Some("deploy".to_string());
+
+        let mut recorder = Recorder::create(&path, &header).unwrap();
+        let euro = "€".as_bytes();
+        recorder.output(b"\x1b[1mhi ").unwrap();
+        recorder.output(&euro[..1]).unwrap();
+        recorder.output(&euro[1..]).unwrap();
+        recorder.resize(100, 30).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4, "{}", text);
+        assert!(lines[0].starts_with(r#"{"version":2,"width":80,"height":24,"timestamp":"#));
+        assert!(lines[0].ends_with(r#""title":"deploy"}"#));
+        assert!(
+            lines[1].ends_with(r#","o","\u001b[1mhi "]"#),
+            "{}",
+            lines[1]
+        );
+
+        let recording = Recording::parse(&text).unwrap();
+        assert_eq!(recording.header.title.as_deref(), Some("deploy"));
+        let events: Vec<&CastEvent> = recording.events.iter().map(|(_, e)| e).collect();
+        assert_eq!(
+            events,
+            vec![
+                &CastEvent::Output("\x1b[1mhi ".to_string()),
+                &CastEvent::Output("€".to_string()),
+                &CastEvent::Resize(100, 30),
+            ],
+            "a character split across reads is recorded whole"
+        );
+        assert!(recording.events[0].0 <= recording.events[2].0);
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let header = r#"{"version": 2, "width": 80, "height": 24}"#;
+        assert!(Recording::parse("").unwrap_err().contains("empty"));
+        assert!(
+            Recording::parse(r#"{"version": 1, "width": 80, "height": 24}"#)
+                .unwrap_err()
+                .contains("unsupported asciicast version 1")
+        );
+        assert!(Recording::parse(&format!("{}\n[0.5, \"o\"]", header))
+            .unwrap_err()
+            .starts_with("line 2: invalid event"));
+        assert!(
+            Recording::parse(&format!("{}\n\n[0.5, \"r\", \"wide\"]", header))
+                .unwrap_err()
+                .starts_with("line 3: invalid resize")
+        );
+
+        let recording = cast(&[
+            header,
+            r#"[0.1, "x", "future event type"]"#,
+            r#"[0.2, "m", "checkpoint"]"#,
+        ]);
+        assert_eq!(
+            recording.events,
+            vec![(0.2, CastEvent::Marker("checkpoint".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_player_paces_events_with_speed_and_idle_limit() {
+        let recording = cast(&[
+            r#"{"version": 2, "width": 40, "height": 5, "idle_time_limit": 2.0}"#,
+            r#"[1.0, "o", "one\r\n"]"#,
+            r#"[11.0, "o", "two\r\n"]"#,
+            r#"[12.0, "i", "q"]"#,
+        ]);
+
+        let mut player = Player::new(recording.clone(), 2.0, None);
+        assert_eq!(player.duration(), Duration::from_millis(2000));
+        assert_eq!(player.advance(Duration::from_millis(400)), None);
+        assert_eq!(
+            player.advance(Duration::from_millis(500)).as_deref(),
+            Some("one\n")
+        );
+        assert_eq!(player.advance(Duration::from_millis(1000)), None);
+        assert_eq!(
+            player.advance(Duration::from_millis(1500)).as_deref(),
+            Some("one\ntwo\n"),
+            "the ten second pause plays as two seconds at double speed"
+        );
+        assert!(!player.is_finished());
+        assert_eq!(
+            player.advance(Duration::from_millis(2000)),
+            None,
+            "input doesn't change the screen"
+        );
+        assert!(player.is_finished());
+
+        let player = Player::new(recording, 1.0, Some(0.5));
+        assert_eq!(
+            player.duration(),
+            Duration::from_millis(1500),
+            "--max-idle overrides the header's limit"
+        );
+    }
+
+    #[test]
+    fn test_replaying_full_screen_output_is_deterministic() {
+        let recording = cast(&[
+            r#"{"version": 2, "width": 20, "height": 4}"#,
+            r#"[0.1, "o", "\u001b[?1049h\u001b[2J\u001b[H"]"#,
+            r#"[0.2, "o", "top\u001b[3;1Hbottom"]"#,
+            r#"[0.3, "o", "\u001b[1;1Hnew"]"#,
+        ]);
+        let screen = |recording: &Recording| {
+            let mut player = Player::new(recording.clone(), 1.0, None);
+            player.finish().unwrap()
+        };
+        let first = screen(&recording);
+        assert_eq!(first, screen(&recording));
+        assert!(first.starts_with("new"), "{:?}", first);
+        assert!(first.contains("bottom"), "{:?}", first);
+    }
+
+    #[test]
+    fn test_recording_path_expands_only_the_home_directory() {
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(
+            recording_path("~/casts/build.cast"),
+            Path::new(&home).join("casts/build.cast")
+        );
+        assert_eq!(
+            recording_path("~casts/build.cast"),
+            Path::new("~casts/build.cast")
+        );
+        assert_eq!(recording_path("build.cast"), Path::new("build.cast"));
+    }
+
+    #[test]
+    fn test_pty_box_records_its_output() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("pty.cast");
+        let execute_script = ExecuteScript {
+            script: vec!["printf 'recorded output\\n'".to_string()],
+            source: ExecutionSource {
+                source_type: SourceType::StaticScript,
+                source_id: "recorded".to_string(),
+                source_reference: SourceReference::StaticConfig("recorded".to_string()),
+            },
+            execution_mode: ExecutionMode::Pty,
+            target_box_id: "recorded".to_string(),
+            libs: Vec::new(),
+            redirect_output: None,
+            append_output: false,
+            stream_id: "recorded-stream".to_string(),
+            target_bounds: None,
+            environment: ScriptEnvironment::default(),
+            timeout_ms: None,
+            output_format: OutputFormat::Text,
+            record: Some(path.to_string_lossy().to_string()),
+        };
+
+        let manager = PtyManager::new().unwrap();
+        let (sender, _receiver) = std::sync::mpsc::channel();
+        manager
+            .handle_execute_script(&execute_script, sender, uuid::Uuid::new_v4())
+            .unwrap();
+
+        let recording = wait_for_recording(&path, "recorded output");
+        assert_eq!((recording.header.width, recording.header.height), (120, 40));
+        assert_eq!(recording.header.title.as_deref(), Some("recorded"));
+        let mut player = Player::new(recording, 1.0, None);
+        assert!(player.finish().unwrap().contains("recorded output"));
+    }
+
+    fn wait_for_recording(path: &Path, expected: &str) -> Recording {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if let Ok(recording) = Recording::load(path) {
+                let output: String = recording
+                    .events
+                    .iter()
+                    .filter_map(|(_, event)| match event {
+                        CastEvent::Output(text) => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                if output.contains(expected) {
+                    return recording;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        panic!("{} never recorded {:?}", path.display(), expected);
+    }
+
+    #[test]
+    fn test_record_option_and_replay_app() {
+        let app = TestDataFactory::load_app_from_yaml_str(
+            r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'deploy'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          execution_mode: Pty
+          record: 'incidents/deploy.cast'
+          script: ['./deploy.sh']
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            app.script_record("deploy").as_deref(),
+            Some("incidents/deploy.cast")
+        );
+        assert_eq!(app.script_record("missing"), None);
+
+        let recording = cast(&[r#"{"version": 2, "width": 80, "height": 24}"#]);
+        let app = replay_app(Path::new("/tmp/deploy.cast"), &recording);
+        let muxbox = app.get_muxbox_by_id(REPLAY_BOX_ID).unwrap();
+        assert_eq!(muxbox.title.as_deref(), Some("Replay: deploy.cast"));
+        assert_eq!(app.get_active_layout().unwrap().id, REPLAY_BOX_ID);
+    }
+}
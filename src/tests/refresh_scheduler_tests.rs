@@ -0,0 +1,251 @@
+#[cfg(test)]
+mod refresh_scheduler_tests {
+    use crate::model::common::{
+        ExecuteScript, ExecutionMode, ExecutionSource, ExecutionSourceType, OutputFormat,
+        OverlapPolicy, ScriptEnvironment, SocketFunction, SourceReference, SourceType,
+    };
+    use crate::refresh_scheduler::{
+        admit, admit_script, finish, finish_script, forget_box, forget_removed_boxes, next_delay,
+        stats, Admission, RunOutcome,
+    };
+    use crate::socket_protocol::{execute_socket_function, SocketResult};
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::utils::run_tracked_script;
+    use std::io::ErrorKind;
+    use std::time::Duration;
+
+    /// Periodic refresh of `box_id` with a stream ID no other test shares
+    fn periodic_script(box_id: &str, script: &str) -> ExecuteScript {
+        ExecuteScript {
+            script: vec![script.to_string()],
+            source: ExecutionSource {
+                source_type: SourceType::PeriodicRefresh,
+                source_id: format!("refresh-{}", box_id),
+                source_reference: SourceReference::PeriodicConfig(box_id.to_string()),
+            },
+            execution_mode: ExecutionMode::Thread,
+            target_box_id: box_id.to_string(),
+            libs: vec![],
+            redirect_output: None,
+            append_output: false,
+            stream_id: uuid::Uuid::new_v4().to_string(),
+            target_bounds: None,
+            environment: ScriptEnvironment::default(),
+            timeout_ms: None,
+            output_format: OutputFormat::Text,
+            record: None,
+        }
+    }
+
+    #[test]
+    fn test_skip_drops_runs_while_one_is_in_flight() {
+        let run = periodic_script("cpu", "true");
+
+        assert_eq!(admit(&run, OverlapPolicy::Skip), Admission::Run);
+        assert_eq!(admit(&run, OverlapPolicy::Skip), Admission::Skip);
+        assert_eq!(admit(&run, OverlapPolicy::Skip), Admission::Skip);
+
+        let counters = stats(&run.stream_id).unwrap();
+        assert_eq!(counters.running, 1);
+        assert_eq!(counters.skipped, 2);
+        assert_eq!(counters.overruns, 2);
+
+        assert_eq!(finish(&run.stream_id, RunOutcome::Succeeded), None);
+        assert_eq!(stats(&run.stream_id).unwrap().running, 0);
+        assert_eq!(admit(&run, OverlapPolicy::Skip), Admission::Run);
+    }
+
+    #[test]
+    fn test_queue_one_keeps_only_the_latest_run() {
+        let first = periodic_script("cpu", "echo first");
+        let mut second = first.clone();
+        second.script = vec!["echo second".to_string()];
+        let mut third = first.clone();
+        third.script = vec!["echo third".to_string()];
+
+        assert_eq!(admit(&first, OverlapPolicy::QueueOne), Admission::Run);
+        assert_eq!(admit(&second, OverlapPolicy::QueueOne), Admission::Queued);
+        assert_eq!(admit(&third, OverlapPolicy::QueueOne), Admission::Queued);
+
+        let counters = stats(&first.stream_id).unwrap();
+        assert!(counters.queued);
+        assert_eq!(counters.skipped, 1, "the displaced queued run is skipped");
+        assert_eq!(counters.overruns, 2);
+
+        let next = finish(&first.stream_id, RunOutcome::Succeeded).unwrap();
+        assert_eq!(next.script, third.script);
+        let counters = stats(&first.stream_id).unwrap();
+        assert!(!counters.queued);
+        assert_eq!(counters.running, 1, "the queued run counts as running");
+
+        assert_eq!(finish(&first.stream_id, RunOutcome::Succeeded), None);
+        assert_eq!(stats(&first.stream_id).unwrap().running, 0);
+    }
+
+    #[test]
+    fn test_allow_runs_concurrently() {
+        let run = periodic_script("cpu", "true");
+
+        assert_eq!(admit(&run, OverlapPolicy::Allow), Admission::Run);
+        assert_eq!(admit(&run, OverlapPolicy::Allow), Admission::Run);
+
+        let counters = stats(&run.stream_id).unwrap();
+        assert_eq!(counters.running, 2);
+        assert_eq!(counters.overruns, 1);
+        assert_eq!(counters.skipped, 0);
+    }
+
+    #[test]
+    fn test_cancel_previous_kills_running_script() {
+        let run = periodic_script("cpu", "sleep 10");
+        assert_eq!(admit(&run, OverlapPolicy::CancelPrevious), Admission::Run);
+
+        let stream_id = run.stream_id.clone();
+        let runner = std::thread::spawn(move || {
+            run_tracked_script(
+                None,
+                &vec!["sleep 10".to_string()],
+                &ScriptEnvironment::default(),
+                None,
+                Some(&stream_id),
+            )
+        });
+        std::thread::sleep(Duration::from_millis(300));
+
+        assert_eq!(admit(&run, OverlapPolicy::CancelPrevious), Admission::Run);
+        let err = runner.join().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Interrupted);
+
+        assert_eq!(finish(&run.stream_id, RunOutcome::Cancelled), None);
+        let counters = stats(&run.stream_id).unwrap();
+        assert_eq!(counters.running, 1);
+        assert_eq!(counters.consecutive_failures, 0);
+    }
+
+    #[test]
+    fn test_backoff_doubles_after_failures_and_resets_on_success() {
+        let run = periodic_script("api", "false");
+        let delay = || next_delay(&run.stream_id, 100, None, Some(1000));
+
+        assert_eq!(delay(), Duration::from_millis(100));
+        for expected in [200, 400, 800, 1000, 1000] {
+            admit(&run, OverlapPolicy::Skip);
+            finish(&run.stream_id, RunOutcome::Failed);
+            assert_eq!(delay(), Duration::from_millis(expected));
+        }
+        assert_eq!(
+            next_delay(&run.stream_id, 100, None, None),
+            Duration::from_millis(100),
+            "no backoff without refresh_backoff_max_ms"
+        );
+
+        admit(&run, OverlapPolicy::Skip);
+        finish(&run.stream_id, RunOutcome::Succeeded);
+        assert_eq!(delay(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        let run = periodic_script("api", "true");
+        for _ in 0..50 {
+            let delay = next_delay(&run.stream_id, 100, Some(50), None);
+            assert!(delay >= Duration::from_millis(100));
+            assert!(delay <= Duration::from_millis(150));
+        }
+    }
+
+    #[test]
+    fn test_box_policy_applies_to_periodic_scripts_only() {
+        let yaml_content = r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'health'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          refresh_interval: 1000
+          overlap_policy: queue-one
+          refresh_jitter_ms: 250
+          refresh_backoff_max_ms: 30000
+          script: ['curl -sf localhost/health']
+"#;
+        let app = TestDataFactory::load_app_from_yaml_str(yaml_content).unwrap();
+
+        let muxbox = app.get_muxbox_by_id("health").unwrap();
+        assert_eq!(muxbox.overlap_policy, Some(OverlapPolicy::QueueOne));
+        assert_eq!(muxbox.refresh_jitter_ms, Some(250));
+        assert_eq!(muxbox.refresh_backoff_max_ms, Some(30000));
+
+        let run = periodic_script("health", "curl -sf localhost/health");
+        assert_eq!(admit_script(&app, &run), Admission::Run);
+        assert_eq!(admit_script(&app, &run), Admission::Queued);
+        assert!(finish_script(&run, RunOutcome::Succeeded).is_some());
+
+        let mut choice = periodic_script("health", "echo hi");
+        choice.source.source_type = SourceType::Choice("deploy".to_string());
+        assert_eq!(admit_script(&app, &choice), Admission::Run);
+        assert_eq!(admit_script(&app, &choice), Admission::Run);
+        assert!(stats(&choice.stream_id).is_none());
+        assert!(finish_script(&choice, RunOutcome::Succeeded).is_none());
+    }
+
+    #[test]
+    fn test_counters_go_away_with_their_box() {
+        // Box IDs of their own, as other tests share the counters
+        let removed = format!("removed-{}", uuid::Uuid::new_v4());
+        let kept = format!("kept-{}", uuid::Uuid::new_v4());
+        let removed_run = periodic_script(&removed, "true");
+        let kept_run = periodic_script(&kept, "true");
+        assert_eq!(admit(&removed_run, OverlapPolicy::QueueOne), Admission::Run);
+        assert_eq!(
+            admit(&removed_run, OverlapPolicy::QueueOne),
+            Admission::Queued
+        );
+        assert_eq!(admit(&kept_run, OverlapPolicy::Skip), Admission::Run);
+
+        let old = TestDataFactory::create_test_app_context_with_boxes(&[&removed, &kept]).app;
+        let new = TestDataFactory::create_test_app_context_with_boxes(&[&kept]).app;
+        forget_removed_boxes(&old, &new);
+        assert!(stats(&removed_run.stream_id).is_none());
+        assert_eq!(
+            finish(&removed_run.stream_id, RunOutcome::Succeeded),
+            None,
+            "the queued run goes with the box"
+        );
+        assert!(stats(&kept_run.stream_id).is_some());
+
+        forget_box(&kept);
+        assert!(stats(&kept_run.stream_id).is_none());
+    }
+
+    #[test]
+    fn test_execution_sources_report_refresh_counters() {
+        let mut app_context = TestDataFactory::create_test_app_context();
+        let stream_id = app_context.app.register_execution_source(
+            ExecutionSourceType::PeriodicScript("uptime".to_string()),
+            "default_muxbox".to_string(),
+        );
+        let mut run = periodic_script("default_muxbox", "uptime");
+        run.stream_id = stream_id.clone();
+        admit(&run, OverlapPolicy::Skip);
+        admit(&run, OverlapPolicy::Skip);
+
+        let (result, _) =
+            execute_socket_function(SocketFunction::ListExecutionSources {}, &app_context).unwrap();
+        match result {
+            SocketResult::ExecutionSources { sources } => {
+                let source = sources
+                    .iter()
+                    .find(|source| source.stream_id == stream_id)
+                    .unwrap();
+                let refresh = source.refresh.as_ref().unwrap();
+                assert_eq!(refresh.overlap_policy, OverlapPolicy::Skip);
+                assert_eq!(refresh.running, 1);
+                assert_eq!(refresh.skipped, 1);
+                assert_eq!(refresh.overruns, 1);
+            }
+            other => panic!("Expected execution sources, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! Remote control listener tests
+//!
+//! Verify the token and allow-list policy, and that TCP and WebSocket connections
+//! are served by the same socket server as the Unix socket.
+
+#[cfg(test)]
+mod tests {
+    use crate::model::app::RemoteControlConfig;
+    use crate::model::common::SocketFunction;
+    use crate::socket_loop::{RemoteAccess, SocketServer};
+    use crate::socket_protocol::*;
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::thread_manager::Message;
+    use crate::AppContext;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const TOKEN: &str = "s3cret";
+
+    fn start_server(
+        allowed_functions: Option<Vec<String>>,
+    ) -> (tempfile::TempDir, SocketServer, Arc<RemoteAccess>) {
+        let dir = tempfile::tempdir().unwrap();
+        let server = SocketServer::bind(&dir.path().join("boxmux-remote-test.sock")).unwrap();
+        let access = Arc::new(RemoteAccess::new(TOKEN, allowed_functions.as_deref()).unwrap());
+        (dir, server, access)
+    }
+
+    fn pump(server: &mut SocketServer, app_context: &AppContext) -> Vec<Message> {
+        let mut messages = Vec::new();
+        for _ in 0..10 {
+            messages.extend(server.poll(app_context, Duration::from_millis(20)));
+        }
+        messages
+    }
+
+    fn request(token: Option<&str>, function: SocketFunction) -> String {
+        let request = SocketRequest {
+            version: SOCKET_PROTOCOL_VERSION,
+            id: Some("r".to_string()),
+            token: token.map(str::to_string),
+            function,
+        };
+        serde_json::to_string(&request).unwrap()
+    }
+
+    fn connect_tcp(
+        server: &SocketServer,
+        access: Arc<RemoteAccess>,
+    ) -> (TcpStream, BufReader<TcpStream>) {
+        let address = server.listen_tcp("127.0.0.1:0", access).unwrap();
+        let stream = TcpStream::connect(address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let reader = BufReader::new(stream.try_clone().unwrap());
+        (stream, reader)
+    }
+
+    fn read_response(reader: &mut BufReader<TcpStream>) -> SocketResponse {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn error_code(response: &SocketResponse) -> Option<SocketErrorCode> {
+        match &response.outcome {
+            SocketOutcome::Error { error } => Some(error.code),
+            SocketOutcome::Ok { .. } => None,
+        }
+    }
+
+    /// Test policy construction, token comparison and the allow-list
+    #[test]
+    fn test_remote_access_policy() {
+        assert!(RemoteAccess::new("", None).is_err());
+        assert!(RemoteAccess::new(TOKEN, Some(&["NoSuchFunction".to_string()])).is_err());
+
+        let access = RemoteAccess::new(TOKEN, Some(&["ReplaceBoxContent".to_string()])).unwrap();
+        assert!(access.token_matches(TOKEN));
+        assert!(!access.token_matches("s3cre"));
+        assert!(!access.token_matches("s3cret!"));
+        assert!(access.allows(&SocketFunction::ReplaceBoxContent {
+            box_id: "a".to_string(),
+            success: true,
+            content: String::new(),
+        }));
+        assert!(!access.allows(&SocketFunction::SpawnPtyProcess {
+            box_id: "a".to_string(),
+            script: vec![],
+            libs: None,
+            redirect_output: None,
+        }));
+
+        let read_only = RemoteAccess::new(TOKEN, None).unwrap();
+        assert!(read_only.allows(&SocketFunction::ListLayouts {}));
+        assert!(read_only.allows(&SocketFunction::Subscribe {
+            box_ids: None,
+            events: None,
+        }));
+        assert!(!read_only.allows(&SocketFunction::SendPtyInput {
+            box_id: "a".to_string(),
+            input: "ls\n".to_string(),
+        }));
+        assert!(!read_only.allows(&SocketFunction::StopBoxRefresh {
+            box_id: "a".to_string(),
+        }));
+        assert!(SocketFunction::READ_ONLY
+            .iter()
+            .all(|name| SocketFunction::NAMES.contains(name)));
+
+        let config = RemoteControlConfig {
+            tcp_address: Some("127.0.0.1:0".to_string()),
+            ..Default::default()
+        };
+        if std::env::var(crate::socket_loop::REMOTE_TOKEN_ENV_VAR).is_err() {
+            assert!(RemoteAccess::from_config(&config).is_err());
+        }
+    }
+
+    /// Test that every function name is in the allow-list vocabulary
+    #[test]
+    fn test_socket_function_names() {
+        let function = SocketFunction::GetPtyBuffer {
+            box_id: "a".to_string(),
+            start: None,
+            count: None,
+        };
+        let wire = serde_json::to_value(&function).unwrap();
+        assert!(wire.get(function.name()).is_some());
+        assert!(SocketFunction::NAMES.contains(&function.name()));
+    }
+
+    /// Test that the token is never printed with a request
+    #[test]
+    fn test_request_debug_redacts_token() {
+        let rendered = format!(
+            "{:?}",
+            SocketRequest {
+                token: Some(TOKEN.to_string()),
+                ..SocketRequest::new(SocketFunction::ListLayouts {})
+            }
+        );
+        assert!(!rendered.contains(TOKEN));
+    }
+
+    /// Test that a TCP request with the token is executed
+    #[test]
+    fn test_tcp_request_with_token() {
+        let (_dir, mut server, access) = start_server(Some(vec![
+            "StopBoxRefresh".to_string(),
+            "ListLayouts".to_string(),
+        ]));
+        let app_context = TestDataFactory::create_test_app_context_with_boxes(&["a"]);
+        let (mut stream, mut reader) = connect_tcp(&server, access);
+
+        writeln!(
+            stream,
+            "{}",
+            request(
+                Some(TOKEN),
+                SocketFunction::StopBoxRefresh {
+                    box_id: "a".to_string()
+                }
+            )
+        )
+        .unwrap();
+        let messages = pump(&mut server, &app_context);
+        assert_eq!(messages, vec![Message::StopBoxRefresh("a".to_string())]);
+        assert!(read_response(&mut reader).is_ok());
+
+        // The connection stays authenticated for later requests
+        writeln!(stream, "{}", request(None, SocketFunction::ListLayouts {})).unwrap();
+        pump(&mut server, &app_context);
+        assert!(read_response(&mut reader).is_ok());
+    }
+
+    /// Test that a missing or wrong token is rejected and the connection closed
+    #[test]
+    fn test_tcp_request_without_token() {
+        let (_dir, mut server, access) = start_server(None);
+        let app_context = TestDataFactory::create_test_app_context();
+        let (mut stream, mut reader) = connect_tcp(&server, access.clone());
+
+        writeln!(
+            stream,
+            "{}",
+            request(
+                Some("wrong"),
+                SocketFunction::StopBoxRefresh {
+                    box_id: "a".to_string()
+                }
+            )
+        )
+        .unwrap();
+        let messages = pump(&mut server, &app_context);
+        assert!(messages.is_empty());
+        assert_eq!(
+            error_code(&read_response(&mut reader)),
+            Some(SocketErrorCode::Unauthorized)
+        );
+        let mut rest = String::new();
+        assert_eq!(reader.read_to_string(&mut rest).unwrap_or(0), 0);
+
+        // Bare legacy functions carry no token
+        let (mut stream, mut reader) = connect_tcp(&server, access);
+        stream
+            .write_all(br#"{"StopBoxRefresh": {"box_id": "a"}}"#)
+            .unwrap();
+        assert!(pump(&mut server, &app_context).is_empty());
+        assert_eq!(
+            error_code(&read_response(&mut reader)),
+            Some(SocketErrorCode::Unauthorized)
+        );
+    }
+
+    /// Test that functions outside the allow-list are forbidden
+    #[test]
+    fn test_tcp_allow_list() {
+        let (_dir, mut server, access) = start_server(Some(vec![
+            "ReplaceBoxContent".to_string(),
+            "Subscribe".to_string(),
+        ]));
+        let app_context = TestDataFactory::create_test_app_context_with_boxes(&["a"]);
+        let (mut stream, mut reader) = connect_tcp(&server, access);
+
+        writeln!(
+            stream,
+            "{}",
+            request(
+                Some(TOKEN),
+                SocketFunction::SpawnPtyProcess {
+                    box_id: "a".to_string(),
+                    script: vec!["bash".to_string()],
+                    libs: None,
+                    redirect_output: None,
+                }
+            )
+        )
+        .unwrap();
+        writeln!(
+            stream,
+            "{}",
+            request(
+                None,
+                SocketFunction::ReplaceBoxContent {
+                    box_id: "a".to_string(),
+                    success: true,
+                    content: "hi".to_string(),
+                }
+            )
+        )
+        .unwrap();
+        let messages = pump(&mut server, &app_context);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(
+            error_code(&read_response(&mut reader)),
+            Some(SocketErrorCode::Forbidden)
+        );
+        assert!(read_response(&mut reader).is_ok());
+    }
+
+    /// Test a request and an event over WebSocket
+    #[test]
+    fn test_websocket_request_and_subscription() {
+        let (_dir, mut server, access) = start_server(None);
+        let app_context = TestDataFactory::create_test_app_context();
+        let address = server.listen_websocket("127.0.0.1:0", access).unwrap();
+
+        let stream = TcpStream::connect(address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let (mut socket, _) = tungstenite::client(format!("ws://{}/", address), stream).unwrap();
+
+        socket
+            .send(tungstenite::Message::Text(request(
+                Some(TOKEN),
+                SocketFunction::Subscribe {
+                    box_ids: None,
+                    events: Some(vec![SocketEventType::LayoutSwitched]),
+                },
+            )))
+            .unwrap();
+        pump(&mut server, &app_context);
+        assert_eq!(server.subscriber_count(), 1);
+
+        let response: SocketResponse =
+            serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+        assert!(response.is_ok());
+
+        server.publish(&Message::SwitchActiveLayout("main".to_string()));
+        pump(&mut server, &app_context);
+        let event: SocketEventLine =
+            serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+        assert_eq!(
+            event.event,
+            SocketEvent::LayoutSwitched {
+                layout_id: "main".to_string()
+            }
+        );
+    }
+}
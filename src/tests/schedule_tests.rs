@@ -0,0 +1,324 @@
+#[cfg(test)]
+mod schedule_tests {
+    use crate::model::app::App;
+    use crate::model::common::{
+        BatchSourceState, BatchStatus, ExecutionMode, OutputFormat, SourceReference, SourceState,
+        SourceType, StreamUpdate,
+    };
+    use crate::schedule_loop::{
+        has_schedules, parse_daily_time, scheduled_script, CronExpr, FileWatch, ScheduleFiring,
+        Scheduler,
+    };
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::thread_manager::Message;
+    use chrono::{NaiveDate, NaiveDateTime};
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// 2026-10-16 is a Friday
+    fn time(day: u32, hour: u32, minute: u32, second: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, second)
+            .unwrap()
+    }
+
+    fn scheduled_app() -> App {
+        TestDataFactory::load_app_from_yaml_str(
+            r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'build'
+          position: {x1: 0%, y1: 0%, x2: 50%, y2: 100%}
+          script: ['make']
+          schedule:
+            cron: '*/15 * * * *'
+            on_layout_activated: true
+        - id: 'summary'
+          position: {x1: 50%, y1: 0%, x2: 100%, y2: 100%}
+          script: ['cat summary.txt']
+          schedule:
+            after_box: build
+            at: '18:00'
+          choices:
+            - id: 'deploy'
+              content: 'Deploy'
+              script: ['./deploy.sh']
+              execution_mode: Thread
+              redirect_output: 'build'
+              schedule:
+                cron: '0 9 * * mon'
+    - id: 'other'
+      children:
+        - id: 'logs'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          script: ['tail logs/app.log']
+          schedule:
+            on_layout_activated: true
+"#,
+        )
+        .unwrap()
+    }
+
+    fn firing(box_id: &str, choice_id: Option<&str>, trigger: &str) -> ScheduleFiring {
+        ScheduleFiring {
+            box_id: box_id.to_string(),
+            choice_id: choice_id.map(str::to_string),
+            trigger: trigger.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cron_fields_ranges_steps_and_names() {
+        let cron = CronExpr::parse("*/15 9-17 * * mon-fri").unwrap();
+        assert!(cron.matches(&time(16, 9, 30, 0)));
+        assert!(cron.matches(&time(16, 17, 45, 0)));
+        assert!(!cron.matches(&time(16, 9, 31, 0)));
+        assert!(!cron.matches(&time(16, 18, 0, 0)));
+        assert!(!cron.matches(&time(17, 9, 30, 0)), "Saturday");
+
+        let daily = CronExpr::parse("@daily").unwrap();
+        assert!(daily.matches(&time(17, 0, 0, 0)));
+        assert!(!daily.matches(&time(17, 0, 1, 0)));
+
+        let sunday = CronExpr::parse("0 12 * * 7").unwrap();
+        assert!(sunday.matches(&time(18, 12, 0, 0)));
+
+        let offset_step = CronExpr::parse("5/20 * * * *").unwrap();
+        assert!(offset_step.matches(&time(16, 3, 45, 0)));
+        assert!(!offset_step.matches(&time(16, 3, 40, 0)));
+    }
+
+    #[test]
+    fn test_cron_day_of_month_or_day_of_week() {
+        // Friday the 13th style: either the 13th or any Friday
+        let cron = CronExpr::parse("0 0 13 * fri").unwrap();
+        assert!(cron.matches(&time(13, 0, 0, 0)), "the 13th, a Tuesday");
+        assert!(cron.matches(&time(16, 0, 0, 0)), "a Friday");
+        assert!(!cron.matches(&time(14, 0, 0, 0)));
+    }
+
+    #[test]
+    fn test_invalid_cron_and_times_are_rejected() {
+        assert!(CronExpr::parse("* * * *").unwrap_err().contains("5 fields"));
+        assert!(CronExpr::parse("60 * * * *").is_err());
+        assert!(CronExpr::parse("*/0 * * * *").is_err());
+        assert!(CronExpr::parse("0 0 * * funday").is_err());
+        assert!(CronExpr::parse("5-1 * * * *").is_err());
+
+        assert_eq!(parse_daily_time("07:05").unwrap(), time(1, 7, 5, 0).time());
+        assert_eq!(
+            parse_daily_time("23:59:30").unwrap(),
+            time(1, 23, 59, 30).time()
+        );
+        assert!(parse_daily_time("25:00").is_err());
+        assert!(parse_daily_time("noon").is_err());
+    }
+
+    #[test]
+    fn test_first_tick_runs_active_layout_triggers() {
+        let app = scheduled_app();
+        assert!(has_schedules(&app));
+
+        let mut scheduler = Scheduler::new(&app, time(16, 10, 1, 0));
+        assert_eq!(
+            scheduler.tick(time(16, 10, 1, 0)),
+            vec![firing("build", None, "layout main activated")]
+        );
+        assert!(scheduler.tick(time(16, 10, 1, 30)).is_empty());
+    }
+
+    #[test]
+    fn test_tick_fires_cron_minutes_and_at_times_once() {
+        let app = scheduled_app();
+        let mut scheduler = Scheduler::new(&app, time(16, 10, 14, 30));
+        scheduler.tick(time(16, 10, 14, 30));
+
+        assert_eq!(
+            scheduler.tick(time(16, 10, 15, 0)),
+            vec![firing("build", None, "cron */15 * * * *")]
+        );
+        assert!(scheduler.tick(time(16, 10, 15, 20)).is_empty());
+
+        let mut scheduler = Scheduler::new(&app, time(16, 17, 59, 59));
+        scheduler.tick(time(16, 17, 59, 59));
+        assert_eq!(
+            scheduler.tick(time(16, 18, 0, 1)),
+            vec![
+                firing("build", None, "cron */15 * * * *"),
+                firing("summary", None, "at 18:00"),
+            ]
+        );
+        assert!(scheduler.tick(time(16, 18, 0, 2)).is_empty());
+
+        // Choice schedules: Monday 09:00
+        let mut scheduler = Scheduler::new(&app, time(19, 8, 59, 50));
+        scheduler.tick(time(19, 8, 59, 50));
+        assert_eq!(
+            scheduler.tick(time(19, 9, 0, 0)),
+            vec![
+                firing("build", None, "cron */15 * * * *"),
+                firing("summary", Some("deploy"), "cron 0 9 * * mon"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_added_and_edited_schedules_are_picked_up() {
+        let mut app = scheduled_app();
+        let mut scheduler = Scheduler::new(&app, time(16, 10, 1, 0));
+        scheduler.tick(time(16, 10, 1, 0));
+        assert!(!scheduler.update(&app), "nothing changed");
+
+        let mut added = app.get_muxbox_by_id("logs").unwrap().clone();
+        added.id = "added".to_string();
+        added.schedule.as_mut().unwrap().at = Some("10:05".to_string());
+        app.get_layout_by_id_mut("main")
+            .unwrap()
+            .children
+            .as_mut()
+            .unwrap()
+            .push(added);
+        app.get_muxbox_by_id_mut("build").unwrap().schedule = None;
+        assert!(scheduler.update(&app));
+
+        assert_eq!(
+            scheduler.tick(time(16, 10, 5, 0)),
+            vec![firing("added", None, "at 10:05")],
+            "layout activation isn't repeated and the removed schedule is gone"
+        );
+    }
+
+    #[test]
+    fn test_layout_switch_and_finished_box_trigger_runs() {
+        let app = scheduled_app();
+        let mut scheduler = Scheduler::new(&app, time(16, 10, 1, 0));
+
+        assert_eq!(
+            scheduler.observe(&Message::SwitchActiveLayout("other".to_string())),
+            vec![firing("logs", None, "layout other activated")]
+        );
+
+        let finished = StreamUpdate {
+            stream_id: "stream".to_string(),
+            target_box_id: "build".to_string(),
+            content_update: "done".to_string(),
+            source_state: SourceState::Batch(BatchSourceState {
+                task_id: "stream".to_string(),
+                queue_wait_time: Duration::from_millis(0),
+                execution_time: Duration::from_millis(10),
+                exit_code: Some(0),
+                status: BatchStatus::Completed,
+            }),
+            execution_mode: ExecutionMode::Immediate,
+            output_format: OutputFormat::Text,
+        };
+        assert_eq!(
+            scheduler.observe(&Message::StreamUpdateMessage(finished.clone())),
+            vec![firing("summary", None, "after box build")]
+        );
+
+        let mut other_box = finished;
+        other_box.target_box_id = "logs".to_string();
+        assert!(scheduler
+            .observe(&Message::StreamUpdateMessage(other_box))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_file_watch_reports_created_and_removed_files() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("logs/nested")).unwrap();
+        fs::write(dir.path().join("logs/notes.txt"), "x").unwrap();
+
+        let mut watch = FileWatch::new("logs/**/*.log", Some(dir.path())).unwrap();
+        assert!(!watch.changed());
+
+        fs::write(dir.path().join("logs/notes.txt"), "ignored").unwrap();
+        assert!(!watch.changed(), "non-matching files are ignored");
+
+        fs::write(dir.path().join("logs/nested/app.log"), "line").unwrap();
+        assert!(watch.changed());
+        assert!(!watch.changed());
+
+        fs::remove_file(dir.path().join("logs/nested/app.log")).unwrap();
+        assert!(watch.changed());
+
+        let mut single = FileWatch::new("config.yaml", Some(dir.path())).unwrap();
+        fs::write(dir.path().join("config.yaml"), "a: 1").unwrap();
+        assert!(single.changed());
+    }
+
+    #[test]
+    fn test_scheduled_script_uses_box_or_choice_settings() {
+        let mut app = scheduled_app();
+
+        let execute_script =
+            scheduled_script(&mut app, &firing("build", None, "cron */15 * * * *")).unwrap();
+        assert_eq!(execute_script.script, vec!["make".to_string()]);
+        assert_eq!(execute_script.target_box_id, "build");
+        assert_eq!(
+            execute_script.source.source_type,
+            SourceType::ScheduledScript
+        );
+        assert_eq!(
+            execute_script.source.source_reference,
+            SourceReference::Schedule("cron */15 * * * *".to_string())
+        );
+
+        let again = scheduled_script(&mut app, &firing("build", None, "at 18:00")).unwrap();
+        assert_eq!(
+            again.stream_id, execute_script.stream_id,
+            "each schedule keeps one stream"
+        );
+
+        let choice = scheduled_script(
+            &mut app,
+            &firing("summary", Some("deploy"), "cron 0 9 * * mon"),
+        )
+        .unwrap();
+        assert_eq!(choice.script, vec!["./deploy.sh".to_string()]);
+        assert_eq!(choice.execution_mode, ExecutionMode::Thread);
+        assert_eq!(choice.redirect_output.as_deref(), Some("build"));
+        assert_ne!(choice.stream_id, execute_script.stream_id);
+
+        assert!(scheduled_script(&mut app, &firing("missing", None, "at 18:00")).is_none());
+    }
+
+    #[test]
+    fn test_invalid_schedules_fail_to_load() {
+        let yaml = |schedule: &str| {
+            format!(
+                r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'build'
+          position: {{x1: 0%, y1: 0%, x2: 100%, y2: 100%}}
+          script: ['make']
+          schedule:
+            {}
+"#,
+                schedule
+            )
+        };
+
+        let err = TestDataFactory::load_app_from_yaml_str(&yaml("cron: '61 * * * *'")).unwrap_err();
+        assert!(err.contains("schedule.cron"), "{}", err);
+        let err = TestDataFactory::load_app_from_yaml_str(&yaml("at: '24:30'")).unwrap_err();
+        assert!(err.contains("schedule.at"), "{}", err);
+        let err = TestDataFactory::load_app_from_yaml_str(&yaml("after_box: nowhere")).unwrap_err();
+        assert!(err.contains("references unknown box 'nowhere'"), "{}", err);
+        let err = TestDataFactory::load_app_from_yaml_str(&yaml("after_box: build")).unwrap_err();
+        assert!(err.contains("scheduled after itself"), "{}", err);
+        assert!(
+            TestDataFactory::load_app_from_yaml_str(&yaml("on_file_change: 'src/**/*.rs'")).is_ok()
+        );
+    }
+}
@@ -0,0 +1,240 @@
+#[cfg(test)]
+mod script_environment_tests {
+    use crate::model::common::{ScriptEnvironment, ShellSpec};
+    use crate::pty_manager::PtyManager;
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::utils::{expand_home, run_script_in_environment, script_command};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    fn environment(cwd: Option<&Path>, env: &[(&str, &str)]) -> ScriptEnvironment {
+        ScriptEnvironment {
+            cwd: cwd.map(|dir| dir.to_string_lossy().to_string()),
+            env: env
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn wait_for_file(path: &Path) -> String {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if let Ok(contents) = fs::read_to_string(path) {
+                if contents.ends_with('\n') {
+                    return contents;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        panic!("{} was never written", path.display());
+    }
+
+    #[test]
+    fn test_yaml_keys_resolve_with_choice_over_box() {
+        let yaml_content = r#"
+app:
+  variables:
+    PROJECT: "/tmp"
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'project'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          cwd: '${PROJECT}'
+          env:
+            STAGE: 'dev'
+            TARGET: '${PROJECT}/out'
+          shell: zsh
+          inherit_env: false
+          choices:
+            - id: 'build'
+              content: 'Build'
+              script: ['make']
+              env:
+                STAGE: 'release'
+              shell: ['python3', '-c']
+            - id: 'plain'
+              content: 'Plain'
+              script: ['ls']
+"#;
+        let app = TestDataFactory::load_app_from_yaml_str(yaml_content).unwrap();
+
+        let muxbox = app.get_muxbox_by_id("project").unwrap();
+        assert_eq!(muxbox.cwd.as_deref(), Some("/tmp"));
+        assert_eq!(muxbox.shell, Some(ShellSpec::Program("zsh".to_string())));
+
+        let build = app.script_environment("project", Some("build"));
+        assert_eq!(build.cwd.as_deref(), Some("/tmp"));
+        assert_eq!(build.env.get("STAGE").map(String::as_str), Some("release"));
+        assert_eq!(
+            build.env.get("TARGET").map(String::as_str),
+            Some("/tmp/out")
+        );
+        assert_eq!(
+            build.shell,
+            Some(ShellSpec::Argv(vec![
+                "python3".to_string(),
+                "-c".to_string()
+            ]))
+        );
+        assert!(!build.inherit_env);
+
+        let plain = app.script_environment("project", Some("plain"));
+        assert_eq!(plain.env.get("STAGE").map(String::as_str), Some("dev"));
+        assert_eq!(plain.shell, Some(ShellSpec::Program("zsh".to_string())));
+
+        let unknown = app.script_environment("missing", None);
+        assert_eq!(unknown, ScriptEnvironment::default());
+    }
+
+    #[test]
+    fn test_command_line_uses_shell_spec() {
+        let mut env = ScriptEnvironment::default();
+        assert_eq!(
+            env.command_line("bash", "echo hi"),
+            (
+                "bash".to_string(),
+                vec!["-c".to_string(), "echo hi".to_string()]
+            )
+        );
+
+        env.shell = Some(ShellSpec::Program("zsh".to_string()));
+        assert_eq!(env.command_line("bash", "echo hi").0, "zsh");
+
+        env.shell = Some(ShellSpec::Argv(vec![
+            "python3".to_string(),
+            "-u".to_string(),
+            "-c".to_string(),
+        ]));
+        assert_eq!(
+            env.command_line("bash", "print(1)"),
+            (
+                "python3".to_string(),
+                vec!["-u".to_string(), "-c".to_string(), "print(1)".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn test_regular_script_runs_in_cwd_with_env() {
+        let dir = TempDir::new().unwrap();
+        let env = environment(Some(dir.path()), &[("BOXMUX_TEST_STAGE", "staging")]);
+
+        let output = run_script_in_environment(
+            None,
+            &vec!["pwd".to_string(), "echo $BOXMUX_TEST_STAGE".to_string()],
+            &env,
+        )
+        .unwrap();
+
+        let expected_dir = dir.path().canonicalize().unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            Path::new(lines[0]).canonicalize().unwrap(),
+            expected_dir,
+            "script should run in the configured cwd"
+        );
+        assert_eq!(lines[1], "staging");
+    }
+
+    #[test]
+    fn test_custom_shell_argv_receives_script() {
+        let mut env = ScriptEnvironment::default();
+        env.shell = Some(ShellSpec::Argv(vec!["sh".to_string(), "-c".to_string()]));
+        let output = script_command(&env, "bash", "echo from-sh")
+            .unwrap()
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "from-sh\n");
+    }
+
+    #[test]
+    fn test_inherit_env_false_keeps_only_base_variables() {
+        std::env::set_var("BOXMUX_TEST_LEAKED", "leaked");
+        let mut env = environment(None, &[("BOXMUX_TEST_OWN", "own")]);
+        env.inherit_env = false;
+
+        let output = script_command(
+            &env,
+            "sh",
+            "echo \"[${BOXMUX_TEST_LEAKED}] [${BOXMUX_TEST_OWN}]\"; command -v sh >/dev/null && echo path-ok",
+        )
+        .unwrap()
+        .output()
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "[] [own]\npath-ok\n"
+        );
+        std::env::remove_var("BOXMUX_TEST_LEAKED");
+    }
+
+    #[test]
+    fn test_only_a_bare_tilde_prefix_means_home() {
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(expand_home("~"), home);
+        assert_eq!(expand_home("~/src"), format!("{}/src", home));
+        assert_eq!(expand_home("~foo/src"), "~foo/src");
+        assert_eq!(expand_home("/tmp/~"), "/tmp/~");
+
+        let env = environment(Some(Path::new("~")), &[]);
+        assert_eq!(env.working_dir().unwrap(), Some(PathBuf::from(&home)));
+        let env = environment(Some(Path::new("~boxmux_no_such_user")), &[]);
+        assert!(env
+            .working_dir()
+            .unwrap_err()
+            .to_string()
+            .contains("Working directory '~boxmux_no_such_user' does not exist"));
+    }
+
+    #[test]
+    fn test_missing_cwd_is_an_error() {
+        let env = environment(Some(Path::new("/nonexistent/boxmux/dir")), &[]);
+        let err = run_script_in_environment(None, &vec!["pwd".to_string()], &env).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Working directory '/nonexistent/boxmux/dir' does not exist"));
+    }
+
+    #[test]
+    fn test_pty_spawn_applies_cwd_env_and_shell() {
+        let dir = TempDir::new().unwrap();
+        let out = dir.path().join("out.txt");
+        let mut env = environment(
+            Some(dir.path()),
+            &[("BOXMUX_TEST_OUT", out.to_str().unwrap())],
+        );
+        env.shell = Some(ShellSpec::Program("sh".to_string()));
+
+        let manager = PtyManager::new().unwrap();
+        let (sender, _receiver) = std::sync::mpsc::channel();
+        manager
+            .spawn_pty_script_with_redirect(
+                "pty_env_box".to_string(),
+                &["echo \"$(pwd) $BOXMUX_TEST_OUT\" > \"$BOXMUX_TEST_OUT\"".to_string()],
+                None,
+                sender,
+                uuid::Uuid::new_v4(),
+                None,
+                Some("pty_env_stream".to_string()),
+                None,
+                &env,
+                None,
+            )
+            .unwrap();
+
+        let contents = wait_for_file(&out);
+        let (cwd, target) = contents.trim_end().split_once(' ').unwrap();
+        assert_eq!(
+            Path::new(cwd).canonicalize().unwrap(),
+            dir.path().canonicalize().unwrap()
+        );
+        assert_eq!(target, out.to_str().unwrap());
+    }
+}
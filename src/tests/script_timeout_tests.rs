@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod script_timeout_tests {
+    use crate::draw_loop::kill_source;
+    use crate::model::common::{
+        ActionType, BatchSourceState, BatchStatus, ExecutionMode, OutputFormat, ScriptEnvironment,
+        SourceAction, SourceState, Stream, StreamType, StreamUpdate,
+    };
+    use crate::socket_protocol::{socket_events_for_message, SocketEvent};
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::thread_manager::Message;
+    use crate::utils::{cancel_scripts, run_script_process, run_tracked_script, script_command};
+    use std::io::ErrorKind;
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_timeout_resolves_choice_over_box() {
+        let yaml_content = r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'health'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          timeout_ms: 3000
+          choices:
+            - id: 'slow'
+              content: 'Slow'
+              script: ['sleep 10']
+              timeout_ms: 500
+            - id: 'default'
+              content: 'Default'
+              script: ['true']
+"#;
+        let app = TestDataFactory::load_app_from_yaml_str(yaml_content).unwrap();
+
+        assert_eq!(app.script_timeout_ms("health", None), Some(3000));
+        assert_eq!(app.script_timeout_ms("health", Some("slow")), Some(500));
+        assert_eq!(app.script_timeout_ms("health", Some("default")), Some(3000));
+        assert_eq!(app.script_timeout_ms("missing", None), None);
+    }
+
+    #[test]
+    fn test_timeout_kills_process_group_and_keeps_partial_output() {
+        let dir = TempDir::new().unwrap();
+        let marker = dir.path().join("late");
+        let script = vec![
+            "echo started".to_string(),
+            format!("(sleep 0.4; echo late > '{}') &", marker.display()),
+            "sleep 5".to_string(),
+        ];
+
+        let started = Instant::now();
+        let err = run_tracked_script(
+            None,
+            &script,
+            &ScriptEnvironment::default(),
+            Some(Duration::from_millis(150)),
+            None,
+        )
+        .unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(3));
+        assert_eq!(err.to_string(), "started\nScript timed out after 150ms");
+
+        std::thread::sleep(Duration::from_millis(600));
+        assert!(
+            !marker.exists(),
+            "background child of the timed out script should have been killed"
+        );
+    }
+
+    #[test]
+    fn test_script_within_timeout_completes() {
+        let command = script_command(&ScriptEnvironment::default(), "sh", "echo quick").unwrap();
+        let output = run_script_process(command, Some(Duration::from_secs(5)), None).unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "quick\n");
+    }
+
+    #[test]
+    fn test_cancel_scripts_interrupts_running_script() {
+        let stream_id = "cancel_test_stream";
+        let runner = std::thread::spawn(move || {
+            run_tracked_script(
+                None,
+                &vec!["sleep 10".to_string()],
+                &ScriptEnvironment::default(),
+                None,
+                Some(stream_id),
+            )
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut cancelled = 0;
+        while cancelled == 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+            cancelled = cancel_scripts(stream_id);
+        }
+        assert_eq!(cancelled, 1);
+
+        let err = runner.join().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Interrupted);
+        assert_eq!(
+            cancel_scripts(stream_id),
+            0,
+            "finished scripts are untracked"
+        );
+    }
+
+    #[test]
+    fn test_kill_source_action_cancels_the_choice_stream_script() {
+        let dir = TempDir::new().unwrap();
+        let pid_file = dir.path().join("pid");
+        let stream_id = "kill_action_stream";
+        let mut app_context = TestDataFactory::create_test_app_context();
+        let muxbox = app_context
+            .app
+            .get_muxbox_by_id_mut("default_muxbox")
+            .unwrap();
+        muxbox.streams.insert(
+            stream_id.to_string(),
+            Stream::new(
+                stream_id.to_string(),
+                StreamType::ChoiceExecution("choice_slow".to_string()),
+                "slow".to_string(),
+                Vec::new(),
+                None,
+                None,
+            ),
+        );
+
+        let script = vec![
+            format!("echo $$ > '{}'", pid_file.display()),
+            "sleep 10".into(),
+        ];
+        let runner = std::thread::spawn(move || {
+            run_tracked_script(
+                None,
+                &script,
+                &ScriptEnvironment::default(),
+                None,
+                Some(stream_id),
+            )
+        });
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !pid_file.exists() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let pid: i32 = std::fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+
+        // What closing the choice's tab sends: the source ID, not the stream ID
+        let kill = SourceAction {
+            action: ActionType::Kill,
+            source_id: "choice_slow".to_string(),
+            execution_mode: ExecutionMode::Thread,
+        };
+        let update = kill_source(&app_context, &kill).unwrap();
+        assert_eq!(update.stream_id, stream_id);
+        assert_eq!(update.target_box_id, "default_muxbox");
+
+        let err = runner.join().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Interrupted);
+        let alive = unsafe { libc::kill(pid, 0) } == 0;
+        assert!(!alive, "the script's process should be gone");
+    }
+
+    #[test]
+    fn test_timed_out_stream_reports_failed_finish() {
+        let update = StreamUpdate {
+            stream_id: "stream".to_string(),
+            target_box_id: "box".to_string(),
+            content_update: "Script timed out after 10ms".to_string(),
+            source_state: SourceState::Batch(BatchSourceState {
+                task_id: "stream".to_string(),
+                queue_wait_time: Duration::from_millis(0),
+                execution_time: Duration::from_millis(10),
+                exit_code: None,
+                status: BatchStatus::Failed("timeout".to_string()),
+            }),
+            execution_mode: ExecutionMode::Immediate,
+            output_format: OutputFormat::Text,
+        };
+
+        let events = socket_events_for_message(&Message::StreamUpdateMessage(update));
+        assert!(events.contains(&SocketEvent::StreamFinished {
+            box_id: "box".to_string(),
+            stream_id: "stream".to_string(),
+            exit_code: None,
+            success: false,
+        }));
+    }
+}
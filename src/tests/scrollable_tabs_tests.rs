@@ -4,7 +4,7 @@ mod tests {
     use crate::draw_utils::{
         calculate_tab_click_index, calculate_tab_navigation_click, TabNavigationAction,
     };
-    use crate::model::common::ScreenBuffer;
+    use crate::model::common::{ScreenBuffer, TextStyle};
     use crate::model::muxbox::MuxBox;
     use crate::tests::test_utils::TestDataFactory;
     use indexmap::IndexMap;
@@ -135,6 +135,7 @@ mod tests {
             bg,
             fg,
             bg,
+            TextStyle::default(),
             tab_labels,
             &close_buttons,
             0,
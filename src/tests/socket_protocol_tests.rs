@@ -0,0 +1,242 @@
+//! Control socket protocol tests
+//!
+//! Verify the versioned request/response envelope, machine-readable error codes,
+//! and that bare SocketFunction payloads keep their original behaviour.
+
+#[cfg(test)]
+mod tests {
+    use crate::circular_buffer::CircularBuffer;
+    use crate::model::common::SocketFunction;
+    use crate::pty_manager::{PtyManager, PtyStatus};
+    use crate::socket_loop::handle_socket_payload;
+    use crate::socket_protocol::*;
+    use crate::tests::test_utils::TestDataFactory;
+    use std::sync::{Arc, Mutex};
+
+    fn parse_response(json: &str) -> SocketResponse {
+        serde_json::from_str(json).expect("response should be a JSON SocketResponse")
+    }
+
+    /// Test that an envelope request parses with its id and version
+    #[test]
+    fn test_parse_envelope_request() {
+        let raw =
+            r#"{"version": 1, "id": "req-1", "function": {"StopBoxRefresh": {"box_id": "logs"}}}"#;
+        match parse_socket_message(raw).unwrap() {
+            IncomingSocketMessage::Request(request) => {
+                assert_eq!(request.version, SOCKET_PROTOCOL_VERSION);
+                assert_eq!(request.id.as_deref(), Some("req-1"));
+                assert_eq!(
+                    request.function,
+                    SocketFunction::StopBoxRefresh {
+                        box_id: "logs".to_string()
+                    }
+                );
+            }
+            other => panic!("Expected envelope request, got {:?}", other),
+        }
+    }
+
+    /// Test that a bare SocketFunction is still accepted
+    #[test]
+    fn test_parse_legacy_socket_function() {
+        let raw = r#"{"StartBoxRefresh": {"box_id": "logs"}}"#;
+        assert!(matches!(
+            parse_socket_message(raw).unwrap(),
+            IncomingSocketMessage::Legacy(SocketFunction::StartBoxRefresh { .. })
+        ));
+    }
+
+    /// Test the machine-readable error codes for malformed input
+    #[test]
+    fn test_parse_error_codes() {
+        let code = |raw: &str| match parse_socket_message(raw).unwrap_err().outcome {
+            SocketOutcome::Error { error } => error.code,
+            other => panic!("Expected error outcome, got {:?}", other),
+        };
+
+        assert_eq!(code("not json"), SocketErrorCode::InvalidJson);
+        assert_eq!(
+            code(r#"{"NoSuchFunction": {}}"#),
+            SocketErrorCode::InvalidRequest
+        );
+        assert_eq!(
+            code(r#"{"id": "x", "function": {"NoSuchFunction": {}}}"#),
+            SocketErrorCode::InvalidRequest
+        );
+        assert_eq!(
+            code(r#"{"version": 99, "function": {"StopBoxRefresh": {"box_id": "a"}}}"#),
+            SocketErrorCode::UnsupportedVersion
+        );
+    }
+
+    /// Test that error responses echo the request id when it can be read
+    #[test]
+    fn test_error_response_keeps_request_id() {
+        let response =
+            parse_socket_message(r#"{"version": 2, "id": "abc", "function": {}}"#).unwrap_err();
+        assert_eq!(response.id.as_deref(), Some("abc"));
+    }
+
+    /// Test that envelope requests get a typed JSON success response
+    #[test]
+    fn test_envelope_request_success_response() {
+        let app_context = TestDataFactory::create_test_app_context_with_boxes(&["status"]);
+        let request = SocketRequest {
+            version: SOCKET_PROTOCOL_VERSION,
+            id: Some("req-7".to_string()),
+            token: None,
+            function: SocketFunction::ReplaceBoxContent {
+                box_id: "status".to_string(),
+                success: true,
+                content: "Online".to_string(),
+            },
+        };
+
+        let (response_json, messages) =
+            handle_socket_payload(&serde_json::to_string(&request).unwrap(), &app_context);
+        assert_eq!(messages.len(), 1);
+
+        let value: serde_json::Value = serde_json::from_str(&response_json).unwrap();
+        assert_eq!(value["status"], "ok");
+        assert_eq!(value["id"], "req-7");
+        assert_eq!(value["result"]["type"], "accepted");
+        assert_eq!(value["result"]["messages"], 1);
+
+        let response = parse_response(&response_json);
+        assert!(response.is_ok());
+    }
+
+    /// Test that bare SocketFunction payloads keep the original text reply
+    #[test]
+    fn test_legacy_request_keeps_text_response() {
+        let app_context = TestDataFactory::create_test_app_context();
+        let (response, messages) =
+            handle_socket_payload(r#"{"StopBoxRefresh": {"box_id": "logs"}}"#, &app_context);
+        assert_eq!(response, LEGACY_SUCCESS_RESPONSE);
+        assert_eq!(messages.len(), 1);
+    }
+
+    /// Test that PTY functions report pty_unavailable without a PTY manager
+    #[test]
+    fn test_pty_function_without_manager_errors() {
+        let app_context = TestDataFactory::create_test_app_context();
+        let request = SocketRequest::new(SocketFunction::KillPtyProcess {
+            box_id: "shell".to_string(),
+        });
+
+        let (response_json, messages) =
+            handle_socket_payload(&serde_json::to_string(&request).unwrap(), &app_context);
+        assert!(messages.is_empty());
+
+        match parse_response(&response_json).outcome {
+            SocketOutcome::Error { error } => {
+                assert_eq!(error.code, SocketErrorCode::PtyUnavailable)
+            }
+            other => panic!("Expected error outcome, got {:?}", other),
+        }
+    }
+
+    /// Test that functions aimed at a box that doesn't exist report not_found
+    #[test]
+    fn test_missing_box_is_not_found() {
+        let app_context = TestDataFactory::create_test_app_context();
+        let request = SocketRequest::new(SocketFunction::ReplaceBoxContent {
+            box_id: "missing".to_string(),
+            success: true,
+            content: "Online".to_string(),
+        });
+
+        let (response_json, messages) =
+            handle_socket_payload(&serde_json::to_string(&request).unwrap(), &app_context);
+        assert!(messages.is_empty());
+
+        match parse_response(&response_json).outcome {
+            SocketOutcome::Error { error } => {
+                assert_eq!(error.code, SocketErrorCode::NotFound);
+                assert!(error.message.contains("missing"), "{}", error.message);
+            }
+            other => panic!("Expected error outcome, got {:?}", other),
+        }
+    }
+
+    /// Test that QueryPtyStatus returns a structured status report
+    #[test]
+    fn test_query_pty_status_structured_result() {
+        let mut app_context = TestDataFactory::create_test_app_context();
+        let pty_manager = PtyManager::new().unwrap();
+        let buffer = Arc::new(Mutex::new(CircularBuffer::new(100)));
+        buffer.lock().unwrap().push("line".to_string());
+        pty_manager.add_test_pty_process_with_status(
+            "shell".to_string(),
+            buffer,
+            PtyStatus::Finished(3),
+            4321,
+        );
+        app_context.pty_manager = Some(Arc::new(pty_manager));
+
+        let (result, messages) = execute_socket_function(
+            SocketFunction::QueryPtyStatus {
+                box_id: "shell".to_string(),
+            },
+            &app_context,
+        )
+        .unwrap();
+        assert!(messages.is_empty(), "Queries should not write into the box");
+
+        match result {
+            SocketResult::PtyStatus(report) => {
+                assert_eq!(report.box_id, "shell");
+                assert_eq!(report.process_id, Some(4321));
+                assert_eq!(report.status, "finished");
+                assert_eq!(report.exit_code, Some(3));
+                assert!(!report.running);
+                assert_eq!(report.buffer_lines, 1);
+            }
+            other => panic!("Expected PTY status result, got {:?}", other),
+        }
+
+        let missing = execute_socket_function(
+            SocketFunction::QueryPtyStatus {
+                box_id: "other".to_string(),
+            },
+            &app_context,
+        )
+        .unwrap_err();
+        assert_eq!(missing.code, SocketErrorCode::NotFound);
+    }
+
+    /// Test a full request/response round trip over a Unix socket
+    #[test]
+    fn test_send_socket_request_round_trip() {
+        use std::io::{Read, Write};
+        use std::os::unix::net::UnixListener;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("boxmux-protocol-test.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let app_context = TestDataFactory::create_test_app_context();
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut payload = String::new();
+            stream.read_to_string(&mut payload).unwrap();
+            let (response, _) = handle_socket_payload(payload.trim(), &app_context);
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        let request = SocketRequest::new(SocketFunction::SwitchActiveLayout {
+            layout_id: "main".to_string(),
+        });
+        let response = send_socket_request(path.to_str().unwrap(), &request).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(response.id, request.id);
+        assert_eq!(
+            response.outcome,
+            SocketOutcome::Ok {
+                result: SocketResult::Accepted { messages: 1 }
+            }
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Control socket query tests
+//!
+//! Verify the read-only query functions: box content, box and layout listings,
+//! execution sources, and PTY scrollback reads.
+
+#[cfg(test)]
+mod tests {
+    use crate::circular_buffer::CircularBuffer;
+    use crate::model::common::{ExecutionSourceStatus, ExecutionSourceType, SocketFunction};
+    use crate::pty_manager::{PtyManager, PtyStatus};
+    use crate::socket_loop::handle_socket_payload;
+    use crate::socket_protocol::*;
+    use crate::socket_queries::PtySearchMatch;
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::AppContext;
+    use std::sync::{Arc, Mutex};
+
+    fn query(function: SocketFunction, app_context: &AppContext) -> SocketResult {
+        let (result, messages) = execute_socket_function(function, app_context).unwrap();
+        assert!(messages.is_empty(), "queries must not produce messages");
+        result
+    }
+
+    fn app_context_with_pty_lines(lines: usize) -> AppContext {
+        let mut app_context = TestDataFactory::create_test_app_context();
+        let pty_manager = PtyManager::new().unwrap();
+        let buffer = Arc::new(Mutex::new(CircularBuffer::new(1000)));
+        for i in 0..lines {
+            buffer.lock().unwrap().push(format!("line {}", i));
+        }
+        pty_manager.add_test_pty_process_with_status(
+            "shell".to_string(),
+            buffer,
+            PtyStatus::Running,
+            4321,
+        );
+        app_context.pty_manager = Some(Arc::new(pty_manager));
+        app_context
+    }
+
+    /// Test that GetBoxContent reports static content and streams
+    #[test]
+    fn test_get_box_content() {
+        let mut app_context = TestDataFactory::create_test_app_context();
+        let muxbox = app_context
+            .app
+            .get_muxbox_by_id_mut("default_muxbox")
+            .unwrap();
+        muxbox.content = Some("hello".to_string());
+
+        match query(
+            SocketFunction::GetBoxContent {
+                box_id: "default_muxbox".to_string(),
+            },
+            &app_context,
+        ) {
+            SocketResult::BoxContent(report) => {
+                assert_eq!(report.box_id, "default_muxbox");
+                assert_eq!(report.layout_id.as_deref(), Some("test_layout"));
+                assert_eq!(report.content.as_deref(), Some("hello"));
+            }
+            other => panic!("Expected box content, got {:?}", other),
+        }
+
+        let error = execute_socket_function(
+            SocketFunction::GetBoxContent {
+                box_id: "missing".to_string(),
+            },
+            &app_context,
+        )
+        .unwrap_err();
+        assert_eq!(error.code, SocketErrorCode::NotFound);
+    }
+
+    /// Test that ListBoxes defaults to the active layout and includes bounds
+    #[test]
+    fn test_list_boxes() {
+        let mut app_context = TestDataFactory::create_test_app_context();
+        app_context.app.layouts[0].active = Some(true);
+
+        match query(SocketFunction::ListBoxes { layout_id: None }, &app_context) {
+            SocketResult::Boxes { layout_id, boxes } => {
+                assert_eq!(layout_id, "test_layout");
+                assert_eq!(boxes.len(), 1);
+                assert_eq!(boxes[0].box_id, "default_muxbox");
+                assert!(boxes[0].bounds.is_some());
+            }
+            other => panic!("Expected boxes, got {:?}", other),
+        }
+
+        let error = execute_socket_function(
+            SocketFunction::ListBoxes {
+                layout_id: Some("missing".to_string()),
+            },
+            &app_context,
+        )
+        .unwrap_err();
+        assert_eq!(error.code, SocketErrorCode::NotFound);
+    }
+
+    /// Test that ListLayouts marks the active and root layouts
+    #[test]
+    fn test_list_layouts() {
+        let mut app_context = TestDataFactory::create_test_app_context();
+        app_context.app = TestDataFactory::create_multi_layout_app();
+        app_context.app.layouts[1].active = Some(true);
+
+        match query(SocketFunction::ListLayouts {}, &app_context) {
+            SocketResult::Layouts {
+                active_layout_id,
+                layouts,
+            } => {
+                assert_eq!(active_layout_id.as_deref(), Some("layout2"));
+                assert_eq!(layouts.len(), 3);
+                assert!(layouts[0].root);
+                assert!(!layouts[0].active);
+                assert!(layouts[1].active);
+                assert_eq!(layouts[1].box_count, 1);
+            }
+            other => panic!("Expected layouts, got {:?}", other),
+        }
+    }
+
+    /// Test that ListExecutionSources reports kind, detail and status
+    #[test]
+    fn test_list_execution_sources() {
+        let mut app_context = TestDataFactory::create_test_app_context();
+        let stream_id = app_context.app.register_execution_source(
+            ExecutionSourceType::ChoiceExecution {
+                choice_id: "deploy".to_string(),
+                script: vec!["echo hi".to_string()],
+                redirect_output: None,
+            },
+            "default_muxbox".to_string(),
+        );
+        let source_id = app_context
+            .app
+            .execution_sources
+            .values()
+            .find(|source| source.stream_id == stream_id)
+            .unwrap()
+            .source_id
+            .clone();
+        app_context.app.update_source_status(
+            &source_id,
+            ExecutionSourceStatus::Failed("boom".to_string()),
+        );
+
+        match query(SocketFunction::ListExecutionSources {}, &app_context) {
+            SocketResult::ExecutionSources { sources } => {
+                assert_eq!(sources.len(), 1);
+                assert_eq!(sources[0].stream_id, stream_id);
+                assert_eq!(sources[0].kind, "choice_execution");
+                assert_eq!(sources[0].detail.as_deref(), Some("deploy"));
+                assert_eq!(sources[0].status, "failed");
+                assert_eq!(sources[0].error.as_deref(), Some("boom"));
+            }
+            other => panic!("Expected execution sources, got {:?}", other),
+        }
+    }
+
+    /// Test that GetPtyBuffer tails the buffer by default and honours start/count
+    #[test]
+    fn test_get_pty_buffer() {
+        let app_context = app_context_with_pty_lines(150);
+
+        match query(
+            SocketFunction::GetPtyBuffer {
+                box_id: "shell".to_string(),
+                start: None,
+                count: Some(3),
+            },
+            &app_context,
+        ) {
+            SocketResult::PtyBuffer {
+                start,
+                total_lines,
+                lines,
+                ..
+            } => {
+                assert_eq!(start, 147);
+                assert_eq!(total_lines, 150);
+                assert_eq!(lines, vec!["line 147", "line 148", "line 149"]);
+            }
+            other => panic!("Expected PTY buffer, got {:?}", other),
+        }
+
+        match query(
+            SocketFunction::GetPtyBuffer {
+                box_id: "shell".to_string(),
+                start: Some(10),
+                count: Some(2),
+            },
+            &app_context,
+        ) {
+            SocketResult::PtyBuffer { start, lines, .. } => {
+                assert_eq!(start, 10);
+                assert_eq!(lines, vec!["line 10", "line 11"]);
+            }
+            other => panic!("Expected PTY buffer, got {:?}", other),
+        }
+    }
+
+    /// Test that SearchPtyBuffer returns matching lines with their indexes
+    #[test]
+    fn test_search_pty_buffer() {
+        let app_context = app_context_with_pty_lines(20);
+
+        match query(
+            SocketFunction::SearchPtyBuffer {
+                box_id: "shell".to_string(),
+                query: "LINE 1".to_string(),
+            },
+            &app_context,
+        ) {
+            SocketResult::PtySearch { matches, .. } => {
+                assert_eq!(matches.len(), 11);
+                assert_eq!(
+                    matches[0],
+                    PtySearchMatch {
+                        line: 1,
+                        text: "line 1".to_string()
+                    }
+                );
+            }
+            other => panic!("Expected PTY search, got {:?}", other),
+        }
+    }
+
+    /// Test PTY queries without a PTY manager or process
+    #[test]
+    fn test_pty_buffer_errors() {
+        let app_context = TestDataFactory::create_test_app_context();
+        let error = execute_socket_function(
+            SocketFunction::GetPtyBuffer {
+                box_id: "shell".to_string(),
+                start: None,
+                count: None,
+            },
+            &app_context,
+        )
+        .unwrap_err();
+        assert_eq!(error.code, SocketErrorCode::PtyUnavailable);
+
+        let app_context = app_context_with_pty_lines(1);
+        let error = execute_socket_function(
+            SocketFunction::SearchPtyBuffer {
+                box_id: "other".to_string(),
+                query: "x".to_string(),
+            },
+            &app_context,
+        )
+        .unwrap_err();
+        assert_eq!(error.code, SocketErrorCode::NotFound);
+    }
+
+    /// Test that query results travel in the envelope and bare queries are rejected
+    #[test]
+    fn test_query_wire_format() {
+        let app_context = TestDataFactory::create_test_app_context();
+
+        let (response, messages) = handle_socket_payload(
+            r#"{"version": 1, "id": "q", "function": {"ListLayouts": {}}}"#,
+            &app_context,
+        );
+        assert!(messages.is_empty());
+        let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["status"], "ok");
+        assert_eq!(response["result"]["type"], "layouts");
+        assert_eq!(response["result"]["layouts"][0]["layout_id"], "test_layout");
+
+        let (response, messages) = handle_socket_payload(r#"{"ListLayouts": {}}"#, &app_context);
+        assert!(messages.is_empty());
+        assert!(
+            response.starts_with("Socket function execution failed"),
+            "got {}",
+            response
+        );
+    }
+}
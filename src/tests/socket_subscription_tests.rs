@@ -0,0 +1,350 @@
+//! Control socket subscription tests
+//!
+//! Verify persistent connections, the Message -> event mapping, subscription
+//! filters, and event delivery to subscribed connections.
+
+#[cfg(test)]
+mod tests {
+    use crate::model::common::{
+        ExecutionMode, ExecutionPtyStatus, OutputFormat, PtySourceState, SocketFunction,
+        SourceState, StreamUpdate,
+    };
+    use crate::socket_loop::{handle_socket_payload, SocketServer};
+    use crate::socket_protocol::*;
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::thread_manager::Message;
+    use crate::AppContext;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    fn pty_update(status: ExecutionPtyStatus, exit_code: Option<i32>) -> Message {
+        Message::StreamUpdateMessage(StreamUpdate {
+            stream_id: "pty-stream".to_string(),
+            target_box_id: "terminal".to_string(),
+            content_update: "output".to_string(),
+            source_state: SourceState::Pty(PtySourceState {
+                process_id: 42,
+                runtime: Duration::from_millis(0),
+                exit_code,
+                status,
+            }),
+            execution_mode: ExecutionMode::Pty,
+            output_format: OutputFormat::Text,
+        })
+    }
+
+    fn start_server() -> (tempfile::TempDir, std::path::PathBuf, SocketServer) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("boxmux-subscribe-test.sock");
+        let server = SocketServer::bind(&path).unwrap();
+        (dir, path, server)
+    }
+
+    fn connect(path: &std::path::Path) -> (UnixStream, BufReader<UnixStream>) {
+        let stream = UnixStream::connect(path).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let reader = BufReader::new(stream.try_clone().unwrap());
+        (stream, reader)
+    }
+
+    /// Poll until the server has had a chance to handle everything sent so far
+    fn pump(server: &mut SocketServer, app_context: &AppContext) -> Vec<Message> {
+        let mut messages = Vec::new();
+        for _ in 0..10 {
+            messages.extend(server.poll(app_context, Duration::from_millis(20)));
+        }
+        messages
+    }
+
+    fn read_line(reader: &mut BufReader<UnixStream>) -> String {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        line
+    }
+
+    fn request(id: &str, function: SocketFunction) -> String {
+        let request = SocketRequest {
+            version: SOCKET_PROTOCOL_VERSION,
+            id: Some(id.to_string()),
+            token: None,
+            function,
+        };
+        format!("{}\n", serde_json::to_string(&request).unwrap())
+    }
+
+    /// Test the mapping from internal messages to subscriber events
+    #[test]
+    fn test_socket_events_for_message() {
+        assert_eq!(
+            socket_events_for_message(&Message::SwitchActiveLayout("main".to_string())),
+            vec![SocketEvent::LayoutSwitched {
+                layout_id: "main".to_string()
+            }]
+        );
+        assert_eq!(
+            socket_events_for_message(&Message::ChoiceExecuted(
+                "menu".to_string(),
+                "deploy".to_string()
+            )),
+            vec![SocketEvent::ChoiceExecuted {
+                box_id: "menu".to_string(),
+                choice_id: "deploy".to_string()
+            }]
+        );
+        assert!(socket_events_for_message(&Message::RedrawApp).is_empty());
+
+        // Running PTY output is a content change only
+        let running = socket_events_for_message(&pty_update(ExecutionPtyStatus::Running, None));
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].event_type(), SocketEventType::BoxContentChanged);
+
+        // A PTY exiting reports the stream finishing and the PTY exit, with its code
+        let exited = socket_events_for_message(&pty_update(ExecutionPtyStatus::Completed, Some(3)));
+        assert!(exited.contains(&SocketEvent::StreamFinished {
+            box_id: "terminal".to_string(),
+            stream_id: "pty-stream".to_string(),
+            exit_code: Some(3),
+            success: false,
+        }));
+        assert!(exited.contains(&SocketEvent::PtyExited {
+            box_id: "terminal".to_string(),
+            stream_id: "pty-stream".to_string(),
+            exit_code: Some(3),
+            success: false,
+        }));
+
+        // The trailing screen flush before exit carries no exit code and is not an exit
+        let flush = socket_events_for_message(&pty_update(ExecutionPtyStatus::Completed, None));
+        assert!(!flush.iter().any(|event| event.is_lifecycle()));
+    }
+
+    /// Test that subscription filters apply to event type and box-scoped events
+    #[test]
+    fn test_subscription_filters() {
+        let content = SocketEvent::BoxContentChanged {
+            box_id: "logs".to_string(),
+            stream_id: None,
+        };
+        let layout = SocketEvent::LayoutSwitched {
+            layout_id: "main".to_string(),
+        };
+
+        assert!(SocketSubscription::default().matches(&content));
+
+        let by_box = SocketSubscription {
+            box_ids: Some(vec!["other".to_string()]),
+            events: None,
+        };
+        assert!(!by_box.matches(&content));
+        assert!(by_box.matches(&layout));
+
+        let by_type = SocketSubscription {
+            box_ids: None,
+            events: Some(vec![SocketEventType::BoxContentChanged]),
+        };
+        assert!(by_type.matches(&content));
+        assert!(!by_type.matches(&layout));
+    }
+
+    /// Test that event lines carry the protocol version and the event tag
+    #[test]
+    fn test_event_line_format() {
+        let line = SocketEventLine::new(SocketEvent::LayoutSwitched {
+            layout_id: "main".to_string(),
+        })
+        .to_json();
+        assert_eq!(
+            line,
+            r#"{"version":1,"event":"layout_switched","layout_id":"main"}"#
+        );
+    }
+
+    /// Test that Subscribe is rejected where there is no persistent connection
+    #[test]
+    fn test_subscribe_requires_connection() {
+        let app_context = TestDataFactory::create_test_app_context();
+        let payload = request(
+            "sub",
+            SocketFunction::Subscribe {
+                box_ids: None,
+                events: None,
+            },
+        );
+        let (response, messages) = handle_socket_payload(payload.trim(), &app_context);
+        let response: SocketResponse = serde_json::from_str(&response).unwrap();
+        assert!(messages.is_empty());
+        match response.outcome {
+            SocketOutcome::Error { error } => {
+                assert_eq!(error.code, SocketErrorCode::InvalidRequest)
+            }
+            other => panic!("Expected error outcome, got {:?}", other),
+        }
+    }
+
+    /// Test several requests on one connection, each answered by its own line
+    #[test]
+    fn test_persistent_connection_multiple_requests() {
+        let (_dir, path, mut server) = start_server();
+        let app_context = TestDataFactory::create_test_app_context_with_boxes(&["a"]);
+        let (mut stream, mut reader) = connect(&path);
+
+        stream
+            .write_all(
+                request(
+                    "one",
+                    SocketFunction::StopBoxRefresh {
+                        box_id: "a".to_string(),
+                    },
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        stream
+            .write_all(
+                request(
+                    "two",
+                    SocketFunction::StartBoxRefresh {
+                        box_id: "a".to_string(),
+                    },
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+
+        let messages = pump(&mut server, &app_context);
+        assert_eq!(messages.len(), 2);
+
+        let first: SocketResponse = serde_json::from_str(&read_line(&mut reader)).unwrap();
+        let second: SocketResponse = serde_json::from_str(&read_line(&mut reader)).unwrap();
+        assert_eq!(first.id.as_deref(), Some("one"));
+        assert_eq!(second.id.as_deref(), Some("two"));
+        assert!(first.is_ok() && second.is_ok());
+    }
+
+    /// Test that one-shot legacy clients still get their reply and a closed connection
+    #[test]
+    fn test_legacy_one_shot_client() {
+        let (_dir, path, mut server) = start_server();
+        let app_context = TestDataFactory::create_test_app_context();
+        let (mut stream, mut reader) = connect(&path);
+
+        stream
+            .write_all(br#"{"StopBoxRefresh": {"box_id": "a"}}"#)
+            .unwrap();
+        stream.shutdown(std::net::Shutdown::Write).unwrap();
+        pump(&mut server, &app_context);
+
+        let mut reply = String::new();
+        reader.read_to_string(&mut reply).unwrap();
+        assert_eq!(reply.trim(), LEGACY_SUCCESS_RESPONSE);
+    }
+
+    /// Test that a subscriber is forgotten once its peer goes away, whether or not it
+    /// closed its write half first
+    #[test]
+    fn test_closed_subscribers_are_dropped() {
+        let (_dir, path, mut server) = start_server();
+        let app_context = TestDataFactory::create_test_app_context();
+        let subscribe = request(
+            "sub",
+            SocketFunction::Subscribe {
+                box_ids: None,
+                events: None,
+            },
+        );
+
+        let (mut closing, closing_reader) = connect(&path);
+        closing.write_all(subscribe.as_bytes()).unwrap();
+        let (mut listening, listening_reader) = connect(&path);
+        listening.write_all(subscribe.as_bytes()).unwrap();
+        listening.shutdown(std::net::Shutdown::Write).unwrap();
+        pump(&mut server, &app_context);
+        assert_eq!(server.subscriber_count(), 2);
+
+        drop(closing);
+        drop(closing_reader);
+        pump(&mut server, &app_context);
+        assert_eq!(server.subscriber_count(), 1);
+
+        drop(listening);
+        drop(listening_reader);
+        pump(&mut server, &app_context);
+        assert_eq!(server.subscriber_count(), 0);
+    }
+
+    /// Test that subscribers receive matching events, including for their own requests
+    #[test]
+    fn test_subscriber_receives_filtered_events() {
+        let (_dir, path, mut server) = start_server();
+        let app_context = TestDataFactory::create_test_app_context_with_boxes(&["logs"]);
+        let (mut stream, mut reader) = connect(&path);
+
+        stream
+            .write_all(
+                request(
+                    "sub",
+                    SocketFunction::Subscribe {
+                        box_ids: Some(vec!["logs".to_string()]),
+                        events: None,
+                    },
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        // Subscribers may close their write half and keep listening
+        stream.shutdown(std::net::Shutdown::Write).unwrap();
+        pump(&mut server, &app_context);
+        assert_eq!(server.subscriber_count(), 1);
+
+        let response: SocketResponse = serde_json::from_str(&read_line(&mut reader)).unwrap();
+        assert!(matches!(
+            response.outcome,
+            SocketOutcome::Ok {
+                result: SocketResult::Subscribed { .. }
+            }
+        ));
+
+        server.publish(&Message::StopBoxRefresh("other".to_string()));
+        server.publish(&Message::StopBoxRefresh("logs".to_string()));
+        server.publish(&Message::SwitchActiveLayout("main".to_string()));
+
+        let first: SocketEventLine = serde_json::from_str(&read_line(&mut reader)).unwrap();
+        let second: SocketEventLine = serde_json::from_str(&read_line(&mut reader)).unwrap();
+        assert_eq!(
+            first.event,
+            SocketEvent::RefreshStopped {
+                box_id: "logs".to_string()
+            }
+        );
+        assert_eq!(
+            second.event,
+            SocketEvent::LayoutSwitched {
+                layout_id: "main".to_string()
+            }
+        );
+
+        // Requests from another connection are published to the subscriber as well
+        let (mut other, mut other_reader) = connect(&path);
+        other
+            .write_all(
+                request(
+                    "replace",
+                    SocketFunction::ReplaceBoxContent {
+                        box_id: "logs".to_string(),
+                        success: true,
+                        content: "hello".to_string(),
+                    },
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        pump(&mut server, &app_context);
+        assert!(read_line(&mut other_reader).contains("\"status\":\"ok\""));
+
+        let event: SocketEventLine = serde_json::from_str(&read_line(&mut reader)).unwrap();
+        assert_eq!(event.event.event_type(), SocketEventType::BoxContentChanged);
+    }
+}
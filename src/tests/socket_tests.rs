@@ -343,4 +343,128 @@ mod tests {
             duration
         );
     }
+
+    /// Test that the default socket path is derived from the YAML file name and PID
+    #[test]
+    fn test_default_socket_path_per_instance() {
+        use crate::socket_loop::default_socket_path;
+
+        let path = default_socket_path(Some("/home/user/dash boards/ops.yaml"), 4242);
+        assert_eq!(
+            path.file_name().unwrap().to_str().unwrap(),
+            "boxmux-ops-4242.sock"
+        );
+
+        let other = default_socket_path(Some("/srv/other.yaml"), 4243);
+        assert_ne!(path, other, "Instances must not share a socket path");
+
+        let unnamed = default_socket_path(None, 7);
+        assert_eq!(
+            unnamed.file_name().unwrap().to_str().unwrap(),
+            "boxmux-app-7.sock"
+        );
+    }
+
+    /// Test that a configured socket path takes precedence over the default
+    #[test]
+    fn test_server_socket_path_uses_config() {
+        use crate::socket_loop::resolve_server_socket_path;
+
+        let mut app_context = TestDataFactory::create_test_app_context();
+        app_context.config = app_context
+            .config
+            .with_socket_path(Some("/tmp/custom_boxmux_test.sock".to_string()));
+
+        assert_eq!(
+            resolve_server_socket_path(&app_context),
+            std::path::PathBuf::from("/tmp/custom_boxmux_test.sock")
+        );
+    }
+
+    /// Test that preparing a socket path clears stale files but refuses live sockets
+    #[test]
+    fn test_prepare_socket_path_does_not_steal_live_socket() {
+        use crate::socket_loop::prepare_socket_path;
+        use std::os::unix::net::UnixListener;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("boxmux-test-1.sock");
+
+        // Missing parent directory is created
+        assert!(prepare_socket_path(&path).is_ok());
+        assert!(path.parent().unwrap().exists());
+
+        // Live listener must not be removed
+        let listener = UnixListener::bind(&path).unwrap();
+        assert!(prepare_socket_path(&path).is_err());
+        assert!(path.exists());
+
+        // Once nobody listens, the file is stale and gets removed
+        drop(listener);
+        assert!(prepare_socket_path(&path).is_ok());
+        assert!(!path.exists());
+    }
+
+    /// Test that only a socket this process bound is removed on exit
+    #[test]
+    fn test_remove_bound_socket_leaves_other_instances_socket() {
+        use crate::socket_loop::{bind_instance_socket, remove_bound_socket};
+        use std::os::unix::net::UnixListener;
+
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("shared.sock");
+        let _other_instance = UnixListener::bind(&shared).unwrap();
+        assert!(bind_instance_socket(&shared).is_err());
+        remove_bound_socket();
+        assert!(shared.exists(), "another instance's socket is kept");
+
+        let own = dir.path().join("own.sock");
+        let _server = bind_instance_socket(&own).unwrap();
+        remove_bound_socket();
+        assert!(!own.exists());
+    }
+
+    /// Test that instance listing reports running instances and prunes dead ones
+    #[test]
+    fn test_list_socket_instances_filters_stale() {
+        use crate::socket_loop::list_socket_instances_in;
+        use std::os::unix::net::UnixListener;
+
+        let own_pid = std::process::id();
+        let mut exited = std::process::Command::new("true").spawn().unwrap();
+        let dead_pid = exited.id();
+        exited.wait().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let live_path = dir
+            .path()
+            .join(format!("boxmux-dashboard-{}.sock", own_pid));
+        let busy_path = dir.path().join(format!("boxmux-busy-{}.sock", own_pid));
+        let stale_path = dir.path().join(format!("boxmux-old-{}.sock", dead_pid));
+        let _live = UnixListener::bind(&live_path).unwrap();
+        // A running instance whose socket refuses a connection, e.g. a full backlog
+        drop(UnixListener::bind(&busy_path).unwrap());
+        drop(UnixListener::bind(&stale_path).unwrap());
+        std::fs::write(dir.path().join("unrelated.txt"), "x").unwrap();
+
+        let instances = list_socket_instances_in(dir.path());
+        let names: Vec<&str> = instances.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["busy", "dashboard"]);
+        assert_eq!(instances[1].pid, Some(own_pid));
+        assert_eq!(instances[1].path, live_path);
+        assert!(busy_path.exists(), "A running instance's socket is kept");
+        assert!(!stale_path.exists(), "Stale socket should be removed");
+    }
+
+    /// Test that an explicit client socket path is used as-is
+    #[test]
+    fn test_resolve_client_socket_path_explicit() {
+        use crate::socket_loop::resolve_client_socket_path;
+
+        let resolved = resolve_client_socket_path(Some("/tmp/some/explicit.sock")).unwrap();
+        assert_eq!(
+            resolved,
+            std::path::PathBuf::from("/tmp/some/explicit.sock")
+        );
+    }
 }
@@ -0,0 +1,248 @@
+#[cfg(test)]
+mod structured_output_tests {
+    use crate::model::common::{
+        BatchSourceState, BatchStatus, ExecutionMode, OutputFormat, SourceState, StreamType,
+        StreamUpdate,
+    };
+    use crate::structured_output::{
+        apply_structured_output, parse_output, parse_stream_update, StructuredOutput,
+    };
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::Bounds;
+    use std::time::Duration;
+
+    fn parse(format: OutputFormat, output: &str) -> StructuredOutput {
+        parse_output(format, output).unwrap()
+    }
+
+    fn finished_update(output_format: OutputFormat, content: &str, exit_code: i32) -> StreamUpdate {
+        StreamUpdate {
+            stream_id: "stream".to_string(),
+            target_box_id: "box".to_string(),
+            content_update: content.to_string(),
+            source_state: SourceState::Batch(BatchSourceState {
+                task_id: "stream".to_string(),
+                queue_wait_time: Duration::from_millis(0),
+                execution_time: Duration::from_millis(5),
+                exit_code: Some(exit_code),
+                status: BatchStatus::Completed,
+            }),
+            execution_mode: ExecutionMode::Immediate,
+            output_format,
+        }
+    }
+
+    #[test]
+    fn test_json_array_of_objects_becomes_table() {
+        match parse(
+            OutputFormat::Json,
+            r#"[{"name": "web", "cpu": 12.5}, {"name": "db", "cpu": 40, "up": true}]"#,
+        ) {
+            StructuredOutput::Table(table) => {
+                assert_eq!(table.headers, vec!["cpu", "name"]);
+                assert_eq!(table.rows, vec![vec!["12.5", "web"], vec!["40", "db"]]);
+            }
+            other => panic!("Expected a table, got {:?}", other),
+        }
+
+        match parse(
+            OutputFormat::Json,
+            r#"[["pod", "status"], ["api-1", "Running"], ["api-2", null]]"#,
+        ) {
+            StructuredOutput::Table(table) => {
+                assert_eq!(table.headers, vec!["pod", "status"]);
+                assert_eq!(table.rows[1], vec!["api-2", ""]);
+            }
+            other => panic!("Expected a table, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_json_objects_with_content_become_choices() {
+        match parse(
+            OutputFormat::Json,
+            r#"[
+                {"id": "main", "content": "main", "script": "git checkout main"},
+                {"content": "feature", "script": ["git checkout feature"], "execution_mode": "Thread"},
+                {"id": 7, "content": "no script"}
+            ]"#,
+        ) {
+            StructuredOutput::Choices(choices) => {
+                assert_eq!(choices.len(), 3);
+                assert_eq!(choices[0].id, "main");
+                assert_eq!(
+                    choices[0].script,
+                    Some(vec!["git checkout main".to_string()])
+                );
+                assert_eq!(choices[1].id, "item_1");
+                assert_eq!(choices[1].execution_mode, ExecutionMode::Thread);
+                assert_eq!(choices[2].id, "7");
+                assert_eq!(choices[2].script, None);
+            }
+            other => panic!("Expected choices, got {:?}", other),
+        }
+
+        let err = parse_output(
+            OutputFormat::Json,
+            r#"[{"content": "bad", "execution_mode": "Sometimes"}]"#,
+        )
+        .unwrap_err();
+        assert!(err.starts_with("item 0:"), "{}", err);
+    }
+
+    #[test]
+    fn test_label_value_data_becomes_chart() {
+        let inputs = [
+            r#"[{"label": "cpu", "value": 42}, {"label": "mem", "value": 7.5}]"#,
+            r#"[["cpu", 42], ["mem", 7.5]]"#,
+            r#"{"cpu": 42, "mem": 7.5}"#,
+        ];
+        for input in inputs {
+            match parse(OutputFormat::Json, input) {
+                StructuredOutput::Chart(points) => {
+                    let points: Vec<(String, f64)> = points
+                        .into_iter()
+                        .map(|point| (point.label, point.value))
+                        .collect();
+                    assert_eq!(
+                        points,
+                        vec![("cpu".to_string(), 42.0), ("mem".to_string(), 7.5)],
+                        "{}",
+                        input
+                    );
+                }
+                other => panic!("Expected a chart for {}, got {:?}", input, other),
+            }
+        }
+
+        match parse(OutputFormat::Json, r#"{"host": "web-1", "load": 0.5}"#) {
+            StructuredOutput::Table(table) => {
+                assert_eq!(table.headers, vec!["Key", "Value"]);
+                assert_eq!(table.rows[0], vec!["host", "web-1"]);
+            }
+            other => panic!("Expected a key/value table, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ndjson_csv_and_key_value_formats() {
+        match parse(
+            OutputFormat::Ndjson,
+            "{\"level\": \"warn\", \"msg\": \"disk\"}\n\n{\"level\": \"info\", \"msg\": \"ok\"}\n",
+        ) {
+            StructuredOutput::Table(table) => assert_eq!(table.rows.len(), 2),
+            other => panic!("Expected a table, got {:?}", other),
+        }
+        let err = parse_output(OutputFormat::Ndjson, "{\"a\": 1}\nnot json").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{}", err);
+
+        match parse(OutputFormat::Csv, "Service,Port\nweb, 80\ndb,5432") {
+            StructuredOutput::Table(table) => {
+                assert_eq!(table.headers, vec!["Service", "Port"]);
+                assert_eq!(table.rows[0], vec!["web", "80"]);
+            }
+            other => panic!("Expected a table, got {:?}", other),
+        }
+
+        match parse(OutputFormat::KeyValue, "# usage\ncpu=42\nmem: 7.5\n") {
+            StructuredOutput::Chart(points) => assert_eq!(points.len(), 2),
+            other => panic!("Expected a chart, got {:?}", other),
+        }
+        match parse(OutputFormat::KeyValue, "host=web-1\nuptime: 3 days, 2:04") {
+            StructuredOutput::Table(table) => {
+                assert_eq!(table.rows[1], vec!["uptime", "3 days, 2:04"]);
+            }
+            other => panic!("Expected a table, got {:?}", other),
+        }
+        let err = parse_output(OutputFormat::KeyValue, "cpu=1\nmem").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{}", err);
+    }
+
+    #[test]
+    fn test_only_finished_successful_structured_updates_are_parsed() {
+        assert!(parse_stream_update(&finished_update(OutputFormat::Text, "[1]", 0)).is_none());
+        assert!(parse_stream_update(&finished_update(OutputFormat::Json, "boom", 1)).is_none());
+
+        match parse_stream_update(&finished_update(OutputFormat::Json, "{oops", 0)) {
+            Some(StructuredOutput::Text(text)) => {
+                assert!(text.starts_with("Output format error: invalid JSON"));
+                assert!(text.ends_with("{oops"));
+            }
+            other => panic!("Expected the parse error as text, got {:?}", other),
+        }
+        assert!(matches!(
+            parse_stream_update(&finished_update(OutputFormat::Csv, "a,b\n1,2", 0)),
+            Some(StructuredOutput::Table(_))
+        ));
+    }
+
+    #[test]
+    fn test_apply_routes_to_table_chart_and_choices() {
+        let mut muxbox = TestDataFactory::create_test_muxbox("box");
+
+        let table = parse(OutputFormat::Csv, "Service,Port\nweb,80");
+        assert!(apply_structured_output(&mut muxbox, table));
+        let rendered = muxbox
+            .generate_table_content(&Bounds::new(0, 0, 40, 10))
+            .unwrap();
+        assert!(rendered.contains("Service"), "{}", rendered);
+        assert!(rendered.contains("web"), "{}", rendered);
+
+        let chart = parse(OutputFormat::KeyValue, "cpu=42");
+        assert!(apply_structured_output(&mut muxbox, chart));
+        assert_eq!(muxbox.chart_data.as_deref(), Some("cpu,42"));
+        assert_eq!(muxbox.chart_type.as_deref(), Some("bar"));
+
+        let choices = parse(
+            OutputFormat::Json,
+            r#"[{"content": "one"}, {"content": "two"}]"#,
+        );
+        assert!(apply_structured_output(&mut muxbox, choices));
+        let stream = muxbox.get_selected_stream().unwrap();
+        assert_eq!(stream.stream_type, StreamType::Choices);
+        assert_eq!(stream.choices.as_ref().unwrap().len(), 2);
+
+        assert!(!apply_structured_output(
+            &mut muxbox,
+            StructuredOutput::Text("plain".to_string())
+        ));
+    }
+
+    #[test]
+    fn test_output_format_loads_per_box_and_choice() {
+        let yaml_content = r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'pods'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          output_format: json
+          script: ['kubectl get pods -o json']
+          choices:
+            - id: 'usage'
+              content: 'Usage'
+              output_format: key_value
+              script: ['./usage.sh']
+            - id: 'plain'
+              content: 'Plain'
+              script: ['echo hi']
+"#;
+        let app = TestDataFactory::load_app_from_yaml_str(yaml_content).unwrap();
+
+        assert_eq!(app.script_output_format("pods", None), OutputFormat::Json);
+        assert_eq!(
+            app.script_output_format("pods", Some("usage")),
+            OutputFormat::KeyValue
+        );
+        assert_eq!(
+            app.script_output_format("pods", Some("plain")),
+            OutputFormat::Text,
+            "choices don't inherit the box's format"
+        );
+
+        let bad = yaml_content.replace("output_format: json", "output_format: xml");
+        assert!(TestDataFactory::load_app_from_yaml_str(&bad).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+#[cfg(test)]
+mod style_class_tests {
+    use crate::color_utils::{get_bg_color, get_fg_color};
+    use crate::components::BoxRenderer;
+    use crate::model::common::{Bounds, TextStyle};
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::{AppContext, Config, MuxBox, ScreenBuffer};
+    use std::collections::HashMap;
+
+    const STYLED_YAML: &str = r#"
+app:
+  styles:
+    card:
+      border_color: 'blue'
+      fg_color: 'white'
+      fill_char: '.'
+      title_style: [bold]
+    warn:
+      border_color: 'yellow'
+      menu_bg_color: 'red'
+    page:
+      bg_color: 'black'
+      title_position: 'center'
+    danger:
+      menu_fg_color: 'bright_white'
+      menu_bg_color: 'red'
+  layouts:
+    - id: 'main'
+      root: true
+      class: 'page'
+      children:
+        - id: 'card'
+          position: {x1: 0%, y1: 0%, x2: 30%, y2: 100%}
+          class: 'card'
+          children:
+            - id: 'nested'
+              position: {x1: 0%, y1: 0%, x2: 100%, y2: 50%}
+        - id: 'both'
+          position: {x1: 30%, y1: 0%, x2: 60%, y2: 100%}
+          class: [card, warn]
+          bg_color: 'blue'
+        - id: 'explicit'
+          position: {x1: 60%, y1: 0%, x2: 100%, y2: 100%}
+          class: 'card warn'
+          border_color: 'green'
+          choices:
+            - id: 'delete'
+              content: 'Delete'
+              class: 'danger'
+              menu_fg_color: 'yellow'
+            - id: 'keep'
+              content: 'Keep'
+"#;
+
+    #[test]
+    fn test_classes_fill_unset_fields() {
+        let app = TestDataFactory::load_app_from_yaml_str(STYLED_YAML).unwrap();
+        let layout = app.get_layout_by_id("main").unwrap();
+        assert_eq!(layout.bg_color.as_deref(), Some("black"));
+        assert_eq!(layout.title_position.as_deref(), Some("center"));
+
+        let field =
+            |id: &str, get: fn(&MuxBox) -> Option<String>| get(app.get_muxbox_by_id(id).unwrap());
+        let border = |m: &MuxBox| m.border_color.clone();
+        assert_eq!(field("card", border).as_deref(), Some("blue"));
+        assert_eq!(
+            field("both", border).as_deref(),
+            Some("yellow"),
+            "later classes win"
+        );
+        assert_eq!(
+            field("explicit", border).as_deref(),
+            Some("green"),
+            "fields set on the box win"
+        );
+        assert_eq!(field("nested", border), None, "classes aren't copied down");
+
+        let card = app.get_muxbox_by_id("card").unwrap();
+        assert_eq!(card.fill_char, Some('.'));
+        assert_eq!(
+            card.title_style,
+            Some(TextStyle {
+                bold: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(card.class, Some(vec!["card".to_string()]));
+        assert_eq!(
+            app.get_muxbox_by_id("explicit").unwrap().class,
+            Some(vec!["card".to_string(), "warn".to_string()])
+        );
+
+        let choices = app
+            .get_muxbox_by_id("explicit")
+            .unwrap()
+            .choices
+            .clone()
+            .unwrap();
+        assert_eq!(choices[0].menu_fg_color.as_deref(), Some("yellow"));
+        assert_eq!(choices[0].menu_bg_color.as_deref(), Some("red"));
+        assert_eq!(choices[1].menu_bg_color, None);
+    }
+
+    #[test]
+    fn test_class_colors_take_precedence_over_layout() {
+        let mut app = TestDataFactory::load_app_from_yaml_str(STYLED_YAML).unwrap();
+        let context = AppContext::new(app.clone(), Config::default());
+        let graph = app.generate_graph();
+        let bg = |id: &str| {
+            app.get_muxbox_by_id(id)
+                .unwrap()
+                .calc_bg_color(&context, &graph)
+        };
+        assert_eq!(
+            bg("card").as_deref(),
+            Some("black"),
+            "from the layout class"
+        );
+        assert_eq!(bg("both").as_deref(), Some("blue"));
+        let nested = app.get_muxbox_by_id("nested").unwrap();
+        assert_eq!(
+            nested.calc_border_color(&context, &graph).as_deref(),
+            Some("blue"),
+            "children inherit what the parent's class set"
+        );
+    }
+
+    #[test]
+    fn test_choice_class_colors_are_drawn() {
+        let mut app = TestDataFactory::load_app_from_yaml_str(STYLED_YAML).unwrap();
+        let context = AppContext::new(app.clone(), Config::default());
+        let graph = app.generate_graph();
+        let mut muxbox = app.get_muxbox_by_id("explicit").unwrap().clone();
+        for stream in muxbox.streams.values_mut() {
+            for choice in stream.choices.iter_mut().flatten() {
+                choice.selected = false;
+            }
+        }
+        let adjusted_bounds = HashMap::from([(
+            "main".to_string(),
+            HashMap::from([(
+                "explicit".to_string(),
+                Bounds {
+                    x1: 0,
+                    y1: 0,
+                    x2: 30,
+                    y2: 10,
+                },
+            )]),
+        )]);
+        let mut buffer = ScreenBuffer::new_custom(40, 12);
+        let layout = app.get_layout_by_id("main").unwrap().clone();
+        BoxRenderer::new(&muxbox, "explicit".to_string()).render(
+            &context,
+            &graph,
+            &adjusted_bounds,
+            &layout,
+            &mut buffer,
+        );
+
+        let cell_of = |text: &str| {
+            buffer
+                .buffer
+                .iter()
+                .find_map(|row| {
+                    let line: String = row.iter().map(|cell| cell.ch).collect();
+                    line.find(text)
+                        .map(|byte| row[line[..byte].chars().count()].clone())
+                })
+                .unwrap_or_else(|| panic!("{:?} not drawn", text))
+        };
+        let delete = cell_of("Delete");
+        assert_eq!(delete.fg_color, get_fg_color("yellow"));
+        assert_eq!(delete.bg_color, get_bg_color("red"));
+        let keep = cell_of("Keep");
+        assert_ne!(keep.fg_color, get_fg_color("yellow"));
+        assert_eq!(keep.bg_color, get_bg_color("red"), "from the box's class");
+    }
+
+    #[test]
+    fn test_unknown_classes_are_flagged() {
+        let error = TestDataFactory::load_app_from_yaml_str(
+            r#"
+app:
+  styles:
+    card:
+      border_color: 'blue'
+  layouts:
+    - id: 'main'
+      root: true
+      class: 'wide'
+      children:
+        - id: 'box'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          class: 'card cardd'
+          choices:
+            - id: 'go'
+              content: 'Go'
+              class: [card, bold]
+"#,
+        )
+        .unwrap_err();
+        for expected in [
+            "Field 'layouts[0].class' references unknown style class 'wide'",
+            "Field 'layouts[0].children[0].class' references unknown style class 'cardd'",
+            "Field 'layouts[0].children[0].choices[0].class' references unknown style class 'bold'",
+        ] {
+            assert!(error.contains(expected), "{}", error);
+        }
+        assert!(!error.contains("'card'"), "{}", error);
+
+        let error = TestDataFactory::load_app_from_yaml_str(
+            r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'box'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          class: 'card'
+"#,
+        )
+        .unwrap_err();
+        assert!(error.contains("unknown style class 'card'"), "{}", error);
+
+        let error = TestDataFactory::load_app_from_yaml_str(
+            r#"
+app:
+  styles:
+    card:
+      border_colour: 'blue'
+  layouts:
+    - id: 'main'
+      root: true
+"#,
+        )
+        .unwrap_err();
+        assert!(error.contains("border_colour"), "{}", error);
+    }
+}
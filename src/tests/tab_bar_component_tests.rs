@@ -1,5 +1,5 @@
 use crate::components::{TabBar, TabNavigationAction};
-use crate::model::common::ScreenBuffer;
+use crate::model::common::{ScreenBuffer, TextStyle};
 
 #[cfg(test)]
 mod tests {
@@ -19,6 +19,7 @@ mod tests {
             &Some("black".to_string()),
             &Some("cyan".to_string()),
             &Some("blue".to_string()),
+            TextStyle::default(),
             &tab_labels,
             &tab_close_buttons,
             0,
@@ -76,6 +77,7 @@ mod tests {
             &Some("black".to_string()),
             &Some("cyan".to_string()),
             &Some("blue".to_string()),
+            TextStyle::default(),
             &tab_labels,
             &tab_close_buttons,
             0,
@@ -305,6 +307,7 @@ mod tests {
             &Some("black".to_string()),
             &Some("cyan".to_string()),
             &Some("blue".to_string()),
+            TextStyle::default(),
             &tab_labels,
             &tab_close_buttons,
             0,
@@ -363,6 +366,7 @@ mod tests {
             &Some("black".to_string()),
             &Some("cyan".to_string()),
             &Some("blue".to_string()),
+            TextStyle::default(),
             &tab_labels,
             &tab_close_buttons,
             0,
@@ -425,6 +429,7 @@ mod tests {
             &Some("black".to_string()),
             &Some("cyan".to_string()),
             &Some("blue".to_string()),
+            TextStyle::default(),
             &tab_labels,
             &tab_close_buttons,
             1,
@@ -495,6 +500,7 @@ mod tests {
             &bg,
             &title_fg,
             &title_bg,
+            TextStyle::default(),
             &tab_labels,
             &tab_close_buttons,
             1,
@@ -652,6 +658,7 @@ mod tests {
                 &bg,
                 &title_fg,
                 &title_bg,
+                TextStyle::default(),
                 &tab_labels,
                 &tab_close_buttons,
                 1,
@@ -768,6 +775,7 @@ mod tests {
                 &bg,
                 &title_fg,
                 &title_bg,
+                TextStyle::default(),
                 &tab_labels,
                 &tab_close_buttons,
                 1,
@@ -833,6 +841,7 @@ mod tests {
             &Some("black".to_string()),
             &Some("cyan".to_string()),
             &Some("blue".to_string()),
+            TextStyle::default(),
             &tab_labels,
             &tab_close_buttons,
             1,
@@ -894,6 +903,7 @@ mod tests {
             &Some("black".to_string()),
             &Some("cyan".to_string()),
             &Some("blue".to_string()),
+            TextStyle::default(),
             &tab_labels,
             &tab_close_buttons,
             0,
@@ -915,6 +925,7 @@ mod tests {
             &Some("black".to_string()),
             &Some("cyan".to_string()),
             &Some("blue".to_string()),
+            TextStyle::default(),
             &tab_labels,
             &tab_close_buttons,
             0,
@@ -1004,6 +1015,7 @@ mod tests {
             &Some("black".to_string()),
             &Some("cyan".to_string()),
             &Some("blue".to_string()),
+            TextStyle::default(),
             &tab_labels,
             &tab_close_buttons,
             2,
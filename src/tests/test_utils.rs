@@ -37,6 +37,7 @@ impl TestDataFactory {
             tab_order: None,
             next_focus_id: None,
             children: None,
+            class: None,
             fill: None,
             fill_char: None,
             selected_fill_char: None,
@@ -51,6 +52,8 @@ impl TestDataFactory {
             selected_title_bg_color: None,
             selected_title_fg_color: None,
             title_position: None,
+            title_style: None,
+            text_style: None,
             error_border_color: None,
             error_bg_color: None,
             error_fg_color: None,
@@ -62,6 +65,8 @@ impl TestDataFactory {
             error_selected_title_bg_color: None,
             error_selected_title_fg_color: None,
             highlighted_fill_char: None,
+            border: None,
+            border_style: None,
             highlighted_border_color: None,
             highlighted_bg_color: None,
             highlighted_fg_color: None,
@@ -103,6 +108,22 @@ impl TestDataFactory {
             hovered_tab_target: None,
             streams: indexmap::IndexMap::new(),
             selected_stream_id: None,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            overlap_policy: None,
+            refresh_jitter_ms: None,
+            refresh_backoff_max_ms: None,
+            schedule: None,
+            output_format: None,
+            choices_script: None,
+            choices_item_script: None,
+            choices_refresh_interval: None,
+            alerts: None,
+            record: None,
+            alert: None,
         }
     }
 
@@ -136,6 +157,7 @@ impl TestDataFactory {
             title: Some(format!("Test Layout {}", id)),
             refresh_interval: None,
             children: Some(boxes),
+            class: None,
             fill: None,
             fill_char: None,
             selected_fill_char: None,
@@ -165,6 +187,8 @@ impl TestDataFactory {
             error_selected_title_bg_color: None,
             error_selected_title_fg_color: None,
             highlighted_fill_char: None,
+            border: None,
+            border_style: None,
             highlighted_border_color: None,
             highlighted_bg_color: None,
             highlighted_fg_color: None,
@@ -242,6 +266,14 @@ impl TestDataFactory {
         }
     }
 
+    /// Load an app from YAML text the way `boxmux <file>` would, through a temporary file
+    pub fn load_app_from_yaml_str(yaml: &str) -> Result<App, String> {
+        let temp_file = tempfile::NamedTempFile::new().map_err(|e| e.to_string())?;
+        std::fs::write(&temp_file, yaml).map_err(|e| e.to_string())?;
+        crate::model::app::load_app_from_yaml(temp_file.path().to_str().unwrap())
+            .map_err(|e| e.to_string())
+    }
+
     /// Create app context for testing
     pub fn create_test_app_context() -> AppContext {
         AppContext {
@@ -256,6 +288,21 @@ impl TestDataFactory {
         }
     }
 
+    /// Create app context whose root layout holds boxes with the given IDs
+    pub fn create_test_app_context_with_boxes(box_ids: &[&str]) -> AppContext {
+        let mut app_context = Self::create_test_app_context();
+        app_context.app.layouts = vec![Self::create_root_layout(
+            "test_layout",
+            Some(
+                box_ids
+                    .iter()
+                    .map(|id| Self::create_test_muxbox(id))
+                    .collect(),
+            ),
+        )];
+        app_context
+    }
+
     /// Create socket function for testing
     pub fn create_socket_function_replace_content(
         box_id: &str,
@@ -445,7 +492,7 @@ impl IntegrationTestUtils {
                 content,
             } => {
                 use crate::model::common::{
-                    BatchSourceState, ExecutionMode, SourceState, StreamUpdate,
+                    BatchSourceState, ExecutionMode, OutputFormat, SourceState, StreamUpdate,
                 };
                 let stream_update = StreamUpdate {
                     stream_id: format!("{}_default", box_id),
@@ -463,6 +510,7 @@ impl IntegrationTestUtils {
                         },
                     }),
                     execution_mode: ExecutionMode::Thread,
+                    output_format: OutputFormat::Text,
                 };
                 Message::StreamUpdateMessage(stream_update)
             }
@@ -482,7 +530,7 @@ impl IntegrationTestUtils {
             // F0137/F0138: Socket PTY Control and Query patterns
             SocketFunction::KillPtyProcess { box_id } => {
                 use crate::model::common::{
-                    BatchSourceState, ExecutionMode, SourceState, StreamUpdate,
+                    BatchSourceState, ExecutionMode, OutputFormat, SourceState, StreamUpdate,
                 };
                 let stream_update = StreamUpdate {
                     stream_id: format!("{}_pty", box_id),
@@ -496,12 +544,13 @@ impl IntegrationTestUtils {
                         status: BatchStatus::Completed,
                     }),
                     execution_mode: ExecutionMode::Pty,
+                    output_format: OutputFormat::Text,
                 };
                 Message::StreamUpdateMessage(stream_update)
             }
             SocketFunction::RestartPtyProcess { box_id } => {
                 use crate::model::common::{
-                    BatchSourceState, ExecutionMode, SourceState, StreamUpdate,
+                    BatchSourceState, ExecutionMode, OutputFormat, SourceState, StreamUpdate,
                 };
                 let stream_update = StreamUpdate {
                     stream_id: format!("{}_pty", box_id),
@@ -515,12 +564,13 @@ impl IntegrationTestUtils {
                         status: BatchStatus::Completed,
                     }),
                     execution_mode: ExecutionMode::Pty,
+                    output_format: OutputFormat::Text,
                 };
                 Message::StreamUpdateMessage(stream_update)
             }
             SocketFunction::QueryPtyStatus { box_id } => {
                 use crate::model::common::{
-                    BatchSourceState, ExecutionMode, SourceState, StreamUpdate,
+                    BatchSourceState, ExecutionMode, OutputFormat, SourceState, StreamUpdate,
                 };
                 let stream_update = StreamUpdate {
                     stream_id: format!("{}_pty", box_id),
@@ -534,13 +584,14 @@ impl IntegrationTestUtils {
                         status: BatchStatus::Completed,
                     }),
                     execution_mode: ExecutionMode::Pty,
+                    output_format: OutputFormat::Text,
                 };
                 Message::StreamUpdateMessage(stream_update)
             }
             // F0136: Socket PTY Spawn pattern
             SocketFunction::SpawnPtyProcess { box_id, .. } => {
                 use crate::model::common::{
-                    BatchSourceState, ExecutionMode, SourceState, StreamUpdate,
+                    BatchSourceState, ExecutionMode, OutputFormat, SourceState, StreamUpdate,
                 };
                 let stream_update = StreamUpdate {
                     stream_id: format!("{}_pty", box_id),
@@ -554,13 +605,14 @@ impl IntegrationTestUtils {
                         status: BatchStatus::Completed,
                     }),
                     execution_mode: ExecutionMode::Pty,
+                    output_format: OutputFormat::Text,
                 };
                 Message::StreamUpdateMessage(stream_update)
             }
             // F0139: Socket PTY Input pattern
             SocketFunction::SendPtyInput { box_id, .. } => {
                 use crate::model::common::{
-                    BatchSourceState, ExecutionMode, SourceState, StreamUpdate,
+                    BatchSourceState, ExecutionMode, OutputFormat, SourceState, StreamUpdate,
                 };
                 let stream_update = StreamUpdate {
                     stream_id: format!("{}_pty", box_id),
@@ -574,9 +626,44 @@ impl IntegrationTestUtils {
                         status: BatchStatus::Completed,
                     }),
                     execution_mode: ExecutionMode::Pty,
+                    output_format: OutputFormat::Text,
                 };
                 Message::StreamUpdateMessage(stream_update)
             }
+            SocketFunction::SendPtySignal { box_id, signal } => {
+                use crate::model::common::{
+                    BatchSourceState, ExecutionMode, OutputFormat, SourceState, StreamUpdate,
+                };
+                let stream_update = StreamUpdate {
+                    stream_id: format!("{}_pty", box_id),
+                    target_box_id: box_id.clone(),
+                    content_update: format!("SIG{} sent", signal),
+                    source_state: SourceState::Batch(BatchSourceState {
+                        task_id: "test_task".to_string(),
+                        queue_wait_time: std::time::Duration::from_millis(0),
+                        execution_time: std::time::Duration::from_millis(100),
+                        exit_code: Some(0),
+                        status: BatchStatus::Completed,
+                    }),
+                    execution_mode: ExecutionMode::Pty,
+                    output_format: OutputFormat::Text,
+                };
+                Message::StreamUpdateMessage(stream_update)
+            }
+            SocketFunction::Subscribe { .. } => {
+                return Err("Subscribe does not produce a message".into());
+            }
+            SocketFunction::Attach { .. } | SocketFunction::Detach {} => {
+                return Err("Detached sessions do not produce a message".into());
+            }
+            SocketFunction::GetBoxContent { .. }
+            | SocketFunction::ListBoxes { .. }
+            | SocketFunction::ListLayouts {}
+            | SocketFunction::ListExecutionSources {}
+            | SocketFunction::GetPtyBuffer { .. }
+            | SocketFunction::SearchPtyBuffer { .. } => {
+                return Err("Queries do not produce a message".into());
+            }
         };
 
         tx.send((test_uuid, boxmux_message))?;
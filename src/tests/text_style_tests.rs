@@ -0,0 +1,173 @@
+#[cfg(test)]
+mod text_style_tests {
+    use crate::ansi_color_processor::process_ansi_text;
+    use crate::ansi_processor::AnsiProcessor;
+    use crate::components::BoxRenderer;
+    use crate::model::common::{Bounds, Stream, StreamType, TextStyle};
+    use crate::tests::test_utils::TestDataFactory;
+    use crate::{AppContext, Config, ScreenBuffer};
+    use indexmap::IndexMap;
+    use std::collections::HashMap;
+
+    fn style(attributes: &str) -> TextStyle {
+        serde_yaml::from_str(attributes).unwrap()
+    }
+
+    #[test]
+    fn test_text_style_yaml_form() {
+        let bold_underline = style("[bold, underline]");
+        assert_eq!(
+            bold_underline,
+            TextStyle {
+                bold: true,
+                underline: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(style("[]"), TextStyle::default());
+        assert_eq!(
+            serde_json::to_string(&style("[strikethrough, dim, reverse, italic]")).unwrap(),
+            r#"["dim","italic","reverse","strikethrough"]"#
+        );
+        assert!(serde_yaml::from_str::<TextStyle>("[blinking]").is_err());
+    }
+
+    #[test]
+    fn test_transitions_only_touch_changed_attributes() {
+        let plain = TextStyle::default();
+        let bold = style("[bold]");
+        assert_eq!(
+            bold.transition_from(None),
+            "\x1b[22;1;23;24;27;29m",
+            "an unknown terminal state gets every attribute"
+        );
+        assert_eq!(bold.transition_from(Some(&bold)), "");
+        assert_eq!(
+            style("[bold, underline]").transition_from(Some(&bold)),
+            "\x1b[4m"
+        );
+        assert_eq!(
+            style("[dim]").transition_from(Some(&style("[bold, dim]"))),
+            "\x1b[22;2m",
+            "dropping bold clears dim too, so dim is set again"
+        );
+        assert_eq!(
+            plain.transition_from(Some(&style("[italic, reverse]"))),
+            "\x1b[23;27m"
+        );
+    }
+
+    #[test]
+    fn test_ansi_text_cells_carry_attributes() {
+        let cells =
+            process_ansi_text("\x1b[1;3mA\x1b[22;2mB\x1b[9;4;7mC\x1b[29;24;27;23mD\x1b[0mE");
+        let styles: Vec<TextStyle> = cells.iter().map(|cell| cell.style).collect();
+        assert_eq!(
+            styles,
+            vec![
+                style("[bold, italic]"),
+                style("[dim, italic]"),
+                style("[dim, italic, underline, reverse, strikethrough]"),
+                style("[dim]"),
+                TextStyle::default(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pty_attributes_reach_screen_cells() {
+        let mut processor = AnsiProcessor::with_screen_size(20, 2);
+        processor.set_screen_mode(true);
+        processor.process_string("\x1b[2;3mfaint\x1b[0m \x1b[9mgone\x1b[0m");
+        let line = processor.get_screen_content_for_stream();
+        let line = line.lines().next().unwrap();
+
+        let cells = process_ansi_text(line);
+        let text: String = cells.iter().map(|cell| cell.ch).collect();
+        assert!(text.starts_with("faint gone"), "{:?}", text);
+        assert_eq!(cells[0].style, style("[dim, italic]"));
+        assert_eq!(cells[5].style, TextStyle::default());
+        assert_eq!(cells[6].style, style("[strikethrough]"));
+    }
+
+    #[test]
+    fn test_box_styles_apply_to_title_and_text() {
+        let app = TestDataFactory::load_app_from_yaml_str(
+            r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'styled'
+          title: 'Status'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+          title_style: [bold, underline]
+          text_style: [italic]
+"#,
+        )
+        .unwrap();
+        let mut muxbox = app.get_muxbox_by_id("styled").unwrap().clone();
+        assert_eq!(muxbox.title_style, Some(style("[bold, underline]")));
+        assert_eq!(muxbox.text_style, Some(style("[italic]")));
+
+        let mut streams = IndexMap::new();
+        streams.insert(
+            "content".to_string(),
+            Stream::new(
+                "content".to_string(),
+                StreamType::Content,
+                "Status".to_string(),
+                vec!["plain \x1b[1mloud\x1b[0m".to_string()],
+                None,
+                None,
+            ),
+        );
+        muxbox.streams = streams;
+
+        let mut app = TestDataFactory::create_test_app();
+        let graph = app.generate_graph();
+        let context = AppContext::new(app.clone(), Config::default());
+        let mut adjusted_bounds = HashMap::new();
+        adjusted_bounds.insert(
+            "test_layout".to_string(),
+            HashMap::from([(
+                "styled".to_string(),
+                Bounds {
+                    x1: 0,
+                    y1: 0,
+                    x2: 40,
+                    y2: 10,
+                },
+            )]),
+        );
+        let mut buffer = ScreenBuffer::new_custom(60, 20);
+        assert!(BoxRenderer::new(&muxbox, "styled".to_string()).render(
+            &context,
+            &graph,
+            &adjusted_bounds,
+            &app.layouts[0],
+            &mut buffer,
+        ));
+
+        let find = |text: &str| {
+            buffer
+                .buffer
+                .iter()
+                .enumerate()
+                .find_map(|(y, row)| {
+                    let line: String = row.iter().map(|cell| cell.ch).collect();
+                    line.find(text)
+                        .map(|byte| (line[..byte].chars().count(), y))
+                })
+                .unwrap_or_else(|| panic!("{:?} not drawn", text))
+        };
+        let (x, y) = find("Status");
+        assert_eq!(buffer.buffer[y][x].style, style("[bold, underline]"));
+        let (x, y) = find("plain");
+        assert_eq!(buffer.buffer[y][x].style, style("[italic]"));
+        let (x, y) = find("loud");
+        assert_eq!(buffer.buffer[y][x].style, style("[bold, italic]"));
+        assert_eq!(buffer.buffer[y][0].style, TextStyle::default(), "border");
+    }
+}
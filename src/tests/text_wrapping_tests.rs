@@ -73,6 +73,20 @@ mod text_wrapping_tests {
                 selected: false,
                 hovered: false,
                 waiting: false,
+                cwd: None,
+                env: None,
+                shell: None,
+                inherit_env: None,
+                timeout_ms: None,
+                schedule: None,
+                output_format: None,
+                class: None,
+                menu_fg_color: None,
+                menu_bg_color: None,
+                selected_menu_fg_color: None,
+                selected_menu_bg_color: None,
+                highlighted_menu_fg_color: None,
+                highlighted_menu_bg_color: None,
             },
             Choice {
                 id: "2".to_string(),
@@ -84,6 +98,20 @@ mod text_wrapping_tests {
                 selected: true,
                 hovered: false,
                 waiting: false,
+                cwd: None,
+                env: None,
+                shell: None,
+                inherit_env: None,
+                timeout_ms: None,
+                schedule: None,
+                output_format: None,
+                class: None,
+                menu_fg_color: None,
+                menu_bg_color: None,
+                selected_menu_fg_color: None,
+                selected_menu_bg_color: None,
+                highlighted_menu_fg_color: None,
+                highlighted_menu_bg_color: None,
             },
         ];
 
@@ -125,6 +153,20 @@ mod text_wrapping_tests {
             selected: false,
             hovered: false,
             waiting: true,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         }];
 
         let wrapped = wrap_choices_to_width(&choices, 15);
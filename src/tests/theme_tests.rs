@@ -0,0 +1,236 @@
+#[cfg(test)]
+mod theme_tests {
+    use crate::color_utils::{
+        default_border_color, default_fg_color, scrollbar_thumb_color, set_theme_override,
+        TerminalTheme,
+    };
+    use crate::model::app::load_app_from_yaml;
+    use crate::theme::{
+        active_theme_name, builtin_theme_names, load_theme, set_active_theme, ThemeColors,
+        ThemeRole, THEME_TEST_LOCK,
+    };
+    use crate::{AppContext, Config};
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    // Removes the theme and the light/dark override on drop so neither leaks to
+    // other tests, even if an assertion panics.
+    struct ResetGuard;
+    impl Drop for ResetGuard {
+        fn drop(&mut self) {
+            set_active_theme(None);
+            set_theme_override(None);
+        }
+    }
+
+    #[test]
+    fn test_builtin_themes_load() {
+        assert_eq!(
+            builtin_theme_names(),
+            vec!["solarized-dark", "solarized-light", "nord", "gruvbox"]
+        );
+        for name in builtin_theme_names() {
+            let theme = load_theme(name, Path::new("/nonexistent")).unwrap();
+            assert_eq!(theme.name, name);
+            for role in [
+                ThemeRole::Fg,
+                ThemeRole::Bg,
+                ThemeRole::Border,
+                ThemeRole::FocusedBorder,
+                ThemeRole::TabBg,
+                ThemeRole::SelectedMenuBg,
+                ThemeRole::ErrorBorder,
+                ThemeRole::ScrollbarThumb,
+            ] {
+                assert!(theme.colors.get(role).is_some(), "{} {:?}", name, role);
+            }
+            assert_eq!(
+                theme.colors.get(ThemeRole::MenuBg),
+                None,
+                "menus match the panel unless a theme says otherwise"
+            );
+        }
+    }
+
+    #[test]
+    fn test_theme_files_extend_and_validate() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("themes")).unwrap();
+        fs::write(
+            dir.path().join("themes/base.yaml"),
+            "extends: nord\ncolors:\n  border: 'ansi(240)'\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("themes/midnight.yaml"),
+            "extends: base.yaml\ncolors:\n  focused_border: 'rgb(235, 203, 139)'\n",
+        )
+        .unwrap();
+
+        let theme = load_theme("themes/midnight.yaml", dir.path()).unwrap();
+        assert_eq!(theme.name, "midnight", "named after the file by default");
+        let nord = load_theme("nord", dir.path()).unwrap().colors;
+        assert_eq!(
+            theme.colors,
+            ThemeColors {
+                border: Some("ansi(240)".to_string()),
+                focused_border: Some("rgb(235, 203, 139)".to_string()),
+                ..nord
+            },
+            "extends resolves relative to the extending file"
+        );
+
+        let write = |file: &str, yaml: &str| {
+            let path = dir.path().join(file);
+            fs::write(&path, yaml).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let named = write("named.yaml", "name: Night Shift\ncolors: {fg: white}\n");
+        assert_eq!(load_theme(&named, dir.path()).unwrap().name, "Night Shift");
+
+        let error = |spec: &str| load_theme(spec, dir.path()).unwrap_err();
+        assert!(error(&write("typo.yaml", "colors: {boder: red}\n")).contains("boder"));
+        assert!(
+            error(&write("bad.yaml", "colors: {hover_bg: '#12345'}\n"))
+                .ends_with("invalid color hover_bg: '#12345'"),
+            "{}",
+            error("bad.yaml")
+        );
+        assert!(error("dracula").contains("solarized-dark, solarized-light, nord, gruvbox"));
+        write("a.yaml", "extends: b.yaml\n");
+        write("b.yaml", "extends: a.yaml\n");
+        assert!(error("a.yaml").contains("cycle"));
+    }
+
+    #[test]
+    fn test_theme_fills_defaults_and_boxes_override() {
+        let _lock = THEME_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let _guard = ResetGuard;
+
+        let dir = TempDir::new().unwrap();
+        let yaml_path = dir.path().join("app.yaml");
+        fs::write(
+            &yaml_path,
+            r#"
+app:
+  theme: 'nord'
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'plain'
+          position: {x1: 0%, y1: 0%, x2: 50%, y2: 100%}
+        - id: 'custom'
+          position: {x1: 50%, y1: 0%, x2: 100%, y2: 100%}
+          border_color: 'magenta'
+          menu_bg_color: 'blue'
+"#,
+        )
+        .unwrap();
+        let mut app = load_app_from_yaml(yaml_path.to_str().unwrap()).unwrap();
+        assert_eq!(app.theme.as_deref(), Some("nord"));
+        set_active_theme(Some(
+            load_theme(app.theme.as_deref().unwrap(), dir.path()).unwrap(),
+        ));
+        assert_eq!(active_theme_name().as_deref(), Some("nord"));
+
+        let colors = |app: &crate::App, id: &str| {
+            let mut app = app.clone();
+            let context = AppContext::new(app.clone(), Config::default());
+            let graph = app.generate_graph();
+            let muxbox = app.get_muxbox_by_id(id).unwrap();
+            (
+                muxbox.calc_border_color(&context, &graph),
+                muxbox.calc_bg_color(&context, &graph),
+                muxbox.calc_menu_bg_color(&context, &graph),
+            )
+        };
+        let some = |color: &str| Some(color.to_string());
+        assert_eq!(
+            colors(&app, "plain"),
+            (some("#4c566a"), some("#2e3440"), some("#2e3440"))
+        );
+        assert_eq!(
+            colors(&app, "custom"),
+            (some("magenta"), some("#2e3440"), some("blue")),
+            "the box's own colors win over the theme"
+        );
+
+        app.get_muxbox_by_id_mut("plain").unwrap().selected = Some(true);
+        assert_eq!(colors(&app, "plain").0, some("#88c0d0"));
+        app.get_muxbox_by_id_mut("plain").unwrap().error_state = true;
+        assert_eq!(colors(&app, "plain").0, some("#bf616a"));
+        assert_eq!(scrollbar_thumb_color(&some("magenta")), some("#81a1c1"));
+
+        // Roles a theme leaves out keep the light/dark defaults
+        let partial = dir.path().join("partial.yaml");
+        fs::write(&partial, "colors: {border: '#101010'}\n").unwrap();
+        set_active_theme(Some(load_theme("partial.yaml", dir.path()).unwrap()));
+        set_theme_override(Some(TerminalTheme::Light));
+        assert_eq!(default_border_color(false), "#101010");
+        assert_eq!(default_border_color(true), "focus_blue");
+        assert_eq!(default_fg_color(false), "black");
+        assert_eq!(scrollbar_thumb_color(&some("magenta")), some("magenta"));
+    }
+
+    #[test]
+    fn test_theme_menu_colors_yield_to_panel_colors() {
+        let _lock = THEME_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let _guard = ResetGuard;
+
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("menus.yaml"),
+            "extends: nord\ncolors: {menu_fg: '#eceff4', menu_bg: '#3b4252'}\n",
+        )
+        .unwrap();
+        let yaml_path = dir.path().join("app.yaml");
+        fs::write(
+            &yaml_path,
+            r#"
+app:
+  layouts:
+    - id: 'main'
+      root: true
+      children:
+        - id: 'plain'
+          position: {x1: 0%, y1: 0%, x2: 30%, y2: 100%}
+        - id: 'blue'
+          position: {x1: 30%, y1: 0%, x2: 60%, y2: 100%}
+          bg_color: 'blue'
+          fg_color: 'yellow'
+          children:
+            - id: 'nested'
+              position: {x1: 0%, y1: 0%, x2: 100%, y2: 50%}
+    - id: 'other'
+      bg_color: 'green'
+      children:
+        - id: 'in_layout'
+          position: {x1: 0%, y1: 0%, x2: 100%, y2: 100%}
+"#,
+        )
+        .unwrap();
+        let mut app = load_app_from_yaml(yaml_path.to_str().unwrap()).unwrap();
+        set_active_theme(Some(load_theme("menus.yaml", dir.path()).unwrap()));
+
+        let context = AppContext::new(app.clone(), Config::default());
+        let graph = app.generate_graph();
+        let menu = |id: &str| {
+            let muxbox = app.get_muxbox_by_id(id).unwrap();
+            (
+                muxbox.calc_menu_fg_color(&context, &graph),
+                muxbox.calc_menu_bg_color(&context, &graph),
+            )
+        };
+        let some = |color: &str| Some(color.to_string());
+        assert_eq!(menu("plain"), (some("#eceff4"), some("#3b4252")));
+        assert_eq!(
+            menu("blue"),
+            (some("yellow"), some("blue")),
+            "the box's panel colors win over the theme's menu colors"
+        );
+        assert_eq!(menu("nested"), (some("yellow"), some("blue")));
+        assert_eq!(menu("in_layout").1, some("green"));
+    }
+}
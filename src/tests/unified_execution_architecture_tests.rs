@@ -32,6 +32,20 @@ pub mod unified_execution_architecture_tests {
                 redirect_output: None,
                 append_output: Some(false),
                 waiting: false,
+                cwd: None,
+                env: None,
+                shell: None,
+                inherit_env: None,
+                timeout_ms: None,
+                schedule: None,
+                output_format: None,
+                class: None,
+                menu_fg_color: None,
+                menu_bg_color: None,
+                selected_menu_fg_color: None,
+                selected_menu_bg_color: None,
+                highlighted_menu_fg_color: None,
+                highlighted_menu_bg_color: None,
             };
 
             // Verify that the choice has the new ExecutionMode field
@@ -75,6 +89,20 @@ pub mod unified_execution_architecture_tests {
             redirect_output: None,
             append_output: Some(false),
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         };
 
         // Verify PTY choice accepts multiple commands
@@ -107,6 +135,20 @@ pub mod unified_execution_architecture_tests {
             redirect_output: None,
             append_output: Some(false),
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         };
 
         // Simulate multiple clicks - each should create a unique stream
@@ -152,6 +194,20 @@ pub mod unified_execution_architecture_tests {
             redirect_output: Some("target_box".to_string()),
             append_output: Some(false),
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         };
 
         // Verify consistent stream ID format
@@ -185,6 +241,20 @@ pub mod unified_execution_architecture_tests {
             redirect_output: None,
             append_output: Some(false),
             waiting: false,
+            cwd: None,
+            env: None,
+            shell: None,
+            inherit_env: None,
+            timeout_ms: None,
+            schedule: None,
+            output_format: None,
+            class: None,
+            menu_fg_color: None,
+            menu_bg_color: None,
+            selected_menu_fg_color: None,
+            selected_menu_bg_color: None,
+            highlighted_menu_fg_color: None,
+            highlighted_menu_bg_color: None,
         };
 
         // Verify ExecutionMode takes precedence over legacy fields
@@ -229,6 +299,20 @@ pub mod unified_execution_architecture_tests {
                 redirect_output: None,
                 append_output: Some(false),
                 waiting: false,
+                cwd: None,
+                env: None,
+                shell: None,
+                inherit_env: None,
+                timeout_ms: None,
+                schedule: None,
+                output_format: None,
+                class: None,
+                menu_fg_color: None,
+                menu_bg_color: None,
+                selected_menu_fg_color: None,
+                selected_menu_bg_color: None,
+                highlighted_menu_fg_color: None,
+                highlighted_menu_bg_color: None,
             };
 
             let stream_id = format!("{}_{}", choice.id, mode.as_stream_suffix());
@@ -0,0 +1,407 @@
+//! Named color themes.
+//!
+//! A theme maps semantic roles (box text, border, focused border, title, tab, menu,
+//! selected menu, hover, error and scrollbar) to colors. Themes only replace the
+//! built-in defaults: a box still takes its own color field first, then its parent's
+//! and its layout's through the `inherit_*` helpers, and a role the theme leaves out
+//! keeps the light/dark default from [`crate::color_utils`].
+//!
+//! `app.theme` (or `--theme`) names a built-in theme or a theme file:
+//!
+//! ```yaml
+//! name: midnight
+//! extends: nord
+//! colors:
+//!   focused_border: '#ebcb8b'
+//!   scrollbar_thumb: '#ebcb8b'
+//! ```
+
+use crate::color_utils::parse_color;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::RwLock;
+
+/// How many `extends` a theme may chain through before it's treated as a cycle
+const MAX_EXTENDS_DEPTH: usize = 8;
+
+/// A semantic color role that themes assign
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThemeRole {
+    Fg,
+    Bg,
+    Border,
+    FocusedBorder,
+    TitleFg,
+    TitleBg,
+    TabFg,
+    TabBg,
+    FocusedTabBg,
+    MenuFg,
+    MenuBg,
+    SelectedMenuFg,
+    SelectedMenuBg,
+    HoverFg,
+    HoverBg,
+    ErrorFg,
+    ErrorBg,
+    ErrorBorder,
+    ErrorTitleFg,
+    ErrorTitleBg,
+    ScrollbarTrack,
+    ScrollbarThumb,
+}
+
+/// A theme's colors by role; unset roles keep the default
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeColors {
+    /// Box text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fg: Option<String>,
+    /// Box background
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border: Option<String>,
+    /// Border of the focused box
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focused_border: Option<String>,
+    /// Title bar and inactive tabs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_fg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_bg: Option<String>,
+    /// Active tab
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_fg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_bg: Option<String>,
+    /// Active tab, and the selected menu item, of the focused box
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focused_tab_bg: Option<String>,
+    /// Menu items; without these they match the box text and background
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub menu_fg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub menu_bg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_menu_fg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_menu_bg: Option<String>,
+    /// Menu items and tabs under the mouse
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hover_fg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hover_bg: Option<String>,
+    /// Boxes whose script failed or whose alert is at error level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_fg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_bg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_border: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_title_fg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_title_bg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrollbar_track: Option<String>,
+    /// Scrollbar knob; without it the knob takes the box's border color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrollbar_thumb: Option<String>,
+}
+
+impl ThemeColors {
+    pub fn get(&self, role: ThemeRole) -> Option<&str> {
+        let color = match role {
+            ThemeRole::Fg => &self.fg,
+            ThemeRole::Bg => &self.bg,
+            ThemeRole::Border => &self.border,
+            ThemeRole::FocusedBorder => &self.focused_border,
+            ThemeRole::TitleFg => &self.title_fg,
+            ThemeRole::TitleBg => &self.title_bg,
+            ThemeRole::TabFg => &self.tab_fg,
+            ThemeRole::TabBg => &self.tab_bg,
+            ThemeRole::FocusedTabBg => &self.focused_tab_bg,
+            ThemeRole::MenuFg => &self.menu_fg,
+            ThemeRole::MenuBg => &self.menu_bg,
+            ThemeRole::SelectedMenuFg => &self.selected_menu_fg,
+            ThemeRole::SelectedMenuBg => &self.selected_menu_bg,
+            ThemeRole::HoverFg => &self.hover_fg,
+            ThemeRole::HoverBg => &self.hover_bg,
+            ThemeRole::ErrorFg => &self.error_fg,
+            ThemeRole::ErrorBg => &self.error_bg,
+            ThemeRole::ErrorBorder => &self.error_border,
+            ThemeRole::ErrorTitleFg => &self.error_title_fg,
+            ThemeRole::ErrorTitleBg => &self.error_title_bg,
+            ThemeRole::ScrollbarTrack => &self.scrollbar_track,
+            ThemeRole::ScrollbarThumb => &self.scrollbar_thumb,
+        };
+        color.as_deref()
+    }
+
+    /// These colors, with the roles they leave unset taken from `base`
+    pub fn extending(self, base: ThemeColors) -> ThemeColors {
+        ThemeColors {
+            fg: self.fg.or(base.fg),
+            bg: self.bg.or(base.bg),
+            border: self.border.or(base.border),
+            focused_border: self.focused_border.or(base.focused_border),
+            title_fg: self.title_fg.or(base.title_fg),
+            title_bg: self.title_bg.or(base.title_bg),
+            tab_fg: self.tab_fg.or(base.tab_fg),
+            tab_bg: self.tab_bg.or(base.tab_bg),
+            focused_tab_bg: self.focused_tab_bg.or(base.focused_tab_bg),
+            menu_fg: self.menu_fg.or(base.menu_fg),
+            menu_bg: self.menu_bg.or(base.menu_bg),
+            selected_menu_fg: self.selected_menu_fg.or(base.selected_menu_fg),
+            selected_menu_bg: self.selected_menu_bg.or(base.selected_menu_bg),
+            hover_fg: self.hover_fg.or(base.hover_fg),
+            hover_bg: self.hover_bg.or(base.hover_bg),
+            error_fg: self.error_fg.or(base.error_fg),
+            error_bg: self.error_bg.or(base.error_bg),
+            error_border: self.error_border.or(base.error_border),
+            error_title_fg: self.error_title_fg.or(base.error_title_fg),
+            error_title_bg: self.error_title_bg.or(base.error_title_bg),
+            scrollbar_track: self.scrollbar_track.or(base.scrollbar_track),
+            scrollbar_thumb: self.scrollbar_thumb.or(base.scrollbar_thumb),
+        }
+    }
+
+    /// The first color that isn't a valid color value, as `role: value`
+    fn invalid_color(&self) -> Option<String> {
+        serde_json::to_value(self)
+            .ok()?
+            .as_object()?
+            .iter()
+            .find_map(|(role, value)| {
+                let color = value.as_str()?;
+                parse_color(color)
+                    .is_none()
+                    .then(|| format!("{}: '{}'", role, color))
+            })
+    }
+}
+
+/// A resolved theme
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    pub colors: ThemeColors,
+}
+
+/// The theme file format; built-in themes use it too
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    #[serde(default)]
+    name: Option<String>,
+    /// Built-in theme or theme file whose colors fill the roles this one leaves out
+    #[serde(default)]
+    extends: Option<String>,
+    #[serde(default)]
+    colors: ThemeColors,
+}
+
+const SOLARIZED_DARK: &str = r##"
+colors:
+  fg: '#839496'
+  bg: '#002b36'
+  border: '#586e75'
+  focused_border: '#268bd2'
+  title_fg: '#93a1a1'
+  title_bg: '#073642'
+  tab_fg: '#fdf6e3'
+  tab_bg: '#586e75'
+  focused_tab_bg: '#268bd2'
+  selected_menu_fg: '#fdf6e3'
+  selected_menu_bg: '#073642'
+  hover_fg: '#002b36'
+  hover_bg: '#b58900'
+  error_fg: '#fdf6e3'
+  error_bg: '#dc322f'
+  error_border: '#dc322f'
+  error_title_fg: '#dc322f'
+  error_title_bg: '#073642'
+  scrollbar_track: '#073642'
+  scrollbar_thumb: '#586e75'
+"##;
+
+const SOLARIZED_LIGHT: &str = r##"
+colors:
+  fg: '#657b83'
+  bg: '#fdf6e3'
+  border: '#93a1a1'
+  focused_border: '#268bd2'
+  title_fg: '#586e75'
+  title_bg: '#eee8d5'
+  tab_fg: '#fdf6e3'
+  tab_bg: '#93a1a1'
+  focused_tab_bg: '#268bd2'
+  selected_menu_fg: '#fdf6e3'
+  selected_menu_bg: '#93a1a1'
+  hover_fg: '#fdf6e3'
+  hover_bg: '#b58900'
+  error_fg: '#fdf6e3'
+  error_bg: '#dc322f'
+  error_border: '#dc322f'
+  error_title_fg: '#dc322f'
+  error_title_bg: '#eee8d5'
+  scrollbar_track: '#eee8d5'
+  scrollbar_thumb: '#93a1a1'
+"##;
+
+const NORD: &str = r##"
+colors:
+  fg: '#d8dee9'
+  bg: '#2e3440'
+  border: '#4c566a'
+  focused_border: '#88c0d0'
+  title_fg: '#e5e9f0'
+  title_bg: '#3b4252'
+  tab_fg: '#eceff4'
+  tab_bg: '#434c5e'
+  focused_tab_bg: '#5e81ac'
+  selected_menu_fg: '#eceff4'
+  selected_menu_bg: '#434c5e'
+  hover_fg: '#2e3440'
+  hover_bg: '#ebcb8b'
+  error_fg: '#eceff4'
+  error_bg: '#bf616a'
+  error_border: '#bf616a'
+  error_title_fg: '#bf616a'
+  error_title_bg: '#3b4252'
+  scrollbar_track: '#3b4252'
+  scrollbar_thumb: '#81a1c1'
+"##;
+
+const GRUVBOX: &str = r##"
+colors:
+  fg: '#ebdbb2'
+  bg: '#282828'
+  border: '#665c54'
+  focused_border: '#fabd2f'
+  title_fg: '#ebdbb2'
+  title_bg: '#3c3836'
+  tab_fg: '#fbf1c7'
+  tab_bg: '#504945'
+  focused_tab_bg: '#458588'
+  selected_menu_fg: '#fbf1c7'
+  selected_menu_bg: '#504945'
+  hover_fg: '#282828'
+  hover_bg: '#fabd2f'
+  error_fg: '#fbf1c7'
+  error_bg: '#cc241d'
+  error_border: '#fb4934'
+  error_title_fg: '#fb4934'
+  error_title_bg: '#3c3836'
+  scrollbar_track: '#3c3836'
+  scrollbar_thumb: '#a89984'
+"##;
+
+/// Built-in themes by name, as theme files
+const BUILTIN_THEMES: &[(&str, &str)] = &[
+    ("solarized-dark", SOLARIZED_DARK),
+    ("solarized-light", SOLARIZED_LIGHT),
+    ("nord", NORD),
+    ("gruvbox", GRUVBOX),
+];
+
+/// Names of the built-in themes
+pub fn builtin_theme_names() -> Vec<&'static str> {
+    BUILTIN_THEMES.iter().map(|(name, _)| *name).collect()
+}
+
+/// Resolve a theme by built-in name, or else as a theme file path relative to
+/// `base_dir` (absolute paths are used as they are)
+pub fn load_theme(spec: &str, base_dir: &Path) -> Result<Theme, String> {
+    load_theme_at_depth(spec, base_dir, 0)
+}
+
+fn load_theme_at_depth(spec: &str, base_dir: &Path, depth: usize) -> Result<Theme, String> {
+    if depth > MAX_EXTENDS_DEPTH {
+        return Err(format!(
+            "theme '{}': more than {} themes extend each other; is there a cycle?",
+            spec, MAX_EXTENDS_DEPTH
+        ));
+    }
+
+    let (file, default_name, file_dir) = match BUILTIN_THEMES.iter().find(|(name, _)| *name == spec)
+    {
+        Some((name, yaml)) => (parse_theme_file(yaml, name)?, name.to_string(), None),
+        None => {
+            let path = base_dir.join(spec);
+            let yaml = std::fs::read_to_string(&path).map_err(|e| {
+                format!(
+                    "theme '{}' is not a built-in theme ({}) and can't be read from {}: {}",
+                    spec,
+                    builtin_theme_names().join(", "),
+                    path.display(),
+                    e
+                )
+            })?;
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| spec.to_string());
+            let file = parse_theme_file(&yaml, &path.display().to_string())?;
+            (file, name, path.parent().map(Path::to_path_buf))
+        }
+    };
+
+    if let Some(invalid) = file.colors.invalid_color() {
+        return Err(format!("theme '{}': invalid color {}", spec, invalid));
+    }
+
+    let colors = match &file.extends {
+        Some(base) => {
+            let base_dir = file_dir.as_deref().unwrap_or(base_dir);
+            let base = load_theme_at_depth(base, base_dir, depth + 1)?;
+            file.colors.extending(base.colors)
+        }
+        None => file.colors,
+    };
+
+    Ok(Theme {
+        name: file.name.unwrap_or(default_name),
+        colors,
+    })
+}
+
+fn parse_theme_file(yaml: &str, source: &str) -> Result<ThemeFile, String> {
+    serde_yaml::from_str(yaml).map_err(|e| format!("theme file {}: {}", source, e))
+}
+
+static ACTIVE_THEME: RwLock<Option<Theme>> = RwLock::new(None);
+
+/// Install the theme whose colors replace the defaults, or `None` for the
+/// light/dark defaults alone
+pub fn set_active_theme(theme: Option<Theme>) {
+    if let Ok(mut active) = ACTIVE_THEME.write() {
+        *active = theme;
+    }
+}
+
+/// Name of the installed theme
+pub fn active_theme_name() -> Option<String> {
+    ACTIVE_THEME
+        .read()
+        .ok()?
+        .as_ref()
+        .map(|theme| theme.name.clone())
+}
+
+/// The installed theme's color for `role`, if it sets one
+pub fn theme_color(role: ThemeRole) -> Option<String> {
+    ACTIVE_THEME
+        .read()
+        .ok()?
+        .as_ref()
+        .and_then(|theme| theme.colors.get(role))
+        .map(str::to_string)
+}
+
+/// Held by tests that install a theme or force light/dark, since either changes the
+/// defaults the other tests check
+#[cfg(test)]
+pub(crate) static THEME_TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
@@ -1,5 +1,6 @@
 use crate::model::app::AppContext;
 // T0325: ExecuteChoice cleanup - keeping Choice import for ChoiceScriptRunner
+use crate::refresh_scheduler::RunOutcome;
 use crate::{FieldUpdate, MuxBox, Updatable};
 use bincode;
 use log::error;
@@ -87,6 +88,17 @@ pub enum Message {
     ExecuteScriptMessage(crate::model::common::ExecuteScript), // Universal script execution entry point
     StreamUpdateMessage(crate::model::common::StreamUpdate),   // Universal stream content updates
     SourceActionMessage(crate::model::common::SourceAction),   // Source lifecycle management
+    ChoiceExecuted(String, String), // muxbox_id, choice_id - notification once a choice starts executing
+    AlertChanged(
+        String,
+        Option<crate::model::common::AlertLevel>,
+        Option<String>,
+    ), // muxbox_id, level (None once cleared), message - a box's matching alert rule changed
+    PluginOutput(
+        String,
+        Option<(String, bool)>,
+        Vec<crate::plugin::PluginAction>,
+    ), // muxbox_id, re-rendered content and whether rendering succeeded, requested actions - a plugin request finished on its worker thread
 }
 
 impl Hash for Message {
@@ -321,6 +333,23 @@ impl Hash for Message {
                 "source_action_message".hash(state);
                 source_action.hash(state);
             }
+            Message::ChoiceExecuted(muxbox_id, choice_id) => {
+                "choice_executed".hash(state);
+                muxbox_id.hash(state);
+                choice_id.hash(state);
+            }
+            Message::AlertChanged(muxbox_id, level, message) => {
+                "alert_changed".hash(state);
+                muxbox_id.hash(state);
+                level.hash(state);
+                message.hash(state);
+            }
+            Message::PluginOutput(muxbox_id, output, actions) => {
+                "plugin_output".hash(state);
+                muxbox_id.hash(state);
+                output.hash(state);
+                actions.hash(state);
+            }
         }
     }
 }
@@ -589,13 +618,25 @@ impl Runnable for RunnableImpl {
 
 impl RunnableImpl {
     fn handle_execute_script(&mut self, execute_script: crate::model::common::ExecuteScript) {
-        use crate::model::common::ExecutionMode;
-
         log::info!(
             "T0315 FIXED: ThreadManager properly handling ExecuteScript for target_box: {}",
             execute_script.target_box_id
         );
 
+        // Periodic refreshes only start when their box's overlap policy allows it
+        match crate::refresh_scheduler::admit_script(&self.app_context.app, &execute_script) {
+            crate::refresh_scheduler::Admission::Run => self.dispatch_script(execute_script),
+            admission => log::debug!(
+                "Periodic refresh {} still running, new run {:?}",
+                execute_script.stream_id,
+                admission
+            ),
+        }
+    }
+
+    fn dispatch_script(&mut self, execute_script: crate::model::common::ExecuteScript) {
+        use crate::model::common::ExecutionMode;
+
         // Use UpdateStreamContent to create/update the output stream
         match execute_script.execution_mode {
             ExecutionMode::Immediate => {
@@ -615,65 +656,22 @@ impl RunnableImpl {
 
     fn execute_immediate_script(&mut self, execute_script: crate::model::common::ExecuteScript) {
         // Run the script synchronously
-        let output = crate::utils::detached_command("sh")
-            .arg("-c")
-            .arg(execute_script.script.join(" "))
-            // Detached from the terminal (own session, null stdin) so it can never
-            // reset boxmux's raw mode / mouse tracking; stdout/stderr are captured.
-            .output();
-
-        let content = match output {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                if stderr.is_empty() {
-                    stdout.to_string()
-                } else {
-                    format!("{}\n{}", stdout, stderr)
-                }
-            }
-            Err(e) => {
-                format!("Error executing script: {}", e)
-            }
-        };
-
-        // Use stream_id from ExecuteScript (already registered in source registry)
-        let stream_id = execute_script.stream_id.clone();
-
-        // Send result via StreamUpdate with target_box_id for auto-creation
-        // REDIRECT FIX: Use redirect destination if specified
-        let target_box_id = if let Some(ref redirect_to) = execute_script.redirect_output {
-            log::info!(
-                "THREADMANAGER REDIRECT FIX UNKNOWN: Using redirect destination: {} (was {})",
-                redirect_to,
-                execute_script.target_box_id
-            );
-            redirect_to.clone()
-        } else {
-            log::info!(
-                "THREADMANAGER REDIRECT FIX UNKNOWN: No redirect, using source box: {}",
-                execute_script.target_box_id
-            );
-            execute_script.target_box_id.clone()
-        };
-
-        let stream_update = crate::model::common::StreamUpdate {
-            stream_id: stream_id.clone(),
-            target_box_id,
-            content_update: content,
-            source_state: crate::model::common::SourceState::Batch(
-                crate::model::common::BatchSourceState {
-                    task_id: stream_id.clone(),
-                    queue_wait_time: std::time::Duration::from_millis(0),
-                    execution_time: std::time::Duration::from_millis(50), // Immediate scripts are very fast
-                    exit_code: Some(0),
-                    status: crate::model::common::BatchStatus::Completed,
-                },
-            ),
-            execution_mode: execute_script.execution_mode,
-        };
+        let (stream_update, outcome) = run_immediate_script(&execute_script);
+        if let Some(stream_update) = stream_update {
+            self.send_message(Message::StreamUpdateMessage(stream_update));
+        }
+        self.finish_periodic_run(&execute_script, outcome);
+    }
 
-        self.send_message(Message::StreamUpdateMessage(stream_update));
+    /// Record the end of a periodic run and start the run queued behind it, if any
+    fn finish_periodic_run(
+        &mut self,
+        execute_script: &crate::model::common::ExecuteScript,
+        outcome: RunOutcome,
+    ) {
+        if let Some(next) = crate::refresh_scheduler::finish_script(execute_script, outcome) {
+            self.dispatch_script(next);
+        }
     }
 
     fn execute_threaded_script(&mut self, execute_script: crate::model::common::ExecuteScript) {
@@ -701,6 +699,7 @@ impl RunnableImpl {
                 },
             ),
             execution_mode: execution_mode.clone(),
+            output_format: execute_script.output_format,
         };
 
         // TODO: Need to send this message back to DrawLoop somehow
@@ -719,64 +718,19 @@ impl RunnableImpl {
 
         // Spawn background thread for actual execution
         thread::spawn(move || {
-            let thread_id = format!("{:?}", thread::current().id());
-
-            // Execute the script
-            let output = crate::utils::detached_command("sh")
-                .arg("-c")
-                .arg(script.join(" "))
-                .output();
-
-            let (content, exit_code, status) = match output {
-                Ok(output) => {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    let content = if stderr.is_empty() {
-                        stdout.to_string()
-                    } else {
-                        format!("{}\n{}", stdout, stderr)
-                    };
-                    let exit_code = output.status.code();
-                    let status = if output.status.success() {
-                        crate::model::common::ExecutionThreadStatus::Completed
-                    } else {
-                        crate::model::common::ExecutionThreadStatus::Failed(
-                            "Script execution failed".to_string(),
-                        )
-                    };
-                    (content, exit_code, status)
-                }
-                Err(e) => (
-                    format!("Error executing script: {}", e),
-                    Some(1),
-                    crate::model::common::ExecutionThreadStatus::Failed(format!(
-                        "Execution error: {}",
-                        e
-                    )),
-                ),
-            };
-
-            // Send final result back to ThreadManager
-            let final_update = crate::model::common::StreamUpdate {
-                stream_id,
-                target_box_id,
-                content_update: content,
-                source_state: crate::model::common::SourceState::Thread(
-                    crate::model::common::ThreadSourceState {
-                        thread_id,
-                        execution_time: std::time::Duration::from_millis(100), // approximate
-                        exit_code,
-                        status,
-                    },
-                ),
-                execution_mode,
-            };
+            // A periodic run queued behind this one starts in the same worker
+            let mut next = Some(execute_script);
+            while let Some(execute_script) = next.take() {
+                let (final_update, outcome) = run_threaded_script(&execute_script);
+
+                // TODO: Need to send this back to DrawLoop
+                log::info!(
+                    "Thread execution completed, would send StreamUpdate: {:?}",
+                    final_update
+                );
 
-            // TODO: Need to send this back to DrawLoop
-            log::info!(
-                "Thread execution completed, would send StreamUpdate: {:?}",
-                final_update
-            );
+                next = crate::refresh_scheduler::finish_script(&execute_script, outcome);
+            }
         });
     }
 }
@@ -847,6 +801,14 @@ impl ThreadManager {
                     //     app_context_updates
                     // );
                     self.app_context.app.apply_updates(app_context_updates);
+                    crate::refresh_scheduler::forget_removed_boxes(
+                        &original_app_context.app,
+                        &self.app_context.app,
+                    );
+                    crate::draw_loop::forget_removed_boxes(
+                        &original_app_context.app,
+                        &self.app_context.app,
+                    );
                     self.send_app_context_update_to_all_threads((
                         uuid,
                         self.app_context.generate_diff(&original_app_context),
@@ -875,10 +837,26 @@ impl ThreadManager {
                         log::info!("ThreadManager processing ExecuteScript from thread {}: target_box_id={}, execution_mode={:?}", 
                                    uuid, execute_script.target_box_id, execute_script.execution_mode);
 
+                        // Let observers (e.g. socket subscribers) know a choice ran
+                        if let Some(choice_id) = execute_script.source.choice_id() {
+                            self.send_message_to_all_threads((
+                                Uuid::new_v4(),
+                                Message::ChoiceExecuted(
+                                    execute_script.target_box_id.clone(),
+                                    choice_id,
+                                ),
+                            ));
+                        }
+
                         // Handle ExecuteScript directly in ThreadManager, don't broadcast
                         self.handle_execute_script(execute_script);
                         has_updates = true;
                     }
+                    Message::RemoveBox(box_id) => {
+                        crate::refresh_scheduler::forget_box(&box_id);
+                        self.send_message_to_all_threads((uuid, Message::RemoveBox(box_id)));
+                        has_updates = true;
+                    }
                     _ => {
                         // For all other messages, broadcast to all threads
                         self.send_message_to_all_threads((uuid, received_msg));
@@ -1019,13 +997,25 @@ impl ThreadManager {
     }
 
     fn handle_execute_script(&mut self, execute_script: crate::model::common::ExecuteScript) {
-        use crate::model::common::ExecutionMode;
-
         log::info!(
             "T0315 FIXED: ThreadManager properly handling ExecuteScript for target_box: {}",
             execute_script.target_box_id
         );
 
+        // Periodic refreshes only start when their box's overlap policy allows it
+        match crate::refresh_scheduler::admit_script(&self.app_context.app, &execute_script) {
+            crate::refresh_scheduler::Admission::Run => self.dispatch_script(execute_script),
+            admission => log::debug!(
+                "Periodic refresh {} still running, new run {:?}",
+                execute_script.stream_id,
+                admission
+            ),
+        }
+    }
+
+    fn dispatch_script(&mut self, execute_script: crate::model::common::ExecuteScript) {
+        use crate::model::common::ExecutionMode;
+
         // Use UpdateStreamContent to create/update the output stream
         match execute_script.execution_mode {
             ExecutionMode::Immediate => {
@@ -1045,155 +1035,258 @@ impl ThreadManager {
 
     fn execute_immediate_script(&mut self, execute_script: crate::model::common::ExecuteScript) {
         // Run the script synchronously
-        let output = crate::utils::detached_command("sh")
-            .arg("-c")
-            .arg(execute_script.script.join(" "))
-            // Detached from the terminal (own session, null stdin) so it can never
-            // reset boxmux's raw mode / mouse tracking; stdout/stderr are captured.
-            .output();
-
-        let content = match output {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                if stderr.is_empty() {
-                    stdout.to_string()
-                } else {
-                    format!("{}\n{}", stdout, stderr)
-                }
-            }
-            Err(e) => {
-                format!("Error executing script: {}", e)
-            }
-        };
-
-        // SOURCE OBJECT ARCHITECTURE: Use stream_id from ExecuteScript (from source object)
-        let stream_id = execute_script.stream_id.clone();
-
-        // Send result via StreamUpdate with target_box_id for auto-creation
-        // REDIRECT FIX: Use redirect destination if specified
-        let target_box_id = if let Some(ref redirect_to) = execute_script.redirect_output {
-            log::info!(
-                "THREADMANAGER REDIRECT FIX IMMEDIATE: Using redirect destination: {} (was {})",
-                redirect_to,
-                execute_script.target_box_id
-            );
-            redirect_to.clone()
-        } else {
-            log::info!(
-                "THREADMANAGER REDIRECT FIX IMMEDIATE: No redirect, using source box: {}",
-                execute_script.target_box_id
-            );
-            execute_script.target_box_id.clone()
-        };
-
-        let stream_update = crate::model::common::StreamUpdate {
-            stream_id,
-            target_box_id,
-            content_update: content,
-            source_state: crate::model::common::SourceState::Batch(
-                crate::model::common::BatchSourceState {
-                    task_id: "immediate".to_string(),
-                    queue_wait_time: std::time::Duration::from_millis(0),
-                    execution_time: std::time::Duration::from_millis(50), // Immediate scripts are very fast
-                    exit_code: Some(0),
-                    status: crate::model::common::BatchStatus::Completed,
-                },
-            ),
-            execution_mode: execute_script.execution_mode,
-        };
-
+        let (stream_update, outcome) = run_immediate_script(&execute_script);
         // Broadcast StreamUpdate to all threads for processing
-        self.send_message_to_all_threads((
-            uuid::Uuid::new_v4(),
-            Message::StreamUpdateMessage(stream_update),
-        ));
+        if let Some(stream_update) = stream_update {
+            self.send_message_to_all_threads((
+                uuid::Uuid::new_v4(),
+                Message::StreamUpdateMessage(stream_update),
+            ));
+        }
+        self.finish_periodic_run(&execute_script, outcome);
+    }
+
+    /// Record the end of a periodic run and start the run queued behind it, if any
+    fn finish_periodic_run(
+        &mut self,
+        execute_script: &crate::model::common::ExecuteScript,
+        outcome: RunOutcome,
+    ) {
+        if let Some(next) = crate::refresh_scheduler::finish_script(execute_script, outcome) {
+            self.dispatch_script(next);
+        }
     }
 
     fn execute_threaded_script(&mut self, execute_script: crate::model::common::ExecuteScript) {
         log::info!("T0315: Thread execution - using existing thread pool infrastructure");
 
-        // SOURCE OBJECT ARCHITECTURE: Use stream_id from ExecuteScript (from source object)
-        let stream_id = execute_script.stream_id.clone();
-
-        // Use existing utils::run_script_with_pty_and_redirect for Thread execution
-        let libs = if execute_script.libs.is_empty() {
-            None
-        } else {
-            Some(execute_script.libs.clone())
-        };
-
-        // REDIRECT FIX: Use redirect destination if specified
-        let target_box_id = if let Some(ref redirect_to) = execute_script.redirect_output {
-            log::info!(
-                "THREADMANAGER REDIRECT FIX THREAD: Using redirect destination: {} (was {})",
-                redirect_to,
-                execute_script.target_box_id
-            );
-            redirect_to.clone()
-        } else {
-            log::info!(
-                "THREADMANAGER REDIRECT FIX THREAD: No redirect, using source box: {}",
-                execute_script.target_box_id
-            );
-            execute_script.target_box_id.clone()
-        };
-        let script = execute_script.script.clone();
-        let execution_mode = execute_script.execution_mode.clone();
-        let redirect_target = execute_script.redirect_output.clone();
         let message_senders = self.message_senders.clone();
         let thread_manager_uuid = uuid::Uuid::new_v4();
 
         // Spawn thread using existing infrastructure pattern
         std::thread::spawn(move || {
-            let result = crate::utils::run_script_with_pty_and_redirect(
-                libs,
-                &script,
-                &execution_mode,
-                None, // No PTY manager for Thread mode
-                None, // No muxbox_id needed for Thread mode
-                None, // No message sender needed - we'll send result directly
-                redirect_target,
-            );
-
-            let (content, is_success) = match result {
-                Ok(output) => (output, true),
-                Err(e) => (format!("Thread execution error: {}", e), false),
-            };
-
-            // Send result via StreamUpdate
-            let final_update = crate::model::common::StreamUpdate {
-                stream_id,
-                target_box_id,
-                content_update: content,
-                source_state: crate::model::common::SourceState::Thread(
-                    crate::model::common::ThreadSourceState {
-                        thread_id: format!("{:?}", std::thread::current().id()),
-                        execution_time: std::time::Duration::from_millis(100), // approximate
-                        exit_code: Some(if is_success { 0 } else { 1 }),       // Success=0, Error=1
-                        status: crate::model::common::ExecutionThreadStatus::Completed,
-                    },
-                ),
-                execution_mode,
-            };
-
-            // Send to all threads via message senders
-            for (uuid, sender) in message_senders.iter() {
-                if let Err(e) = sender.send((
-                    thread_manager_uuid,
-                    Message::StreamUpdateMessage(final_update.clone()),
-                )) {
-                    log::error!(
-                        "Failed to send thread execution result to thread {}: {}",
-                        uuid,
-                        e
-                    );
+            // A periodic run queued behind this one starts in the same worker
+            let mut next = Some(execute_script);
+            while let Some(execute_script) = next.take() {
+                let (final_update, outcome) = run_threaded_script(&execute_script);
+
+                // Send to all threads via message senders
+                if let Some(final_update) = final_update {
+                    for (uuid, sender) in message_senders.iter() {
+                        if let Err(e) = sender.send((
+                            thread_manager_uuid,
+                            Message::StreamUpdateMessage(final_update.clone()),
+                        )) {
+                            log::error!(
+                                "Failed to send thread execution result to thread {}: {}",
+                                uuid,
+                                e
+                            );
+                        }
+                    }
                 }
+
+                next = crate::refresh_scheduler::finish_script(&execute_script, outcome);
             }
         });
     }
 }
 
+/// Run a Thread-mode script to completion on the calling thread. Returns the final
+/// StreamUpdate, or `None` when a Kill source action cancelled it and reports the
+/// termination itself.
+/// Run an Immediate-mode script to completion and build the update that finishes its
+/// stream; `None` when a Kill source action cancelled it
+pub(crate) fn run_immediate_script(
+    execute_script: &crate::model::common::ExecuteScript,
+) -> (Option<crate::model::common::StreamUpdate>, RunOutcome) {
+    // Detached from the terminal (own session, null stdin) so it can never
+    // reset boxmux's raw mode / mouse tracking; stdout/stderr are captured.
+    let output = crate::utils::script_command(
+        &execute_script.environment,
+        "sh",
+        &execute_script.script.join(" "),
+    )
+    .and_then(|command| {
+        crate::utils::run_script_process(
+            command,
+            execute_script
+                .timeout_ms
+                .map(std::time::Duration::from_millis),
+            Some(&execute_script.stream_id),
+        )
+    });
+
+    let mut outcome = RunOutcome::Failed;
+    let (content, exit_code, status) = match output {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let content = if stderr.is_empty() {
+                stdout.to_string()
+            } else {
+                format!("{}\n{}", stdout, stderr)
+            };
+            outcome = if output.status.success() {
+                RunOutcome::Succeeded
+            } else {
+                RunOutcome::Failed
+            };
+            // A script killed by a signal has no exit code but still finished
+            let status = match output.status.code() {
+                Some(_) => crate::model::common::BatchStatus::Completed,
+                None => crate::model::common::BatchStatus::Failed(output.status.to_string()),
+            };
+            (content, output.status.code(), status)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => (
+            e.to_string(),
+            None,
+            crate::model::common::BatchStatus::Failed("timeout".to_string()),
+        ),
+        // Cancelled by a Kill source action, which reports the termination itself
+        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {
+            return (None, RunOutcome::Cancelled)
+        }
+        Err(e) => (
+            format!("Error executing script: {}", e),
+            None,
+            crate::model::common::BatchStatus::Failed(e.to_string()),
+        ),
+    };
+
+    // Use stream_id from ExecuteScript (already registered in source registry)
+    let stream_id = execute_script.stream_id.clone();
+
+    // Send result via StreamUpdate with target_box_id for auto-creation
+    // REDIRECT FIX: Use redirect destination if specified
+    let target_box_id = if let Some(ref redirect_to) = execute_script.redirect_output {
+        log::info!(
+            "THREADMANAGER REDIRECT FIX UNKNOWN: Using redirect destination: {} (was {})",
+            redirect_to,
+            execute_script.target_box_id
+        );
+        redirect_to.clone()
+    } else {
+        log::info!(
+            "THREADMANAGER REDIRECT FIX UNKNOWN: No redirect, using source box: {}",
+            execute_script.target_box_id
+        );
+        execute_script.target_box_id.clone()
+    };
+
+    let stream_update = crate::model::common::StreamUpdate {
+        stream_id: stream_id.clone(),
+        target_box_id,
+        content_update: content,
+        source_state: crate::model::common::SourceState::Batch(
+            crate::model::common::BatchSourceState {
+                task_id: stream_id.clone(),
+                queue_wait_time: std::time::Duration::from_millis(0),
+                execution_time: std::time::Duration::from_millis(50), // Immediate scripts are very fast
+                exit_code,
+                status,
+            },
+        ),
+        execution_mode: execute_script.execution_mode.clone(),
+        output_format: execute_script.output_format,
+    };
+    (Some(stream_update), outcome)
+}
+
+pub(crate) fn run_threaded_script(
+    execute_script: &crate::model::common::ExecuteScript,
+) -> (Option<crate::model::common::StreamUpdate>, RunOutcome) {
+    // SOURCE OBJECT ARCHITECTURE: Use stream_id from ExecuteScript (from source object)
+    let stream_id = execute_script.stream_id.clone();
+
+    // Use existing utils::run_tracked_script for Thread execution
+    let libs = if execute_script.libs.is_empty() {
+        None
+    } else {
+        Some(execute_script.libs.clone())
+    };
+
+    // REDIRECT FIX: Use redirect destination if specified
+    let target_box_id = if let Some(ref redirect_to) = execute_script.redirect_output {
+        log::info!(
+            "THREADMANAGER REDIRECT FIX THREAD: Using redirect destination: {} (was {})",
+            redirect_to,
+            execute_script.target_box_id
+        );
+        redirect_to.clone()
+    } else {
+        log::info!(
+            "THREADMANAGER REDIRECT FIX THREAD: No redirect, using source box: {}",
+            execute_script.target_box_id
+        );
+        execute_script.target_box_id.clone()
+    };
+    let timeout = execute_script
+        .timeout_ms
+        .map(std::time::Duration::from_millis);
+
+    let result = crate::utils::run_tracked_script(
+        libs,
+        &execute_script.script,
+        &execute_script.environment,
+        timeout,
+        Some(&stream_id),
+    );
+
+    let (content, exit_code, status, outcome) = match result {
+        Ok(output) => (
+            output,
+            Some(0),
+            crate::model::common::ExecutionThreadStatus::Completed,
+            RunOutcome::Succeeded,
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => (
+            e.to_string(),
+            None,
+            crate::model::common::ExecutionThreadStatus::Failed("timeout".to_string()),
+            RunOutcome::Failed,
+        ),
+        // Cancelled by a Kill source action, which reports the termination itself
+        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {
+            return (None, RunOutcome::Cancelled)
+        }
+        // A script that ran and exited non-zero completed; anything else failed to run
+        Err(e) => match crate::utils::script_exit_code(&e) {
+            Some(code) => (
+                format!("Thread execution error: {}", e),
+                Some(code),
+                crate::model::common::ExecutionThreadStatus::Completed,
+                RunOutcome::Failed,
+            ),
+            None => (
+                format!("Thread execution error: {}", e),
+                None,
+                crate::model::common::ExecutionThreadStatus::Failed(e.to_string()),
+                RunOutcome::Failed,
+            ),
+        },
+    };
+
+    // Send result via StreamUpdate
+    let final_update = crate::model::common::StreamUpdate {
+        stream_id,
+        target_box_id,
+        content_update: content,
+        source_state: crate::model::common::SourceState::Thread(
+            crate::model::common::ThreadSourceState {
+                thread_id: format!("{:?}", std::thread::current().id()),
+                execution_time: std::time::Duration::from_millis(100), // approximate
+                exit_code,
+                status,
+            },
+        ),
+        execution_mode: execute_script.execution_mode.clone(),
+        output_format: execute_script.output_format,
+    };
+    (Some(final_update), outcome)
+}
+
 #[macro_export]
 macro_rules! create_runnable {
     ($name:ident, $init_body:expr, $process_body:expr) => {
@@ -1510,6 +1603,7 @@ pub fn run_script_in_thread(
                             },
                         ),
                         execution_mode: execution_mode.clone(),
+                        output_format: crate::model::common::OutputFormat::Text,
                     };
                     inner.send_message(Message::StreamUpdateMessage(stream_update))
                 }
@@ -1524,13 +1618,14 @@ pub fn run_script_in_thread(
                             crate::model::common::ThreadSourceState {
                                 thread_id: format!("{:?}", std::thread::current().id()),
                                 execution_time: std::time::Duration::from_millis(0),
-                                exit_code: Some(1),
+                                exit_code: crate::utils::script_exit_code(&e),
                                 status: crate::model::common::ExecutionThreadStatus::Failed(
                                     e.to_string(),
                                 ),
                             },
                         ),
                         execution_mode: execution_mode.clone(),
+                        output_format: crate::model::common::OutputFormat::Text,
                     };
                     inner.send_message(Message::StreamUpdateMessage(stream_update))
                 }
@@ -1,5 +1,5 @@
 use crate::{
-    model::common::{Bounds, InputBounds, ScreenBuffer},
+    model::common::{Bounds, Cell, InputBounds, ScreenBuffer, TextStyle},
     pty_manager::PtyManager,
     Layout,
 };
@@ -29,6 +29,19 @@ pub fn screen_bounds() -> Bounds {
     }
 }
 
+/// `path` with a leading `~` or `~/` expanded to `$HOME`. Anything else, `~user`
+/// included, comes back unchanged, as does every path when `HOME` is unset.
+pub fn expand_home(path: &str) -> String {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest,
+        _ => return path.to_string(),
+    };
+    match std::env::var("HOME") {
+        Ok(home) => format!("{}{}", home, rest),
+        Err(_) => path.to_string(),
+    }
+}
+
 /// Build a Command for running a user script that is fully detached from
 /// boxmux's terminal: stdin is null and the child runs in its own session
 /// (`setsid`) so it has NO controlling terminal and therefore cannot reset
@@ -50,6 +63,166 @@ pub fn detached_command(program: &str) -> Command {
     cmd
 }
 
+/// A [`detached_command`] running `script` under the environment's shell (or
+/// `default_shell -c`), in its working directory and with its variables applied.
+/// Fails if the configured working directory does not exist.
+pub fn script_command(
+    environment: &crate::model::common::ScriptEnvironment,
+    default_shell: &str,
+    script: &str,
+) -> io::Result<Command> {
+    let (program, args) = environment.command_line(default_shell, script);
+    let mut cmd = detached_command(&program);
+    cmd.args(args);
+    if let Some(dir) = environment.working_dir()? {
+        cmd.current_dir(dir);
+    }
+    if !environment.inherit_env {
+        cmd.env_clear();
+    }
+    cmd.envs(environment.variables());
+    Ok(cmd)
+}
+
+/// Process IDs and cancellation flags of running scripts, by stream ID
+type RunningScripts = HashMap<String, Vec<(u32, std::sync::Arc<std::sync::atomic::AtomicBool>)>>;
+
+/// In-flight script processes by stream ID, so a Kill source action can cancel them
+static RUNNING_SCRIPTS: once_cell::sync::Lazy<std::sync::Mutex<RunningScripts>> =
+    once_cell::sync::Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+const SCRIPT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// Run a script command to completion, capturing its output. Past `timeout` its
+/// process group is killed and an `ErrorKind::TimedOut` error carrying any partial
+/// output is returned. With a `stream_id` the run can be stopped by
+/// [`cancel_scripts`], which yields an `ErrorKind::Interrupted` error.
+pub fn run_script_process(
+    mut cmd: Command,
+    timeout: Option<std::time::Duration>,
+    stream_id: Option<&str>,
+) -> io::Result<std::process::Output> {
+    use std::io::Read;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    cmd.stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+    let mut child = cmd.spawn()?;
+    let pid = child.id();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    if let Some(stream_id) = stream_id {
+        RUNNING_SCRIPTS
+            .lock()
+            .unwrap()
+            .entry(stream_id.to_string())
+            .or_default()
+            .push((pid, cancelled.clone()));
+    }
+
+    let mut stdout = child.stdout.take();
+    let mut stderr = child.stderr.take();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(out) = stdout.as_mut() {
+            let _ = out.read_to_end(&mut buf);
+        }
+        buf
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(err) = stderr.as_mut() {
+            let _ = err.read_to_end(&mut buf);
+        }
+        buf
+    });
+
+    let deadline = timeout.map(|timeout| std::time::Instant::now() + timeout);
+    let mut timed_out = false;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) => {}
+            Err(e) => break Err(e),
+        }
+        if !timed_out && deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline) {
+            timed_out = true;
+            // setsid made the script a process group leader: take its children with it
+            if let Err(e) =
+                crate::pty_manager::signal_process_group(pid, crate::pty_manager::PtySignal::Kill)
+            {
+                log::warn!("Failed to kill timed out script {}: {}", pid, e);
+            }
+        }
+        std::thread::sleep(SCRIPT_POLL_INTERVAL);
+    };
+
+    if let Some(stream_id) = stream_id {
+        let mut running = RUNNING_SCRIPTS.lock().unwrap();
+        if let Some(pids) = running.get_mut(stream_id) {
+            pids.retain(|(running_pid, _)| *running_pid != pid);
+            if pids.is_empty() {
+                running.remove(stream_id);
+            }
+        }
+    }
+
+    let output = std::process::Output {
+        status: status?,
+        stdout: stdout_reader.join().unwrap_or_default(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+    };
+
+    if cancelled.load(Ordering::SeqCst) {
+        return Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "Script cancelled",
+        ));
+    }
+    if timed_out {
+        let partial = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let message = format!(
+            "Script timed out after {}ms",
+            timeout.unwrap_or_default().as_millis()
+        );
+        return Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            if partial.trim().is_empty() {
+                message
+            } else {
+                format!("{}\n{}", partial.trim_end(), message)
+            },
+        ));
+    }
+    Ok(output)
+}
+
+/// Kill the process groups of every script running for `stream_id`; returns how many
+pub fn cancel_scripts(stream_id: &str) -> usize {
+    let running = RUNNING_SCRIPTS.lock().unwrap();
+    let Some(pids) = running.get(stream_id) else {
+        return 0;
+    };
+    for (pid, cancelled) in pids {
+        cancelled.store(true, std::sync::atomic::Ordering::SeqCst);
+        if let Err(e) =
+            crate::pty_manager::signal_process_group(*pid, crate::pty_manager::PtySignal::Kill)
+        {
+            log::warn!(
+                "Failed to cancel script {} for stream {}: {}",
+                pid,
+                stream_id,
+                e
+            );
+        }
+    }
+    pids.len()
+}
+
 /// Force the controlling terminal back into raw mode if it has been reset to
 /// cooked mode by a child process (e.g. something that ran `stty`/`reset` or set
 /// terminal attributes via /dev/tty). crossterm's `enable_raw_mode()` is a no-op
@@ -404,22 +577,50 @@ pub fn inherit_optional_f64(
 }
 
 pub fn apply_buffer(screen_buffer: &mut ScreenBuffer, stdout: &mut Stdout) {
+    let mut style = None;
     for y in 0..screen_buffer.height {
         for x in 0..screen_buffer.width {
             if let Some(cell) = screen_buffer.get(x, y) {
                 execute!(stdout, crossterm::cursor::MoveTo(x as u16, y as u16)).unwrap();
-                write!(stdout, "{}{}{}", cell.bg_color, cell.fg_color, cell.ch).unwrap();
+                write_cell(stdout, cell, &mut style);
             }
         }
     }
+    finish_cells(stdout, style);
     stdout.flush().unwrap();
 }
 
+/// Write a cell, switching text attributes only when they differ from `style`,
+/// the attributes the terminal currently has (None when unknown)
+fn write_cell(stdout: &mut Stdout, cell: &Cell, style: &mut Option<TextStyle>) {
+    let transition = cell.style.transition_from(style.as_ref());
+    write!(
+        stdout,
+        "{}{}{}{}",
+        transition, cell.bg_color, cell.fg_color, cell.ch
+    )
+    .unwrap();
+    *style = Some(cell.style);
+}
+
+/// Leave the terminal without text attributes after writing cells
+fn finish_cells(stdout: &mut Stdout, style: Option<TextStyle>) {
+    if let Some(style) = style {
+        write!(
+            stdout,
+            "{}",
+            TextStyle::default().transition_from(Some(&style))
+        )
+        .unwrap();
+    }
+}
+
 pub fn apply_buffer_if_changed(
     previous_buffer: &ScreenBuffer,
     current_buffer: &ScreenBuffer,
     stdout: &mut Stdout,
 ) {
+    let mut style = None;
     for y in 0..current_buffer.height {
         let mut last_changed_index: Option<u16> = None;
         let mut changes = Vec::new(); // Store changes for each line
@@ -440,7 +641,7 @@ pub fn apply_buffer_if_changed(
                 if let Some(start) = last_changed_index {
                     execute!(stdout, crossterm::cursor::MoveTo(start, y as u16)).unwrap();
                     for cell in &changes {
-                        write!(stdout, "{}{}{}", cell.bg_color, cell.fg_color, cell.ch).unwrap();
+                        write_cell(stdout, cell, &mut style);
                     }
                     changes.clear();
                     last_changed_index = None;
@@ -451,11 +652,12 @@ pub fn apply_buffer_if_changed(
         // Check if there's a pending sequence at the end of the line
         if let Some(start) = last_changed_index {
             execute!(stdout, crossterm::cursor::MoveTo(start, y as u16)).unwrap();
-            for cell in changes {
-                write!(stdout, "{}{}{}", cell.bg_color, cell.fg_color, cell.ch).unwrap();
+            for cell in &changes {
+                write_cell(stdout, cell, &mut style);
             }
         }
     }
+    finish_cells(stdout, style);
     stdout.flush().unwrap(); // Make sure to flush only once after all changes
 }
 
@@ -608,6 +810,8 @@ pub fn run_script_with_pty_and_redirect(
                 redirect_target,
                 None, // Use default stream ID generation
                 None, // No bounds available in utils.rs context
+                &crate::model::common::ScriptEnvironment::default(),
+                None,
             ) {
                 Ok(_) => {
                     // PTY started successfully - clear any previous failures
@@ -637,6 +841,54 @@ pub fn run_script_with_pty_and_redirect(
 }
 
 fn run_script_regular(libs_paths: Option<Vec<String>>, script: &Vec<String>) -> io::Result<String> {
+    run_script_in_environment(
+        libs_paths,
+        script,
+        &crate::model::common::ScriptEnvironment::default(),
+    )
+}
+
+/// Run `script` (after sourcing `libs_paths`) under the given environment,
+/// defaulting to `bash`, and return its combined output
+pub fn run_script_in_environment(
+    libs_paths: Option<Vec<String>>,
+    script: &Vec<String>,
+    environment: &crate::model::common::ScriptEnvironment,
+) -> io::Result<String> {
+    run_tracked_script(libs_paths, script, environment, None, None)
+}
+
+/// Error of a script that ran but exited unsuccessfully; [`script_exit_code`] reads the
+/// exit code back out of the `io::Error`
+#[derive(Debug)]
+struct ScriptFailed {
+    exit_code: Option<i32>,
+    message: String,
+}
+
+impl std::fmt::Display for ScriptFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ScriptFailed {}
+
+/// Exit code of a script whose [`run_tracked_script`] error says it exited unsuccessfully;
+/// `None` for other errors and for scripts killed by a signal
+pub fn script_exit_code(error: &io::Error) -> Option<i32> {
+    error.get_ref()?.downcast_ref::<ScriptFailed>()?.exit_code
+}
+
+/// [`run_script_in_environment`] with an optional timeout, cancellable through
+/// [`cancel_scripts`] when a `stream_id` is given (see [`run_script_process`])
+pub fn run_tracked_script(
+    libs_paths: Option<Vec<String>>,
+    script: &Vec<String>,
+    environment: &crate::model::common::ScriptEnvironment,
+    timeout: Option<std::time::Duration>,
+    stream_id: Option<&str>,
+) -> io::Result<String> {
     // Create the script content in-memory
     let mut script_content = String::new();
     if let Some(paths) = libs_paths {
@@ -653,10 +905,11 @@ fn run_script_regular(libs_paths: Option<Vec<String>>, script: &Vec<String>) ->
     // Execute the script and capture stdout and stderr
     // Detach the child from the real terminal so it can't reset boxmux's raw
     // mode / mouse tracking via stdin or /dev/tty; stdout+stderr are captured.
-    let output = detached_command("bash")
-        .arg("-c")
-        .arg(script_content)
-        .output();
+    let output = run_script_process(
+        script_command(environment, "bash", &script_content)?,
+        timeout,
+        stream_id,
+    );
 
     match output {
         Ok(output) => {
@@ -680,10 +933,13 @@ fn run_script_regular(libs_paths: Option<Vec<String>>, script: &Vec<String>) ->
                 } else {
                     combined_output
                 };
-                Err(io::Error::other(error_message))
+                Err(io::Error::other(ScriptFailed {
+                    exit_code: output.status.code(),
+                    message: error_message,
+                }))
             }
         }
-        Err(e) => Err(io::Error::other(e.to_string())),
+        Err(e) => Err(e),
     }
 }
 
@@ -127,6 +127,8 @@ pub struct SchemaValidator {
     warnings: Vec<ValidationError>,
     muxbox_ids: HashSet<String>,
     layout_ids: HashSet<String>,
+    /// Names in `app.styles`; `None` outside `validate_app`, where `class` isn't checked
+    style_classes: Option<HashSet<String>>,
 }
 
 impl SchemaValidator {
@@ -136,6 +138,7 @@ impl SchemaValidator {
             warnings: Vec::new(),
             muxbox_ids: HashSet::new(),
             layout_ids: HashSet::new(),
+            style_classes: None,
         }
     }
 
@@ -177,6 +180,8 @@ impl SchemaValidator {
             });
         }
 
+        self.validate_classes(&layout.class, &format!("{}.class", path));
+
         // Validate muxboxes if present
         if let Some(muxboxes) = &layout.children {
             for (idx, muxbox) in muxboxes.iter().enumerate() {
@@ -203,6 +208,8 @@ impl SchemaValidator {
         // Validate position bounds
         self.validate_input_bounds_schema(&muxbox.position, &format!("{}.position", path));
 
+        self.validate_classes(&muxbox.class, &format!("{}.class", path));
+
         // Validate child muxboxes recursively
         if let Some(children) = &muxbox.children {
             for (idx, child) in children.iter().enumerate() {
@@ -229,6 +236,20 @@ impl SchemaValidator {
             &format!("{}.execution_mode", path),
         );
 
+        if let Some(schedule) = &muxbox.schedule {
+            self.validate_schedule(schedule, Some(&muxbox.id), &format!("{}.schedule", path));
+        }
+
+        for (idx, rule) in muxbox.alerts.iter().flatten().enumerate() {
+            for (field, value, constraint) in crate::alerts::validate_rule(rule) {
+                self.add_error(ValidationError::InvalidFieldValue {
+                    field: format!("{}.alerts[{}].{}", path, idx, field),
+                    value,
+                    constraint,
+                });
+            }
+        }
+
         // Validate choices if present
         if let Some(choices) = &muxbox.choices {
             for (idx, choice) in choices.iter().enumerate() {
@@ -429,6 +450,12 @@ impl SchemaValidator {
             &format!("{}.execution_mode", path),
         );
 
+        if let Some(schedule) = &choice.schedule {
+            self.validate_schedule(schedule, None, &format!("{}.schedule", path));
+        }
+
+        self.validate_classes(&choice.class, &format!("{}.class", path));
+
         // Return both errors and warnings for comprehensive validation
         let mut all_issues = self.errors.clone();
         all_issues.extend(self.warnings.clone());
@@ -440,6 +467,68 @@ impl SchemaValidator {
         }
     }
 
+    /// Flag `class` names missing from `app.styles`
+    fn validate_classes(&mut self, classes: &Option<Vec<String>>, path: &str) {
+        let Some(style_classes) = &self.style_classes else {
+            return;
+        };
+        let unknown: Vec<String> = classes
+            .iter()
+            .flatten()
+            .filter(|class| !style_classes.contains(*class))
+            .cloned()
+            .collect();
+        for class in unknown {
+            self.add_error(ValidationError::InvalidReference {
+                field: path.to_string(),
+                reference: class,
+                target_type: "style class".to_string(),
+            });
+        }
+    }
+
+    /// Validate cron and `at` syntax and the box named by `after_box`
+    fn validate_schedule(
+        &mut self,
+        schedule: &crate::model::common::Schedule,
+        muxbox_id: Option<&str>,
+        path: &str,
+    ) {
+        if let Some(cron) = &schedule.cron {
+            if let Err(message) = crate::schedule_loop::CronExpr::parse(cron) {
+                self.add_error(ValidationError::InvalidFieldValue {
+                    field: format!("{}.cron", path),
+                    value: cron.clone(),
+                    constraint: message,
+                });
+            }
+        }
+        if let Some(at) = &schedule.at {
+            if let Err(message) = crate::schedule_loop::parse_daily_time(at) {
+                self.add_error(ValidationError::InvalidFieldValue {
+                    field: format!("{}.at", path),
+                    value: at.clone(),
+                    constraint: message,
+                });
+            }
+        }
+        if let Some(after_box) = &schedule.after_box {
+            if muxbox_id == Some(after_box.as_str()) {
+                self.add_error(ValidationError::InvalidFieldValue {
+                    field: format!("{}.after_box", path),
+                    value: after_box.clone(),
+                    constraint: "a box cannot be scheduled after itself".to_string(),
+                });
+            } else if !self.muxbox_ids.is_empty() && !self.muxbox_ids.contains(after_box) {
+                self.add_error(ValidationError::InvalidReference {
+                    field: format!("{}.after_box", path),
+                    reference: after_box.clone(),
+                    target_type: "box".to_string(),
+                });
+            }
+        }
+    }
+
     /// Validate ExecutionMode field consistency and provide deprecation warnings
     fn validate_execution_mode_fields(
         &mut self,
@@ -466,6 +555,7 @@ impl SchemaValidator {
         self.warnings.clear();
         self.muxbox_ids.clear();
         self.layout_ids.clear();
+        self.style_classes = None;
     }
 
     fn add_error(&mut self, error: ValidationError) {
@@ -485,6 +575,7 @@ impl SchemaValidator {
     }
 
     fn collect_ids(&mut self, app: &App) {
+        self.style_classes = Some(app.styles.iter().flat_map(|s| s.keys().cloned()).collect());
         for layout in &app.layouts {
             if !self.layout_ids.insert(layout.id.clone()) {
                 self.add_error(ValidationError::DuplicateId {
@@ -690,6 +781,7 @@ mod tests {
             frame_delay: 0,
             locked: false,
             calibrate: false,
+            socket_path: None,
         };
 
         let result = validator.validate_config(&config);
@@ -710,6 +802,7 @@ mod tests {
             frame_delay: 2000,
             locked: false,
             calibrate: false,
+            socket_path: None,
         };
 
         let result = validator.validate_config(&config);